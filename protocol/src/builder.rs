@@ -0,0 +1,245 @@
+use crate::header::{OpCode, ResultCode};
+use crate::packet::{Class, Packet, Question, Record};
+use crate::records::opt::EdnsOption;
+use crate::records::OPT;
+
+// UDP payload size advertised when EDNS is enabled implicitly,
+// matching the size of a `BytePacketBuffer`.
+const DEFAULT_UDP_PAYLOAD_SIZE: u16 = 512;
+
+/// Builder of queries and responses, created with `Packet::query`
+/// or `Packet::reply`.
+///
+/// Section counts don't need to be set, they are derived from the
+/// sections on serialization.
+#[derive(Debug, Clone)]
+pub struct PacketBuilder {
+    packet: Packet,
+}
+
+impl PacketBuilder {
+    pub(crate) fn new(packet: Packet) -> PacketBuilder {
+        PacketBuilder { packet }
+    }
+
+    pub fn id(mut self, id: u16) -> Self {
+        self.packet.header.id = id;
+        self
+    }
+
+    pub fn opcode(mut self, opcode: OpCode) -> Self {
+        self.packet.header.opcode = opcode;
+        self
+    }
+
+    /// Class of the questions.
+    pub fn class(mut self, class: Class) -> Self {
+        for question in &mut self.packet.questions {
+            question.class = class;
+        }
+
+        self
+    }
+
+    pub fn authoritative(mut self, authoritative: bool) -> Self {
+        self.packet.header.authoritative_answer = authoritative;
+        self
+    }
+
+    pub fn truncated(mut self, truncated: bool) -> Self {
+        self.packet.header.truncated = truncated;
+        self
+    }
+
+    pub fn recursion_desired(mut self, recursion_desired: bool) -> Self {
+        self.packet.header.recursion_desired = recursion_desired;
+        self
+    }
+
+    pub fn recursion_available(mut self, recursion_available: bool) -> Self {
+        self.packet.header.recursion_available = recursion_available;
+        self
+    }
+
+    pub fn authenticated_data(mut self, authenticated_data: bool) -> Self {
+        self.packet.header.authenticated_data = authenticated_data;
+        self
+    }
+
+    pub fn checking_disabled(mut self, checking_disabled: bool) -> Self {
+        self.packet.header.checking_disabled = checking_disabled;
+        self
+    }
+
+    /// Result code, extended ones requiring EDNS to be enabled.
+    pub fn rcode(mut self, rcode: ResultCode) -> Self {
+        self.packet.header.result_code = rcode;
+        self
+    }
+
+    pub fn question(mut self, question: Question) -> Self {
+        self.packet.questions.push(question);
+        self
+    }
+
+    pub fn answer(mut self, record: Record) -> Self {
+        self.packet.answers.push(record);
+        self
+    }
+
+    pub fn answers<I>(mut self, records: I) -> Self
+        where I: IntoIterator<Item=Record>
+    {
+        self.packet.answers.extend(records);
+        self
+    }
+
+    pub fn authority(mut self, record: Record) -> Self {
+        self.packet.authorities.push(record);
+        self
+    }
+
+    pub fn authorities<I>(mut self, records: I) -> Self
+        where I: IntoIterator<Item=Record>
+    {
+        self.packet.authorities.extend(records);
+        self
+    }
+
+    /// Add an additional record, use `edns` rather than adding an OPT record.
+    pub fn additional(mut self, record: Record) -> Self {
+        self.packet.additionals.push(record);
+        self
+    }
+
+    pub fn additionals<I>(mut self, records: I) -> Self
+        where I: IntoIterator<Item=Record>
+    {
+        self.packet.additionals.extend(records);
+        self
+    }
+
+    /// Enable EDNS, advertising the given UDP payload size.
+    pub fn edns(mut self, udp_payload_size: u16) -> Self {
+        self.opt().udp_payload_size = udp_payload_size;
+        self
+    }
+
+    /// Set the DNSSEC OK flag, enabling EDNS if needed.
+    pub fn dnssec_ok(mut self, dnssec_ok: bool) -> Self {
+        self.opt().set_dnssec_ok(dnssec_ok);
+        self
+    }
+
+    /// Add an EDNS option, enabling EDNS if needed.
+    pub fn edns_option(mut self, option: EdnsOption) -> Self {
+        self.opt().options.push(option);
+        self
+    }
+
+    pub fn edns_options<I>(mut self, options: I) -> Self
+        where I: IntoIterator<Item=EdnsOption>
+    {
+        self.opt().options.extend(options);
+        self
+    }
+
+    pub fn build(self) -> Packet {
+        self.packet
+    }
+
+    fn opt(&mut self) -> &mut OPT {
+        if self.packet.edns().is_none() {
+            self.packet.additionals.push(Record::Opt(OPT::new(DEFAULT_UDP_PAYLOAD_SIZE)));
+        }
+
+        self.packet.edns_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use crate::header::{OpCode, ResultCode};
+    use crate::packet::{Class, Packet, QueryType, Question, Record};
+    use crate::records::opt::{EdnsOption, ExtendedErrorCode};
+    use crate::records::A;
+
+    #[test]
+    fn build_query() {
+        let query = Packet::query("example.com", QueryType::A)
+            .id(0x862a)
+            .recursion_desired(true)
+            .edns(1232)
+            .dnssec_ok(true)
+            .build();
+
+        assert_eq!(0x862a, query.header.id);
+        assert!(!query.header.is_response);
+        assert_eq!(OpCode::Query, query.header.opcode);
+        assert!(query.header.recursion_desired);
+        assert_eq!(vec![Question {
+            name: "example.com".to_string(),
+            qtype: QueryType::A,
+            class: Class::Internet,
+        }], query.questions);
+
+        assert_eq!(1, query.additionals.len());
+        let opt = query.edns().unwrap();
+        assert_eq!(1232, opt.udp_payload_size);
+        assert!(opt.dnssec_ok());
+    }
+
+    #[test]
+    fn build_chaos_query() {
+        let query = Packet::query("version.bind", QueryType::Text).class(Class::Chaos).build();
+        assert_eq!(Class::Chaos, query.questions[0].class);
+        assert!(query.edns().is_none());
+    }
+
+    #[test]
+    fn build_reply() {
+        let request = Packet::query("example.com", QueryType::A)
+            .id(0x862a)
+            .recursion_desired(true)
+            .edns(1232)
+            .build();
+
+        let record = Record::A(A {
+            domain: "example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            ip: Ipv4Addr::new(127, 0, 0, 1),
+        });
+        let response = request.reply().answer(record.clone()).build();
+
+        assert_eq!(0x862a, response.header.id);
+        assert!(response.header.is_response);
+        assert!(response.header.recursion_desired);
+        assert_eq!(ResultCode::NoError, response.header.result_code);
+        assert_eq!(request.questions, response.questions);
+        assert_eq!(vec![record], response.answers);
+
+        // EDNS is hop-by-hop, it is not copied from the request.
+        assert!(response.edns().is_none());
+    }
+
+    #[test]
+    fn build_error_reply() {
+        let request = Packet::query("example.com", QueryType::A).opcode(OpCode::Notify).build();
+        let response = request.reply()
+            .rcode(ResultCode::NotImplemented)
+            .edns_option(EdnsOption::ExtendedDnsError {
+                info_code: ExtendedErrorCode::NotSupported,
+                extra_text: String::new(),
+            })
+            .build();
+
+        assert_eq!(OpCode::Notify, response.header.opcode);
+        assert_eq!(ResultCode::NotImplemented, response.header.result_code);
+        assert_eq!(512, response.edns().unwrap().udp_payload_size);
+        assert_eq!(1, response.edns().unwrap().options.len());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::de::Deserializer;
-use crate::errors::Error::OutOfRange;
+use crate::errors::Error::{InvalidData, OutOfRange};
 use crate::result::Result;
 use crate::seek::Seek;
 use crate::ser::Serializer;
@@ -62,7 +62,9 @@ impl Serializer for BytePacketBuffer {
     }
 
     fn serialize_qname(&mut self, qname: &str) -> Result<()> {
-        for label in qname.split('.') {
+        // Skipping empty labels, the root domain ("") being
+        // only made of the terminating zero length label.
+        for label in qname.split('.').filter(|label| !label.is_empty()) {
             let len = label.len();
             self.serialize_u8(len as u8)?;
 
@@ -79,42 +81,48 @@ impl Serializer for BytePacketBuffer {
 
         self.serialize_u8(0)
     }
-}
 
-impl<'a> Deserializer for &'a mut BytePacketBuffer {
-    #[inline]
-    fn deserialize_u8(self) -> Result<u8> {
-        if self.pos > DEFAULT_BUFFER_SIZE {
+    fn serialize_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        if self.pos + bytes.len() > DEFAULT_BUFFER_SIZE {
             return Err(OutOfRange {
-                expected: self.pos,
+                expected: self.pos + bytes.len(),
                 max: DEFAULT_BUFFER_SIZE,
             });
         }
 
-        let byte = self.buf[self.pos];
-        let position = self.position();
-        self.seek(position + 1)?;
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+        Ok(())
+    }
+}
+
+// Maximum number of compression pointers followed for a single name,
+// protecting against pointer loops.
+pub(crate) const MAX_POINTER_JUMPS: usize = 64;
+
+impl Deserializer for BytePacketBuffer {
+    fn deserialize_u8(&mut self) -> Result<u8> {
+        let byte = self.get_u8(self.pos)?;
+        self.pos += 1;
         Ok(byte)
     }
 
-    #[inline]
-    fn deserialize_u16(self) -> Result<u16> {
+    fn deserialize_u16(&mut self) -> Result<u16> {
         let msb = (self.deserialize_u8()? as u16) << 8;
         let lsb = self.deserialize_u8()? as u16;
         Ok(msb | lsb)
     }
 
-    #[inline]
-    fn deserialize_u32(self) -> Result<u32> {
+    fn deserialize_u32(&mut self) -> Result<u32> {
         let msb = (self.deserialize_u16()? as u32) << 16;
         let lsb = self.deserialize_u16()? as u32;
         Ok(msb | lsb)
     }
 
-    fn deserialize_qname(self) -> Result<String> {
+    fn deserialize_qname(&mut self) -> Result<String> {
         let mut out = String::new();
         let mut working_pos = self.position();
-        let mut jumped = false;
+        let mut jumps = 0;
 
         // Starting with an empty delimiter to not pushing the first delimiter.
         // The first delimiter corresponds to the last char in  the qname (e.g: "foo.bar.com.").
@@ -130,15 +138,24 @@ impl<'a> Deserializer for &'a mut BytePacketBuffer {
 
                 // Pointer to a qname in the packet.
                 _ if len & 0xC0 == 0xC0 => {
-                    if !jumped {
+                    if jumps == 0 {
                         self.seek(working_pos + 1)?;
                     }
 
+                    jumps += 1;
+                    if jumps > MAX_POINTER_JUMPS {
+                        return Err(InvalidData("too many compression pointers in domain name".to_string()));
+                    }
+
                     let msb = len as u16 ^ 0xC0;
                     let lsb = self.get_u8(working_pos)? as u16;
                     let offset = (msb << 8) | lsb;
                     working_pos = offset as usize;
-                    jumped = true;
+                }
+
+                // Labels starting with 0b01 or 0b10 are reserved.
+                _ if len & 0xC0 != 0 => {
+                    return Err(InvalidData(format!("unsupported label type {:#04x}", len & 0xC0)));
                 }
 
                 // Normal case where the first byte is the length of the following label.
@@ -152,12 +169,18 @@ impl<'a> Deserializer for &'a mut BytePacketBuffer {
             }
         }
 
-        if !jumped {
+        if jumps == 0 {
             self.seek(working_pos)?;
         }
 
         Ok(out)
     }
+
+    fn deserialize_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        let bytes = self.get_range(self.pos, len)?.to_vec();
+        self.pos += len;
+        Ok(bytes)
+    }
 }
 
 impl Seek for BytePacketBuffer {
@@ -196,7 +219,7 @@ impl BytePacketBuffer {
     }
 
     fn get_u8(&self, pos: usize) -> Result<u8> {
-        if pos > DEFAULT_BUFFER_SIZE {
+        if pos >= DEFAULT_BUFFER_SIZE {
             return Err(OutOfRange { expected: pos, max: DEFAULT_BUFFER_SIZE });
         }
 
@@ -214,80 +237,6 @@ impl BytePacketBuffer {
         Ok(&self.buf[pos..pos + len])
     }
 
-    pub fn read_u8(&mut self) -> u8 {
-        assert!(self.pos < DEFAULT_BUFFER_SIZE, "pos out of range: {:?} >= {:?}", self.pos, DEFAULT_BUFFER_SIZE);
-        let c = self.buf[self.pos];
-        self.pos += 1;
-        c
-    }
-
-    pub fn read_n(&mut self, len: usize) -> Vec<u8> {
-        assert!(self.pos + len < DEFAULT_BUFFER_SIZE, "pos out of range: {:?} >= {:?}", self.pos + len, DEFAULT_BUFFER_SIZE);
-        let out = self.get_range(self.pos, len).unwrap().into();
-        let position = self.position();
-        self.seek(position + len).unwrap();
-        out
-    }
-
-    pub fn read_u16(&mut self) -> u16 {
-        let first_byte = (self.read_u8() as u16) << 8;
-        let second_byte = self.read_u8() as u16;
-        first_byte | second_byte
-    }
-
-    pub fn read_u32(&mut self) -> u32 {
-        let first_byte = (self.read_u8() as u32) << 24;
-        let second_byte = (self.read_u8() as u32) << 16;
-        let third_byte = (self.read_u8() as u32) << 8;
-        let fourth_byte = self.read_u8() as u32;
-        first_byte | second_byte | third_byte | fourth_byte
-    }
-
-    pub fn read_qname(&mut self) -> String {
-        let mut out = String::new();
-        let mut delimiter = "";
-        let mut pos = self.position();
-        let mut jumped = false;
-
-        loop {
-            let len = self.get_u8(pos).unwrap();
-            pos += 1;
-
-            match len {
-                // End of qname.
-                0 => break,
-
-                // Pointer to a qname in the packet.
-                _ if len & 0xC0 == 0xC0 => {
-                    if !jumped {
-                        self.seek(pos + 1).unwrap();
-                    }
-
-                    let b1 = len as u16 ^ 0xC0;
-                    let b2 = self.get_u8(pos).unwrap() as u16;
-                    let offset = (b1 << 8) | b2;
-                    pos = offset as usize;
-                    jumped = true;
-                }
-
-                // Normal case where the first byte is the length of the following label.
-                _ => {
-                    let label = self.get_range(pos, len as usize).unwrap();
-                    out.push_str(delimiter);
-                    out.push_str(&String::from_utf8_lossy(label).to_lowercase());
-                    delimiter = ".";
-                    pos += len as usize;
-                }
-            }
-        }
-
-        if !jumped {
-            self.seek(pos).unwrap();
-        }
-
-        out
-    }
-
     pub fn set_u8(&mut self, pos: usize, value: u8) {
         self.buf[pos] = value;
     }
@@ -312,29 +261,30 @@ impl BytePacketBuffer {
 #[cfg(test)]
 mod test {
     use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::de::Deserializer;
     use crate::ser::Serializer;
 
     #[test]
-    fn read_u8() {
+    fn deserialize_u8() {
         let mut buf = BytePacketBuffer::from_raw_data(&[0xDE, 0xAD]);
-        assert_eq!(0xDE, buf.read_u8());
-        assert_eq!(0xAD, buf.read_u8());
+        assert_eq!(0xDE, buf.deserialize_u8().unwrap());
+        assert_eq!(0xAD, buf.deserialize_u8().unwrap());
     }
 
     #[test]
-    fn read_u16() {
+    fn deserialize_u16() {
         let mut buf = BytePacketBuffer::from_raw_data(&[0xDE, 0xAD]);
-        assert_eq!(0xDEAD, buf.read_u16());
+        assert_eq!(0xDEAD, buf.deserialize_u16().unwrap());
     }
 
     #[test]
-    fn read_u32() {
+    fn deserialize_u32() {
         let mut buf = BytePacketBuffer::from_raw_data(&[0xDE, 0xAD, 0xBE, 0xEF]);
-        assert_eq!(0xDEAD_BEEF, buf.read_u32());
+        assert_eq!(0xDEAD_BEEF, buf.deserialize_u32().unwrap());
     }
 
     #[test]
-    fn read_qname() {
+    fn deserialize_qname() {
         let packet: &[u8] = &[
             0x03, 0x77, 0x77, 0x77, // len=3 label="www"
             0x06, 0x67, 0x6f, 0x6f, 0x67, 0x6c, 0x65, // len=6 label="google"
@@ -343,11 +293,11 @@ mod test {
         ];
 
         let mut buf = BytePacketBuffer::from_raw_data(packet);
-        assert_eq!("www.google.com", buf.read_qname());
+        assert_eq!("www.google.com", buf.deserialize_qname().unwrap());
     }
 
     #[test]
-    fn read_qname_pointer() {
+    fn deserialize_qname_pointer() {
         let packet: &[u8] = &[
             0xC0, 0x02, // pointer to pos=2
             0x03, 0x77, 0x77, 0x77, // len=3 label="www"
@@ -357,11 +307,11 @@ mod test {
         ];
 
         let mut buf = BytePacketBuffer::from_raw_data(packet);
-        assert_eq!("www.google.com", buf.read_qname());
+        assert_eq!("www.google.com", buf.deserialize_qname().unwrap());
     }
 
     #[test]
-    fn read_qnames() {
+    fn deserialize_qnames() {
         let packet: &[u8] = &[
             0x03, 0x77, 0x77, 0x77, // len=3 label="www"
             0x06, 0x67, 0x6f, 0x6f, 0x67, 0x6c, 0x65, // len=6 label="google"
@@ -374,12 +324,12 @@ mod test {
         ];
 
         let mut buf = BytePacketBuffer::from_raw_data(packet);
-        assert_eq!("www.google.com", buf.read_qname());
-        assert_eq!("www.yahoo.com", buf.read_qname());
+        assert_eq!("www.google.com", buf.deserialize_qname().unwrap());
+        assert_eq!("www.yahoo.com", buf.deserialize_qname().unwrap());
     }
 
     #[test]
-    fn read_qname_pointers() {
+    fn deserialize_qname_pointers() {
         let packet: &[u8] = &[
             0x03, 0x77, 0x77, 0x77, // len=3 label="www"
             0x06, 0x67, 0x6f, 0x6f, 0x67, 0x6c, 0x65, // len=6 label="google"
@@ -394,14 +344,14 @@ mod test {
         ];
 
         let mut buf = BytePacketBuffer::from_raw_data(packet);
-        assert_eq!("www.google.com", buf.read_qname());
-        assert_eq!("www.yahoo.com", buf.read_qname());
-        assert_eq!("www.yahoo.com", buf.read_qname());
-        assert_eq!("www.google.com", buf.read_qname());
+        assert_eq!("www.google.com", buf.deserialize_qname().unwrap());
+        assert_eq!("www.yahoo.com", buf.deserialize_qname().unwrap());
+        assert_eq!("www.yahoo.com", buf.deserialize_qname().unwrap());
+        assert_eq!("www.google.com", buf.deserialize_qname().unwrap());
     }
 
     #[test]
-    fn read_qname_pointer_to_pointer() {
+    fn deserialize_qname_pointer_to_pointer() {
         let packet: &[u8] = &[
             0x03, 0x77, 0x77, 0x77, // len=3 label="www"
             0x05, 0x79, 0x61, 0x68, 0x6f, 0x6f, // len=6 label="yahoo"
@@ -416,10 +366,10 @@ mod test {
         ];
 
         let mut buf = BytePacketBuffer::from_raw_data(packet);
-        assert_eq!("www.yahoo.com", buf.read_qname());
-        assert_eq!("www.yahoo.com", buf.read_qname());
-        assert_eq!("www.yahoo.com", buf.read_qname());
-        assert_eq!("www.google.com", buf.read_qname());
+        assert_eq!("www.yahoo.com", buf.deserialize_qname().unwrap());
+        assert_eq!("www.yahoo.com", buf.deserialize_qname().unwrap());
+        assert_eq!("www.yahoo.com", buf.deserialize_qname().unwrap());
+        assert_eq!("www.google.com", buf.deserialize_qname().unwrap());
     }
 
     #[test]
@@ -506,4 +456,28 @@ mod test {
             0x00,
         ], &serializer.buf[..31]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn serialize_root_qname() {
+        let serializer = &mut BytePacketBuffer::new();
+
+        let res = serializer.serialize_qname("");
+        assert!(res.is_ok());
+
+        assert_eq!(&[0x00], &serializer.buf[..serializer.pos]);
+    }
+
+    #[test]
+    fn deserialize_out_of_range() {
+        let mut buf = BytePacketBuffer::from_raw_data(&[]);
+        buf.pos = 511;
+        assert!(buf.deserialize_u16().is_err());
+        assert!(buf.deserialize_bytes(2).is_err());
+    }
+
+    #[test]
+    fn deserialize_qname_pointer_loop() {
+        let mut buf = BytePacketBuffer::from_raw_data(&[0xC0, 0x00]);
+        assert!(buf.deserialize_qname().is_err());
+    }
+}
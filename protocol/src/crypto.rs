@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+use ring::digest;
+use ring::rand::SystemRandom;
+use ring::rsa::{KeyPairComponents, PublicKeyComponents};
+use ring::signature::{self, EcdsaKeyPair, Ed25519KeyPair, RsaKeyPair, RsaPublicKeyComponents, UnparsedPublicKey, VerificationAlgorithm};
+
+use crate::errors::Error::{InvalidData, InvalidPresentation, InvalidSignature};
+use crate::records::dnssec::{Algorithm, DigestType};
+use crate::result::Result;
+
+/// Whether signatures of the algorithm can be verified.
+pub fn is_supported(algorithm: Algorithm) -> bool {
+    matches!(
+        algorithm,
+        Algorithm::RsaSha1
+            | Algorithm::RsaSha1Nsec3Sha1
+            | Algorithm::RsaSha256
+            | Algorithm::RsaSha512
+            | Algorithm::EcdsaP256Sha256
+            | Algorithm::EcdsaP384Sha384
+            | Algorithm::Ed25519
+    )
+}
+
+/// Whether digests of the type can be computed.
+pub fn is_supported_digest(digest_type: DigestType) -> bool {
+    matches!(digest_type, DigestType::Sha1 | DigestType::Sha256 | DigestType::Sha384)
+}
+
+/// Verify the signature of the data, made with the private key matching
+/// the public key of a DNSKEY record.
+pub fn verify(algorithm: Algorithm, public_key: &[u8], data: &[u8], signature: &[u8]) -> Result<()> {
+    let verified = match algorithm {
+        Algorithm::RsaSha1 | Algorithm::RsaSha1Nsec3Sha1 => verify_rsa(&signature::RSA_PKCS1_1024_8192_SHA1_FOR_LEGACY_USE_ONLY, public_key, data, signature)?,
+        Algorithm::RsaSha256 => verify_rsa(&signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY, public_key, data, signature)?,
+        Algorithm::RsaSha512 => verify_rsa(&signature::RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY, public_key, data, signature)?,
+        Algorithm::EcdsaP256Sha256 => verify_ecdsa(&signature::ECDSA_P256_SHA256_FIXED, 64, public_key, data, signature)?,
+        Algorithm::EcdsaP384Sha384 => verify_ecdsa(&signature::ECDSA_P384_SHA384_FIXED, 96, public_key, data, signature)?,
+        Algorithm::Ed25519 => UnparsedPublicKey::new(&signature::ED25519, public_key).verify(data, signature).is_ok(),
+        other => return Err(InvalidSignature(format!("unsupported algorithm {}", other.as_u8()))),
+    };
+
+    if !verified {
+        return Err(InvalidSignature(format!("{:?} signature doesn't match the key", algorithm)));
+    }
+
+    Ok(())
+}
+
+// RSA public keys are made of the exponent length, the exponent and
+// the modulus (RFC 3110 section 2).
+fn verify_rsa(params: &'static signature::RsaParameters, public_key: &[u8], data: &[u8], signature: &[u8]) -> Result<bool> {
+    let (len, offset) = match public_key {
+        [0, high, low, ..] => (((*high as usize) << 8) | *low as usize, 3),
+        [len, ..] => (*len as usize, 1),
+        [] => return Err(InvalidData("empty RSA public key".to_string())),
+    };
+
+    if len == 0 || public_key.len() <= offset + len {
+        return Err(InvalidData("truncated RSA public key".to_string()));
+    }
+
+    let components = RsaPublicKeyComponents {
+        n: &public_key[offset + len..],
+        e: &public_key[offset..offset + len],
+    };
+
+    Ok(components.verify(params, data, signature).is_ok())
+}
+
+// ECDSA public keys are the point coordinates, without the uncompressed
+// point prefix expected by ring (RFC 6605 section 4).
+fn verify_ecdsa(params: &'static dyn VerificationAlgorithm, len: usize, public_key: &[u8], data: &[u8], signature: &[u8]) -> Result<bool> {
+    if public_key.len() != len {
+        return Err(InvalidData(format!("ECDSA public key must be {} bytes but got {}", len, public_key.len())));
+    }
+
+    let mut point = Vec::with_capacity(len + 1);
+    point.push(0x04);
+    point.extend_from_slice(public_key);
+    Ok(UnparsedPublicKey::new(params, point).verify(data, signature).is_ok())
+}
+
+/// Private key making signatures which can be checked with the public key
+/// of a DNSKEY record.
+pub struct SigningKey {
+    algorithm: Algorithm,
+    public_key: Vec<u8>,
+    pair: KeyPair,
+}
+
+enum KeyPair {
+    Rsa(RsaKeyPair),
+    Ecdsa(EcdsaKeyPair),
+    Ed25519(Ed25519KeyPair),
+}
+
+impl SigningKey {
+    /// Load a private key in the format of BIND `.private` key files, along
+    /// with the public key of its DNSKEY record:
+    ///
+    /// ```txt
+    /// Private-key-format: v1.3
+    /// Algorithm: 15 (ED25519)
+    /// PrivateKey: ODIyNjAzODQ2MjgwODAxMjI2NDUxOTAyMDQxNDIyNjI=
+    /// ```
+    pub fn from_private_key(public_key: &[u8], private_key: &str) -> Result<SigningKey> {
+        let fields: HashMap<&str, &str> = private_key
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim(), value.trim()))
+            .collect();
+
+        let field = |name: &str| -> Result<Vec<u8>> {
+            let value = fields
+                .get(name)
+                .ok_or_else(|| InvalidPresentation(format!("private key has no {} field", name)))?;
+            data_encoding::BASE64
+                .decode(value.as_bytes())
+                .map_err(|err| InvalidPresentation(format!("invalid {} field: {}", name, err)))
+        };
+
+        // The algorithm number is followed by its mnemonic in parentheses.
+        let algorithm = fields
+            .get("Algorithm")
+            .and_then(|value| value.split_whitespace().next())
+            .and_then(|number| number.parse().ok())
+            .map(Algorithm::from_u8)
+            .ok_or_else(|| InvalidPresentation("private key has no valid Algorithm field".to_string()))?;
+
+        let rng = SystemRandom::new();
+        let pair = match algorithm {
+            Algorithm::RsaSha256 | Algorithm::RsaSha512 => {
+                let components = KeyPairComponents {
+                    public_key: PublicKeyComponents { n: field("Modulus")?, e: field("PublicExponent")? },
+                    d: field("PrivateExponent")?,
+                    p: field("Prime1")?,
+                    q: field("Prime2")?,
+                    dP: field("Exponent1")?,
+                    dQ: field("Exponent2")?,
+                    qInv: field("Coefficient")?,
+                };
+
+                if rsa_public_key(&components.public_key.e, &components.public_key.n) != public_key {
+                    return Err(InvalidData("RSA private key doesn't match the public key".to_string()));
+                }
+
+                RsaKeyPair::from_components(&components).map(KeyPair::Rsa).map_err(|err| InvalidData(format!("invalid RSA private key: {}", err)))?
+            }
+            Algorithm::EcdsaP256Sha256 | Algorithm::EcdsaP384Sha384 => {
+                let params = match algorithm {
+                    Algorithm::EcdsaP256Sha256 => &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+                    _ => &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+                };
+
+                let mut point = vec![0x04];
+                point.extend_from_slice(public_key);
+                EcdsaKeyPair::from_private_key_and_public_key(params, &field("PrivateKey")?, &point, &rng)
+                    .map(KeyPair::Ecdsa)
+                    .map_err(|err| InvalidData(format!("invalid ECDSA private key: {}", err)))?
+            }
+            Algorithm::Ed25519 => Ed25519KeyPair::from_seed_and_public_key(&field("PrivateKey")?, public_key)
+                .map(KeyPair::Ed25519)
+                .map_err(|err| InvalidData(format!("invalid Ed25519 private key: {}", err)))?,
+            // RSA/SHA-1 is deprecated for signing (RFC 8624 section 3.1).
+            other => return Err(InvalidData(format!("unsupported signing algorithm {}", other.as_u8()))),
+        };
+
+        Ok(SigningKey { algorithm, public_key: public_key.to_vec(), pair })
+    }
+
+    /// Load an ECDSA or Ed25519 private key from a PKCS#8 document.
+    pub fn from_pkcs8(algorithm: Algorithm, pkcs8: &[u8]) -> Result<SigningKey> {
+        use ring::signature::KeyPair as _;
+
+        let rng = SystemRandom::new();
+        let (pair, public_key) = match algorithm {
+            Algorithm::EcdsaP256Sha256 | Algorithm::EcdsaP384Sha384 => {
+                let params = match algorithm {
+                    Algorithm::EcdsaP256Sha256 => &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+                    _ => &signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+                };
+
+                let pair = EcdsaKeyPair::from_pkcs8(params, pkcs8, &rng).map_err(|err| InvalidData(format!("invalid ECDSA private key: {}", err)))?;
+                // The uncompressed point prefix isn't part of DNSKEY records.
+                let public_key = pair.public_key().as_ref()[1..].to_vec();
+                (KeyPair::Ecdsa(pair), public_key)
+            }
+            Algorithm::Ed25519 => {
+                let pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8).map_err(|err| InvalidData(format!("invalid Ed25519 private key: {}", err)))?;
+                let public_key = pair.public_key().as_ref().to_vec();
+                (KeyPair::Ed25519(pair), public_key)
+            }
+            other => return Err(InvalidData(format!("unsupported PKCS#8 algorithm {}", other.as_u8()))),
+        };
+
+        Ok(SigningKey { algorithm, public_key, pair })
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Public key, as held by DNSKEY records.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Sign the data, in the signature format of the key algorithm.
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let rng = SystemRandom::new();
+        let failed = |_| InvalidSignature(format!("{:?} signing failed", self.algorithm));
+
+        match &self.pair {
+            KeyPair::Rsa(pair) => {
+                let padding = match self.algorithm {
+                    Algorithm::RsaSha512 => &signature::RSA_PKCS1_SHA512,
+                    _ => &signature::RSA_PKCS1_SHA256,
+                };
+
+                let mut signature = vec![0; pair.public().modulus_len()];
+                pair.sign(padding, &rng, data, &mut signature).map_err(failed)?;
+                Ok(signature)
+            }
+            KeyPair::Ecdsa(pair) => pair.sign(&rng, data).map(|signature| signature.as_ref().to_vec()).map_err(failed),
+            KeyPair::Ed25519(pair) => Ok(pair.sign(data).as_ref().to_vec()),
+        }
+    }
+}
+
+// Private keys are kept out of logs.
+impl Debug for SigningKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("SigningKey")
+            .field("algorithm", &self.algorithm)
+            .field("public_key", &data_encoding::BASE64.encode(&self.public_key))
+            .finish()
+    }
+}
+
+// RSA public key in the format of DNSKEY records (RFC 3110 section 2).
+fn rsa_public_key(exponent: &[u8], modulus: &[u8]) -> Vec<u8> {
+    let mut public_key = vec![];
+    if exponent.len() > u8::MAX as usize {
+        public_key.push(0);
+        public_key.extend_from_slice(&(exponent.len() as u16).to_be_bytes());
+    } else {
+        public_key.push(exponent.len() as u8);
+    }
+
+    public_key.extend_from_slice(exponent);
+    public_key.extend_from_slice(modulus);
+    public_key
+}
+
+/// Digest of the data, as used by DS records.
+pub fn digest(digest_type: DigestType, data: &[u8]) -> Result<Vec<u8>> {
+    let algorithm = match digest_type {
+        DigestType::Sha1 => &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        DigestType::Sha256 => &digest::SHA256,
+        DigestType::Sha384 => &digest::SHA384,
+        other => return Err(InvalidData(format!("unsupported digest type {}", other.as_u8()))),
+    };
+
+    Ok(digest::digest(algorithm, data).as_ref().to_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use ring::rand::SystemRandom;
+    use ring::signature::{self, EcdsaKeyPair, Ed25519KeyPair, KeyPair};
+
+    use crate::crypto::{digest, is_supported, verify, SigningKey};
+    use crate::records::dnssec::{Algorithm, DigestType};
+
+    #[test]
+    fn verify_ed25519() {
+        let key = Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap();
+        let signature = key.sign(b"data");
+
+        assert!(verify(Algorithm::Ed25519, key.public_key().as_ref(), b"data", signature.as_ref()).is_ok());
+        assert!(verify(Algorithm::Ed25519, key.public_key().as_ref(), b"other", signature.as_ref()).is_err());
+    }
+
+    #[test]
+    fn verify_ecdsa() {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&signature::ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let key = EcdsaKeyPair::from_pkcs8(&signature::ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+        let signature = key.sign(&rng, b"data").unwrap();
+
+        // The uncompressed point prefix isn't part of DNSKEY records.
+        let public_key = &key.public_key().as_ref()[1..];
+        assert!(verify(Algorithm::EcdsaP256Sha256, public_key, b"data", signature.as_ref()).is_ok());
+        assert!(verify(Algorithm::EcdsaP256Sha256, public_key, b"other", signature.as_ref()).is_err());
+        assert!(verify(Algorithm::EcdsaP384Sha384, public_key, b"data", signature.as_ref()).is_err());
+    }
+
+    #[test]
+    fn sign_with_private_key() {
+        // Key of RFC 6605 section 6.1.
+        let public_key = data_encoding::BASE64
+            .decode(b"GojIhhXUN/u4v54ZQqGSnyhWJwaubCvTmeexv7bR6edbkrSqQpF64cYbcB7wNcP+e+MAnLr+Wi9xMWyQLc8NAA==")
+            .unwrap();
+        let private_key = "Private-key-format: v1.2\n\
+            Algorithm: 13 (ECDSAP256SHA256)\n\
+            PrivateKey: GU6SnQ/Ou+xC5RumuIUIuJZteXT2z0O/ok1s38Et6mQ=\n";
+
+        let key = SigningKey::from_private_key(&public_key, private_key).unwrap();
+        assert_eq!(Algorithm::EcdsaP256Sha256, key.algorithm());
+        let signature = key.sign(b"data").unwrap();
+        assert!(verify(Algorithm::EcdsaP256Sha256, &public_key, b"data", &signature).is_ok());
+
+        // The private key must match the public key.
+        assert!(SigningKey::from_private_key(&[1; 64], private_key).is_err());
+        assert!(SigningKey::from_private_key(&public_key, "Algorithm: 13 (ECDSAP256SHA256)").is_err());
+        assert!(SigningKey::from_private_key(&public_key, "Algorithm: 5 (RSASHA1)\nPrivateKey: AA==").is_err());
+    }
+
+    #[test]
+    fn unsupported_algorithm() {
+        assert!(!is_supported(Algorithm::RsaMd5));
+        assert!(verify(Algorithm::RsaMd5, &[1, 3, 0], b"data", &[0]).is_err());
+        assert!(verify(Algorithm::RsaSha256, &[], b"data", &[0]).is_err());
+    }
+
+    #[test]
+    fn digests() {
+        assert_eq!(
+            data_encoding::HEXLOWER.decode(b"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad").unwrap(),
+            digest(DigestType::Sha256, b"abc").unwrap(),
+        );
+        assert_eq!(20, digest(DigestType::Sha1, b"abc").unwrap().len());
+        assert!(digest(DigestType::Gost, b"abc").is_err());
+    }
+}
@@ -1,8 +1,116 @@
 use crate::result::Result;
+use crate::seek::Seek;
 
-pub trait Deserializer : Sized {
-    fn deserialize_u8(self) -> Result<u8>;
-    fn deserialize_u16(self) -> Result<u16>;
-    fn deserialize_u32(self) -> Result<u32>;
-    fn deserialize_qname(self) -> Result<String>;
-}
\ No newline at end of file
+pub trait Deserializer {
+    fn deserialize_u8(&mut self) -> Result<u8>;
+    fn deserialize_u16(&mut self) -> Result<u16>;
+    fn deserialize_u32(&mut self) -> Result<u32>;
+    fn deserialize_qname(&mut self) -> Result<String>;
+
+    fn deserialize_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(len);
+        for _ in 0..len {
+            bytes.push(self.deserialize_u8()?);
+        }
+
+        Ok(bytes)
+    }
+}
+
+pub trait Deserialize: Sized {
+    fn deserialize<D>(deserializer: &mut D) -> Result<Self>
+        where
+            D: Deserializer + Seek;
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+
+    use crate::de::{Deserialize, Deserializer};
+    use crate::errors::Error::OutOfRange;
+    use crate::packet::Record;
+    use crate::result::Result;
+    use crate::seek::Seek;
+
+    // Minimal deserializer over a slice, without name compression support,
+    // relying on the default `deserialize_bytes`.
+    struct SliceDeserializer<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl Deserializer for SliceDeserializer<'_> {
+        fn deserialize_u8(&mut self) -> Result<u8> {
+            let byte = *self.data.get(self.pos).ok_or(OutOfRange { expected: self.pos + 1, max: self.data.len() })?;
+            self.pos += 1;
+            Ok(byte)
+        }
+
+        fn deserialize_u16(&mut self) -> Result<u16> {
+            Ok(((self.deserialize_u8()? as u16) << 8) | self.deserialize_u8()? as u16)
+        }
+
+        fn deserialize_u32(&mut self) -> Result<u32> {
+            Ok(((self.deserialize_u16()? as u32) << 16) | self.deserialize_u16()? as u32)
+        }
+
+        fn deserialize_qname(&mut self) -> Result<String> {
+            let mut labels = vec![];
+            loop {
+                let len = self.deserialize_u8()? as usize;
+                if len == 0 {
+                    return Ok(labels.join("."));
+                }
+
+                labels.push(String::from_utf8_lossy(&self.deserialize_bytes(len)?).to_string());
+            }
+        }
+    }
+
+    impl Seek for SliceDeserializer<'_> {
+        fn seek(&mut self, pos: usize) -> Result<()> {
+            self.pos = pos;
+            Ok(())
+        }
+
+        fn position(&self) -> usize {
+            self.pos
+        }
+    }
+
+    #[test]
+    fn custom_deserializer() {
+        let mut deserializer = SliceDeserializer {
+            data: &[
+                0x01, 0x78, // len=1 label="x"
+                0x00,
+                0x00, 0x01, // Type A.
+                0x00, 0x01, // Class IN.
+                0x00, 0x00, 0x00, 0x3C, // TTL.
+                0x00, 0x04, // RD length.
+                0x7F, 0x00, 0x00, 0x01, // Address.
+                0x01, 0x78, // len=1 label="x"
+                0x00,
+                0x00, 0x10, // Type TXT.
+                0x00, 0x01, // Class IN.
+                0x00, 0x00, 0x00, 0x3C, // TTL.
+                0x00, 0x03, // RD length.
+                0x02, 0x68, 0x69, // Text="hi".
+            ],
+            pos: 0,
+        };
+
+        match Record::deserialize(&mut deserializer).unwrap() {
+            Record::A(a) => assert_eq!(Ipv4Addr::new(127, 0, 0, 1), a.ip),
+            record => panic!("unexpected record {:?}", record),
+        }
+
+        match Record::deserialize(&mut deserializer).unwrap() {
+            Record::Text(txt) => assert_eq!(vec![b"hi".to_vec()], txt.strings),
+            record => panic!("unexpected record {:?}", record),
+        }
+
+        assert!(Record::deserialize(&mut deserializer).is_err());
+    }
+}
@@ -1,19 +1,34 @@
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io;
 
 #[derive(Debug)]
 pub enum Error {
     OutOfRange {
         expected: usize,
         max: usize,
-    }
+    },
+    InvalidData(String),
+    InvalidPresentation(String),
+    InvalidSignature(String),
+    Io(io::Error),
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        match *self {
+        match self {
             Error::OutOfRange { expected, max } => write!(f, "out of range error: expected {} but the limit is {}", expected, max),
+            Error::InvalidData(reason) => write!(f, "invalid data error: {}", reason),
+            Error::InvalidPresentation(reason) => write!(f, "invalid presentation error: {}", reason),
+            Error::InvalidSignature(reason) => write!(f, "invalid signature error: {}", reason),
+            Error::Io(err) => write!(f, "io error: {}", err),
         }
     }
 }
 
-impl std::error::Error for Error {}
\ No newline at end of file
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
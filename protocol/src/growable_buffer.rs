@@ -0,0 +1,154 @@
+use std::ops::DerefMut;
+
+use crate::errors::Error::OutOfRange;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::Serializer;
+
+/// Serializer over a buffer growing as needed, like a `Vec<u8>` or a
+/// `bytes::BytesMut`.
+///
+/// Serialization starts at the end of the buffer, so that its content
+/// (e.g. the length prefix of a TCP frame) is kept.
+pub struct GrowableBuffer<B> {
+    buf: B,
+    pos: usize,
+}
+
+impl<B> GrowableBuffer<B>
+    where B: DerefMut<Target = [u8]> + for<'a> Extend<&'a u8>
+{
+    pub fn new(buf: B) -> GrowableBuffer<B> {
+        let pos = buf.len();
+        GrowableBuffer { buf, pos }
+    }
+
+    pub fn get_ref(&self) -> &B {
+        &self.buf
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+impl Default for GrowableBuffer<Vec<u8>> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<B> Serializer for GrowableBuffer<B>
+    where B: DerefMut<Target = [u8]> + for<'a> Extend<&'a u8>
+{
+    fn serialize_u8(&mut self, value: u8) -> Result<()> {
+        self.serialize_bytes(&[value])
+    }
+
+    fn serialize_u16(&mut self, value: u16) -> Result<()> {
+        self.serialize_bytes(&value.to_be_bytes())
+    }
+
+    fn serialize_u32(&mut self, value: u32) -> Result<()> {
+        self.serialize_bytes(&value.to_be_bytes())
+    }
+
+    fn serialize_qname(&mut self, qname: &str) -> Result<()> {
+        // Skipping empty labels, the root domain ("") being
+        // only made of the terminating zero length label.
+        for label in qname.split('.').filter(|label| !label.is_empty()) {
+            self.serialize_u8(label.len() as u8)?;
+            self.serialize_bytes(label.as_bytes())?;
+        }
+
+        self.serialize_u8(0)
+    }
+
+    fn serialize_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        // Overwriting what follows the position, after seeking back,
+        // before growing the buffer.
+        let overlap = bytes.len().min(self.buf.len() - self.pos);
+        self.buf[self.pos..self.pos + overlap].copy_from_slice(&bytes[..overlap]);
+        self.buf.extend(&bytes[overlap..]);
+        self.pos += bytes.len();
+        Ok(())
+    }
+}
+
+impl<B> Seek for GrowableBuffer<B>
+    where B: DerefMut<Target = [u8]>
+{
+    fn seek(&mut self, pos: usize) -> Result<()> {
+        if pos > self.buf.len() {
+            return Err(OutOfRange {
+                expected: pos,
+                max: self.buf.len(),
+            });
+        }
+
+        self.pos = pos;
+        Ok(())
+    }
+
+    fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+
+    use crate::growable_buffer::GrowableBuffer;
+    use crate::packet::{Class, Packet, QueryType, Question};
+    use crate::seek::Seek;
+    use crate::ser::{Serialize, Serializer};
+
+    fn query() -> Packet {
+        let mut packet = Packet::new();
+        packet.header.id = 0x862a;
+        packet.questions.push(Question {
+            name: "example.com".to_string(),
+            qtype: QueryType::A,
+            class: Class::Internet,
+        });
+
+        packet
+    }
+
+    #[test]
+    fn serialize_vec() {
+        let mut serializer = GrowableBuffer::default();
+        assert!(query().serialize(&mut serializer).is_ok());
+        let bytes = serializer.into_inner();
+        assert_eq!(29, bytes.len());
+        assert_eq!(&[0x86, 0x2a], &bytes[..2]);
+        assert_eq!(&[0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x03, 0x63, 0x6f, 0x6d, 0x00], &bytes[12..25]);
+    }
+
+    #[test]
+    fn serialize_bytes_mut_frame() {
+        // Length prefixed frame, as sent over TCP.
+        let mut serializer = GrowableBuffer::new(BytesMut::from(&[0x00, 0x00][..]));
+        assert!(query().serialize(&mut serializer).is_ok());
+
+        let len = serializer.position() - 2;
+        assert!(serializer.seek(0).is_ok());
+        assert!(serializer.serialize_u16(len as u16).is_ok());
+
+        let bytes = serializer.into_inner();
+        assert_eq!(31, bytes.len());
+        assert_eq!(&[0x00, 0x1D, 0x86, 0x2a], &bytes[..4]);
+    }
+
+    #[test]
+    fn overwrite_after_seek() {
+        let mut serializer = GrowableBuffer::default();
+        assert!(serializer.serialize_u32(0xDEAD_BEEF).is_ok());
+        assert!(serializer.seek(3).is_ok());
+        assert!(serializer.serialize_u16(0xCAFE).is_ok());
+        assert_eq!(&[0xDE, 0xAD, 0xBE, 0xCA, 0xFE], serializer.get_ref().as_slice());
+
+        assert!(serializer.seek(6).is_err());
+    }
+}
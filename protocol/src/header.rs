@@ -1,9 +1,11 @@
-use crate::byte_packet_buffer::BytePacketBuffer;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::de::{Deserialize, Deserializer};
 use crate::result::Result;
 use crate::seek::Seek;
 use crate::ser::{Serialize, Serializer};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Header {
     pub id: u16,
     pub is_response: bool,
@@ -28,19 +30,35 @@ impl Default for Header {
     }
 }
 
+/// Kind of query of a message.
+///
+/// See: https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-5
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum OpCode {
+    // Unassigned opcodes, kept as is to be able to reply with them.
+    Unknown(u8),
     Query,
+    // Inverse query, obsoleted by RFC 3425.
     IQuery,
     Status,
+    // Zone change notification, see RFC 1996.
+    Notify,
+    // Dynamic update, see RFC 2136.
+    Update,
+    // DNS stateful operations, see RFC 8490.
+    Dso,
 }
 
 impl OpCode {
     pub fn from_u8(num: u8) -> OpCode {
         match num {
+            0 => OpCode::Query,
             1 => OpCode::IQuery,
             2 => OpCode::Status,
-            _ => OpCode::Query
+            4 => OpCode::Notify,
+            5 => OpCode::Update,
+            6 => OpCode::Dso,
+            _ => OpCode::Unknown(num),
         }
     }
 
@@ -49,33 +67,87 @@ impl OpCode {
             OpCode::Query => 0,
             OpCode::IQuery => 1,
             OpCode::Status => 2,
+            OpCode::Notify => 4,
+            OpCode::Update => 5,
+            OpCode::Dso => 6,
+            OpCode::Unknown(num) => num,
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// Result of a query, on 12 bits when the message has an OPT record
+/// carrying the upper 8 bits, 4 bits otherwise.
+///
+/// See: https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-6
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum ResultCode {
+    // Unassigned or private use result codes, kept as is.
+    Unknown(u16),
     NoError,
     FormError,
     ServerFailure,
     NxDomain,
     NotImplemented,
     Refused,
+    // Name exists when it should not, see RFC 2136.
+    YXDomain,
+    // RRset exists when it should not, see RFC 2136.
+    YXRRSet,
+    // RRset that should exist does not, see RFC 2136.
+    NXRRSet,
+    // Server not authoritative for zone (RFC 2136) or not authorized (RFC 8945).
+    NotAuth,
+    // Name not contained in zone, see RFC 2136.
+    NotZone,
+    // Bad OPT version (RFC 6891), or TSIG signature failure (RFC 8945).
+    BadVers,
+    // Key not recognized, see RFC 8945.
+    BadKey,
+    // Signature out of time window, see RFC 8945.
+    BadTime,
+    // Bad TKEY mode, see RFC 2930.
+    BadMode,
+    // Duplicate key name, see RFC 2930.
+    BadName,
+    // Algorithm not supported, see RFC 2930.
+    BadAlg,
+    // Bad truncation, see RFC 8945.
+    BadTrunc,
+    // Bad or missing server cookie, see RFC 7873.
+    BadCookie,
 }
 
 impl ResultCode {
-    pub fn from_u8(num: u8) -> ResultCode {
+    /// BADSIG shares its value with BADVERS, it is only used in the
+    /// error field of TSIG records.
+    pub const BAD_SIG: ResultCode = ResultCode::BadVers;
+
+    pub fn from_u16(num: u16) -> ResultCode {
         match num {
+            0 => ResultCode::NoError,
             1 => ResultCode::FormError,
             2 => ResultCode::ServerFailure,
             3 => ResultCode::NxDomain,
             4 => ResultCode::NotImplemented,
             5 => ResultCode::Refused,
-            _ => ResultCode::NoError,
+            6 => ResultCode::YXDomain,
+            7 => ResultCode::YXRRSet,
+            8 => ResultCode::NXRRSet,
+            9 => ResultCode::NotAuth,
+            10 => ResultCode::NotZone,
+            16 => ResultCode::BadVers,
+            17 => ResultCode::BadKey,
+            18 => ResultCode::BadTime,
+            19 => ResultCode::BadMode,
+            20 => ResultCode::BadName,
+            21 => ResultCode::BadAlg,
+            22 => ResultCode::BadTrunc,
+            23 => ResultCode::BadCookie,
+            _ => ResultCode::Unknown(num),
         }
     }
 
-    pub fn as_u8(&self) -> u8 {
+    pub fn as_u16(&self) -> u16 {
         match *self {
             ResultCode::NoError => 0,
             ResultCode::FormError => 1,
@@ -83,8 +155,57 @@ impl ResultCode {
             ResultCode::NxDomain => 3,
             ResultCode::NotImplemented => 4,
             ResultCode::Refused => 5,
+            ResultCode::YXDomain => 6,
+            ResultCode::YXRRSet => 7,
+            ResultCode::NXRRSet => 8,
+            ResultCode::NotAuth => 9,
+            ResultCode::NotZone => 10,
+            ResultCode::BadVers => 16,
+            ResultCode::BadKey => 17,
+            ResultCode::BadTime => 18,
+            ResultCode::BadMode => 19,
+            ResultCode::BadName => 20,
+            ResultCode::BadAlg => 21,
+            ResultCode::BadTrunc => 22,
+            ResultCode::BadCookie => 23,
+            ResultCode::Unknown(num) => num,
         }
     }
+
+    /// Whether the result code needs the extended bits of an OPT record
+    /// to be represented.
+    pub fn is_extended(&self) -> bool {
+        self.as_u16() > 0x0F
+    }
+}
+
+impl Display for ResultCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mnemonic = match *self {
+            ResultCode::NoError => "NOERROR",
+            ResultCode::FormError => "FORMERR",
+            ResultCode::ServerFailure => "SERVFAIL",
+            ResultCode::NxDomain => "NXDOMAIN",
+            ResultCode::NotImplemented => "NOTIMP",
+            ResultCode::Refused => "REFUSED",
+            ResultCode::YXDomain => "YXDOMAIN",
+            ResultCode::YXRRSet => "YXRRSET",
+            ResultCode::NXRRSet => "NXRRSET",
+            ResultCode::NotAuth => "NOTAUTH",
+            ResultCode::NotZone => "NOTZONE",
+            ResultCode::BadVers => "BADVERS",
+            ResultCode::BadKey => "BADKEY",
+            ResultCode::BadTime => "BADTIME",
+            ResultCode::BadMode => "BADMODE",
+            ResultCode::BadName => "BADNAME",
+            ResultCode::BadAlg => "BADALG",
+            ResultCode::BadTrunc => "BADTRUNC",
+            ResultCode::BadCookie => "BADCOOKIE",
+            ResultCode::Unknown(num) => return write!(f, "RCODE{}", num),
+        };
+
+        write!(f, "{}", mnemonic)
+    }
 }
 
 impl Header {
@@ -107,31 +228,36 @@ impl Header {
             total_additional_records: 0,
         }
     }
+}
 
-    pub fn from_buffer(buf: &mut BytePacketBuffer) -> Header {
+impl Deserialize for Header {
+    fn deserialize<D>(deserializer: &mut D) -> Result<Header>
+        where
+            D: Deserializer + Seek
+    {
         let mut header = Header::new();
-        header.id = buf.read_u16();
+        header.id = deserializer.deserialize_u16()?;
 
-        let byte = buf.read_u8();
+        let byte = deserializer.deserialize_u8()?;
         header.is_response = byte >> 7 > 0;
         header.opcode = OpCode::from_u8((byte >> 3) & 0x0F);
         header.authoritative_answer = byte & (1 << 2) > 0;
         header.truncated = byte & (1 << 1) > 0;
         header.recursion_desired = byte & 1 > 0;
 
-        let byte = buf.read_u8();
+        let byte = deserializer.deserialize_u8()?;
         header.recursion_available = byte >> 7 > 0;
         header.z = byte & (1 << 6) > 0;
         header.authenticated_data = byte & (1 << 5) > 0;
         header.checking_disabled = byte & (1 << 4) > 0;
-        header.result_code = ResultCode::from_u8(byte & 0x0F);
+        header.result_code = ResultCode::from_u16((byte & 0x0F) as u16);
 
-        header.total_questions = buf.read_u16();
-        header.total_answer_records = buf.read_u16();
-        header.total_authority_records = buf.read_u16();
-        header.total_additional_records = buf.read_u16();
+        header.total_questions = deserializer.deserialize_u16()?;
+        header.total_answer_records = deserializer.deserialize_u16()?;
+        header.total_authority_records = deserializer.deserialize_u16()?;
+        header.total_additional_records = deserializer.deserialize_u16()?;
 
-        header
+        Ok(header)
     }
 }
 
@@ -146,11 +272,13 @@ impl Serialize for Header {
         byte |= self.recursion_desired as u8;
         byte |= (self.truncated as u8) << 1;
         byte |= (self.authoritative_answer as u8) << 2;
-        byte |= self.opcode.as_u8() << 3;
+        byte |= (self.opcode.as_u8() & 0x0F) << 3;
         byte |= (self.is_response as u8) << 7;
         serializer.serialize_u8(byte)?;
 
-        byte = self.result_code.as_u8();
+        // Only the lower 4 bits of the result code are part of the header,
+        // the upper ones are carried by the OPT record if any.
+        byte = (self.result_code.as_u16() & 0x0F) as u8;
         byte |= (self.checking_disabled as u8) << 4;
         byte |= (self.authenticated_data as u8) << 5;
         byte |= (self.z as u8) << 6;
@@ -167,6 +295,7 @@ impl Serialize for Header {
 #[cfg(test)]
 mod test {
     use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::de::Deserialize;
     use crate::header::{Header, OpCode, ResultCode};
     use crate::ser::Serialize;
 
@@ -180,7 +309,7 @@ mod test {
         ];
 
         let mut buffer = BytePacketBuffer::from_raw_data(packet);
-        let header = Header::from_buffer(&mut buffer);
+        let header = Header::deserialize(&mut buffer).unwrap();
 
         assert_eq!(Header {
             id: 23099,
@@ -231,4 +360,35 @@ mod test {
             0x00, 0x00, 0x00, 0x00
         ], serializer.bytes().as_slice());
     }
+
+    #[test]
+    fn opcode_round_trip() {
+        for opcode in [OpCode::Notify, OpCode::Update, OpCode::Dso, OpCode::Unknown(3), OpCode::Unknown(15)].iter() {
+            let header = Header {
+                is_response: true,
+                opcode: *opcode,
+                ..Header::new()
+            };
+
+            let mut serializer = BytePacketBuffer::new();
+            assert!(header.serialize(&mut serializer).is_ok());
+
+            let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+            assert_eq!(header, Header::deserialize(&mut buffer).unwrap());
+        }
+    }
+
+    #[test]
+    fn result_code_round_trip() {
+        for num in 0..=0x0FFF {
+            assert_eq!(num, ResultCode::from_u16(num).as_u16());
+        }
+
+        assert_eq!(ResultCode::YXDomain, ResultCode::from_u16(6));
+        assert_eq!(ResultCode::BAD_SIG, ResultCode::from_u16(16));
+        assert_eq!("NOTAUTH", ResultCode::NotAuth.to_string());
+        assert_eq!("RCODE3841", ResultCode::from_u16(3841).to_string());
+        assert!(ResultCode::BadCookie.is_extended());
+        assert!(!ResultCode::NotZone.is_extended());
+    }
 }
@@ -0,0 +1,142 @@
+use std::io::{Seek as IoSeek, SeekFrom, Write};
+
+use crate::errors::Error::OutOfRange;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::Serializer;
+
+/// Serializer over a seekable writer, like a file.
+///
+/// Positions are relative to the position of the writer on creation,
+/// so that messages can be appended to a stream (e.g. a pcap file).
+pub struct IoSerializer<W> {
+    writer: W,
+    start: u64,
+    pos: usize,
+    end: usize,
+}
+
+impl<W> IoSerializer<W>
+    where W: Write + IoSeek
+{
+    pub fn new(mut writer: W) -> Result<IoSerializer<W>> {
+        let start = writer.stream_position()?;
+
+        Ok(IoSerializer { writer, start, pos: 0, end: 0 })
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Return the writer, positioned after the serialized data.
+    pub fn into_inner(mut self) -> Result<W> {
+        self.writer.seek(SeekFrom::Start(self.start + self.end as u64))?;
+        Ok(self.writer)
+    }
+}
+
+impl<W> Serializer for IoSerializer<W>
+    where W: Write + IoSeek
+{
+    fn serialize_u8(&mut self, value: u8) -> Result<()> {
+        self.serialize_bytes(&[value])
+    }
+
+    fn serialize_u16(&mut self, value: u16) -> Result<()> {
+        self.serialize_bytes(&value.to_be_bytes())
+    }
+
+    fn serialize_u32(&mut self, value: u32) -> Result<()> {
+        self.serialize_bytes(&value.to_be_bytes())
+    }
+
+    fn serialize_qname(&mut self, qname: &str) -> Result<()> {
+        // Skipping empty labels, the root domain ("") being
+        // only made of the terminating zero length label.
+        for label in qname.split('.').filter(|label| !label.is_empty()) {
+            self.serialize_u8(label.len() as u8)?;
+            self.serialize_bytes(label.as_bytes())?;
+        }
+
+        self.serialize_u8(0)
+    }
+
+    fn serialize_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_all(bytes)?;
+        self.pos += bytes.len();
+        self.end = self.end.max(self.pos);
+        Ok(())
+    }
+}
+
+impl<W> Seek for IoSerializer<W>
+    where W: Write + IoSeek
+{
+    fn seek(&mut self, pos: usize) -> Result<()> {
+        // Seeking past the written data would leave a gap in the stream.
+        if pos > self.end {
+            return Err(OutOfRange {
+                expected: pos,
+                max: self.end,
+            });
+        }
+
+        self.writer.seek(SeekFrom::Start(self.start + pos as u64))?;
+        self.pos = pos;
+        Ok(())
+    }
+
+    fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Cursor, Write};
+
+    use crate::io_serializer::IoSerializer;
+    use crate::packet::{Class, Packet, QueryType, Question};
+    use crate::seek::Seek;
+    use crate::ser::{Serialize, Serializer};
+
+    #[test]
+    fn serialize_to_writer() {
+        let mut packet = Packet::new();
+        packet.header.id = 0x862a;
+        packet.questions.push(Question {
+            name: "example.com".to_string(),
+            qtype: QueryType::A,
+            class: Class::Internet,
+        });
+
+        let mut writer = Cursor::new(Vec::new());
+        writer.write_all(&[0xFF; 4]).unwrap();
+
+        let mut serializer = IoSerializer::new(writer).unwrap();
+        assert!(packet.serialize(&mut serializer).is_ok());
+        assert_eq!(29, serializer.position());
+
+        let mut writer = serializer.into_inner().unwrap();
+        writer.write_all(&[0xEE]).unwrap();
+
+        let bytes = writer.into_inner();
+        assert_eq!(34, bytes.len());
+        assert_eq!(&[0xFF, 0xFF, 0xFF, 0xFF, 0x86, 0x2a], &bytes[..6]);
+        assert_eq!(0xEE, bytes[33]);
+    }
+
+    #[test]
+    fn overwrite_after_seek() {
+        let mut serializer = IoSerializer::new(Cursor::new(Vec::new())).unwrap();
+        assert!(serializer.serialize_u32(0xDEAD_BEEF).is_ok());
+        assert!(serializer.seek(1).is_ok());
+        assert!(serializer.serialize_u16(0xCAFE).is_ok());
+        assert_eq!(3, serializer.position());
+        assert!(serializer.seek(5).is_err());
+
+        let bytes = serializer.into_inner().unwrap().into_inner();
+        assert_eq!(vec![0xDE, 0xCA, 0xFE, 0xEF], bytes);
+    }
+}
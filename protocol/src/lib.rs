@@ -1,9 +1,21 @@
+pub mod builder;
 pub mod byte_packet_buffer;
+pub mod crypto;
+pub mod de;
+pub mod errors;
+pub mod growable_buffer;
 pub mod header;
+pub mod io_serializer;
 pub mod packet;
+pub mod presentation;
 pub mod records;
+pub mod result;
+pub mod reverse;
+pub mod rrset;
+pub mod seek;
 pub mod ser;
-mod de;
-mod seek;
-mod errors;
-mod result;
+pub mod sig0;
+pub mod signing;
+pub mod tsig;
+pub mod validation;
+pub mod view;
@@ -1,14 +1,20 @@
-use std::net::Ipv4Addr;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
 use std::time::Duration;
 
-use crate::byte_packet_buffer::BytePacketBuffer;
-use crate::header::Header;
+use crate::builder::PacketBuilder;
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::header::{Header, ResultCode};
+use crate::presentation::{tokenize, RecordHeader};
 use crate::records;
 use crate::result::Result;
+use crate::rrset::{self, RRset};
 use crate::seek::Seek;
 use crate::ser::{Serialize, Serializer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Packet {
     pub header: Header,
     pub questions: Vec<Question>,
@@ -34,31 +40,121 @@ impl Packet {
         }
     }
 
-    pub fn from_buffer(buf: &mut BytePacketBuffer) -> Packet {
+    /// Start building a query for the given name and type, in the Internet class.
+    pub fn query<S>(name: S, qtype: QueryType) -> PacketBuilder
+        where S: ToString
+    {
+        let mut packet = Packet::new();
+        packet.questions.push(Question {
+            name: name.to_string(),
+            qtype,
+            class: Class::Internet,
+        });
+
+        PacketBuilder::new(packet)
+    }
+
+    /// Start building a response to this request, with its ID, opcode,
+    /// questions and recursion desired flag.
+    pub fn reply(&self) -> PacketBuilder {
         let mut packet = Packet::new();
-        packet.header = Header::from_buffer(buf);
+        packet.header.id = self.header.id;
+        packet.header.is_response = true;
+        packet.header.opcode = self.header.opcode;
+        packet.header.recursion_desired = self.header.recursion_desired;
+        packet.questions = self.questions.clone();
+
+        PacketBuilder::new(packet)
+    }
+
+    /// EDNS parameters of the message, from its OPT record.
+    pub fn edns(&self) -> Option<&records::OPT> {
+        self.additionals.iter().find_map(|record| match record {
+            Record::Opt(opt) => Some(opt),
+            _ => None,
+        })
+    }
+
+    pub fn edns_mut(&mut self) -> Option<&mut records::OPT> {
+        self.additionals.iter_mut().find_map(|record| match record {
+            Record::Opt(opt) => Some(opt),
+            _ => None,
+        })
+    }
+
+    /// Transaction signature of the message, which is its last record.
+    pub fn tsig(&self) -> Option<&records::TSIG> {
+        match self.additionals.last() {
+            Some(Record::TransactionSignature(tsig)) => Some(tsig),
+            _ => None,
+        }
+    }
+
+    /// SIG(0) signature of the message, which is its last record.
+    pub fn sig0(&self) -> Option<&records::SIG> {
+        match self.additionals.last() {
+            Some(Record::SecuritySignature(sig)) => Some(sig),
+            _ => None,
+        }
+    }
+
+    pub fn answer_rrsets(&self) -> Vec<RRset> {
+        RRset::group(self.answers.iter().cloned())
+    }
+
+    pub fn authority_rrsets(&self) -> Vec<RRset> {
+        RRset::group(self.authorities.iter().cloned())
+    }
+
+    /// RRsets of the additional section, without the OPT, TSIG and SIG(0) records.
+    pub fn additional_rrsets(&self) -> Vec<RRset> {
+        RRset::group(self.additionals.iter().cloned())
+    }
+
+    /// Remove the records duplicated within each section, which only
+    /// differ by their TTL if any, keeping the first occurrence.
+    pub fn dedup(&mut self) {
+        rrset::dedup(&mut self.answers);
+        rrset::dedup(&mut self.authorities);
+        rrset::dedup(&mut self.additionals);
+    }
+}
+
+impl Deserialize for Packet {
+    fn deserialize<D>(deserializer: &mut D) -> Result<Packet>
+        where
+            D: Deserializer + Seek
+    {
+        let mut packet = Packet::new();
+        packet.header = Header::deserialize(deserializer)?;
 
         packet.questions = Vec::with_capacity(packet.header.total_questions as usize);
         for _ in 0..packet.header.total_questions {
-            packet.questions.push(Question::from_buffer(buf));
+            packet.questions.push(Question::deserialize(deserializer)?);
         }
 
         packet.answers = Vec::with_capacity(packet.header.total_answer_records as usize);
         for _ in 0..packet.header.total_answer_records {
-            packet.answers.push(Record::from_buffer(buf));
+            packet.answers.push(Record::deserialize(deserializer)?);
         }
 
         packet.authorities = Vec::with_capacity(packet.header.total_authority_records as usize);
         for _ in 0..packet.header.total_authority_records {
-            packet.authorities.push(Record::from_buffer(buf));
+            packet.authorities.push(Record::deserialize(deserializer)?);
         }
 
         packet.additionals = Vec::with_capacity(packet.header.total_additional_records as usize);
         for _ in 0..packet.header.total_additional_records {
-            packet.additionals.push(Record::from_buffer(buf));
+            packet.additionals.push(Record::deserialize(deserializer)?);
+        }
+
+        // The OPT record holds the upper 8 bits of the result code.
+        if let Some(extended_rcode) = packet.edns().map(|opt| opt.extended_rcode) {
+            let rcode = ((extended_rcode as u16) << 4) | packet.header.result_code.as_u16();
+            packet.header.result_code = ResultCode::from_u16(rcode);
         }
 
-        packet
+        Ok(packet)
     }
 }
 
@@ -67,7 +163,20 @@ impl Serialize for Packet {
         where
             S: Serializer + Seek,
     {
-        self.header.serialize(serializer)?;
+        // Section counts are derived from the sections themselves
+        // so that records added to a packet are always announced.
+        let mut header = self.header.clone();
+        header.total_questions = self.questions.len() as u16;
+        header.total_answer_records = self.answers.len() as u16;
+        header.total_authority_records = self.authorities.len() as u16;
+        header.total_additional_records = self.additionals.len() as u16;
+        header.serialize(serializer)?;
+
+        // Extended result codes can only be sent along with an OPT record.
+        let rcode = self.header.result_code;
+        if rcode.is_extended() && self.edns().is_none() {
+            return Err(InvalidData(format!("result code {} requires an OPT record", rcode)));
+        }
 
         for question in self.questions.iter() {
             question.serialize(serializer)?;
@@ -82,14 +191,21 @@ impl Serialize for Packet {
         }
 
         for additional in self.additionals.iter() {
-            additional.serialize(serializer)?;
+            match additional {
+                Record::Opt(opt) => {
+                    let mut opt = opt.clone();
+                    opt.extended_rcode = (rcode.as_u16() >> 4) as u8;
+                    opt.serialize(serializer)?;
+                }
+                _ => additional.serialize(serializer)?,
+            }
         }
 
         Ok(())
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum QueryType {
     Unknown(u16),
     // A, IPv4 address.
@@ -124,6 +240,46 @@ pub enum QueryType {
     MailExchange,
     // TXT, Text strings.
     Text,
+    // SIG, Security signature.
+    SecuritySignature,
+    // KEY, Security key.
+    SecurityKey,
+    // SRV, Service location.
+    Service,
+    // NAPTR, Naming authority pointer.
+    NamingAuthorityPointer,
+    // OPT, EDNS pseudo-record.
+    Opt,
+    // DS, Delegation signer.
+    DelegationSigner,
+    // SSHFP, SSH key fingerprint.
+    SshFingerprint,
+    // RRSIG, DNSSEC signature.
+    Signature,
+    // NSEC, Next secure record.
+    NextSecure,
+    // DNSKEY, DNSSEC public key.
+    DnsKey,
+    // NSEC3, Hashed next secure record.
+    NextSecure3,
+    // NSEC3PARAM, NSEC3 hashing parameters.
+    Nsec3Parameters,
+    // TLSA, TLS certificate association.
+    TlsAssociation,
+    // CDS, Child copy of a DS record.
+    ChildDelegationSigner,
+    // CDNSKEY, Child copy of a DNSKEY record.
+    ChildDnsKey,
+    // SVCB, General purpose service binding.
+    ServiceBinding,
+    // HTTPS, Service binding for HTTP origins.
+    Https,
+    // URI, Uniform resource identifier.
+    Uri,
+    // CAA, Certification authority authorization.
+    CertificationAuthorityAuthorization,
+    // TSIG, Transaction signature.
+    TransactionSignature,
 }
 
 impl QueryType {
@@ -145,6 +301,26 @@ impl QueryType {
             14 => QueryType::MailInformation,
             15 => QueryType::MailExchange,
             16 => QueryType::Text,
+            24 => QueryType::SecuritySignature,
+            25 => QueryType::SecurityKey,
+            33 => QueryType::Service,
+            35 => QueryType::NamingAuthorityPointer,
+            41 => QueryType::Opt,
+            43 => QueryType::DelegationSigner,
+            44 => QueryType::SshFingerprint,
+            46 => QueryType::Signature,
+            47 => QueryType::NextSecure,
+            48 => QueryType::DnsKey,
+            50 => QueryType::NextSecure3,
+            51 => QueryType::Nsec3Parameters,
+            52 => QueryType::TlsAssociation,
+            59 => QueryType::ChildDelegationSigner,
+            60 => QueryType::ChildDnsKey,
+            64 => QueryType::ServiceBinding,
+            65 => QueryType::Https,
+            250 => QueryType::TransactionSignature,
+            256 => QueryType::Uri,
+            257 => QueryType::CertificationAuthorityAuthorization,
             _ => QueryType::Unknown(num),
         }
     }
@@ -167,25 +343,221 @@ impl QueryType {
             QueryType::MailInformation => 14,
             QueryType::MailExchange => 15,
             QueryType::Text => 16,
+            QueryType::SecuritySignature => 24,
+            QueryType::SecurityKey => 25,
+            QueryType::Service => 33,
+            QueryType::NamingAuthorityPointer => 35,
+            QueryType::Opt => 41,
+            QueryType::DelegationSigner => 43,
+            QueryType::SshFingerprint => 44,
+            QueryType::Signature => 46,
+            QueryType::NextSecure => 47,
+            QueryType::DnsKey => 48,
+            QueryType::NextSecure3 => 50,
+            QueryType::Nsec3Parameters => 51,
+            QueryType::TlsAssociation => 52,
+            QueryType::ChildDelegationSigner => 59,
+            QueryType::ChildDnsKey => 60,
+            QueryType::ServiceBinding => 64,
+            QueryType::Https => 65,
+            QueryType::Uri => 256,
+            QueryType::CertificationAuthorityAuthorization => 257,
+            QueryType::TransactionSignature => 250,
             QueryType::Unknown(num) => num,
         }
     }
 }
 
-#[derive(Debug)]
+impl Display for QueryType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mnemonic = match *self {
+            QueryType::A => "A",
+            QueryType::AuthoritativeNameServer => "NS",
+            QueryType::MailDestination => "MD",
+            QueryType::MailForwarder => "MF",
+            QueryType::CanonicalName => "CNAME",
+            QueryType::StartOfAuthority => "SOA",
+            QueryType::Mailbox => "MB",
+            QueryType::MailGroup => "MG",
+            QueryType::MailRename => "MR",
+            QueryType::Null => "NULL",
+            QueryType::WellKnownService => "WKS",
+            QueryType::DomainPointer => "PTR",
+            QueryType::HostInformation => "HINFO",
+            QueryType::MailInformation => "MINFO",
+            QueryType::MailExchange => "MX",
+            QueryType::Text => "TXT",
+            QueryType::SecuritySignature => "SIG",
+            QueryType::SecurityKey => "KEY",
+            QueryType::Service => "SRV",
+            QueryType::NamingAuthorityPointer => "NAPTR",
+            QueryType::Opt => "OPT",
+            QueryType::DelegationSigner => "DS",
+            QueryType::SshFingerprint => "SSHFP",
+            QueryType::Signature => "RRSIG",
+            QueryType::NextSecure => "NSEC",
+            QueryType::DnsKey => "DNSKEY",
+            QueryType::NextSecure3 => "NSEC3",
+            QueryType::Nsec3Parameters => "NSEC3PARAM",
+            QueryType::TlsAssociation => "TLSA",
+            QueryType::ChildDelegationSigner => "CDS",
+            QueryType::ChildDnsKey => "CDNSKEY",
+            QueryType::ServiceBinding => "SVCB",
+            QueryType::Https => "HTTPS",
+            QueryType::Uri => "URI",
+            QueryType::CertificationAuthorityAuthorization => "CAA",
+            QueryType::TransactionSignature => "TSIG",
+            // Generic type syntax (RFC 3597 section 5).
+            QueryType::Unknown(num) => return write!(f, "TYPE{}", num),
+        };
+
+        f.write_str(mnemonic)
+    }
+}
+
+impl FromStr for QueryType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<QueryType> {
+        let qtype = match s.to_uppercase().as_str() {
+            "A" => QueryType::A,
+            "NS" => QueryType::AuthoritativeNameServer,
+            "MD" => QueryType::MailDestination,
+            "MF" => QueryType::MailForwarder,
+            "CNAME" => QueryType::CanonicalName,
+            "SOA" => QueryType::StartOfAuthority,
+            "MB" => QueryType::Mailbox,
+            "MG" => QueryType::MailGroup,
+            "MR" => QueryType::MailRename,
+            "NULL" => QueryType::Null,
+            "WKS" => QueryType::WellKnownService,
+            "PTR" => QueryType::DomainPointer,
+            "HINFO" => QueryType::HostInformation,
+            "MINFO" => QueryType::MailInformation,
+            "MX" => QueryType::MailExchange,
+            "TXT" => QueryType::Text,
+            "SIG" => QueryType::SecuritySignature,
+            "KEY" => QueryType::SecurityKey,
+            "SRV" => QueryType::Service,
+            "NAPTR" => QueryType::NamingAuthorityPointer,
+            "OPT" => QueryType::Opt,
+            "DS" => QueryType::DelegationSigner,
+            "SSHFP" => QueryType::SshFingerprint,
+            "RRSIG" => QueryType::Signature,
+            "NSEC" => QueryType::NextSecure,
+            "DNSKEY" => QueryType::DnsKey,
+            "NSEC3" => QueryType::NextSecure3,
+            "NSEC3PARAM" => QueryType::Nsec3Parameters,
+            "TLSA" => QueryType::TlsAssociation,
+            "CDS" => QueryType::ChildDelegationSigner,
+            "CDNSKEY" => QueryType::ChildDnsKey,
+            "SVCB" => QueryType::ServiceBinding,
+            "HTTPS" => QueryType::Https,
+            "URI" => QueryType::Uri,
+            "CAA" => QueryType::CertificationAuthorityAuthorization,
+            "TSIG" => QueryType::TransactionSignature,
+            other => match other.strip_prefix("TYPE").map(str::parse::<u16>) {
+                Some(Ok(num)) => QueryType::from_u16(num),
+                _ => return Err(InvalidPresentation(format!("unknown record type: {}", s))),
+            },
+        };
+
+        Ok(qtype)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Class {
+    Unknown(u16),
+    // IN, Internet.
+    Internet,
+    // CH, Chaos.
+    Chaos,
+    // HS, Hesiod.
+    Hesiod,
+    // NONE, Used by dynamic updates to delete records (RFC 2136).
+    None,
+    // ANY, Matches any class in questions and dynamic updates (RFC 2136).
+    Any,
+}
+
+impl Class {
+    pub fn from_u16(num: u16) -> Class {
+        match num {
+            1 => Class::Internet,
+            3 => Class::Chaos,
+            4 => Class::Hesiod,
+            254 => Class::None,
+            255 => Class::Any,
+            _ => Class::Unknown(num),
+        }
+    }
+
+    pub fn as_u16(&self) -> u16 {
+        match *self {
+            Class::Internet => 1,
+            Class::Chaos => 3,
+            Class::Hesiod => 4,
+            Class::None => 254,
+            Class::Any => 255,
+            Class::Unknown(num) => num,
+        }
+    }
+}
+
+impl Display for Class {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mnemonic = match *self {
+            Class::Internet => "IN",
+            Class::Chaos => "CH",
+            Class::Hesiod => "HS",
+            Class::None => "NONE",
+            Class::Any => "ANY",
+            // Generic class syntax (RFC 3597 section 5).
+            Class::Unknown(num) => return write!(f, "CLASS{}", num),
+        };
+
+        f.write_str(mnemonic)
+    }
+}
+
+impl FromStr for Class {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Class> {
+        let class = match s.to_uppercase().as_str() {
+            "IN" => Class::Internet,
+            "CH" => Class::Chaos,
+            "HS" => Class::Hesiod,
+            "NONE" => Class::None,
+            "ANY" => Class::Any,
+            other => match other.strip_prefix("CLASS").map(str::parse::<u16>) {
+                Some(Ok(num)) => Class::from_u16(num),
+                _ => return Err(InvalidPresentation(format!("unknown class: {}", s))),
+            },
+        };
+
+        Ok(class)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Question {
     pub name: String,
     pub qtype: QueryType,
-    pub _class: u16,
+    pub class: Class,
 }
 
-impl Question {
-    fn from_buffer(buf: &mut BytePacketBuffer) -> Question {
-        Question {
-            name: buf.read_qname(),
-            qtype: QueryType::from_u16(buf.read_u16()),
-            _class: buf.read_u16(),
-        }
+impl Deserialize for Question {
+    fn deserialize<D>(deserializer: &mut D) -> Result<Question>
+        where
+            D: Deserializer + Seek
+    {
+        Ok(Question {
+            name: deserializer.deserialize_qname()?,
+            qtype: QueryType::from_u16(deserializer.deserialize_u16()?),
+            class: Class::from_u16(deserializer.deserialize_u16()?),
+        })
     }
 }
 
@@ -196,70 +568,292 @@ impl Serialize for Question {
     {
         serializer.serialize_qname(&self.name)?;
         serializer.serialize_u16(self.qtype.as_u16())?;
-        serializer.serialize_u16(1)
+        serializer.serialize_u16(self.class.as_u16())
     }
 }
 
-#[derive(Debug)]
+// Match a record, binding the typed records sharing the owner, class and
+// TTL fields to `$fields`, the other variants being matched explicitly.
+macro_rules! match_common_fields {
+    ($record:expr, $fields:ident => $expr:expr, $($pattern:pat => $other:expr),* $(,)?) => {
+        match $record {
+            Record::A($fields) => $expr,
+            Record::AuthoritativeNameServer($fields) => $expr,
+            Record::CanonicalName($fields) => $expr,
+            Record::MailDestination($fields) => $expr,
+            Record::MailForwarder($fields) => $expr,
+            Record::StartOfAuthority($fields) => $expr,
+            Record::Mailbox($fields) => $expr,
+            Record::MailGroup($fields) => $expr,
+            Record::MailRename($fields) => $expr,
+            Record::Null($fields) => $expr,
+            Record::WellKnownService($fields) => $expr,
+            Record::HostInformation($fields) => $expr,
+            Record::MailInformation($fields) => $expr,
+            Record::MailExchange($fields) => $expr,
+            Record::DomainPointer($fields) => $expr,
+            Record::Text($fields) => $expr,
+            Record::Service($fields) => $expr,
+            Record::NamingAuthorityPointer($fields) => $expr,
+            Record::DelegationSigner($fields) => $expr,
+            Record::SshFingerprint($fields) => $expr,
+            Record::Signature($fields) => $expr,
+            Record::NextSecure($fields) => $expr,
+            Record::DnsKey($fields) => $expr,
+            Record::NextSecure3($fields) => $expr,
+            Record::Nsec3Parameters($fields) => $expr,
+            Record::TlsAssociation($fields) => $expr,
+            Record::ServiceBinding($fields) => $expr,
+            Record::Uri($fields) => $expr,
+            Record::CertificationAuthorityAuthorization($fields) => $expr,
+            Record::TransactionSignature($fields) => $expr,
+            Record::Https(records::HTTPS($fields)) => $expr,
+            Record::ChildDelegationSigner(records::CDS($fields)) => $expr,
+            Record::ChildDnsKey(records::CDNSKEY($fields)) => $expr,
+            Record::SecuritySignature(records::SIG($fields)) => $expr,
+            Record::SecurityKey(records::KEY($fields)) => $expr,
+            Record::Custom($fields) => $expr,
+            $($pattern => $other),*
+        }
+    };
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Record {
     Unknown {
         domain: String,
         qtype: QueryType,
-        _class: u16,
+        class: Class,
         ttl: Duration,
         data: Vec<u8>,
     },
     A(records::A),
     AuthoritativeNameServer(records::AuthoritativeNameServer),
     CanonicalName(records::CName),
+    MailDestination(records::MD),
+    MailForwarder(records::MF),
+    StartOfAuthority(records::SOA),
+    Mailbox(records::MB),
+    MailGroup(records::MG),
+    MailRename(records::MR),
+    Null(records::NULL),
+    WellKnownService(records::WKS),
+    HostInformation(records::HINFO),
+    MailInformation(records::MINFO),
     MailExchange(records::MailExchange),
+    DomainPointer(records::PTR),
+    Text(records::TXT),
+    SecuritySignature(records::SIG),
+    SecurityKey(records::KEY),
+    Service(records::SRV),
+    NamingAuthorityPointer(records::NAPTR),
+    Opt(records::OPT),
+    DelegationSigner(records::DS),
+    SshFingerprint(records::SSHFP),
+    Signature(records::RRSIG),
+    NextSecure(records::NSEC),
+    DnsKey(records::DNSKEY),
+    NextSecure3(records::NSEC3),
+    Nsec3Parameters(records::NSEC3PARAM),
+    TlsAssociation(records::TLSA),
+    ChildDelegationSigner(records::CDS),
+    ChildDnsKey(records::CDNSKEY),
+    ServiceBinding(records::SVCB),
+    Https(records::HTTPS),
+    Uri(records::URI),
+    CertificationAuthorityAuthorization(records::CAA),
+    TransactionSignature(records::TSIG),
+    // Record of a type registered with `records::custom::register`.
+    Custom(records::CustomRecord),
 }
 
 impl Record {
-    fn from_buffer(buf: &mut BytePacketBuffer) -> Record {
-        let domain = buf.read_qname();
-        let qtype = QueryType::from_u16(buf.read_u16());
-        let class = buf.read_u16();
-        let ttl = Duration::from_secs(buf.read_u32() as u64);
-        let len = buf.read_u16();
+    /// Owner name, the root for OPT records.
+    pub fn domain(&self) -> &str {
+        match_common_fields!(self, record => record.domain.as_str(),
+            Record::Unknown { domain, .. } => domain.as_str(),
+            Record::Opt(_) => "",
+        )
+    }
 
+    pub fn qtype(&self) -> QueryType {
+        match self {
+            Record::Unknown { qtype, .. } => *qtype,
+            Record::A(_) => QueryType::A,
+            Record::AuthoritativeNameServer(_) => QueryType::AuthoritativeNameServer,
+            Record::CanonicalName(_) => QueryType::CanonicalName,
+            Record::MailDestination(_) => QueryType::MailDestination,
+            Record::MailForwarder(_) => QueryType::MailForwarder,
+            Record::StartOfAuthority(_) => QueryType::StartOfAuthority,
+            Record::Mailbox(_) => QueryType::Mailbox,
+            Record::MailGroup(_) => QueryType::MailGroup,
+            Record::MailRename(_) => QueryType::MailRename,
+            Record::Null(_) => QueryType::Null,
+            Record::WellKnownService(_) => QueryType::WellKnownService,
+            Record::HostInformation(_) => QueryType::HostInformation,
+            Record::MailInformation(_) => QueryType::MailInformation,
+            Record::MailExchange(_) => QueryType::MailExchange,
+            Record::DomainPointer(_) => QueryType::DomainPointer,
+            Record::Text(_) => QueryType::Text,
+            Record::SecuritySignature(_) => QueryType::SecuritySignature,
+            Record::SecurityKey(_) => QueryType::SecurityKey,
+            Record::Service(_) => QueryType::Service,
+            Record::NamingAuthorityPointer(_) => QueryType::NamingAuthorityPointer,
+            Record::Opt(_) => QueryType::Opt,
+            Record::DelegationSigner(_) => QueryType::DelegationSigner,
+            Record::SshFingerprint(_) => QueryType::SshFingerprint,
+            Record::Signature(_) => QueryType::Signature,
+            Record::NextSecure(_) => QueryType::NextSecure,
+            Record::DnsKey(_) => QueryType::DnsKey,
+            Record::NextSecure3(_) => QueryType::NextSecure3,
+            Record::Nsec3Parameters(_) => QueryType::Nsec3Parameters,
+            Record::TlsAssociation(_) => QueryType::TlsAssociation,
+            Record::ChildDelegationSigner(_) => QueryType::ChildDelegationSigner,
+            Record::ChildDnsKey(_) => QueryType::ChildDnsKey,
+            Record::ServiceBinding(_) => QueryType::ServiceBinding,
+            Record::Https(_) => QueryType::Https,
+            Record::Uri(_) => QueryType::Uri,
+            Record::CertificationAuthorityAuthorization(_) => QueryType::CertificationAuthorityAuthorization,
+            Record::TransactionSignature(_) => QueryType::TransactionSignature,
+            Record::Custom(record) => record.qtype(),
+        }
+    }
+
+    /// Class, which is the UDP payload size for OPT records.
+    pub fn class(&self) -> Class {
+        match_common_fields!(self, record => record.class,
+            Record::Unknown { class, .. } => *class,
+            Record::Opt(opt) => Class::from_u16(opt.udp_payload_size),
+        )
+    }
+
+    /// TTL, which holds the extended result code, version and flags for OPT records.
+    pub fn ttl(&self) -> Duration {
+        match_common_fields!(self, record => record.ttl,
+            Record::Unknown { ttl, .. } => *ttl,
+            Record::Opt(opt) => {
+                let ttl = ((opt.extended_rcode as u64) << 24) | ((opt.version as u64) << 16) | opt.flags as u64;
+                Duration::from_secs(ttl)
+            }
+        )
+    }
+
+    /// Owner name, to be modified in place, OPT records having none.
+    pub fn domain_mut(&mut self) -> Option<&mut String> {
+        match_common_fields!(self, record => Some(&mut record.domain),
+            Record::Unknown { domain, .. } => Some(domain),
+            Record::Opt(_) => None,
+        )
+    }
+
+    /// Set the TTL, OPT records being left untouched.
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        match_common_fields!(self, record => record.ttl = ttl,
+            Record::Unknown { ttl: current, .. } => *current = ttl,
+            Record::Opt(_) => {},
+        )
+    }
+
+    // Deserialize a record as an unknown one, keeping its raw data.
+    fn deserialize_unknown<D>(deserializer: &mut D) -> Result<Record>
+        where
+            D: Deserializer + Seek
+    {
+        let domain = deserializer.deserialize_qname()?;
+        let qtype = QueryType::from_u16(deserializer.deserialize_u16()?);
+        let class = Class::from_u16(deserializer.deserialize_u16()?);
+        let ttl = Duration::from_secs(deserializer.deserialize_u32()? as u64);
+        let len = deserializer.deserialize_u16()?;
+        let data = deserializer.deserialize_bytes(len as usize)?;
+        Ok(Record::Unknown { domain, qtype, class, ttl, data })
+    }
+
+    fn deserialize_typed<D>(deserializer: &mut D, qtype: QueryType) -> Result<Record>
+        where
+            D: Deserializer + Seek
+    {
         match qtype {
-            QueryType::A => Record::A(records::A {
-                domain,
-                _class: class,
-                ttl,
-                ip: Ipv4Addr::from(buf.read_u32()),
-            }),
-            QueryType::AuthoritativeNameServer => Record::AuthoritativeNameServer(records::AuthoritativeNameServer {
-                domain,
-                _class: class,
-                ttl,
-                ns_name: buf.read_qname(),
-            }),
-            QueryType::CanonicalName => Record::CanonicalName(records::CName {
-                domain,
-                _class: class,
-                ttl,
-                alias: buf.read_qname(),
-            }),
-            QueryType::MailExchange => Record::MailExchange(records::MailExchange {
-                domain,
-                _class: class,
-                ttl,
-                preference: buf.read_u16(),
-                exchange: buf.read_qname(),
-            }),
-            _ => Record::Unknown {
-                domain,
-                qtype,
-                _class: class,
-                ttl,
-                data: buf.read_n(len as usize),
+            QueryType::A => records::A::deserialize(deserializer).map(Record::A),
+            QueryType::AuthoritativeNameServer => records::AuthoritativeNameServer::deserialize(deserializer).map(Record::AuthoritativeNameServer),
+            QueryType::CanonicalName => records::CName::deserialize(deserializer).map(Record::CanonicalName),
+            QueryType::MailDestination => records::MD::deserialize(deserializer).map(Record::MailDestination),
+            QueryType::MailForwarder => records::MF::deserialize(deserializer).map(Record::MailForwarder),
+            QueryType::StartOfAuthority => records::SOA::deserialize(deserializer).map(Record::StartOfAuthority),
+            QueryType::Mailbox => records::MB::deserialize(deserializer).map(Record::Mailbox),
+            QueryType::MailGroup => records::MG::deserialize(deserializer).map(Record::MailGroup),
+            QueryType::MailRename => records::MR::deserialize(deserializer).map(Record::MailRename),
+            QueryType::Null => records::NULL::deserialize(deserializer).map(Record::Null),
+            QueryType::WellKnownService => records::WKS::deserialize(deserializer).map(Record::WellKnownService),
+            QueryType::HostInformation => records::HINFO::deserialize(deserializer).map(Record::HostInformation),
+            QueryType::MailInformation => records::MINFO::deserialize(deserializer).map(Record::MailInformation),
+            QueryType::MailExchange => records::MailExchange::deserialize(deserializer).map(Record::MailExchange),
+            QueryType::DomainPointer => records::PTR::deserialize(deserializer).map(Record::DomainPointer),
+            QueryType::Text => records::TXT::deserialize(deserializer).map(Record::Text),
+            QueryType::SecuritySignature => records::SIG::deserialize(deserializer).map(Record::SecuritySignature),
+            QueryType::SecurityKey => records::KEY::deserialize(deserializer).map(Record::SecurityKey),
+            QueryType::Service => records::SRV::deserialize(deserializer).map(Record::Service),
+            QueryType::NamingAuthorityPointer => records::NAPTR::deserialize(deserializer).map(Record::NamingAuthorityPointer),
+            QueryType::Opt => records::OPT::deserialize(deserializer).map(Record::Opt),
+            QueryType::DelegationSigner => records::DS::deserialize(deserializer).map(Record::DelegationSigner),
+            QueryType::SshFingerprint => records::SSHFP::deserialize(deserializer).map(Record::SshFingerprint),
+            QueryType::Signature => records::RRSIG::deserialize(deserializer).map(Record::Signature),
+            QueryType::NextSecure => records::NSEC::deserialize(deserializer).map(Record::NextSecure),
+            QueryType::DnsKey => records::DNSKEY::deserialize(deserializer).map(Record::DnsKey),
+            QueryType::NextSecure3 => records::NSEC3::deserialize(deserializer).map(Record::NextSecure3),
+            QueryType::Nsec3Parameters => records::NSEC3PARAM::deserialize(deserializer).map(Record::Nsec3Parameters),
+            QueryType::TlsAssociation => records::TLSA::deserialize(deserializer).map(Record::TlsAssociation),
+            QueryType::ChildDelegationSigner => records::CDS::deserialize(deserializer).map(Record::ChildDelegationSigner),
+            QueryType::ChildDnsKey => records::CDNSKEY::deserialize(deserializer).map(Record::ChildDnsKey),
+            QueryType::ServiceBinding => records::SVCB::deserialize(deserializer).map(Record::ServiceBinding),
+            QueryType::Https => records::HTTPS::deserialize(deserializer).map(Record::Https),
+            QueryType::Uri => records::URI::deserialize(deserializer).map(Record::Uri),
+            QueryType::CertificationAuthorityAuthorization => records::CAA::deserialize(deserializer).map(Record::CertificationAuthorityAuthorization),
+            QueryType::TransactionSignature => records::TSIG::deserialize(deserializer).map(Record::TransactionSignature),
+            QueryType::Unknown(num) => match records::CustomRecord::deserialize(deserializer, num) {
+                Some(record) => record.map(Record::Custom),
+                None => Record::deserialize_unknown(deserializer),
             },
         }
     }
 }
 
+impl Deserialize for Record {
+    fn deserialize<D>(deserializer: &mut D) -> Result<Record>
+        where
+            D: Deserializer + Seek
+    {
+        // Reading the record type and data length ahead, the typed
+        // records deserialize the whole record on their own.
+        let start = deserializer.position();
+        deserializer.deserialize_qname()?;
+        let qtype = QueryType::from_u16(deserializer.deserialize_u16()?);
+        let class = Class::from_u16(deserializer.deserialize_u16()?);
+        deserializer.deserialize_u32()?;
+        let len = deserializer.deserialize_u16()?;
+        let end = deserializer.position() + len as usize;
+        deserializer.seek(start)?;
+
+        // Dynamic updates (RFC 2136) use empty RDATA along with the
+        // ANY or NONE classes to delete whole RRsets, those records
+        // cannot be decoded as their typed counterpart. The OPT
+        // pseudo-record has no options most of the time though.
+        let empty_update = len == 0 && matches!(class, Class::Any | Class::None);
+        if empty_update && qtype != QueryType::Opt {
+            return Record::deserialize_unknown(deserializer);
+        }
+
+        // Records whose data is not valid for their type are kept
+        // as unknown records.
+        match Record::deserialize_typed(deserializer, qtype) {
+            Ok(record) if deserializer.position() == end => Ok(record),
+            _ => {
+                deserializer.seek(start)?;
+                Record::deserialize_unknown(deserializer)
+            }
+        }
+    }
+}
+
 impl Serialize for Record {
     fn serialize<S>(&self, serializer: &mut S) -> Result<()>
         where
@@ -269,10 +863,276 @@ impl Serialize for Record {
             Record::A(record) => { record.serialize(serializer)?; }
             Record::AuthoritativeNameServer(record) => { record.serialize(serializer)?; }
             Record::CanonicalName(record) => { record.serialize(serializer)?; }
+            Record::MailDestination(record) => { record.serialize(serializer)?; }
+            Record::MailForwarder(record) => { record.serialize(serializer)?; }
+            Record::StartOfAuthority(record) => { record.serialize(serializer)?; }
+            Record::Mailbox(record) => { record.serialize(serializer)?; }
+            Record::MailGroup(record) => { record.serialize(serializer)?; }
+            Record::MailRename(record) => { record.serialize(serializer)?; }
+            Record::Null(record) => { record.serialize(serializer)?; }
+            Record::WellKnownService(record) => { record.serialize(serializer)?; }
+            Record::HostInformation(record) => { record.serialize(serializer)?; }
+            Record::MailInformation(record) => { record.serialize(serializer)?; }
             Record::MailExchange(record) => { record.serialize(serializer)?; }
-            _ => {}
+            Record::DomainPointer(record) => { record.serialize(serializer)?; }
+            Record::Text(record) => { record.serialize(serializer)?; }
+            Record::SecuritySignature(record) => { record.serialize(serializer)?; }
+            Record::SecurityKey(record) => { record.serialize(serializer)?; }
+            Record::Service(record) => { record.serialize(serializer)?; }
+            Record::NamingAuthorityPointer(record) => { record.serialize(serializer)?; }
+            Record::Opt(record) => { record.serialize(serializer)?; }
+            Record::DelegationSigner(record) => { record.serialize(serializer)?; }
+            Record::SshFingerprint(record) => { record.serialize(serializer)?; }
+            Record::Signature(record) => { record.serialize(serializer)?; }
+            Record::NextSecure(record) => { record.serialize(serializer)?; }
+            Record::DnsKey(record) => { record.serialize(serializer)?; }
+            Record::NextSecure3(record) => { record.serialize(serializer)?; }
+            Record::Nsec3Parameters(record) => { record.serialize(serializer)?; }
+            Record::TlsAssociation(record) => { record.serialize(serializer)?; }
+            Record::ChildDelegationSigner(record) => { record.serialize(serializer)?; }
+            Record::ChildDnsKey(record) => { record.serialize(serializer)?; }
+            Record::ServiceBinding(record) => { record.serialize(serializer)?; }
+            Record::Https(record) => { record.serialize(serializer)?; }
+            Record::Uri(record) => { record.serialize(serializer)?; }
+            Record::CertificationAuthorityAuthorization(record) => { record.serialize(serializer)?; }
+            Record::TransactionSignature(record) => { record.serialize(serializer)?; }
+            Record::Custom(record) => { record.serialize(serializer)?; }
+            Record::Unknown { domain, qtype, class, ttl, data } => {
+                serializer.serialize_qname(domain)?;
+                serializer.serialize_u16(qtype.as_u16())?;
+                serializer.serialize_u16(class.as_u16())?;
+                serializer.serialize_u32(ttl.as_secs() as u32)?;
+                serializer.serialize_u16(data.len() as u16)?;
+                serializer.serialize_bytes(data)?;
+            }
         };
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl FromStr for Record {
+    type Err = Error;
+
+    /// Parse a record in presentation format, of any type which has one.
+    fn from_str(s: &str) -> Result<Record> {
+        let qtype = RecordHeader::parse_any(&mut tokenize(s)?.into_iter())?.qtype;
+        match qtype {
+            QueryType::A => s.parse().map(Record::A),
+            QueryType::AuthoritativeNameServer => s.parse().map(Record::AuthoritativeNameServer),
+            QueryType::CanonicalName => s.parse().map(Record::CanonicalName),
+            QueryType::MailDestination => s.parse().map(Record::MailDestination),
+            QueryType::MailForwarder => s.parse().map(Record::MailForwarder),
+            QueryType::StartOfAuthority => s.parse().map(Record::StartOfAuthority),
+            QueryType::Mailbox => s.parse().map(Record::Mailbox),
+            QueryType::MailGroup => s.parse().map(Record::MailGroup),
+            QueryType::MailRename => s.parse().map(Record::MailRename),
+            QueryType::Null => s.parse().map(Record::Null),
+            QueryType::WellKnownService => s.parse().map(Record::WellKnownService),
+            QueryType::HostInformation => s.parse().map(Record::HostInformation),
+            QueryType::MailInformation => s.parse().map(Record::MailInformation),
+            QueryType::MailExchange => s.parse().map(Record::MailExchange),
+            QueryType::DomainPointer => s.parse().map(Record::DomainPointer),
+            QueryType::Text => s.parse().map(Record::Text),
+            QueryType::SecuritySignature => s.parse().map(Record::SecuritySignature),
+            QueryType::SecurityKey => s.parse().map(Record::SecurityKey),
+            QueryType::Service => s.parse().map(Record::Service),
+            QueryType::NamingAuthorityPointer => s.parse().map(Record::NamingAuthorityPointer),
+            QueryType::DelegationSigner => s.parse().map(Record::DelegationSigner),
+            QueryType::SshFingerprint => s.parse().map(Record::SshFingerprint),
+            QueryType::Signature => s.parse().map(Record::Signature),
+            QueryType::NextSecure => s.parse().map(Record::NextSecure),
+            QueryType::DnsKey => s.parse().map(Record::DnsKey),
+            QueryType::NextSecure3 => s.parse().map(Record::NextSecure3),
+            QueryType::Nsec3Parameters => s.parse().map(Record::Nsec3Parameters),
+            QueryType::TlsAssociation => s.parse().map(Record::TlsAssociation),
+            QueryType::ChildDelegationSigner => s.parse().map(Record::ChildDelegationSigner),
+            QueryType::ChildDnsKey => s.parse().map(Record::ChildDnsKey),
+            QueryType::ServiceBinding => s.parse().map(Record::ServiceBinding),
+            QueryType::Https => s.parse().map(Record::Https),
+            QueryType::Uri => s.parse().map(Record::Uri),
+            QueryType::CertificationAuthorityAuthorization => s.parse().map(Record::CertificationAuthorityAuthorization),
+            // Pseudo-records only exist on the wire.
+            QueryType::Opt | QueryType::TransactionSignature | QueryType::Unknown(_) => {
+                Err(InvalidPresentation(format!("{} records have no presentation format", qtype)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::de::Deserialize;
+    use crate::header::ResultCode;
+    use crate::packet::{Class, Packet, QueryType, Question, Record};
+    use crate::records;
+    use crate::ser::Serialize;
+
+    #[test]
+    fn class_round_trip() {
+        for num in [1, 3, 4, 254, 255, 42] {
+            assert_eq!(num, Class::from_u16(num).as_u16());
+        }
+
+        assert_eq!(Class::Chaos, Class::from_u16(3));
+        assert_eq!(Class::Unknown(42), Class::from_u16(42));
+    }
+
+    #[test]
+    fn serialize_chaos_question() {
+        let question = Question {
+            name: "version.bind".to_string(),
+            qtype: QueryType::Text,
+            class: Class::Chaos,
+        };
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(question.serialize(&mut serializer).is_ok());
+
+        let bytes = serializer.bytes();
+        assert_eq!(&[
+            0x07, 0x76, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e, // len=7 label="version"
+            0x04, 0x62, 0x69, 0x6e, 0x64, // len=4 label="bind"
+            0x00,
+            0x00, 0x10, // Type TXT.
+            0x00, 0x03, // Class CH.
+        ], bytes.as_slice());
+
+        let mut buffer = BytePacketBuffer::from_raw_data(&bytes);
+        assert_eq!(question, Question::deserialize(&mut buffer).unwrap());
+    }
+
+    #[test]
+    fn unknown_record_round_trip() {
+        // RFC 2136 "delete an RRset" update: class ANY, TTL 0 and no RDATA.
+        let record = Record::Unknown {
+            domain: "www.google.com".to_string(),
+            qtype: QueryType::A,
+            class: Class::Any,
+            ttl: Duration::from_secs(0),
+            data: vec![],
+        };
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+
+        let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+        assert_eq!(Record::Unknown {
+            domain: "www.google.com".to_string(),
+            qtype: QueryType::A,
+            class: Class::Any,
+            ttl: Duration::from_secs(0),
+            data: vec![],
+        }, Record::deserialize(&mut buffer).unwrap());
+    }
+
+    #[test]
+    fn record_common_fields() {
+        let mut record = Record::Text(records::TXT::from_long_value("example.com", Class::Internet, Duration::from_secs(60), b"hi"));
+        assert_eq!("example.com", record.domain());
+        assert_eq!(QueryType::Text, record.qtype());
+        assert_eq!(Class::Internet, record.class());
+        assert_eq!(Duration::from_secs(60), record.ttl());
+
+        record.set_ttl(Duration::from_secs(30));
+        assert_eq!(Duration::from_secs(30), record.ttl());
+
+        let mut opt = records::OPT::new(1232);
+        opt.set_dnssec_ok(true);
+        let record = Record::Opt(opt);
+        assert_eq!("", record.domain());
+        assert_eq!(QueryType::Opt, record.qtype());
+        assert_eq!(Class::Unknown(1232), record.class());
+        assert_eq!(Duration::from_secs(0x8000), record.ttl());
+    }
+
+    #[test]
+    fn dedup_sections() {
+        let record = |ttl| Record::Text(records::TXT::from_long_value("example.com", Class::Internet, Duration::from_secs(ttl), b"hi"));
+
+        let mut packet = Packet::new();
+        packet.answers = vec![record(60), record(30), record(60)];
+        packet.additionals = vec![record(60)];
+        packet.dedup();
+
+        assert_eq!(vec![record(60)], packet.answers);
+        assert_eq!(vec![record(60)], packet.additionals);
+        assert_eq!(1, packet.answer_rrsets().len());
+    }
+
+    #[test]
+    fn legacy_records_round_trip() {
+        let records = vec![
+            Record::StartOfAuthority("x. 3600 IN SOA ns.x. admin.x. 1 7200 900 1209600 300".parse().unwrap()),
+            Record::MailGroup("x. 60 IN MG alice.x.".parse().unwrap()),
+            Record::Null(r"x. 60 IN NULL \# 2 CAFE".parse().unwrap()),
+            Record::WellKnownService("x. 60 IN WKS 10.0.0.1 tcp 25".parse().unwrap()),
+            Record::HostInformation(r#"x. 60 IN HINFO "RFC8482" """#.parse().unwrap()),
+            Record::MailInformation("x. 60 IN MINFO owner.x. err.x.".parse().unwrap()),
+        ];
+
+        for record in records {
+            let mut serializer = BytePacketBuffer::new();
+            assert!(record.serialize(&mut serializer).is_ok());
+
+            let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+            assert_eq!(record, Record::deserialize(&mut buffer).unwrap());
+        }
+    }
+
+    #[test]
+    fn extended_result_code_round_trip() {
+        let mut packet = Packet::new();
+        packet.header.result_code = ResultCode::BadCookie;
+        packet.additionals.push(Record::Opt(records::OPT::new(1232)));
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(packet.serialize(&mut serializer).is_ok());
+
+        let bytes = serializer.bytes();
+        // Lower 4 bits in the header and upper 8 bits in the OPT record.
+        assert_eq!(0x07, bytes[3] & 0x0F);
+        assert_eq!(0x01, bytes[12 + 5]);
+
+        let mut buffer = BytePacketBuffer::from_raw_data(&bytes);
+        let parsed = Packet::deserialize(&mut buffer).unwrap();
+        assert_eq!(ResultCode::BadCookie, parsed.header.result_code);
+        assert_eq!(Some(1232), parsed.edns().map(|opt| opt.udp_payload_size));
+
+        packet.additionals.clear();
+        assert!(packet.serialize(&mut BytePacketBuffer::new()).is_err());
+    }
+
+    #[test]
+    fn result_code_without_opt() {
+        let mut packet = Packet::new();
+        packet.header.result_code = ResultCode::NotAuth;
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(packet.serialize(&mut serializer).is_ok());
+
+        let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+        assert_eq!(ResultCode::NotAuth, Packet::deserialize(&mut buffer).unwrap().header.result_code);
+    }
+
+    #[test]
+    fn parse_records() {
+        let record: Record = "www.example.com. 300 IN A 192.0.2.1".parse().unwrap();
+        assert_eq!(QueryType::A, record.qtype());
+        assert_eq!("www.example.com", record.domain());
+        assert_eq!(Duration::from_secs(300), record.ttl());
+
+        let record: Record = "example.com. MX 10 mail.example.com. ; comment".parse().unwrap();
+        assert_eq!(Record::MailExchange("example.com. 0 IN MX 10 mail.example.com.".parse().unwrap()), record);
+
+        let record: Record = r#"example.com. 60 TXT "hi""#.parse().unwrap();
+        assert_eq!(QueryType::Text, record.qtype());
+
+        assert!("example.com. 60 IN TSIG hmac-sha256.".parse::<Record>().is_err());
+        assert!("example.com. 60 IN TYPE65000 foo".parse::<Record>().is_err());
+        assert!("example.com. 60 IN A".parse::<Record>().is_err());
+    }
+}
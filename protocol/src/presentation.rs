@@ -0,0 +1,292 @@
+//! Helpers for the textual representation of records, as used by zone
+//! files and tools like dig (RFC 1035 section 5.1).
+
+use std::time::Duration;
+
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::result::Result;
+
+/// Owner, TTL, class and type which start every record in presentation format.
+#[derive(Debug, PartialEq)]
+pub(crate) struct RecordHeader {
+    pub domain: String,
+    pub ttl: Duration,
+    pub class: Class,
+    pub qtype: QueryType,
+}
+
+impl RecordHeader {
+    /// Parse the record header from the first tokens of a record, making
+    /// sure the record type is the expected one.
+    ///
+    /// The TTL and the class are optional and can appear in any order,
+    /// they default to 0 and IN.
+    pub fn parse(tokens: &mut std::vec::IntoIter<Vec<u8>>, expected: QueryType) -> Result<RecordHeader> {
+        let header = RecordHeader::parse_any(tokens)?;
+        if header.qtype != expected {
+            return Err(InvalidPresentation(format!("expected a {} record but got {}", expected, header.qtype)));
+        }
+
+        Ok(header)
+    }
+
+    /// Parse the record header from the first tokens of a record of any type.
+    pub fn parse_any(tokens: &mut std::vec::IntoIter<Vec<u8>>) -> Result<RecordHeader> {
+        let domain = match tokens.next() {
+            Some(token) => parse_name(&token_str(&token)?),
+            None => return Err(InvalidPresentation("missing record owner".to_string())),
+        };
+
+        let mut ttl = None;
+        let mut class = None;
+
+        loop {
+            let token = match tokens.next() {
+                Some(token) => token_str(&token)?,
+                None => return Err(InvalidPresentation("missing record type".to_string())),
+            };
+
+            if ttl.is_none() && !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit()) {
+                ttl = Some(Duration::from_secs(parse_number::<u32>(&token)? as u64));
+            } else if class.is_none() && token.parse::<Class>().is_ok() {
+                class = token.parse::<Class>().ok();
+            } else {
+                return Ok(RecordHeader {
+                    domain,
+                    ttl: ttl.unwrap_or_default(),
+                    class: class.unwrap_or(Class::Internet),
+                    qtype: token.parse()?,
+                });
+            }
+        }
+    }
+}
+
+/// Split a record in presentation format into tokens.
+///
+/// Quoted strings are returned as a single token, escape sequences
+/// (`\X` and `\DDD`) are decoded and parentheses used to span
+/// records over multiple lines are dropped along with comments.
+pub fn tokenize(line: &str) -> Result<Vec<Vec<u8>>> {
+    let mut tokens = vec![];
+    let mut bytes = line.bytes().peekable();
+
+    while let Some(&byte) = bytes.peek() {
+        match byte {
+            b' ' | b'\t' | b'\r' | b'\n' | b'(' | b')' => { bytes.next(); }
+            // Comments run up to the end of the line, the record may
+            // continue on the next one within parentheses.
+            b';' => {
+                while bytes.next().is_some_and(|byte| byte != b'\n') {}
+            }
+            b'"' => {
+                bytes.next();
+                let mut token = vec![];
+                read_quoted(&mut bytes, &mut token)?;
+                tokens.push(token);
+            }
+            _ => {
+                let mut token = vec![];
+                while let Some(&byte) = bytes.peek() {
+                    match byte {
+                        b' ' | b'\t' | b'\r' | b'\n' | b'(' | b')' | b';' => break,
+                        b'\\' => {
+                            bytes.next();
+                            token.push(unescape(&mut bytes)?);
+                        }
+                        // Quoted values are part of the token (e.g. `alpn="h2,h3"`).
+                        b'"' => {
+                            bytes.next();
+                            read_quoted(&mut bytes, &mut token)?;
+                        }
+                        _ => {
+                            bytes.next();
+                            token.push(byte);
+                        }
+                    }
+                }
+                tokens.push(token);
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Read a quoted string up to its closing quote, the opening quote
+// being already consumed.
+fn read_quoted<I>(bytes: &mut std::iter::Peekable<I>, token: &mut Vec<u8>) -> Result<()>
+    where I: Iterator<Item=u8>
+{
+    loop {
+        match bytes.next() {
+            Some(b'"') => return Ok(()),
+            Some(b'\\') => token.push(unescape(bytes)?),
+            Some(byte) => token.push(byte),
+            None => return Err(InvalidPresentation("unterminated quoted string".to_string())),
+        }
+    }
+}
+
+// Decode an escape sequence, the backslash being already consumed.
+fn unescape<I>(bytes: &mut std::iter::Peekable<I>) -> Result<u8>
+    where I: Iterator<Item=u8>
+{
+    match bytes.next() {
+        Some(digit) if digit.is_ascii_digit() => {
+            let mut value = (digit - b'0') as u16;
+            for _ in 0..2 {
+                match bytes.next() {
+                    Some(digit) if digit.is_ascii_digit() => value = value * 10 + (digit - b'0') as u16,
+                    _ => return Err(InvalidPresentation("escape sequence \\DDD expects 3 digits".to_string())),
+                }
+            }
+
+            if value > 255 {
+                return Err(InvalidPresentation(format!("escape sequence \\{} is out of range", value)));
+            }
+
+            Ok(value as u8)
+        }
+        Some(byte) => Ok(byte),
+        None => Err(InvalidPresentation("dangling escape character".to_string())),
+    }
+}
+
+/// Quote a <character-string>, escaping quotes, backslashes and non
+/// printable bytes.
+pub fn quote(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() + 2);
+    out.push('"');
+
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => {
+                out.push('\\');
+                out.push(byte as char);
+            }
+            0x20..=0x7E => out.push(byte as char),
+            _ => out.push_str(&format!("\\{:03}", byte)),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+/// Format a domain name as a fully qualified name.
+pub(crate) fn format_name(name: &str) -> String {
+    format!("{}.", name)
+}
+
+/// Parse a domain name, dropping the trailing dot of fully qualified names.
+pub(crate) fn parse_name(name: &str) -> String {
+    name.strip_suffix('.').unwrap_or(name).to_lowercase()
+}
+
+/// Format binary data as hexadecimal, as used by TLSA or SSHFP records.
+pub(crate) fn format_hex(data: &[u8]) -> String {
+    data_encoding::HEXUPPER.encode(data)
+}
+
+/// Parse hexadecimal data which may be split across multiple tokens.
+pub(crate) fn parse_hex<I>(tokens: I) -> Result<Vec<u8>>
+    where I: Iterator<Item=Vec<u8>>
+{
+    let hex: Vec<u8> = tokens.flatten().collect();
+    data_encoding::HEXUPPER_PERMISSIVE
+        .decode(&hex)
+        .map_err(|err| InvalidPresentation(format!("invalid hexadecimal data: {}", err)))
+}
+
+/// Format binary data as base64, as used by keys and signatures.
+pub(crate) fn format_base64(data: &[u8]) -> String {
+    data_encoding::BASE64.encode(data)
+}
+
+/// Parse base64 data which may be split across multiple tokens.
+pub(crate) fn parse_base64<I>(tokens: I) -> Result<Vec<u8>>
+    where I: Iterator<Item=Vec<u8>>
+{
+    let base64: Vec<u8> = tokens.flatten().collect();
+    data_encoding::BASE64
+        .decode(&base64)
+        .map_err(|err| InvalidPresentation(format!("invalid base64 data: {}", err)))
+}
+
+/// Format binary data as unpadded base32hex, as used by NSEC3 hashed names.
+pub(crate) fn format_base32hex(data: &[u8]) -> String {
+    data_encoding::BASE32HEX_NOPAD.encode(data)
+}
+
+/// Parse unpadded base32hex data, ignoring case.
+pub(crate) fn parse_base32hex(token: &str) -> Result<Vec<u8>> {
+    data_encoding::BASE32HEX_NOPAD
+        .decode(token.to_uppercase().as_bytes())
+        .map_err(|err| InvalidPresentation(format!("invalid base32hex data: {}", err)))
+}
+
+pub(crate) fn token_str(token: &[u8]) -> Result<String> {
+    String::from_utf8(token.to_vec())
+        .map_err(|_| InvalidPresentation("token is not valid UTF-8".to_string()))
+}
+
+pub(crate) fn parse_number<T>(token: &str) -> Result<T>
+    where T: std::str::FromStr
+{
+    token
+        .parse()
+        .map_err(|_| InvalidPresentation(format!("invalid number: {}", token)))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::packet::{Class, QueryType};
+    use crate::presentation::{quote, tokenize, RecordHeader};
+
+    #[test]
+    fn tokenize_quoted_strings() {
+        let tokens = tokenize(r#"example.com. IN TXT "v=spf1 -all" ( "a\"b" \065\\ ) ; comment"#).unwrap();
+
+        assert_eq!(vec![
+            b"example.com.".to_vec(),
+            b"IN".to_vec(),
+            b"TXT".to_vec(),
+            b"v=spf1 -all".to_vec(),
+            b"a\"b".to_vec(),
+            b"A\\".to_vec(),
+        ], tokens);
+    }
+
+    #[test]
+    fn tokenize_invalid_escape() {
+        assert!(tokenize(r#""\25""#).is_err());
+        assert!(tokenize(r#""\256""#).is_err());
+        assert!(tokenize(r#""unterminated"#).is_err());
+    }
+
+    #[test]
+    fn quote_escapes() {
+        assert_eq!(r#""a\"b\\c\010""#, quote(b"a\"b\\c\n"));
+    }
+
+    #[test]
+    fn parse_record_header() {
+        let mut tokens = tokenize("Example.com. CH 300 TXT \"foo\"").unwrap().into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::Text).unwrap();
+
+        assert_eq!(RecordHeader {
+            domain: "example.com".to_string(),
+            ttl: Duration::from_secs(300),
+            class: Class::Chaos,
+            qtype: QueryType::Text,
+        }, header);
+        assert_eq!(Some(b"foo".to_vec()), tokens.next());
+
+        let mut tokens = tokenize("example.com. A 127.0.0.1").unwrap().into_iter();
+        assert!(RecordHeader::parse(&mut tokens, QueryType::Text).is_err());
+    }
+}
@@ -1,3 +1,18 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
 /// https://datatracker.ietf.org/doc/html/rfc1034
 /// https://datatracker.ietf.org/doc/html/rfc1035
 ///
@@ -21,22 +36,32 @@
 /// |                                               |
 /// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
 /// ```
-
-use std::net::Ipv4Addr;
-use std::time::Duration;
-
-use crate::result::Result;
-use crate::seek::Seek;
-use crate::ser::{Serialize, Serializer};
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct A {
     pub domain: String,
-    pub _class: u16,
+    pub class: Class,
     pub ttl: Duration,
     pub ip: Ipv4Addr,
 }
 
+impl Deserialize for A {
+    fn deserialize<D>(deserializer: &mut D) -> Result<A>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::A)?;
+        let record = A {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            ip: Ipv4Addr::from(deserializer.deserialize_u32()?),
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
 impl Serialize for A {
     fn serialize<S>(&self, serializer: &mut S) -> Result<()>
         where
@@ -49,9 +74,8 @@ impl Serialize for A {
         // See: https://datatracker.ietf.org/doc/html/rfc1035#section-3.2.2
         serializer.serialize_u16(1)?;
 
-        // Class. (IN for now)
-        // TODO: Support other class types.
-        serializer.serialize_u16(1)?;
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
 
         // TTL.
         serializer.serialize_u32(self.ttl.as_secs() as u32)?;
@@ -69,12 +93,43 @@ impl Serialize for A {
     }
 }
 
+impl Display for A {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} A {}", format_name(&self.domain), self.ttl.as_secs(), self.class, self.ip)
+    }
+}
+
+impl FromStr for A {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<A> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::A)?;
+
+        let ip = match (tokens.next(), tokens.next()) {
+            (Some(token), None) => {
+                let token = token_str(&token)?;
+                token.parse().map_err(|_| InvalidPresentation(format!("invalid IPv4 address: {}", token)))?
+            }
+            _ => return Err(InvalidPresentation("A record expects a single IPv4 address".to_string())),
+        };
+
+        Ok(A {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            ip,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::net::Ipv4Addr;
     use std::time::Duration;
 
     use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
     use crate::records::A;
     use crate::ser::Serialize;
 
@@ -83,7 +138,7 @@ mod test {
         let mut serializer = BytePacketBuffer::new();
         let cname = A {
             domain: "www.google.com".to_string(),
-            _class: 1,
+            class: Class::Internet,
             ttl: Duration::from_secs(60),
             ip: Ipv4Addr::new(127, 0, 0, 1),
         };
@@ -103,4 +158,19 @@ mod test {
             0x7F, 0x00, 0x00, 0x01,
         ], serializer.bytes().as_slice());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn presentation() {
+        let a = A {
+            domain: "www.example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            ip: Ipv4Addr::new(192, 0, 2, 1),
+        };
+
+        let text = a.to_string();
+        assert_eq!("www.example.com. 60 IN A 192.0.2.1", text);
+        assert_eq!(a, text.parse().unwrap());
+        assert!("www.example.com. 60 IN A 192.0.2".parse::<A>().is_err());
+    }
+}
@@ -1,3 +1,17 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_name, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
 /// https://datatracker.ietf.org/doc/html/rfc1034
 /// https://datatracker.ietf.org/doc/html/rfc1035
 ///
@@ -21,21 +35,32 @@
 /// .                                               .
 /// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
 /// ```
-
-use std::time::Duration;
-
-use crate::result::Result;
-use crate::seek::Seek;
-use crate::ser::{Serialize, Serializer};
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AuthoritativeNameServer {
     pub domain: String,
-    pub _class: u16,
+    pub class: Class,
     pub ttl: Duration,
     pub ns_name: String,
 }
 
+impl Deserialize for AuthoritativeNameServer {
+    fn deserialize<D>(deserializer: &mut D) -> Result<AuthoritativeNameServer>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::AuthoritativeNameServer)?;
+        let record = AuthoritativeNameServer {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            ns_name: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
 impl Serialize for AuthoritativeNameServer {
     fn serialize<S>(&self, serializer: &mut S) -> Result<()>
         where
@@ -48,9 +73,8 @@ impl Serialize for AuthoritativeNameServer {
         // See: https://datatracker.ietf.org/doc/html/rfc1035#section-3.2.2
         serializer.serialize_u16(2)?;
 
-        // Class. (IN for now)
-        // TODO: Support other class types.
-        serializer.serialize_u16(1)?;
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
 
         // TTL.
         serializer.serialize_u32(self.ttl.as_secs() as u32)?;
@@ -74,11 +98,39 @@ impl Serialize for AuthoritativeNameServer {
     }
 }
 
+impl Display for AuthoritativeNameServer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} NS {}", format_name(&self.domain), self.ttl.as_secs(), self.class, format_name(&self.ns_name))
+    }
+}
+
+impl FromStr for AuthoritativeNameServer {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<AuthoritativeNameServer> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::AuthoritativeNameServer)?;
+
+        let ns_name = match (tokens.next(), tokens.next()) {
+            (Some(token), None) => parse_name(&token_str(&token)?),
+            _ => return Err(InvalidPresentation("NS record expects a single domain name".to_string())),
+        };
+
+        Ok(AuthoritativeNameServer {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            ns_name,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::time::Duration;
 
     use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
     use crate::records::AuthoritativeNameServer;
     use crate::ser::Serialize;
 
@@ -87,7 +139,7 @@ mod test {
         let mut serializer = BytePacketBuffer::new();
         let ns = AuthoritativeNameServer {
             domain: "test.www.google.com".to_string(),
-            _class: 1,
+            class: Class::Internet,
             ttl: Duration::from_secs(60),
             ns_name: "www.google.com".to_string(),
         };
@@ -111,4 +163,19 @@ mod test {
             0x00,
         ], serializer.bytes().as_slice());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn presentation() {
+        let record = AuthoritativeNameServer {
+            domain: "example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            ns_name: "ns.example.com".to_string(),
+        };
+
+        let text = record.to_string();
+        assert_eq!("example.com. 60 IN NS ns.example.com.", text);
+        assert_eq!(record, text.parse().unwrap());
+        assert!("example.com. 60 IN NS".parse::<AuthoritativeNameServer>().is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_number, quote, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// Flag telling that the record must be understood to issue a certificate.
+pub const ISSUER_CRITICAL: u8 = 0x80;
+
+/// https://datatracker.ietf.org/doc/html/rfc8659#section-4.1
+///
+/// ```txt
+/// +0-1-2-3-4-5-6-7-|0-1-2-3-4-5-6-7-|
+/// | Flags          | Tag Length = n |
+/// +----------------|----------------+...+---------------+
+/// | Tag char 0     | Tag char 1     |...| Tag char n-1  |
+/// +----------------|----------------+...+---------------+
+/// +----------------|----------------+.....+----------------+
+/// | Value byte 0   | Value byte 1   |.....| Value byte m-1 |
+/// +----------------|----------------+.....+----------------+
+/// ```
+///
+/// The tag is made of 1 to 15 ASCII letters and digits, the value
+/// spans over the rest of the record data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CAA {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub flags: u8,
+    pub tag: String,
+    pub value: Vec<u8>,
+}
+
+impl CAA {
+    /// Whether the issuer must understand the property to issue a certificate.
+    pub fn issuer_critical(&self) -> bool {
+        self.flags & ISSUER_CRITICAL != 0
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<CAA> {
+        if data.len() < 2 {
+            return Err(InvalidData("CAA data is too short".to_string()));
+        }
+
+        let tag_len = data[1] as usize;
+        if data.len() < 2 + tag_len {
+            return Err(InvalidData(format!("CAA tag of {} bytes overflows the record data", tag_len)));
+        }
+
+        let tag = String::from_utf8_lossy(&data[2..2 + tag_len]).into_owned();
+        validate_tag(&tag).map_err(InvalidData)?;
+
+        Ok(CAA {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            flags: data[0],
+            tag,
+            value: data[2 + tag_len..].to_vec(),
+        })
+    }
+}
+
+// Tags are between 1 and 15 ASCII letters and digits.
+fn validate_tag(tag: &str) -> std::result::Result<(), String> {
+    if tag.is_empty() || tag.len() > 15 || !tag.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err(format!("invalid CAA tag {:?}", tag));
+    }
+
+    Ok(())
+}
+
+impl Deserialize for CAA {
+    fn deserialize<D>(deserializer: &mut D) -> Result<CAA>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::CertificationAuthorityAuthorization)?;
+        CAA::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for CAA {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        validate_tag(&self.tag).map_err(InvalidData)?;
+
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 257 for CAA)
+        serializer.serialize_u16(QueryType::CertificationAuthorityAuthorization.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        serializer.serialize_u16((2 + self.tag.len() + self.value.len()) as u16)?;
+
+        serializer.serialize_u8(self.flags)?;
+        serializer.serialize_u8(self.tag.len() as u8)?;
+        serializer.serialize_bytes(self.tag.as_bytes())?;
+        serializer.serialize_bytes(&self.value)
+    }
+}
+
+impl Display for CAA {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} CAA {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            self.flags,
+            self.tag,
+            quote(&self.value),
+        )
+    }
+}
+
+impl FromStr for CAA {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<CAA> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::CertificationAuthorityAuthorization)?;
+
+        let (flags, tag, value) = match (tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+            (Some(flags), Some(tag), Some(value), None) => (flags, tag, value),
+            _ => return Err(InvalidPresentation("CAA record expects flags, tag and value".to_string())),
+        };
+
+        let tag = token_str(&tag)?.to_lowercase();
+        validate_tag(&tag).map_err(InvalidPresentation)?;
+
+        Ok(CAA {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            flags: parse_number(&token_str(&flags)?)?,
+            tag,
+            value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::CAA;
+    use crate::ser::Serialize;
+
+    fn caa() -> CAA {
+        CAA {
+            domain: "google.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            flags: 128,
+            tag: "issue".to_string(),
+            value: b"pki.goog".to_vec(),
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+
+        let res = caa().serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x06, 0x67, 0x6f, 0x6f, 0x67, 0x6c, 0x65, // len=6 label="google"
+            0x03, 0x63, 0x6f, 0x6d, // len=3 label="com"
+            0x00,
+            0x01, 0x01, // Type CAA.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x0F, // RD length.
+            0x80, // Flags.
+            0x05, 0x69, 0x73, 0x73, 0x75, 0x65, // len=5 tag="issue"
+            0x70, 0x6b, 0x69, 0x2e, 0x67, 0x6f, 0x6f, 0x67, // value="pki.goog"
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn from_rdata() {
+        let record = CAA::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"\x80\x05issuepki.goog").unwrap();
+        assert_eq!(caa(), record);
+        assert!(record.issuer_critical());
+
+        assert!(CAA::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"\x00\x06issue").is_err());
+        assert!(CAA::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"\x00\x00").is_err());
+        assert!(CAA::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"\x00\x02i-").is_err());
+    }
+
+    #[test]
+    fn presentation() {
+        let text = caa().to_string();
+        assert_eq!("google.com. 60 IN CAA 128 issue \"pki.goog\"", text);
+        assert_eq!(caa(), text.parse().unwrap());
+        assert!("google.com. 60 IN CAA 0 is_sue \"pki.goog\"".parse::<CAA>().is_err());
+    }
+}
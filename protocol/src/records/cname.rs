@@ -1,3 +1,17 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_name, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
 /// https://datatracker.ietf.org/doc/html/rfc1034
 /// https://datatracker.ietf.org/doc/html/rfc1035
 ///
@@ -21,21 +35,32 @@
 /// .                                               .
 /// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
 /// ```
-
-use std::time::Duration;
-
-use crate::result::Result;
-use crate::seek::Seek;
-use crate::ser::{Serialize, Serializer};
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CName {
     pub domain: String,
-    pub _class: u16,
+    pub class: Class,
     pub ttl: Duration,
     pub alias: String,
 }
 
+impl Deserialize for CName {
+    fn deserialize<D>(deserializer: &mut D) -> Result<CName>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::CanonicalName)?;
+        let record = CName {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            alias: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
 impl Serialize for CName {
     fn serialize<S>(&self, serializer: &mut S) -> Result<()>
         where
@@ -48,9 +73,8 @@ impl Serialize for CName {
         // See: https://datatracker.ietf.org/doc/html/rfc1035#section-3.2.2
         serializer.serialize_u16(5)?;
 
-        // Class. (IN for now)
-        // TODO: Support other class types.
-        serializer.serialize_u16(1)?;
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
 
         // TTL.
         serializer.serialize_u32(self.ttl.as_secs() as u32)?;
@@ -74,11 +98,39 @@ impl Serialize for CName {
     }
 }
 
+impl Display for CName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} CNAME {}", format_name(&self.domain), self.ttl.as_secs(), self.class, format_name(&self.alias))
+    }
+}
+
+impl FromStr for CName {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<CName> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::CanonicalName)?;
+
+        let alias = match (tokens.next(), tokens.next()) {
+            (Some(token), None) => parse_name(&token_str(&token)?),
+            _ => return Err(InvalidPresentation("CNAME record expects a single domain name".to_string())),
+        };
+
+        Ok(CName {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            alias,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::time::Duration;
 
     use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
     use crate::records::CName;
     use crate::ser::Serialize;
 
@@ -87,7 +139,7 @@ mod test {
         let mut serializer = BytePacketBuffer::new();
         let cname = CName {
             domain: "www.google.com".to_string(),
-            _class: 1,
+            class: Class::Internet,
             ttl: Duration::from_secs(60),
             alias: "test.www.google.com".to_string(),
         };
@@ -111,4 +163,19 @@ mod test {
             0x00,
         ], serializer.bytes().as_slice());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn presentation() {
+        let record = CName {
+            domain: "www.example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            alias: "example.com".to_string(),
+        };
+
+        let text = record.to_string();
+        assert_eq!("www.example.com. 60 IN CNAME example.com.", text);
+        assert_eq!(record, text.parse().unwrap());
+        assert!("www.example.com. 60 IN CNAME".parse::<CName>().is_err());
+    }
+}
@@ -0,0 +1,304 @@
+//! Extension point for record types unknown to this crate, like
+//! private use types (TYPE65280 to TYPE65534).
+//!
+//! Record data types implement `RData` and are registered by type code
+//! with `register`, records of those types are then deserialized as
+//! `Record::Custom` instead of `Record::Unknown`.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use crate::de::Deserializer;
+use crate::errors::Error::InvalidData;
+use crate::packet::{Class, QueryType};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// Data of a record, without the owner, type, class and TTL fields.
+pub trait RData: Debug + Clone + PartialEq + Send + Sync + 'static {
+    /// Decode the record data, which can't hold compressed names.
+    fn from_wire(data: &[u8]) -> Result<Self>;
+
+    /// Encode the record data.
+    fn to_wire(&self) -> Result<Vec<u8>>;
+}
+
+// Object safe counterpart of `RData`, to store any registered type.
+trait DynRData: Debug + Send + Sync {
+    fn to_wire(&self) -> Result<Vec<u8>>;
+    fn clone_box(&self) -> Box<dyn DynRData>;
+    fn eq_dyn(&self, other: &dyn DynRData) -> bool;
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T> DynRData for T
+    where T: RData
+{
+    fn to_wire(&self) -> Result<Vec<u8>> {
+        RData::to_wire(self)
+    }
+
+    fn clone_box(&self) -> Box<dyn DynRData> {
+        Box::new(self.clone())
+    }
+
+    fn eq_dyn(&self, other: &dyn DynRData) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+type Parser = fn(&[u8]) -> Result<Box<dyn DynRData>>;
+
+fn registry() -> &'static RwLock<HashMap<u16, Parser>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<u16, Parser>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+fn parse<T>(data: &[u8]) -> Result<Box<dyn DynRData>>
+    where T: RData
+{
+    Ok(Box::new(T::from_wire(data)?))
+}
+
+/// Register a record data type for the given type code, replacing the
+/// type previously registered for this code if any.
+///
+/// Only the type codes unknown to this crate can be registered, the
+/// others always being deserialized as their built-in record.
+pub fn register<T>(qtype: u16) -> Result<()>
+    where T: RData
+{
+    if !matches!(QueryType::from_u16(qtype), QueryType::Unknown(_)) {
+        return Err(InvalidData(format!("type {} is already supported", QueryType::from_u16(qtype))));
+    }
+
+    registry().write().unwrap().insert(qtype, parse::<T>);
+    Ok(())
+}
+
+/// Remove the record data type registered for the given type code.
+pub fn unregister(qtype: u16) {
+    registry().write().unwrap().remove(&qtype);
+}
+
+/// Whether a record data type is registered for the given type code.
+pub fn is_registered(qtype: u16) -> bool {
+    registry().read().unwrap().contains_key(&qtype)
+}
+
+/// Record whose data type was registered with `register`.
+pub struct CustomRecord {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    qtype: u16,
+    data: Box<dyn DynRData>,
+}
+
+impl CustomRecord {
+    pub fn new<S, T>(domain: S, class: Class, ttl: Duration, qtype: u16, data: T) -> CustomRecord
+        where
+            S: ToString,
+            T: RData,
+    {
+        CustomRecord {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            qtype,
+            data: Box::new(data),
+        }
+    }
+
+    pub fn qtype(&self) -> QueryType {
+        QueryType::from_u16(self.qtype)
+    }
+
+    /// Record data, if it is of the given type.
+    pub fn data<T>(&self) -> Option<&T>
+        where T: RData
+    {
+        self.data.as_any().downcast_ref()
+    }
+
+    /// Deserialize a record if a data type is registered for its type,
+    /// `None` otherwise.
+    pub(crate) fn deserialize<D>(deserializer: &mut D, qtype: u16) -> Option<Result<CustomRecord>>
+        where
+            D: Deserializer + Seek
+    {
+        let parse = *registry().read().unwrap().get(&qtype)?;
+
+        Some(WireHeader::deserialize_with_data(deserializer, QueryType::from_u16(qtype)).and_then(|(header, data)| {
+            Ok(CustomRecord {
+                domain: header.domain,
+                class: header.class,
+                ttl: header.ttl,
+                qtype,
+                data: parse(&data)?,
+            })
+        }))
+    }
+}
+
+impl Clone for CustomRecord {
+    fn clone(&self) -> Self {
+        CustomRecord {
+            domain: self.domain.clone(),
+            class: self.class,
+            ttl: self.ttl,
+            qtype: self.qtype,
+            data: self.data.clone_box(),
+        }
+    }
+}
+
+impl PartialEq for CustomRecord {
+    fn eq(&self, other: &Self) -> bool {
+        self.domain == other.domain
+            && self.class == other.class
+            && self.ttl == other.ttl
+            && self.qtype == other.qtype
+            && self.data.eq_dyn(other.data.as_ref())
+    }
+}
+
+impl Debug for CustomRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("CustomRecord")
+            .field("domain", &self.domain)
+            .field("class", &self.class)
+            .field("ttl", &self.ttl)
+            .field("qtype", &self.qtype())
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+impl Serialize for CustomRecord {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        let data = self.data.to_wire()?;
+        if data.len() > u16::MAX as usize {
+            return Err(InvalidData(format!("record data of {} bytes is too long", data.len())));
+        }
+
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type.
+        serializer.serialize_u16(self.qtype)?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        serializer.serialize_u16(data.len() as u16)?;
+
+        // Data.
+        serializer.serialize_bytes(&data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::de::Deserialize;
+    use crate::errors::Error::InvalidData;
+    use crate::packet::{Class, QueryType, Record};
+    use crate::records::custom::{self, CustomRecord, RData};
+    use crate::result::Result;
+    use crate::ser::Serialize;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Color {
+        rgb: [u8; 3],
+    }
+
+    impl RData for Color {
+        fn from_wire(data: &[u8]) -> Result<Color> {
+            match data {
+                [r, g, b] => Ok(Color { rgb: [*r, *g, *b] }),
+                _ => Err(InvalidData("a color is made of 3 bytes".to_string())),
+            }
+        }
+
+        fn to_wire(&self) -> Result<Vec<u8>> {
+            Ok(self.rgb.to_vec())
+        }
+    }
+
+    #[test]
+    fn custom_record_round_trip() {
+        let record = CustomRecord::new("x", Class::Internet, Duration::from_secs(60), 65280, Color { rgb: [0xFF, 0x80, 0x00] });
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+        let bytes = serializer.bytes();
+        assert_eq!(&[
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0xFF, 0x00, // Type TYPE65280.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x03, // RD length.
+            0xFF, 0x80, 0x00, // Color.
+        ], bytes.as_slice());
+
+        // Unregistered types are kept as unknown records.
+        let mut buffer = BytePacketBuffer::from_raw_data(&bytes);
+        assert!(matches!(Record::deserialize(&mut buffer).unwrap(), Record::Unknown { .. }));
+
+        custom::register::<Color>(65280).unwrap();
+        assert!(custom::is_registered(65280));
+
+        let mut buffer = BytePacketBuffer::from_raw_data(&bytes);
+        match Record::deserialize(&mut buffer).unwrap() {
+            Record::Custom(custom) => {
+                assert_eq!(QueryType::Unknown(65280), custom.qtype());
+                assert_eq!(Some(&Color { rgb: [0xFF, 0x80, 0x00] }), custom.data::<Color>());
+                assert_eq!(record, custom);
+            }
+            record => panic!("unexpected record {:?}", record),
+        }
+
+        custom::unregister(65280);
+        assert!(!custom::is_registered(65280));
+    }
+
+    #[test]
+    fn invalid_custom_data() {
+        custom::register::<Color>(65281).unwrap();
+
+        let mut buffer = BytePacketBuffer::from_raw_data(&[
+            0x00, // Root.
+            0xFF, 0x01, // Type TYPE65281.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x01, // RD length.
+            0xFF,
+        ]);
+        assert!(matches!(Record::deserialize(&mut buffer).unwrap(), Record::Unknown { .. }));
+    }
+
+    #[test]
+    fn register_builtin_type() {
+        assert!(custom::register::<Color>(QueryType::A.as_u16()).is_err());
+    }
+}
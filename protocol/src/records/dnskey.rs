@@ -0,0 +1,373 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_base64, format_name, parse_base64, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::dnssec::Algorithm;
+use crate::records::rdata::{serialize_record, RDataReader, WireHeader};
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// Zone Key flag, set on the keys used to sign the zone data.
+pub const ZONE_KEY: u16 = 0x0100;
+
+/// Revoke flag of RFC 5011, set on revoked trust anchors.
+pub const REVOKE: u16 = 0x0080;
+
+/// Secure Entry Point flag, set on key signing keys.
+pub const SECURE_ENTRY_POINT: u16 = 0x0001;
+
+// Value of the protocol field, the only one allowed.
+const PROTOCOL: u8 = 3;
+
+/// https://datatracker.ietf.org/doc/html/rfc4034#section-2.1
+///
+/// ```txt
+///                      1 1 1 1 1 1 1 1 1 1 2 2 2 2 2 2 2 2 2 2 3 3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |              Flags            |    Protocol   |   Algorithm   |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                                                               /
+/// /                            Public Key                         /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DNSKEY {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub flags: u16,
+    pub protocol: u8,
+    pub algorithm: Algorithm,
+    pub public_key: Vec<u8>,
+}
+
+/// Child copy of a DNSKEY record, for the parent to update its DS
+/// records (RFC 7344).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CDNSKEY(pub DNSKEY);
+
+/// Public key of RFC 2535 sharing the DNSKEY format, still used to verify
+/// SIG(0) transaction signatures (RFC 2931).
+#[derive(Debug, Clone, PartialEq)]
+pub struct KEY(pub DNSKEY);
+
+impl DNSKEY {
+    pub fn new<S>(domain: S, class: Class, ttl: Duration, flags: u16, algorithm: Algorithm, public_key: Vec<u8>) -> DNSKEY
+        where S: ToString
+    {
+        DNSKEY {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            flags,
+            protocol: PROTOCOL,
+            algorithm,
+            public_key,
+        }
+    }
+
+    pub fn is_zone_key(&self) -> bool {
+        self.flags & ZONE_KEY != 0
+    }
+
+    pub fn is_secure_entry_point(&self) -> bool {
+        self.flags & SECURE_ENTRY_POINT != 0
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.flags & REVOKE != 0
+    }
+
+    /// Tag identifying the key in RRSIG and DS records (RFC 4034 appendix B).
+    pub fn key_tag(&self) -> u16 {
+        // The RSA/MD5 tag is made of the least significant bits of the modulus.
+        if self.algorithm == Algorithm::RsaMd5 {
+            let len = self.public_key.len();
+            if len < 3 {
+                return 0;
+            }
+
+            return ((self.public_key[len - 3] as u16) << 8) | self.public_key[len - 2] as u16;
+        }
+
+        let mut acc: u32 = 0;
+        for (i, byte) in self.rdata().iter().enumerate() {
+            acc += if i & 1 == 1 { *byte as u32 } else { (*byte as u32) << 8 };
+        }
+
+        acc += (acc >> 16) & 0xFFFF;
+        (acc & 0xFFFF) as u16
+    }
+
+    /// Record data, as hashed by DS records.
+    pub fn rdata(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(4 + self.public_key.len());
+        data.extend_from_slice(&self.flags.to_be_bytes());
+        data.push(self.protocol);
+        data.push(self.algorithm.as_u8());
+        data.extend_from_slice(&self.public_key);
+        data
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.protocol != PROTOCOL {
+            return Err(InvalidData(format!("DNSKEY protocol must be {} but got {}", PROTOCOL, self.protocol)));
+        }
+
+        if self.public_key.is_empty() {
+            return Err(InvalidData("DNSKEY public key is empty".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<DNSKEY> {
+        let mut reader = RDataReader::new(data);
+
+        let record = DNSKEY {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            flags: reader.read_u16()?,
+            protocol: reader.read_u8()?,
+            algorithm: Algorithm::from_u8(reader.read_u8()?),
+            public_key: data[4..].to_vec(),
+        };
+
+        record.validate()?;
+        Ok(record)
+    }
+
+    fn serialize_as<S>(&self, qtype: QueryType, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.validate()?;
+        serialize_record(serializer, &self.domain, qtype, self.class, self.ttl, &self.rdata())
+    }
+
+    fn fmt_as(&self, qtype: QueryType, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            qtype,
+            self.flags,
+            self.protocol,
+            self.algorithm.as_u8(),
+            format_base64(&self.public_key),
+        )
+    }
+
+    fn parse_as(s: &str, qtype: QueryType) -> Result<DNSKEY> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, qtype)?;
+
+        let (flags, protocol, algorithm) = match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(flags), Some(protocol), Some(algorithm)) => (
+                parse_number(&token_str(&flags)?)?,
+                parse_number(&token_str(&protocol)?)?,
+                Algorithm::from_u8(parse_number(&token_str(&algorithm)?)?),
+            ),
+            _ => return Err(InvalidPresentation(format!("{} record expects flags, protocol, algorithm and public key", qtype))),
+        };
+
+        let record = DNSKEY {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            flags,
+            protocol,
+            algorithm,
+            public_key: parse_base64(tokens)?,
+        };
+
+        record.validate().map_err(|err| InvalidPresentation(err.to_string()))?;
+        Ok(record)
+    }
+}
+
+impl Deserialize for DNSKEY {
+    fn deserialize<D>(deserializer: &mut D) -> Result<DNSKEY>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::DnsKey)?;
+        DNSKEY::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for DNSKEY {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.serialize_as(QueryType::DnsKey, serializer)
+    }
+}
+
+impl Display for DNSKEY {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.fmt_as(QueryType::DnsKey, f)
+    }
+}
+
+impl FromStr for DNSKEY {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<DNSKEY> {
+        DNSKEY::parse_as(s, QueryType::DnsKey)
+    }
+}
+
+impl Deserialize for CDNSKEY {
+    fn deserialize<D>(deserializer: &mut D) -> Result<CDNSKEY>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::ChildDnsKey)?;
+        DNSKEY::from_rdata(&header.domain, header.class, header.ttl, &data).map(CDNSKEY)
+    }
+}
+
+impl Serialize for CDNSKEY {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.0.serialize_as(QueryType::ChildDnsKey, serializer)
+    }
+}
+
+impl Display for CDNSKEY {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.0.fmt_as(QueryType::ChildDnsKey, f)
+    }
+}
+
+impl FromStr for CDNSKEY {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<CDNSKEY> {
+        DNSKEY::parse_as(s, QueryType::ChildDnsKey).map(CDNSKEY)
+    }
+}
+
+impl Deserialize for KEY {
+    fn deserialize<D>(deserializer: &mut D) -> Result<KEY>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::SecurityKey)?;
+        DNSKEY::from_rdata(&header.domain, header.class, header.ttl, &data).map(KEY)
+    }
+}
+
+impl Serialize for KEY {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.0.serialize_as(QueryType::SecurityKey, serializer)
+    }
+}
+
+impl Display for KEY {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.0.fmt_as(QueryType::SecurityKey, f)
+    }
+}
+
+impl FromStr for KEY {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<KEY> {
+        DNSKEY::parse_as(s, QueryType::SecurityKey).map(KEY)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::de::Deserialize;
+    use crate::packet::{Class, Record};
+    use crate::records::dnskey::{CDNSKEY, DNSKEY, KEY, SECURE_ENTRY_POINT, ZONE_KEY};
+    use crate::records::dnssec::Algorithm;
+    use crate::ser::Serialize;
+
+    // Key of RFC 4034 section 2.3.
+    const PRESENTATION: &str = "example.com. 86400 IN DNSKEY 256 3 5 \
+        AQOeiiR0GOMYkDshWoSKz9XzfwJr1AYtsmx3TGkJaNXVbfi/2pHm822aJ5iI9BMzNXxeYCmZDRD99WYwYqUSdjMmmAphXdvxegXd/M5+X7OrzKBaMbCVdFLUUh6DhweJBjEVv5f2wwjM9XzcnOf+EPbtG9DMBmADjFDc2w/rljwvFw==";
+
+    fn dnskey() -> DNSKEY {
+        PRESENTATION.parse().unwrap()
+    }
+
+    #[test]
+    fn presentation() {
+        let record = dnskey();
+        assert_eq!("example.com", record.domain);
+        assert_eq!(Duration::from_secs(86400), record.ttl);
+        assert_eq!(ZONE_KEY, record.flags);
+        assert_eq!(Algorithm::RsaSha1, record.algorithm);
+        assert!(record.is_zone_key());
+        assert!(!record.is_secure_entry_point());
+        assert_eq!(PRESENTATION, record.to_string());
+
+        assert!("example.com. 86400 IN DNSKEY 256 2 5 AQOeiiR0".parse::<DNSKEY>().is_err());
+    }
+
+    #[test]
+    fn key_tag() {
+        // Tag of the RFC 4034 section 5.4 DS record made from this key.
+        assert_eq!(60485, dnskey().key_tag());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut record = dnskey();
+        record.flags |= SECURE_ENTRY_POINT;
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+        let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+        assert_eq!(Record::DnsKey(record.clone()), Record::deserialize(&mut buffer).unwrap());
+
+        let child = CDNSKEY(record);
+        let mut serializer = BytePacketBuffer::new();
+        assert!(child.serialize(&mut serializer).is_ok());
+        let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+        assert_eq!(Record::ChildDnsKey(child), Record::deserialize(&mut buffer).unwrap());
+    }
+
+    #[test]
+    fn cdnskey_presentation() {
+        let record = CDNSKEY(DNSKEY::new("example.com", Class::Internet, Duration::from_secs(0), 0, Algorithm::Unknown(0), vec![0]));
+        assert_eq!("example.com. 0 IN CDNSKEY 0 3 0 AA==", record.to_string());
+        assert_eq!(record, record.to_string().parse().unwrap());
+    }
+
+    #[test]
+    fn key() {
+        let record: KEY = "host.example.com. 3600 IN KEY 512 3 15 AA==".parse().unwrap();
+        assert_eq!(512, record.0.flags);
+        assert_eq!("host.example.com. 3600 IN KEY 512 3 15 AA==", record.to_string());
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+        let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+        assert_eq!(Record::SecurityKey(record), Record::deserialize(&mut buffer).unwrap());
+    }
+}
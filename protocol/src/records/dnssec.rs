@@ -0,0 +1,443 @@
+//! Definitions shared by the DNSSEC records (RFC 4034, RFC 5155), and the
+//! canonical form of records used to compute signatures.
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::iter::FromIterator;
+
+use crate::errors::Error::InvalidData;
+use crate::growable_buffer::GrowableBuffer;
+use crate::packet::{QueryType, Record};
+use crate::presentation::token_str;
+use crate::result::Result;
+use crate::ser::Serialize;
+
+/// DNSSEC algorithm numbers.
+///
+/// https://www.iana.org/assignments/dns-sec-alg-numbers/dns-sec-alg-numbers.xhtml
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Algorithm {
+    Unknown(u8),
+    // RSAMD5, Deprecated.
+    RsaMd5,
+    // DSA/SHA1.
+    Dsa,
+    // RSASHA1.
+    RsaSha1,
+    // DSA-NSEC3-SHA1.
+    DsaNsec3Sha1,
+    // RSASHA1-NSEC3-SHA1.
+    RsaSha1Nsec3Sha1,
+    // RSASHA256.
+    RsaSha256,
+    // RSASHA512.
+    RsaSha512,
+    // ECC-GOST.
+    EccGost,
+    // ECDSAP256SHA256.
+    EcdsaP256Sha256,
+    // ECDSAP384SHA384.
+    EcdsaP384Sha384,
+    // ED25519.
+    Ed25519,
+    // ED448.
+    Ed448,
+    // PRIVATEDNS, Private algorithm identified by a domain name.
+    PrivateDns,
+    // PRIVATEOID, Private algorithm identified by an OID.
+    PrivateOid,
+}
+
+impl Algorithm {
+    pub fn from_u8(num: u8) -> Algorithm {
+        match num {
+            1 => Algorithm::RsaMd5,
+            3 => Algorithm::Dsa,
+            5 => Algorithm::RsaSha1,
+            6 => Algorithm::DsaNsec3Sha1,
+            7 => Algorithm::RsaSha1Nsec3Sha1,
+            8 => Algorithm::RsaSha256,
+            10 => Algorithm::RsaSha512,
+            12 => Algorithm::EccGost,
+            13 => Algorithm::EcdsaP256Sha256,
+            14 => Algorithm::EcdsaP384Sha384,
+            15 => Algorithm::Ed25519,
+            16 => Algorithm::Ed448,
+            253 => Algorithm::PrivateDns,
+            254 => Algorithm::PrivateOid,
+            _ => Algorithm::Unknown(num),
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match *self {
+            Algorithm::RsaMd5 => 1,
+            Algorithm::Dsa => 3,
+            Algorithm::RsaSha1 => 5,
+            Algorithm::DsaNsec3Sha1 => 6,
+            Algorithm::RsaSha1Nsec3Sha1 => 7,
+            Algorithm::RsaSha256 => 8,
+            Algorithm::RsaSha512 => 10,
+            Algorithm::EccGost => 12,
+            Algorithm::EcdsaP256Sha256 => 13,
+            Algorithm::EcdsaP384Sha384 => 14,
+            Algorithm::Ed25519 => 15,
+            Algorithm::Ed448 => 16,
+            Algorithm::PrivateDns => 253,
+            Algorithm::PrivateOid => 254,
+            Algorithm::Unknown(num) => num,
+        }
+    }
+}
+
+/// Digest algorithms of DS records.
+///
+/// https://www.iana.org/assignments/ds-rr-types/ds-rr-types.xhtml
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum DigestType {
+    Unknown(u8),
+    Sha1,
+    Sha256,
+    Gost,
+    Sha384,
+}
+
+impl DigestType {
+    pub fn from_u8(num: u8) -> DigestType {
+        match num {
+            1 => DigestType::Sha1,
+            2 => DigestType::Sha256,
+            3 => DigestType::Gost,
+            4 => DigestType::Sha384,
+            _ => DigestType::Unknown(num),
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match *self {
+            DigestType::Sha1 => 1,
+            DigestType::Sha256 => 2,
+            DigestType::Gost => 3,
+            DigestType::Sha384 => 4,
+            DigestType::Unknown(num) => num,
+        }
+    }
+
+    /// Length of the digests, when the algorithm is known.
+    pub fn digest_len(&self) -> Option<usize> {
+        match *self {
+            DigestType::Sha1 => Some(20),
+            DigestType::Sha256 | DigestType::Gost => Some(32),
+            DigestType::Sha384 => Some(48),
+            DigestType::Unknown(_) => None,
+        }
+    }
+}
+
+/// Types present at a name, as listed by NSEC and NSEC3 records.
+///
+/// https://datatracker.ietf.org/doc/html/rfc4034#section-4.1.2
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TypeBitmap {
+    types: BTreeSet<u16>,
+}
+
+impl TypeBitmap {
+    pub fn new() -> TypeBitmap {
+        TypeBitmap::default()
+    }
+
+    pub fn insert(&mut self, qtype: QueryType) {
+        self.types.insert(qtype.as_u16());
+    }
+
+    pub fn contains(&self, qtype: QueryType) -> bool {
+        self.types.contains(&qtype.as_u16())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Types in increasing order of their number.
+    pub fn iter(&self) -> impl Iterator<Item=QueryType> + '_ {
+        self.types.iter().map(|num| QueryType::from_u16(*num))
+    }
+
+    pub(crate) fn from_wire(data: &[u8]) -> Result<TypeBitmap> {
+        let mut types = BTreeSet::new();
+        let mut previous_window = None;
+        let mut pos = 0;
+
+        while pos < data.len() {
+            if pos + 2 > data.len() {
+                return Err(InvalidData("truncated type bitmap window".to_string()));
+            }
+
+            let (window, len) = (data[pos], data[pos + 1] as usize);
+            if previous_window.is_some_and(|previous| previous >= window) {
+                return Err(InvalidData("type bitmap windows must be in increasing order".to_string()));
+            }
+
+            if len == 0 || len > 32 || pos + 2 + len > data.len() {
+                return Err(InvalidData(format!("invalid type bitmap length {}", len)));
+            }
+
+            for (i, byte) in data[pos + 2..pos + 2 + len].iter().enumerate() {
+                for bit in 0..8 {
+                    if byte & (0x80 >> bit) != 0 {
+                        types.insert(((window as u16) << 8) | (i * 8 + bit) as u16);
+                    }
+                }
+            }
+
+            previous_window = Some(window);
+            pos += 2 + len;
+        }
+
+        Ok(TypeBitmap { types })
+    }
+
+    pub(crate) fn to_wire(&self) -> Vec<u8> {
+        let mut data = vec![];
+        let mut window: Option<(u8, [u8; 32])> = None;
+
+        for num in &self.types {
+            let (current, bit) = ((num >> 8) as u8, (num & 0xFF) as usize);
+            match &mut window {
+                Some((index, bitmap)) if *index == current => bitmap[bit / 8] |= 0x80 >> (bit % 8),
+                _ => {
+                    if let Some((index, bitmap)) = window {
+                        push_window(&mut data, index, &bitmap);
+                    }
+
+                    let mut bitmap = [0; 32];
+                    bitmap[bit / 8] |= 0x80 >> (bit % 8);
+                    window = Some((current, bitmap));
+                }
+            }
+        }
+
+        if let Some((index, bitmap)) = window {
+            push_window(&mut data, index, &bitmap);
+        }
+
+        data
+    }
+
+    /// Parse the remaining tokens of a record as type mnemonics.
+    pub(crate) fn parse<I>(tokens: I) -> Result<TypeBitmap>
+        where I: Iterator<Item=Vec<u8>>
+    {
+        let mut bitmap = TypeBitmap::new();
+        for token in tokens {
+            bitmap.insert(token_str(&token)?.parse()?);
+        }
+
+        Ok(bitmap)
+    }
+}
+
+// Append a window, trimmed of its trailing empty bytes.
+fn push_window(data: &mut Vec<u8>, index: u8, bitmap: &[u8; 32]) {
+    let len = bitmap.iter().rposition(|byte| *byte != 0).map_or(0, |pos| pos + 1);
+    data.push(index);
+    data.push(len as u8);
+    data.extend_from_slice(&bitmap[..len]);
+}
+
+impl FromIterator<QueryType> for TypeBitmap {
+    fn from_iter<I>(iter: I) -> Self
+        where I: IntoIterator<Item=QueryType>
+    {
+        TypeBitmap {
+            types: iter.into_iter().map(|qtype| qtype.as_u16()).collect(),
+        }
+    }
+}
+
+impl Display for TypeBitmap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for (i, qtype) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+
+            write!(f, "{}", qtype)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compare names in canonical order, label by label from the rightmost
+/// one, ignoring case (RFC 4034 section 6.1).
+pub fn canonical_name_cmp(name: &str, other: &str) -> Ordering {
+    let labels = |name: &str| -> Vec<Vec<u8>> {
+        name.split('.')
+            .filter(|label| !label.is_empty())
+            .rev()
+            .map(|label| label.to_ascii_lowercase().into_bytes())
+            .collect()
+    };
+
+    labels(name).cmp(&labels(other))
+}
+
+/// Number of labels of a name, without the root and a leading wildcard,
+/// as counted by the RRSIG labels field.
+pub fn label_count(name: &str) -> u8 {
+    let name = name.strip_prefix("*.").or_else(|| name.strip_prefix('*')).unwrap_or(name);
+    name.split('.').filter(|label| !label.is_empty()).count() as u8
+}
+
+/// Record in canonical form: its owner and the domain names embedded in
+/// its data are lowercased (RFC 4034 section 6.2, updated by RFC 6840).
+pub fn canonical_record(record: &Record) -> Record {
+    let mut record = record.clone();
+    if let Some(domain) = record.domain_mut() {
+        *domain = domain.to_lowercase();
+    }
+
+    match &mut record {
+        Record::AuthoritativeNameServer(ns) => ns.ns_name = ns.ns_name.to_lowercase(),
+        Record::MailDestination(md) => md.madname = md.madname.to_lowercase(),
+        Record::MailForwarder(mf) => mf.madname = mf.madname.to_lowercase(),
+        Record::CanonicalName(cname) => cname.alias = cname.alias.to_lowercase(),
+        Record::StartOfAuthority(soa) => {
+            soa.mname = soa.mname.to_lowercase();
+            soa.rname = soa.rname.to_lowercase();
+        }
+        Record::Mailbox(mb) => mb.madname = mb.madname.to_lowercase(),
+        Record::MailGroup(mg) => mg.mgmname = mg.mgmname.to_lowercase(),
+        Record::MailRename(mr) => mr.newname = mr.newname.to_lowercase(),
+        Record::DomainPointer(ptr) => ptr.ptr_name = ptr.ptr_name.to_lowercase(),
+        Record::MailInformation(minfo) => {
+            minfo.rmailbx = minfo.rmailbx.to_lowercase();
+            minfo.emailbx = minfo.emailbx.to_lowercase();
+        }
+        Record::MailExchange(mx) => mx.exchange = mx.exchange.to_lowercase(),
+        Record::Service(srv) => srv.target = srv.target.to_lowercase(),
+        Record::NamingAuthorityPointer(naptr) => naptr.replacement = naptr.replacement.to_lowercase(),
+        Record::Signature(rrsig) => rrsig.signer_name = rrsig.signer_name.to_lowercase(),
+        Record::SecuritySignature(sig) => sig.0.signer_name = sig.0.signer_name.to_lowercase(),
+        _ => {}
+    }
+
+    record
+}
+
+/// Wire format of a record in canonical form, without name compression.
+pub fn canonical_wire(record: &Record) -> Result<Vec<u8>> {
+    let mut serializer = GrowableBuffer::default();
+    canonical_record(record).serialize(&mut serializer)?;
+    Ok(serializer.into_inner())
+}
+
+// Split the canonical wire format of a record into its header and its data.
+pub(crate) fn split_rdata(wire: &[u8]) -> (&[u8], &[u8]) {
+    // The owner name is made of length prefixed labels up to the root label.
+    let mut pos = 0;
+    while wire[pos] != 0 {
+        pos += 1 + wire[pos] as usize;
+    }
+
+    // Root label, type, class, TTL and data length.
+    wire.split_at(pos + 11)
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+    use std::time::Duration;
+
+    use crate::packet::{Class, QueryType, Record};
+    use crate::records::dnssec::{canonical_name_cmp, canonical_wire, label_count, TypeBitmap};
+    use crate::records::MailExchange;
+
+    #[test]
+    fn type_bitmap_round_trip() {
+        // Example of RFC 4034 section 4.3.
+        let bitmap: TypeBitmap = vec![
+            QueryType::A,
+            QueryType::MailExchange,
+            QueryType::Signature,
+            QueryType::NextSecure,
+            QueryType::Unknown(1234),
+        ].into_iter().collect();
+
+        let wire = bitmap.to_wire();
+        assert_eq!(vec![
+            0x00, 0x06, 0x40, 0x01, 0x00, 0x00, 0x00, 0x03,
+            0x04, 0x1b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20,
+        ], wire);
+
+        let parsed = TypeBitmap::from_wire(&wire).unwrap();
+        assert_eq!(bitmap, parsed);
+        assert!(parsed.contains(QueryType::Signature));
+        assert!(!parsed.contains(QueryType::Text));
+        assert_eq!("A MX RRSIG NSEC TYPE1234", parsed.to_string());
+    }
+
+    #[test]
+    fn invalid_type_bitmap() {
+        assert!(TypeBitmap::from_wire(&[0x00, 0x00]).is_err());
+        assert!(TypeBitmap::from_wire(&[0x00, 0x21]).is_err());
+        assert!(TypeBitmap::from_wire(&[0x00, 0x02, 0x40]).is_err());
+        assert!(TypeBitmap::from_wire(&[0x01, 0x01, 0x40, 0x00, 0x01, 0x40]).is_err());
+        assert!(TypeBitmap::from_wire(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn canonical_order() {
+        // Example of RFC 4034 section 6.1.
+        let names = [
+            "example",
+            "a.example",
+            "yljkjljk.a.example",
+            "Z.a.example",
+            "zABC.a.EXAMPLE",
+            "z.example",
+            "\u{1}.z.example",
+            "*.z.example",
+            "\u{200}.z.example",
+        ];
+
+        for window in names.windows(2) {
+            assert_eq!(Ordering::Less, canonical_name_cmp(window[0], window[1]), "{:?}", window);
+        }
+
+        assert_eq!(Ordering::Equal, canonical_name_cmp("Example.COM.", "example.com"));
+    }
+
+    #[test]
+    fn count_labels() {
+        assert_eq!(0, label_count(""));
+        assert_eq!(2, label_count("example.com."));
+        assert_eq!(2, label_count("*.example.com"));
+    }
+
+    #[test]
+    fn canonical_form() {
+        let record = Record::MailExchange(MailExchange {
+            domain: "Example.COM".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            preference: 10,
+            exchange: "MX.Example.com".to_string(),
+        });
+
+        assert_eq!(vec![
+            0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x03, 0x63, 0x6f, 0x6d, 0x00,
+            0x00, 0x0F, // Type MX.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x12, // RD length.
+            0x00, 0x0A, // Preference.
+            0x02, 0x6d, 0x78, 0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x03, 0x63, 0x6f, 0x6d, 0x00,
+        ], canonical_wire(&record).unwrap());
+    }
+}
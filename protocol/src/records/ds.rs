@@ -0,0 +1,253 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_hex, format_name, parse_hex, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::dnssec::{Algorithm, DigestType};
+use crate::records::rdata::{serialize_record, RDataReader, WireHeader};
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc4034#section-5.1
+///
+/// ```txt
+///                      1 1 1 1 1 1 1 1 1 1 2 2 2 2 2 2 2 2 2 2 3 3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |           Key Tag             |  Algorithm    |  Digest Type  |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                                                               /
+/// /                            Digest                             /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DS {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub key_tag: u16,
+    pub algorithm: Algorithm,
+    pub digest_type: DigestType,
+    pub digest: Vec<u8>,
+}
+
+/// Child copy of a DS record, for the parent to update its own (RFC 7344).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CDS(pub DS);
+
+impl DS {
+    /// Check that the digest length matches its algorithm.
+    pub fn validate(&self) -> Result<()> {
+        match self.digest_type.digest_len() {
+            Some(len) if len != self.digest.len() => Err(InvalidData(format!(
+                "DS digest of {} bytes doesn't match the {:?} digest type",
+                self.digest.len(),
+                self.digest_type,
+            ))),
+            _ if self.digest.is_empty() => Err(InvalidData("DS digest is empty".to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn rdata(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(4 + self.digest.len());
+        data.extend_from_slice(&self.key_tag.to_be_bytes());
+        data.push(self.algorithm.as_u8());
+        data.push(self.digest_type.as_u8());
+        data.extend_from_slice(&self.digest);
+        data
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<DS> {
+        let mut reader = RDataReader::new(data);
+
+        let record = DS {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            key_tag: reader.read_u16()?,
+            algorithm: Algorithm::from_u8(reader.read_u8()?),
+            digest_type: DigestType::from_u8(reader.read_u8()?),
+            digest: data[4..].to_vec(),
+        };
+
+        record.validate()?;
+        Ok(record)
+    }
+
+    fn serialize_as<S>(&self, qtype: QueryType, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.validate()?;
+        serialize_record(serializer, &self.domain, qtype, self.class, self.ttl, &self.rdata())
+    }
+
+    fn fmt_as(&self, qtype: QueryType, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            qtype,
+            self.key_tag,
+            self.algorithm.as_u8(),
+            self.digest_type.as_u8(),
+            format_hex(&self.digest),
+        )
+    }
+
+    fn parse_as(s: &str, qtype: QueryType) -> Result<DS> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, qtype)?;
+
+        let (key_tag, algorithm, digest_type) = match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(key_tag), Some(algorithm), Some(digest_type)) => (
+                parse_number(&token_str(&key_tag)?)?,
+                Algorithm::from_u8(parse_number(&token_str(&algorithm)?)?),
+                DigestType::from_u8(parse_number(&token_str(&digest_type)?)?),
+            ),
+            _ => return Err(InvalidPresentation(format!("{} record expects key tag, algorithm, digest type and digest", qtype))),
+        };
+
+        let record = DS {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            key_tag,
+            algorithm,
+            digest_type,
+            digest: parse_hex(tokens)?,
+        };
+
+        record.validate().map_err(|err| InvalidPresentation(err.to_string()))?;
+        Ok(record)
+    }
+}
+
+impl Deserialize for DS {
+    fn deserialize<D>(deserializer: &mut D) -> Result<DS>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::DelegationSigner)?;
+        DS::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for DS {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.serialize_as(QueryType::DelegationSigner, serializer)
+    }
+}
+
+impl Display for DS {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.fmt_as(QueryType::DelegationSigner, f)
+    }
+}
+
+impl FromStr for DS {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<DS> {
+        DS::parse_as(s, QueryType::DelegationSigner)
+    }
+}
+
+impl Deserialize for CDS {
+    fn deserialize<D>(deserializer: &mut D) -> Result<CDS>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::ChildDelegationSigner)?;
+        DS::from_rdata(&header.domain, header.class, header.ttl, &data).map(CDS)
+    }
+}
+
+impl Serialize for CDS {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.0.serialize_as(QueryType::ChildDelegationSigner, serializer)
+    }
+}
+
+impl Display for CDS {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.0.fmt_as(QueryType::ChildDelegationSigner, f)
+    }
+}
+
+impl FromStr for CDS {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<CDS> {
+        DS::parse_as(s, QueryType::ChildDelegationSigner).map(CDS)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::de::Deserialize;
+    use crate::packet::Record;
+    use crate::records::dnssec::{Algorithm, DigestType};
+    use crate::records::ds::{CDS, DS};
+    use crate::ser::Serialize;
+
+    // Record of RFC 4034 section 5.4.
+    const PRESENTATION: &str = "dskey.example.com. 86400 IN DS 60485 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118";
+
+    #[test]
+    fn presentation() {
+        let record: DS = PRESENTATION.parse().unwrap();
+        assert_eq!(60485, record.key_tag);
+        assert_eq!(Algorithm::RsaSha1, record.algorithm);
+        assert_eq!(DigestType::Sha1, record.digest_type);
+        assert_eq!(PRESENTATION, record.to_string());
+
+        let split = "dskey.example.com. 86400 IN DS 60485 5 1 ( 2BB183AF5F22588179A5 3B0A98631FAD1A292118 )";
+        assert_eq!(record, split.parse().unwrap());
+
+        assert!("dskey.example.com. 86400 IN DS 60485 5 2 2BB183AF5F22588179A53B0A98631FAD1A292118".parse::<DS>().is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let record: DS = PRESENTATION.parse().unwrap();
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+        let bytes = serializer.bytes();
+        assert_eq!(&[
+            0x00, 0x2B, // Type DS.
+            0x00, 0x01, // Class IN.
+            0x00, 0x01, 0x51, 0x80, // TTL.
+            0x00, 0x18, // RD length.
+            0xEC, 0x45, // Key tag.
+            0x05, // Algorithm.
+            0x01, // Digest type.
+        ], &bytes[19..33]);
+
+        let mut buffer = BytePacketBuffer::from_raw_data(&bytes);
+        assert_eq!(Record::DelegationSigner(record.clone()), Record::deserialize(&mut buffer).unwrap());
+
+        let child = CDS(record);
+        let mut serializer = BytePacketBuffer::new();
+        assert!(child.serialize(&mut serializer).is_ok());
+        let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+        assert_eq!(Record::ChildDelegationSigner(child), Record::deserialize(&mut buffer).unwrap());
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, quote, tokenize, RecordHeader};
+use crate::records::rdata::RDataReader;
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.2
+///
+/// ```txt
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                      CPU                      /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                       OS                      /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// CPU and OS are <character-string>. HINFO records are also used to
+/// answer ANY queries with a minimal response (RFC 8482).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HINFO {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub cpu: Vec<u8>,
+    pub os: Vec<u8>,
+}
+
+impl HINFO {
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<HINFO> {
+        let mut reader = RDataReader::new(data);
+        let cpu = reader.read_character_string()?.to_vec();
+        let os = reader.read_character_string()?.to_vec();
+
+        if !reader.is_empty() {
+            return Err(InvalidData("unexpected trailing HINFO data".to_string()));
+        }
+
+        Ok(HINFO {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            cpu,
+            os,
+        })
+    }
+}
+
+impl Deserialize for HINFO {
+    fn deserialize<D>(deserializer: &mut D) -> Result<HINFO>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::HostInformation)?;
+        HINFO::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for HINFO {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        if self.cpu.len() > 255 || self.os.len() > 255 {
+            return Err(InvalidData("HINFO character-string exceeds 255 bytes".to_string()));
+        }
+
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 13 for HINFO)
+        serializer.serialize_u16(QueryType::HostInformation.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        serializer.serialize_u16((2 + self.cpu.len() + self.os.len()) as u16)?;
+
+        // CPU.
+        serializer.serialize_u8(self.cpu.len() as u8)?;
+        serializer.serialize_bytes(&self.cpu)?;
+
+        // OS.
+        serializer.serialize_u8(self.os.len() as u8)?;
+        serializer.serialize_bytes(&self.os)
+    }
+}
+
+impl Display for HINFO {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} HINFO {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            quote(&self.cpu),
+            quote(&self.os),
+        )
+    }
+}
+
+impl FromStr for HINFO {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<HINFO> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::HostInformation)?;
+
+        match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(cpu), Some(os), None) if cpu.len() <= 255 && os.len() <= 255 => Ok(HINFO {
+                domain: header.domain,
+                class: header.class,
+                ttl: header.ttl,
+                cpu,
+                os,
+            }),
+            _ => Err(InvalidPresentation("HINFO record expects a CPU and an OS string".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::HINFO;
+    use crate::ser::Serialize;
+
+    fn hinfo() -> HINFO {
+        HINFO {
+            domain: "x".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            cpu: b"RFC8482".to_vec(),
+            os: vec![],
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+
+        let res = hinfo().serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0x00, 0x0D, // Type HINFO.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x09, // RD length.
+            0x07, 0x52, 0x46, 0x43, 0x38, 0x34, 0x38, 0x32, // CPU="RFC8482".
+            0x00, // OS="".
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn from_rdata() {
+        assert_eq!(hinfo(), HINFO::from_rdata("x", Class::Internet, Duration::from_secs(60), b"\x07RFC8482\x00").unwrap());
+        assert!(HINFO::from_rdata("x", Class::Internet, Duration::from_secs(60), b"\x07RFC8482").is_err());
+        assert!(HINFO::from_rdata("x", Class::Internet, Duration::from_secs(60), b"\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn presentation() {
+        let text = hinfo().to_string();
+        assert_eq!(r#"x. 60 IN HINFO "RFC8482" """#, text);
+        assert_eq!(hinfo(), text.parse().unwrap());
+    }
+}
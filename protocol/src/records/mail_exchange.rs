@@ -1,18 +1,45 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
 use std::time::Duration;
 
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_name, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
 use crate::result::Result;
 use crate::seek::Seek;
 use crate::ser::{Serialize, Serializer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MailExchange {
     pub domain: String,
-    pub _class: u16,
+    pub class: Class,
     pub ttl: Duration,
     pub preference: u16,
     pub exchange: String,
 }
 
+impl Deserialize for MailExchange {
+    fn deserialize<D>(deserializer: &mut D) -> Result<MailExchange>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::MailExchange)?;
+        let record = MailExchange {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            preference: deserializer.deserialize_u16()?,
+            exchange: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
 impl Serialize for MailExchange {
     fn serialize<S>(&self, serializer: &mut S) -> Result<()>
         where
@@ -20,17 +47,99 @@ impl Serialize for MailExchange {
     {
         serializer.serialize_qname(&self.domain)?;
         serializer.serialize_u16(15)?;
-        serializer.serialize_u16(1)?;
+        serializer.serialize_u16(self.class.as_u16())?;
         serializer.serialize_u32(self.ttl.as_secs() as u32)?;
         let size_pos = serializer.position();
         serializer.serialize_u16(0)?;
         serializer.serialize_u16(self.preference)?;
         serializer.serialize_qname(&self.exchange)?;
-        let payload_size = serializer.position() - size_pos + 2;
+        let payload_size = serializer.position() - (size_pos + 2);
 
         let current_position = serializer.position();
         serializer.seek(size_pos)?;
         serializer.serialize_u16(payload_size as u16)?;
         serializer.seek(current_position)
     }
-}
\ No newline at end of file
+}
+impl Display for MailExchange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} MX {} {}", format_name(&self.domain), self.ttl.as_secs(), self.class, self.preference, format_name(&self.exchange))
+    }
+}
+
+impl FromStr for MailExchange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<MailExchange> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::MailExchange)?;
+
+        let fields = tokens.map(|token| token_str(&token)).collect::<Result<Vec<String>>>()?;
+        match &fields[..] {
+            [preference, exchange] => Ok(MailExchange {
+                domain: header.domain,
+                class: header.class,
+                ttl: header.ttl,
+                preference: parse_number(preference)?,
+                exchange: parse_name(exchange),
+            }),
+            _ => Err(InvalidPresentation("MX record expects a preference and an exchange".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::MailExchange;
+    use crate::ser::Serialize;
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+        let mx = MailExchange {
+            domain: "example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            preference: 10,
+            exchange: "mail.example.com".to_string(),
+        };
+
+        let res = mx.serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, // len=7 label="example"
+            0x03, 0x63, 0x6f, 0x6d, // len=3 label="com"
+            0x00,
+            0x00, 0x0F, // Type MX.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x14, // RD length, without the length field itself.
+            0x00, 0x0A, // Preference.
+            0x04, 0x6d, 0x61, 0x69, 0x6c, // len=4 label="mail"
+            0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, // len=7 label="example"
+            0x03, 0x63, 0x6f, 0x6d, // len=3 label="com"
+            0x00,
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn presentation() {
+        let mx = MailExchange {
+            domain: "example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            preference: 10,
+            exchange: "mail.example.com".to_string(),
+        };
+
+        let text = mx.to_string();
+        assert_eq!("example.com. 60 IN MX 10 mail.example.com.", text);
+        assert_eq!(mx, text.parse().unwrap());
+        assert!("example.com. 60 IN MX mail.example.com.".parse::<MailExchange>().is_err());
+    }
+}
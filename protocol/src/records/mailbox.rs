@@ -0,0 +1,405 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_name, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.4
+///
+/// ```txt
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                   MADNAME                     /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// Obsolete, superseded by MX records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MD {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub madname: String,
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.5
+///
+/// ```txt
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                   MADNAME                     /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// Obsolete, superseded by MX records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MF {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub madname: String,
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.3
+///
+/// ```txt
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                   MADNAME                     /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// MADNAME is a host which has the mailbox named by the owner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MB {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub madname: String,
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.6
+///
+/// ```txt
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                   MGMNAME                     /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// MGMNAME is a mailbox member of the mail group named by the owner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MG {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub mgmname: String,
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.8
+///
+/// ```txt
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                   NEWNAME                     /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// NEWNAME is the mailbox which is the proper rename of the owner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MR {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub newname: String,
+}
+
+// Every record of this module holds a single domain name, serialized
+// the same way as CNAME or PTR records.
+fn serialize_name_record<S>(serializer: &mut S, domain: &str, qtype: QueryType, class: Class, ttl: Duration, name: &str) -> Result<()>
+    where
+        S: Serializer + Seek
+{
+    // Name.
+    serializer.serialize_qname(domain)?;
+
+    // Type.
+    serializer.serialize_u16(qtype.as_u16())?;
+
+    // Class.
+    serializer.serialize_u16(class.as_u16())?;
+
+    // TTL.
+    serializer.serialize_u32(ttl.as_secs() as u32)?;
+
+    // Domain name size.
+    // Saving a pointer to this field to be able to
+    // set the size after domain length computation.
+    let size_pos = serializer.position();
+    serializer.serialize_u16(0)?;
+
+    // Domain name.
+    serializer.serialize_qname(name)?;
+
+    let payload_size = serializer.position() - (size_pos + 2);
+    let current_position = serializer.position();
+    serializer.seek(size_pos)?;
+    serializer.serialize_u16(payload_size as u16)?;
+    serializer.seek(current_position)
+}
+
+fn parse_name_record(s: &str, qtype: QueryType) -> Result<(RecordHeader, String)> {
+    let mut tokens = tokenize(s)?.into_iter();
+    let header = RecordHeader::parse(&mut tokens, qtype)?;
+
+    match (tokens.next(), tokens.next()) {
+        (Some(token), None) => Ok((header, parse_name(&token_str(&token)?))),
+        _ => Err(InvalidPresentation(format!("{} record expects a single domain name", qtype))),
+    }
+}
+
+impl Deserialize for MD {
+    fn deserialize<D>(deserializer: &mut D) -> Result<MD>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::MailDestination)?;
+        let record = MD {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            madname: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
+impl Serialize for MD {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        serialize_name_record(serializer, &self.domain, QueryType::MailDestination, self.class, self.ttl, &self.madname)
+    }
+}
+
+impl Display for MD {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} MD {}", format_name(&self.domain), self.ttl.as_secs(), self.class, format_name(&self.madname))
+    }
+}
+
+impl FromStr for MD {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<MD> {
+        let (header, madname) = parse_name_record(s, QueryType::MailDestination)?;
+        Ok(MD { domain: header.domain, class: header.class, ttl: header.ttl, madname })
+    }
+}
+
+impl Deserialize for MF {
+    fn deserialize<D>(deserializer: &mut D) -> Result<MF>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::MailForwarder)?;
+        let record = MF {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            madname: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
+impl Serialize for MF {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        serialize_name_record(serializer, &self.domain, QueryType::MailForwarder, self.class, self.ttl, &self.madname)
+    }
+}
+
+impl Display for MF {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} MF {}", format_name(&self.domain), self.ttl.as_secs(), self.class, format_name(&self.madname))
+    }
+}
+
+impl FromStr for MF {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<MF> {
+        let (header, madname) = parse_name_record(s, QueryType::MailForwarder)?;
+        Ok(MF { domain: header.domain, class: header.class, ttl: header.ttl, madname })
+    }
+}
+
+impl Deserialize for MB {
+    fn deserialize<D>(deserializer: &mut D) -> Result<MB>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::Mailbox)?;
+        let record = MB {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            madname: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
+impl Serialize for MB {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        serialize_name_record(serializer, &self.domain, QueryType::Mailbox, self.class, self.ttl, &self.madname)
+    }
+}
+
+impl Display for MB {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} MB {}", format_name(&self.domain), self.ttl.as_secs(), self.class, format_name(&self.madname))
+    }
+}
+
+impl FromStr for MB {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<MB> {
+        let (header, madname) = parse_name_record(s, QueryType::Mailbox)?;
+        Ok(MB { domain: header.domain, class: header.class, ttl: header.ttl, madname })
+    }
+}
+
+impl Deserialize for MG {
+    fn deserialize<D>(deserializer: &mut D) -> Result<MG>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::MailGroup)?;
+        let record = MG {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            mgmname: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
+impl Serialize for MG {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        serialize_name_record(serializer, &self.domain, QueryType::MailGroup, self.class, self.ttl, &self.mgmname)
+    }
+}
+
+impl Display for MG {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} MG {}", format_name(&self.domain), self.ttl.as_secs(), self.class, format_name(&self.mgmname))
+    }
+}
+
+impl FromStr for MG {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<MG> {
+        let (header, mgmname) = parse_name_record(s, QueryType::MailGroup)?;
+        Ok(MG { domain: header.domain, class: header.class, ttl: header.ttl, mgmname })
+    }
+}
+
+impl Deserialize for MR {
+    fn deserialize<D>(deserializer: &mut D) -> Result<MR>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::MailRename)?;
+        let record = MR {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            newname: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
+impl Serialize for MR {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        serialize_name_record(serializer, &self.domain, QueryType::MailRename, self.class, self.ttl, &self.newname)
+    }
+}
+
+impl Display for MR {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} MR {}", format_name(&self.domain), self.ttl.as_secs(), self.class, format_name(&self.newname))
+    }
+}
+
+impl FromStr for MR {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<MR> {
+        let (header, newname) = parse_name_record(s, QueryType::MailRename)?;
+        Ok(MR { domain: header.domain, class: header.class, ttl: header.ttl, newname })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::{MB, MG, MR};
+    use crate::ser::Serialize;
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+        let mb = MB {
+            domain: "x".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            madname: "mail.x".to_string(),
+        };
+
+        let res = mb.serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0x00, 0x07, // Type MB.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x08, // RD length.
+            0x04, 0x6d, 0x61, 0x69, 0x6c, // len=4 label="mail"
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn presentation() {
+        let mg: MG = "staff.x. 60 IN MG alice.x.".parse().unwrap();
+        assert_eq!("alice.x", mg.mgmname);
+        assert_eq!("staff.x. 60 IN MG alice.x.", mg.to_string());
+
+        let mr: MR = "bob.x. MR robert.x.".parse().unwrap();
+        assert_eq!("robert.x", mr.newname);
+        assert!("bob.x. MG robert.x.".parse::<MR>().is_err());
+        assert!("bob.x. MR".parse::<MR>().is_err());
+    }
+}
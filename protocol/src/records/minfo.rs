@@ -0,0 +1,173 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_name, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.7
+///
+/// ```txt
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                    RMAILBX                    /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                    EMAILBX                    /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// RMAILBX is the mailbox responsible for the mailing list named by the
+/// owner, EMAILBX the mailbox receiving errors related to the list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MINFO {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub rmailbx: String,
+    pub emailbx: String,
+}
+
+impl Deserialize for MINFO {
+    fn deserialize<D>(deserializer: &mut D) -> Result<MINFO>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::MailInformation)?;
+        let record = MINFO {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            rmailbx: deserializer.deserialize_qname()?,
+            emailbx: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
+impl Serialize for MINFO {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 14 for MINFO)
+        serializer.serialize_u16(QueryType::MailInformation.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        // Saving a pointer to this field to be able to
+        // set the size after mailboxes length computation.
+        let size_pos = serializer.position();
+        serializer.serialize_u16(0)?;
+
+        // Mailboxes.
+        serializer.serialize_qname(&self.rmailbx)?;
+        serializer.serialize_qname(&self.emailbx)?;
+
+        let payload_size = serializer.position() - (size_pos + 2);
+        let current_position = serializer.position();
+        serializer.seek(size_pos)?;
+        serializer.serialize_u16(payload_size as u16)?;
+        serializer.seek(current_position)
+    }
+}
+
+impl Display for MINFO {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} MINFO {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            format_name(&self.rmailbx),
+            format_name(&self.emailbx),
+        )
+    }
+}
+
+impl FromStr for MINFO {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<MINFO> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::MailInformation)?;
+
+        match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(rmailbx), Some(emailbx), None) => Ok(MINFO {
+                domain: header.domain,
+                class: header.class,
+                ttl: header.ttl,
+                rmailbx: parse_name(&token_str(&rmailbx)?),
+                emailbx: parse_name(&token_str(&emailbx)?),
+            }),
+            _ => Err(InvalidPresentation("MINFO record expects two mailboxes".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::MINFO;
+    use crate::ser::Serialize;
+
+    fn minfo() -> MINFO {
+        MINFO {
+            domain: "list.x".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            rmailbx: "owner.x".to_string(),
+            emailbx: "err.x".to_string(),
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+
+        let res = minfo().serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x04, 0x6c, 0x69, 0x73, 0x74, // len=4 label="list"
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0x00, 0x0E, // Type MINFO.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x10, // RD length.
+            0x05, 0x6f, 0x77, 0x6e, 0x65, 0x72, // len=5 label="owner"
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0x03, 0x65, 0x72, 0x72, // len=3 label="err"
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn presentation() {
+        let text = minfo().to_string();
+        assert_eq!("list.x. 60 IN MINFO owner.x. err.x.", text);
+        assert_eq!(minfo(), text.parse().unwrap());
+    }
+}
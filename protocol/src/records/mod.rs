@@ -1,9 +1,57 @@
 pub use a::A;
 pub use authoritative_name_server::AuthoritativeNameServer;
+pub use caa::CAA;
 pub use cname::CName;
+pub use custom::CustomRecord;
+pub use dnskey::{CDNSKEY, DNSKEY, KEY};
+pub use ds::{CDS, DS};
+pub use hinfo::HINFO;
 pub use mail_exchange::MailExchange;
+pub use mailbox::{MB, MD, MF, MG, MR};
+pub use minfo::MINFO;
+pub use naptr::NAPTR;
+pub use nsec::NSEC;
+pub use nsec3::{NSEC3, NSEC3PARAM};
+pub use null::NULL;
+pub use opt::OPT;
+pub use ptr::PTR;
+pub use rrsig::{RRSIG, SIG};
+pub use soa::SOA;
+pub use srv::SRV;
+pub use sshfp::SSHFP;
+pub use svcb::{HTTPS, SVCB};
+pub use tlsa::TLSA;
+pub use tsig::TSIG;
+pub use txt::TXT;
+pub use uri::URI;
+pub use wks::WKS;
 
 mod a;
 mod authoritative_name_server;
+mod caa;
 mod cname;
+pub mod custom;
+pub mod dnskey;
+pub mod dnssec;
+mod ds;
+mod hinfo;
 mod mail_exchange;
+mod mailbox;
+mod minfo;
+pub mod naptr;
+mod nsec;
+pub mod nsec3;
+mod null;
+pub mod opt;
+mod ptr;
+mod rdata;
+mod rrsig;
+mod soa;
+mod srv;
+pub mod sshfp;
+pub mod svcb;
+pub mod tlsa;
+pub mod tsig;
+mod txt;
+mod uri;
+pub mod wks;
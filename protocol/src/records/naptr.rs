@@ -0,0 +1,357 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_name, parse_number, quote, token_str, tokenize, RecordHeader};
+use crate::records::rdata::RDataReader;
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc3403#section-4.1
+///
+/// ```txt
+///                                 1  1  1  1  1  1
+///   0  1  2  3  4  5  6  7  8  9  0  1  2  3  4  5
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                     ORDER                     |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                   PREFERENCE                  |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                     FLAGS                     /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                   SERVICES                    /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                    REGEXP                     /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                  REPLACEMENT                  /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// FLAGS, SERVICES and REGEXP are <character-string>. A rule either
+/// rewrites the input with its REGEXP or replaces it with REPLACEMENT,
+/// never both.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NAPTR {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub order: u16,
+    pub preference: u16,
+    pub flags: Vec<u8>,
+    pub services: Vec<u8>,
+    pub regexp: Vec<u8>,
+    pub replacement: String,
+}
+
+/// Substitution expression held by the REGEXP field, made of an
+/// extended regular expression and a replacement template
+/// (e.g. `!^.*$!sip:info@example.com!i`).
+///
+/// See: https://datatracker.ietf.org/doc/html/rfc3402#section-3.2
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubstitutionExpression {
+    pub pattern: String,
+    pub replacement: String,
+    pub case_insensitive: bool,
+}
+
+impl NAPTR {
+    /// Whether the rule is terminal, meaning the lookups stop with this
+    /// rule, as opposed to the empty flags asking for another NAPTR lookup.
+    pub fn is_terminal(&self) -> bool {
+        !self.flags.is_empty()
+    }
+
+    /// Split the REGEXP field into its parts, or `None` when the rule
+    /// uses the REPLACEMENT field instead.
+    pub fn substitution(&self) -> Result<Option<SubstitutionExpression>> {
+        if self.regexp.is_empty() {
+            return Ok(None);
+        }
+
+        let regexp = String::from_utf8(self.regexp.clone())
+            .map_err(|_| InvalidData("NAPTR regexp is not valid UTF-8".to_string()))?;
+
+        // The first character is the delimiter, which can't be a digit,
+        // a backslash or the flag "i" (RFC 3402 section 3.2).
+        let delimiter = match regexp.chars().next() {
+            Some(c) if !c.is_ascii_digit() && c != '\\' && c != 'i' => c,
+            _ => return Err(InvalidData(format!("invalid NAPTR regexp delimiter in {:?}", regexp))),
+        };
+
+        let mut parts = vec![String::new()];
+        let mut chars = regexp[delimiter.len_utf8()..].chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    // Escaped delimiters are part of the expression.
+                    let escaped = chars.next().unwrap_or('\\');
+                    if escaped != delimiter {
+                        parts.last_mut().unwrap().push('\\');
+                    }
+                    parts.last_mut().unwrap().push(escaped);
+                }
+                _ if c == delimiter => parts.push(String::new()),
+                _ => parts.last_mut().unwrap().push(c),
+            }
+        }
+
+        match &parts[..] {
+            [pattern, replacement, flags] if flags.is_empty() || flags == "i" => Ok(Some(SubstitutionExpression {
+                pattern: pattern.clone(),
+                replacement: replacement.clone(),
+                case_insensitive: flags == "i",
+            })),
+            _ => Err(InvalidData(format!("invalid NAPTR regexp {:?}", regexp))),
+        }
+    }
+
+    /// A rule must either use REGEXP or REPLACEMENT.
+    pub fn validate(&self) -> Result<()> {
+        if !self.regexp.is_empty() && !self.replacement.is_empty() {
+            return Err(InvalidData("NAPTR regexp and replacement are mutually exclusive".to_string()));
+        }
+
+        if let Some(field) = [&self.flags, &self.services, &self.regexp].iter().find(|field| field.len() > 255) {
+            return Err(InvalidData(format!("NAPTR character-string of {} bytes exceeds 255 bytes", field.len())));
+        }
+
+        if !self.flags.iter().all(u8::is_ascii_alphanumeric) {
+            return Err(InvalidData("NAPTR flags must be alphanumeric".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<NAPTR> {
+        let mut reader = RDataReader::new(data);
+
+        let record = NAPTR {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            order: reader.read_u16()?,
+            preference: reader.read_u16()?,
+            flags: reader.read_character_string()?.to_vec(),
+            services: reader.read_character_string()?.to_vec(),
+            regexp: reader.read_character_string()?.to_vec(),
+            replacement: reader.read_name()?,
+        };
+
+        if !reader.is_empty() {
+            return Err(InvalidData("unexpected trailing NAPTR data".to_string()));
+        }
+
+        record.validate()?;
+        Ok(record)
+    }
+}
+
+impl Deserialize for NAPTR {
+    fn deserialize<D>(deserializer: &mut D) -> Result<NAPTR>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::NamingAuthorityPointer)?;
+        NAPTR::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for NAPTR {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.validate()?;
+
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 35 for NAPTR)
+        serializer.serialize_u16(QueryType::NamingAuthorityPointer.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        // Saving a pointer to this field to be able to
+        // set the size after replacement length computation.
+        let size_pos = serializer.position();
+        serializer.serialize_u16(0)?;
+
+        serializer.serialize_u16(self.order)?;
+        serializer.serialize_u16(self.preference)?;
+        for string in [&self.flags, &self.services, &self.regexp].iter() {
+            serializer.serialize_u8(string.len() as u8)?;
+            serializer.serialize_bytes(string)?;
+        }
+
+        // Replacement, which must not be compressed.
+        serializer.serialize_qname(&self.replacement)?;
+
+        let payload_size = serializer.position() - (size_pos + 2);
+        let current_position = serializer.position();
+        serializer.seek(size_pos)?;
+        serializer.serialize_u16(payload_size as u16)?;
+        serializer.seek(current_position)
+    }
+}
+
+impl Display for NAPTR {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} NAPTR {} {} {} {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            self.order,
+            self.preference,
+            quote(&self.flags),
+            quote(&self.services),
+            quote(&self.regexp),
+            format_name(&self.replacement),
+        )
+    }
+}
+
+impl FromStr for NAPTR {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<NAPTR> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::NamingAuthorityPointer)?;
+
+        let fields: Vec<Vec<u8>> = tokens.collect();
+        let record = match &fields[..] {
+            [order, preference, flags, services, regexp, replacement] => NAPTR {
+                domain: header.domain,
+                class: header.class,
+                ttl: header.ttl,
+                order: parse_number(&token_str(order)?)?,
+                preference: parse_number(&token_str(preference)?)?,
+                flags: flags.clone(),
+                services: services.clone(),
+                regexp: regexp.clone(),
+                replacement: parse_name(&token_str(replacement)?),
+            },
+            _ => return Err(InvalidPresentation("NAPTR record expects order, preference, flags, services, regexp and replacement".to_string())),
+        };
+
+        record.validate().map_err(|err| InvalidPresentation(err.to_string()))?;
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::naptr::SubstitutionExpression;
+    use crate::records::NAPTR;
+    use crate::ser::Serialize;
+
+    fn enum_rule() -> NAPTR {
+        NAPTR {
+            domain: "4.3.2.1.5.5.5.0.0.8.1.e164.arpa".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            order: 100,
+            preference: 10,
+            flags: b"u".to_vec(),
+            services: b"E2U+sip".to_vec(),
+            regexp: b"!^.*$!sip:info@example.com!".to_vec(),
+            replacement: "".to_string(),
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+        let record = NAPTR {
+            domain: "x".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            order: 100,
+            preference: 10,
+            flags: b"s".to_vec(),
+            services: b"SIP+D2U".to_vec(),
+            regexp: vec![],
+            replacement: "_sip._udp.x".to_string(),
+        };
+
+        let res = record.serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0x00, 0x23, // Type NAPTR.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x1C, // RD length.
+            0x00, 0x64, // Order.
+            0x00, 0x0A, // Preference.
+            0x01, 0x73, // Flags="s".
+            0x07, 0x53, 0x49, 0x50, 0x2b, 0x44, 0x32, 0x55, // Services="SIP+D2U".
+            0x00, // Regexp="".
+            0x04, 0x5f, 0x73, 0x69, 0x70, // len=4 label="_sip"
+            0x04, 0x5f, 0x75, 0x64, 0x70, // len=4 label="_udp"
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn from_rdata() {
+        let data = b"\x00\x64\x00\x0a\x01u\x07E2U+sip\x1b!^.*$!sip:info@example.com!\x00";
+        let record = NAPTR::from_rdata("4.3.2.1.5.5.5.0.0.8.1.e164.arpa", Class::Internet, Duration::from_secs(60), data).unwrap();
+        assert_eq!(enum_rule(), record);
+        assert!(record.is_terminal());
+
+        let both = b"\x00\x64\x00\x0a\x01u\x07E2U+sip\x03!a!\x01x\x00";
+        assert!(NAPTR::from_rdata("x", Class::Internet, Duration::from_secs(60), both).is_err());
+    }
+
+    #[test]
+    fn substitution() {
+        assert_eq!(Some(SubstitutionExpression {
+            pattern: "^.*$".to_string(),
+            replacement: "sip:info@example.com".to_string(),
+            case_insensitive: false,
+        }), enum_rule().substitution().unwrap());
+
+        let mut record = enum_rule();
+        record.regexp = br"/^\+1(.*)$/sip:\1\/x@example.com/i".to_vec();
+        assert_eq!(Some(SubstitutionExpression {
+            pattern: r"^\+1(.*)$".to_string(),
+            replacement: r"sip:\1/x@example.com".to_string(),
+            case_insensitive: true,
+        }), record.substitution().unwrap());
+
+        record.regexp = b"!^.*$!sip:info@example.com".to_vec();
+        assert!(record.substitution().is_err());
+
+        record.regexp = vec![];
+        assert_eq!(None, record.substitution().unwrap());
+    }
+
+    #[test]
+    fn presentation() {
+        let text = enum_rule().to_string();
+        assert_eq!(r#"4.3.2.1.5.5.5.0.0.8.1.e164.arpa. 60 IN NAPTR 100 10 "u" "E2U+sip" "!^.*$!sip:info@example.com!" ."#, text);
+        assert_eq!(enum_rule(), text.parse().unwrap());
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_name, token_str, tokenize, RecordHeader};
+use crate::records::dnssec::TypeBitmap;
+use crate::records::rdata::{serialize_record, RDataReader, WireHeader};
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc4034#section-4.1
+///
+/// ```txt
+///                      1 1 1 1 1 1 1 1 1 1 2 2 2 2 2 2 2 2 2 2 3 3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                      Next Domain Name                         /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                       Type Bit Maps                           /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NSEC {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub next_domain: String,
+    pub types: TypeBitmap,
+}
+
+impl NSEC {
+    pub fn rdata(&self) -> Vec<u8> {
+        let mut data = vec![];
+        for label in self.next_domain.split('.').filter(|label| !label.is_empty()) {
+            data.push(label.len() as u8);
+            data.extend_from_slice(label.as_bytes());
+        }
+
+        data.push(0);
+        data.extend_from_slice(&self.types.to_wire());
+        data
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<NSEC> {
+        let mut reader = RDataReader::new(data);
+        let next_domain = reader.read_name()?;
+
+        let mut bitmap = vec![];
+        while !reader.is_empty() {
+            bitmap.push(reader.read_u8()?);
+        }
+
+        Ok(NSEC {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            next_domain,
+            types: TypeBitmap::from_wire(&bitmap)?,
+        })
+    }
+}
+
+impl Deserialize for NSEC {
+    fn deserialize<D>(deserializer: &mut D) -> Result<NSEC>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::NextSecure)?;
+        NSEC::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for NSEC {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        serialize_record(serializer, &self.domain, QueryType::NextSecure, self.class, self.ttl, &self.rdata())
+    }
+}
+
+impl Display for NSEC {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} NSEC {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            format_name(&self.next_domain),
+        )?;
+
+        if !self.types.is_empty() {
+            write!(f, " {}", self.types)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for NSEC {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<NSEC> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::NextSecure)?;
+
+        let next_domain = match tokens.next() {
+            Some(token) => parse_name(&token_str(&token)?),
+            None => return Err(InvalidPresentation("NSEC record expects a next domain name".to_string())),
+        };
+
+        Ok(NSEC {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            next_domain,
+            types: TypeBitmap::parse(tokens)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::de::Deserialize;
+    use crate::packet::{QueryType, Record};
+    use crate::records::nsec::NSEC;
+    use crate::ser::Serialize;
+
+    // Record of RFC 4034 section 4.3.
+    const PRESENTATION: &str = "alfa.example.com. 86400 IN NSEC host.example.com. A MX RRSIG NSEC TYPE1234";
+
+    #[test]
+    fn presentation() {
+        let record: NSEC = PRESENTATION.parse().unwrap();
+        assert_eq!("host.example.com", record.next_domain);
+        assert!(record.types.contains(QueryType::MailExchange));
+        assert!(!record.types.contains(QueryType::AuthoritativeNameServer));
+        assert_eq!(PRESENTATION, record.to_string());
+
+        assert!("alfa.example.com. 86400 IN NSEC".parse::<NSEC>().is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let record: NSEC = PRESENTATION.parse().unwrap();
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+        let bytes = serializer.bytes();
+
+        // The type bitmap follows the next domain name.
+        let mut expected = vec![
+            0x04, 0x68, 0x6f, 0x73, 0x74, 0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x03, 0x63, 0x6f, 0x6d, 0x00,
+            0x00, 0x06, 0x40, 0x01, 0x00, 0x00, 0x00, 0x03,
+            0x04, 0x1b,
+        ];
+        expected.extend_from_slice(&[0; 26]);
+        expected.push(0x20);
+        assert_eq!(expected, &bytes[28..]);
+
+        let mut buffer = BytePacketBuffer::from_raw_data(&bytes);
+        assert_eq!(Record::NextSecure(record), Record::deserialize(&mut buffer).unwrap());
+    }
+}
@@ -0,0 +1,380 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_base32hex, format_hex, format_name, parse_base32hex, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::dnssec::TypeBitmap;
+use crate::records::rdata::{serialize_record, RDataReader, WireHeader};
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// SHA-1 hash algorithm, the only one defined.
+pub const SHA1: u8 = 1;
+
+/// Opt-Out flag, set when the NSEC3 record may cover unsigned delegations.
+pub const OPT_OUT: u8 = 0x01;
+
+/// https://datatracker.ietf.org/doc/html/rfc5155#section-3.2
+///
+/// ```txt
+///                      1 1 1 1 1 1 1 1 1 1 2 2 2 2 2 2 2 2 2 2 3 3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |   Hash Alg.   |     Flags     |          Iterations           |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |  Salt Length  |                     Salt                      /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |  Hash Length  |             Next Hashed Owner Name            /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                         Type Bit Maps                         /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NSEC3 {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub hash_algorithm: u8,
+    pub flags: u8,
+    pub iterations: u16,
+    pub salt: Vec<u8>,
+    // Raw hash, shown in base32hex like the owner name label.
+    pub next_hashed_owner: Vec<u8>,
+    pub types: TypeBitmap,
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc5155#section-4.2
+///
+/// ```txt
+///                      1 1 1 1 1 1 1 1 1 1 2 2 2 2 2 2 2 2 2 2 3 3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |   Hash Alg.   |     Flags     |          Iterations           |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |  Salt Length  |                     Salt                      /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NSEC3PARAM {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub hash_algorithm: u8,
+    pub flags: u8,
+    pub iterations: u16,
+    pub salt: Vec<u8>,
+}
+
+impl NSEC3 {
+    pub fn is_opt_out(&self) -> bool {
+        self.flags & OPT_OUT != 0
+    }
+
+    pub fn rdata(&self) -> Result<Vec<u8>> {
+        let mut data = hash_parameters(self.hash_algorithm, self.flags, self.iterations, &self.salt)?;
+
+        if self.next_hashed_owner.is_empty() || self.next_hashed_owner.len() > u8::MAX as usize {
+            return Err(InvalidData(format!("invalid NSEC3 hash length {}", self.next_hashed_owner.len())));
+        }
+
+        data.push(self.next_hashed_owner.len() as u8);
+        data.extend_from_slice(&self.next_hashed_owner);
+        data.extend_from_slice(&self.types.to_wire());
+        Ok(data)
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<NSEC3> {
+        let mut reader = RDataReader::new(data);
+        let hash_algorithm = reader.read_u8()?;
+        let flags = reader.read_u8()?;
+        let iterations = reader.read_u16()?;
+        let salt = reader.read_character_string()?.to_vec();
+        let next_hashed_owner = reader.read_character_string()?.to_vec();
+        if next_hashed_owner.is_empty() {
+            return Err(InvalidData("NSEC3 next hashed owner name is empty".to_string()));
+        }
+
+        let mut bitmap = vec![];
+        while !reader.is_empty() {
+            bitmap.push(reader.read_u8()?);
+        }
+
+        Ok(NSEC3 {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+            next_hashed_owner,
+            types: TypeBitmap::from_wire(&bitmap)?,
+        })
+    }
+}
+
+impl NSEC3PARAM {
+    pub fn rdata(&self) -> Result<Vec<u8>> {
+        hash_parameters(self.hash_algorithm, self.flags, self.iterations, &self.salt)
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<NSEC3PARAM> {
+        let mut reader = RDataReader::new(data);
+
+        let record = NSEC3PARAM {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            hash_algorithm: reader.read_u8()?,
+            flags: reader.read_u8()?,
+            iterations: reader.read_u16()?,
+            salt: reader.read_character_string()?.to_vec(),
+        };
+
+        if !reader.is_empty() {
+            return Err(InvalidData("unexpected data after the NSEC3PARAM salt".to_string()));
+        }
+
+        Ok(record)
+    }
+}
+
+// Fields shared by NSEC3 and NSEC3PARAM records.
+fn hash_parameters(hash_algorithm: u8, flags: u8, iterations: u16, salt: &[u8]) -> Result<Vec<u8>> {
+    if salt.len() > u8::MAX as usize {
+        return Err(InvalidData(format!("NSEC3 salt of {} bytes is too long", salt.len())));
+    }
+
+    let mut data = vec![hash_algorithm, flags];
+    data.extend_from_slice(&iterations.to_be_bytes());
+    data.push(salt.len() as u8);
+    data.extend_from_slice(salt);
+    Ok(data)
+}
+
+// An empty salt is shown as a single dash.
+fn format_salt(salt: &[u8]) -> String {
+    if salt.is_empty() {
+        return "-".to_string();
+    }
+
+    format_hex(salt)
+}
+
+fn parse_salt(token: &str) -> Result<Vec<u8>> {
+    if token == "-" {
+        return Ok(vec![]);
+    }
+
+    data_encoding::HEXUPPER_PERMISSIVE
+        .decode(token.as_bytes())
+        .map_err(|err| InvalidPresentation(format!("invalid NSEC3 salt: {}", err)))
+}
+
+// Parse the hash algorithm, flags, iterations and salt fields.
+fn parse_hash_parameters<I>(tokens: &mut I, qtype: QueryType) -> Result<(u8, u8, u16, Vec<u8>)>
+    where I: Iterator<Item=Vec<u8>>
+{
+    match (tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+        (Some(hash_algorithm), Some(flags), Some(iterations), Some(salt)) => Ok((
+            parse_number(&token_str(&hash_algorithm)?)?,
+            parse_number(&token_str(&flags)?)?,
+            parse_number(&token_str(&iterations)?)?,
+            parse_salt(&token_str(&salt)?)?,
+        )),
+        _ => Err(InvalidPresentation(format!("{} record expects hash algorithm, flags, iterations and salt", qtype))),
+    }
+}
+
+impl Deserialize for NSEC3 {
+    fn deserialize<D>(deserializer: &mut D) -> Result<NSEC3>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::NextSecure3)?;
+        NSEC3::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for NSEC3 {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        serialize_record(serializer, &self.domain, QueryType::NextSecure3, self.class, self.ttl, &self.rdata()?)
+    }
+}
+
+impl Display for NSEC3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} NSEC3 {} {} {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            format_salt(&self.salt),
+            format_base32hex(&self.next_hashed_owner),
+        )?;
+
+        if !self.types.is_empty() {
+            write!(f, " {}", self.types)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for NSEC3 {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<NSEC3> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::NextSecure3)?;
+        let (hash_algorithm, flags, iterations, salt) = parse_hash_parameters(&mut tokens, QueryType::NextSecure3)?;
+
+        let next_hashed_owner = match tokens.next() {
+            Some(token) => parse_base32hex(&token_str(&token)?)?,
+            None => return Err(InvalidPresentation("NSEC3 record expects a next hashed owner name".to_string())),
+        };
+
+        if next_hashed_owner.is_empty() {
+            return Err(InvalidPresentation("NSEC3 next hashed owner name is empty".to_string()));
+        }
+
+        Ok(NSEC3 {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+            next_hashed_owner,
+            types: TypeBitmap::parse(tokens)?,
+        })
+    }
+}
+
+impl Deserialize for NSEC3PARAM {
+    fn deserialize<D>(deserializer: &mut D) -> Result<NSEC3PARAM>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::Nsec3Parameters)?;
+        NSEC3PARAM::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for NSEC3PARAM {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        serialize_record(serializer, &self.domain, QueryType::Nsec3Parameters, self.class, self.ttl, &self.rdata()?)
+    }
+}
+
+impl Display for NSEC3PARAM {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} NSEC3PARAM {} {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            format_salt(&self.salt),
+        )
+    }
+}
+
+impl FromStr for NSEC3PARAM {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<NSEC3PARAM> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::Nsec3Parameters)?;
+        let (hash_algorithm, flags, iterations, salt) = parse_hash_parameters(&mut tokens, QueryType::Nsec3Parameters)?;
+
+        if tokens.next().is_some() {
+            return Err(InvalidPresentation("unexpected data after the NSEC3PARAM salt".to_string()));
+        }
+
+        Ok(NSEC3PARAM {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::de::Deserialize;
+    use crate::packet::{QueryType, Record};
+    use crate::records::nsec3::{NSEC3, NSEC3PARAM, SHA1};
+    use crate::ser::Serialize;
+
+    // Records of RFC 5155 appendix A.
+    const PRESENTATION: &str = "0p9mhaveqvm6t7vbl5lop2u3t2rp3tom.example. 3600 IN NSEC3 1 1 12 AABBCCDD 2T7B4G4VSA5SMI47K61MV5BV1A22BOJR NS SOA MX RRSIG DNSKEY NSEC3PARAM";
+
+    #[test]
+    fn presentation() {
+        let record: NSEC3 = PRESENTATION.parse().unwrap();
+        assert_eq!(SHA1, record.hash_algorithm);
+        assert!(record.is_opt_out());
+        assert_eq!(12, record.iterations);
+        assert_eq!(vec![0xAA, 0xBB, 0xCC, 0xDD], record.salt);
+        assert_eq!(20, record.next_hashed_owner.len());
+        assert!(record.types.contains(QueryType::Nsec3Parameters));
+        assert_eq!(PRESENTATION, record.to_string());
+
+        // Hashes are case-insensitive.
+        let lowercase = PRESENTATION.replace("2T7B4G4VSA5SMI47K61MV5BV1A22BOJR", "2t7b4g4vsa5smi47k61mv5bv1a22bojr");
+        assert_eq!(record, lowercase.parse().unwrap());
+    }
+
+    #[test]
+    fn round_trip() {
+        let record: NSEC3 = PRESENTATION.parse().unwrap();
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+        let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+        assert_eq!(Record::NextSecure3(record), Record::deserialize(&mut buffer).unwrap());
+    }
+
+    #[test]
+    fn parameters() {
+        let record: NSEC3PARAM = "example. 0 IN NSEC3PARAM 1 0 12 AABBCCDD".parse().unwrap();
+        assert_eq!("example. 0 IN NSEC3PARAM 1 0 12 AABBCCDD", record.to_string());
+
+        let unsalted: NSEC3PARAM = "example. 0 IN NSEC3PARAM 1 0 0 -".parse().unwrap();
+        assert!(unsalted.salt.is_empty());
+        assert_eq!("example. 0 IN NSEC3PARAM 1 0 0 -", unsalted.to_string());
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+        let bytes = serializer.bytes();
+        assert_eq!(&[0x01, 0x00, 0x00, 0x0C, 0x04, 0xAA, 0xBB, 0xCC, 0xDD], &bytes[bytes.len() - 9..]);
+
+        let mut buffer = BytePacketBuffer::from_raw_data(&bytes);
+        assert_eq!(Record::Nsec3Parameters(record), Record::deserialize(&mut buffer).unwrap());
+    }
+}
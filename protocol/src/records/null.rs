@@ -0,0 +1,166 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_hex, format_name, parse_hex, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.10
+///
+/// ```txt
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                  <anything>                   /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// NULL records have no presentation format of their own and use the
+/// generic `\# <length> <hex>` notation of RFC 3597.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NULL {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub data: Vec<u8>,
+}
+
+impl NULL {
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<NULL> {
+        Ok(NULL {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            data: data.to_vec(),
+        })
+    }
+}
+
+impl Deserialize for NULL {
+    fn deserialize<D>(deserializer: &mut D) -> Result<NULL>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::Null)?;
+        NULL::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for NULL {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 10 for NULL)
+        serializer.serialize_u16(QueryType::Null.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        serializer.serialize_u16(self.data.len() as u16)?;
+
+        // Data.
+        serializer.serialize_bytes(&self.data)
+    }
+}
+
+impl Display for NULL {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} NULL \\# {}", format_name(&self.domain), self.ttl.as_secs(), self.class, self.data.len())?;
+        if !self.data.is_empty() {
+            write!(f, " {}", format_hex(&self.data))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for NULL {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<NULL> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::Null)?;
+
+        // The tokenizer decodes the `\#` escape sequence.
+        if tokens.next().as_deref() != Some(b"#") {
+            return Err(InvalidPresentation("NULL record expects the \\# generic notation".to_string()));
+        }
+
+        let len: usize = match tokens.next() {
+            Some(token) => parse_number(&token_str(&token)?)?,
+            None => return Err(InvalidPresentation("NULL record expects the data length".to_string())),
+        };
+
+        let data = parse_hex(tokens)?;
+        if data.len() != len {
+            return Err(InvalidPresentation(format!("NULL record data is {} bytes long, expected {}", data.len(), len)));
+        }
+
+        Ok(NULL {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::NULL;
+    use crate::ser::Serialize;
+
+    fn null() -> NULL {
+        NULL {
+            domain: "x".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            data: vec![0xDE, 0xAD, 0xBE, 0xEF],
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+
+        let res = null().serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0x00, 0x0A, // Type NULL.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x04, // RD length.
+            0xDE, 0xAD, 0xBE, 0xEF, // Data.
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn presentation() {
+        let text = null().to_string();
+        assert_eq!(r"x. 60 IN NULL \# 4 DEADBEEF", text);
+        assert_eq!(null(), text.parse().unwrap());
+        assert!(r"x. 60 IN NULL \# 3 DEADBEEF".parse::<NULL>().is_err());
+        assert!("x. 60 IN NULL DEADBEEF".parse::<NULL>().is_err());
+    }
+}
@@ -0,0 +1,448 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error::InvalidData;
+use crate::packet::QueryType;
+use crate::records::rdata::RDataReader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// Flag telling the DNSSEC records are wanted (RFC 3225).
+pub const DNSSEC_OK: u16 = 0x8000;
+
+/// Option code of Extended DNS Errors.
+pub const EXTENDED_DNS_ERROR: u16 = 15;
+
+/// https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.2
+///
+/// ```txt
+/// +------------+--------------+------------------------------+
+/// | Field Name | Field Type   | Description                  |
+/// +------------+--------------+------------------------------+
+/// | NAME       | domain name  | MUST be 0 (root domain)      |
+/// | TYPE       | u_int16_t    | OPT (41)                     |
+/// | CLASS      | u_int16_t    | requestor's UDP payload size |
+/// | TTL        | u_int32_t    | extended RCODE and flags     |
+/// | RDLEN      | u_int16_t    | length of all RDATA          |
+/// | RDATA      | octet stream | {attribute,value} pairs      |
+/// +------------+--------------+------------------------------+
+/// ```
+///
+/// The OPT pseudo-record carries the EDNS parameters of a message in its
+/// additional section, it is not a regular record and is never cached.
+/// The extended RCODE holds the upper 8 bits of the 12-bit result code,
+/// see `Packet` which combines both parts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OPT {
+    pub udp_payload_size: u16,
+    pub extended_rcode: u8,
+    pub version: u8,
+    pub flags: u16,
+    pub options: Vec<EdnsOption>,
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.2
+///
+/// ```txt
+///                +0 (MSB)                            +1 (LSB)
+///     +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+///  0: |                          OPTION-CODE                          |
+///     +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+///  2: |                         OPTION-LENGTH                         |
+///     +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+///  4: |                                                               |
+///     /                          OPTION-DATA                          /
+///     /                                                               /
+///     +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum EdnsOption {
+    Unknown {
+        code: u16,
+        data: Vec<u8>,
+    },
+    // EDE, Additional information about the cause of an error (RFC 8914).
+    ExtendedDnsError {
+        info_code: ExtendedErrorCode,
+        extra_text: String,
+    },
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc8914#section-4
+///
+/// See: https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#extended-dns-error-codes
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum ExtendedErrorCode {
+    Unknown(u16),
+    OtherError,
+    UnsupportedDnskeyAlgorithm,
+    UnsupportedDsDigestType,
+    StaleAnswer,
+    ForgedAnswer,
+    DnssecIndeterminate,
+    DnssecBogus,
+    SignatureExpired,
+    SignatureNotYetValid,
+    DnskeyMissing,
+    RrsigsMissing,
+    NoZoneKeyBitSet,
+    NsecMissing,
+    CachedError,
+    NotReady,
+    Blocked,
+    Censored,
+    Filtered,
+    Prohibited,
+    StaleNxDomainAnswer,
+    NotAuthoritative,
+    NotSupported,
+    NoReachableAuthority,
+    NetworkError,
+    InvalidData,
+    SignatureExpiredBeforeValid,
+    TooEarly,
+    UnsupportedNsec3IterationsValue,
+    UnableToConformToPolicy,
+    Synthesized,
+    InvalidQueryType,
+}
+
+impl ExtendedErrorCode {
+    pub fn from_u16(num: u16) -> ExtendedErrorCode {
+        match num {
+            0 => ExtendedErrorCode::OtherError,
+            1 => ExtendedErrorCode::UnsupportedDnskeyAlgorithm,
+            2 => ExtendedErrorCode::UnsupportedDsDigestType,
+            3 => ExtendedErrorCode::StaleAnswer,
+            4 => ExtendedErrorCode::ForgedAnswer,
+            5 => ExtendedErrorCode::DnssecIndeterminate,
+            6 => ExtendedErrorCode::DnssecBogus,
+            7 => ExtendedErrorCode::SignatureExpired,
+            8 => ExtendedErrorCode::SignatureNotYetValid,
+            9 => ExtendedErrorCode::DnskeyMissing,
+            10 => ExtendedErrorCode::RrsigsMissing,
+            11 => ExtendedErrorCode::NoZoneKeyBitSet,
+            12 => ExtendedErrorCode::NsecMissing,
+            13 => ExtendedErrorCode::CachedError,
+            14 => ExtendedErrorCode::NotReady,
+            15 => ExtendedErrorCode::Blocked,
+            16 => ExtendedErrorCode::Censored,
+            17 => ExtendedErrorCode::Filtered,
+            18 => ExtendedErrorCode::Prohibited,
+            19 => ExtendedErrorCode::StaleNxDomainAnswer,
+            20 => ExtendedErrorCode::NotAuthoritative,
+            21 => ExtendedErrorCode::NotSupported,
+            22 => ExtendedErrorCode::NoReachableAuthority,
+            23 => ExtendedErrorCode::NetworkError,
+            24 => ExtendedErrorCode::InvalidData,
+            25 => ExtendedErrorCode::SignatureExpiredBeforeValid,
+            26 => ExtendedErrorCode::TooEarly,
+            27 => ExtendedErrorCode::UnsupportedNsec3IterationsValue,
+            28 => ExtendedErrorCode::UnableToConformToPolicy,
+            29 => ExtendedErrorCode::Synthesized,
+            30 => ExtendedErrorCode::InvalidQueryType,
+            _ => ExtendedErrorCode::Unknown(num),
+        }
+    }
+
+    pub fn as_u16(&self) -> u16 {
+        match *self {
+            ExtendedErrorCode::OtherError => 0,
+            ExtendedErrorCode::UnsupportedDnskeyAlgorithm => 1,
+            ExtendedErrorCode::UnsupportedDsDigestType => 2,
+            ExtendedErrorCode::StaleAnswer => 3,
+            ExtendedErrorCode::ForgedAnswer => 4,
+            ExtendedErrorCode::DnssecIndeterminate => 5,
+            ExtendedErrorCode::DnssecBogus => 6,
+            ExtendedErrorCode::SignatureExpired => 7,
+            ExtendedErrorCode::SignatureNotYetValid => 8,
+            ExtendedErrorCode::DnskeyMissing => 9,
+            ExtendedErrorCode::RrsigsMissing => 10,
+            ExtendedErrorCode::NoZoneKeyBitSet => 11,
+            ExtendedErrorCode::NsecMissing => 12,
+            ExtendedErrorCode::CachedError => 13,
+            ExtendedErrorCode::NotReady => 14,
+            ExtendedErrorCode::Blocked => 15,
+            ExtendedErrorCode::Censored => 16,
+            ExtendedErrorCode::Filtered => 17,
+            ExtendedErrorCode::Prohibited => 18,
+            ExtendedErrorCode::StaleNxDomainAnswer => 19,
+            ExtendedErrorCode::NotAuthoritative => 20,
+            ExtendedErrorCode::NotSupported => 21,
+            ExtendedErrorCode::NoReachableAuthority => 22,
+            ExtendedErrorCode::NetworkError => 23,
+            ExtendedErrorCode::InvalidData => 24,
+            ExtendedErrorCode::SignatureExpiredBeforeValid => 25,
+            ExtendedErrorCode::TooEarly => 26,
+            ExtendedErrorCode::UnsupportedNsec3IterationsValue => 27,
+            ExtendedErrorCode::UnableToConformToPolicy => 28,
+            ExtendedErrorCode::Synthesized => 29,
+            ExtendedErrorCode::InvalidQueryType => 30,
+            ExtendedErrorCode::Unknown(num) => num,
+        }
+    }
+}
+
+impl Display for ExtendedErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let purpose = match *self {
+            ExtendedErrorCode::OtherError => "Other Error",
+            ExtendedErrorCode::UnsupportedDnskeyAlgorithm => "Unsupported DNSKEY Algorithm",
+            ExtendedErrorCode::UnsupportedDsDigestType => "Unsupported DS Digest Type",
+            ExtendedErrorCode::StaleAnswer => "Stale Answer",
+            ExtendedErrorCode::ForgedAnswer => "Forged Answer",
+            ExtendedErrorCode::DnssecIndeterminate => "DNSSEC Indeterminate",
+            ExtendedErrorCode::DnssecBogus => "DNSSEC Bogus",
+            ExtendedErrorCode::SignatureExpired => "Signature Expired",
+            ExtendedErrorCode::SignatureNotYetValid => "Signature Not Yet Valid",
+            ExtendedErrorCode::DnskeyMissing => "DNSKEY Missing",
+            ExtendedErrorCode::RrsigsMissing => "RRSIGs Missing",
+            ExtendedErrorCode::NoZoneKeyBitSet => "No Zone Key Bit Set",
+            ExtendedErrorCode::NsecMissing => "NSEC Missing",
+            ExtendedErrorCode::CachedError => "Cached Error",
+            ExtendedErrorCode::NotReady => "Not Ready",
+            ExtendedErrorCode::Blocked => "Blocked",
+            ExtendedErrorCode::Censored => "Censored",
+            ExtendedErrorCode::Filtered => "Filtered",
+            ExtendedErrorCode::Prohibited => "Prohibited",
+            ExtendedErrorCode::StaleNxDomainAnswer => "Stale NXDOMAIN Answer",
+            ExtendedErrorCode::NotAuthoritative => "Not Authoritative",
+            ExtendedErrorCode::NotSupported => "Not Supported",
+            ExtendedErrorCode::NoReachableAuthority => "No Reachable Authority",
+            ExtendedErrorCode::NetworkError => "Network Error",
+            ExtendedErrorCode::InvalidData => "Invalid Data",
+            ExtendedErrorCode::SignatureExpiredBeforeValid => "Signature Expired before Valid",
+            ExtendedErrorCode::TooEarly => "Too Early",
+            ExtendedErrorCode::UnsupportedNsec3IterationsValue => "Unsupported NSEC3 Iterations Value",
+            ExtendedErrorCode::UnableToConformToPolicy => "Unable to conform to policy",
+            ExtendedErrorCode::Synthesized => "Synthesized",
+            ExtendedErrorCode::InvalidQueryType => "Invalid Query Type",
+            ExtendedErrorCode::Unknown(num) => return write!(f, "EDE{}", num),
+        };
+
+        write!(f, "{}", purpose)
+    }
+}
+
+impl EdnsOption {
+    pub fn code(&self) -> u16 {
+        match self {
+            EdnsOption::Unknown { code, .. } => *code,
+            EdnsOption::ExtendedDnsError { .. } => EXTENDED_DNS_ERROR,
+        }
+    }
+
+    /// Decode an option from its code and data.
+    pub fn from_wire(code: u16, data: &[u8]) -> Result<EdnsOption> {
+        match code {
+            EXTENDED_DNS_ERROR => {
+                if data.len() < 2 {
+                    return Err(InvalidData("extended DNS error option is too short".to_string()));
+                }
+
+                // The text is meant to be UTF-8 but is only informative.
+                Ok(EdnsOption::ExtendedDnsError {
+                    info_code: ExtendedErrorCode::from_u16(((data[0] as u16) << 8) | data[1] as u16),
+                    extra_text: String::from_utf8_lossy(&data[2..]).to_string(),
+                })
+            }
+            _ => Ok(EdnsOption::Unknown { code, data: data.to_vec() }),
+        }
+    }
+
+    /// Encode the option data, without the code and length.
+    pub fn to_wire(&self) -> Vec<u8> {
+        match self {
+            EdnsOption::Unknown { data, .. } => data.clone(),
+            EdnsOption::ExtendedDnsError { info_code, extra_text } => {
+                let mut data = info_code.as_u16().to_be_bytes().to_vec();
+                data.extend_from_slice(extra_text.as_bytes());
+                data
+            }
+        }
+    }
+}
+
+impl OPT {
+    pub fn new(udp_payload_size: u16) -> OPT {
+        OPT {
+            udp_payload_size,
+            extended_rcode: 0,
+            version: 0,
+            flags: 0,
+            options: vec![],
+        }
+    }
+
+    pub fn dnssec_ok(&self) -> bool {
+        self.flags & DNSSEC_OK != 0
+    }
+
+    pub fn set_dnssec_ok(&mut self, dnssec_ok: bool) {
+        if dnssec_ok {
+            self.flags |= DNSSEC_OK;
+        } else {
+            self.flags &= !DNSSEC_OK;
+        }
+    }
+
+    pub fn option(&self, code: u16) -> Option<&EdnsOption> {
+        self.options.iter().find(|option| option.code() == code)
+    }
+
+    /// Extended DNS errors of the message, there can be several of them.
+    pub fn extended_errors(&self) -> impl Iterator<Item=(ExtendedErrorCode, &str)> {
+        self.options.iter().filter_map(|option| match option {
+            EdnsOption::ExtendedDnsError { info_code, extra_text } => Some((*info_code, extra_text.as_str())),
+            _ => None,
+        })
+    }
+
+    // The payload size and the flags are stored in the CLASS and TTL
+    // fields of the record.
+    pub(crate) fn from_rdata(class: u16, ttl: u32, data: &[u8]) -> Result<OPT> {
+        let mut reader = RDataReader::new(data);
+        let mut options = vec![];
+        while !reader.is_empty() {
+            let code = reader.read_u16()?;
+            let len = reader.read_u16()?;
+            options.push(EdnsOption::from_wire(code, reader.read_bytes(len as usize)?)?);
+        }
+
+        Ok(OPT {
+            udp_payload_size: class,
+            extended_rcode: (ttl >> 24) as u8,
+            version: (ttl >> 16) as u8,
+            flags: ttl as u16,
+            options,
+        })
+    }
+}
+
+impl Deserialize for OPT {
+    fn deserialize<D>(deserializer: &mut D) -> Result<OPT>
+        where
+            D: Deserializer + Seek
+    {
+        if !deserializer.deserialize_qname()?.is_empty() {
+            return Err(InvalidData("OPT record owner must be the root domain".to_string()));
+        }
+
+        let qtype = QueryType::from_u16(deserializer.deserialize_u16()?);
+        if qtype != QueryType::Opt {
+            return Err(InvalidData(format!("expected an OPT record but got {}", qtype)));
+        }
+
+        let class = deserializer.deserialize_u16()?;
+        let ttl = deserializer.deserialize_u32()?;
+        let len = deserializer.deserialize_u16()?;
+        let data = deserializer.deserialize_bytes(len as usize)?;
+        OPT::from_rdata(class, ttl, &data)
+    }
+}
+
+impl Serialize for OPT {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        let options: Vec<(u16, Vec<u8>)> = self.options
+            .iter()
+            .map(|option| (option.code(), option.to_wire()))
+            .collect();
+
+        if let Some((code, data)) = options.iter().find(|(_, data)| data.len() > u16::MAX as usize) {
+            return Err(InvalidData(format!("EDNS option {} of {} bytes is too long", code, data.len())));
+        }
+
+        // Name. (Always the root domain)
+        serializer.serialize_u8(0)?;
+
+        // Type. (Always 41 for OPT)
+        serializer.serialize_u16(QueryType::Opt.as_u16())?;
+
+        // Requestor's UDP payload size.
+        serializer.serialize_u16(self.udp_payload_size)?;
+
+        // Extended RCODE, version and flags.
+        serializer.serialize_u8(self.extended_rcode)?;
+        serializer.serialize_u8(self.version)?;
+        serializer.serialize_u16(self.flags)?;
+
+        // Payload size.
+        let payload_size: usize = options.iter().map(|(_, data)| 4 + data.len()).sum();
+        serializer.serialize_u16(payload_size as u16)?;
+
+        for (code, data) in options {
+            serializer.serialize_u16(code)?;
+            serializer.serialize_u16(data.len() as u16)?;
+            serializer.serialize_bytes(&data)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::records::opt::{EdnsOption, ExtendedErrorCode};
+    use crate::records::OPT;
+    use crate::ser::Serialize;
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+        let mut opt = OPT::new(1232);
+        opt.extended_rcode = 1;
+        opt.set_dnssec_ok(true);
+        opt.options.push(EdnsOption::Unknown { code: 10, data: vec![0xCA, 0xFE] });
+
+        let res = opt.serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x00, // Root domain.
+            0x00, 0x29, // Type OPT.
+            0x04, 0xD0, // UDP payload size.
+            0x01, // Extended RCODE.
+            0x00, // Version.
+            0x80, 0x00, // Flags DO.
+            0x00, 0x06, // RD length.
+            0x00, 0x0A, // Option code.
+            0x00, 0x02, // Option length.
+            0xCA, 0xFE, // Option data.
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn from_rdata() {
+        let opt = OPT::from_rdata(4096, 0x0100_8000, &[0x00, 0x0A, 0x00, 0x02, 0xCA, 0xFE]).unwrap();
+        assert_eq!(4096, opt.udp_payload_size);
+        assert_eq!(1, opt.extended_rcode);
+        assert_eq!(0, opt.version);
+        assert!(opt.dnssec_ok());
+        assert_eq!(Some(&EdnsOption::Unknown { code: 10, data: vec![0xCA, 0xFE] }), opt.option(10));
+
+        assert!(OPT::from_rdata(4096, 0, &[0x00, 0x0A, 0x00, 0x04, 0xCA, 0xFE]).is_err());
+    }
+
+    #[test]
+    fn extended_dns_error() {
+        let option = EdnsOption::ExtendedDnsError {
+            info_code: ExtendedErrorCode::NoReachableAuthority,
+            extra_text: "no ns".to_string(),
+        };
+
+        let data = option.to_wire();
+        assert_eq!(&[0x00, 0x16, 0x6e, 0x6f, 0x20, 0x6e, 0x73], data.as_slice());
+        assert_eq!(option, EdnsOption::from_wire(15, &data).unwrap());
+        assert!(EdnsOption::from_wire(15, &[0x00]).is_err());
+
+        let mut opt = OPT::new(1232);
+        opt.options.push(option);
+        assert_eq!(vec![(ExtendedErrorCode::NoReachableAuthority, "no ns")], opt.extended_errors().collect::<Vec<_>>());
+        assert_eq!("No Reachable Authority", ExtendedErrorCode::from_u16(22).to_string());
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_name, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.12
+///
+/// ```txt
+///                                 1  1  1  1  1  1
+///   0  1  2  3  4  5  6  7  8  9  0  1  2  3  4  5
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// .                      NAME                     .
+/// .                                               .
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                      TYPE                     |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                     CLASS                     |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                      TTL                      |
+/// |                                               |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                   RDLENGTH                    |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// .                   PTRDNAME                    .
+/// .                                               .
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PTR {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub ptr_name: String,
+}
+
+impl Deserialize for PTR {
+    fn deserialize<D>(deserializer: &mut D) -> Result<PTR>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::DomainPointer)?;
+        let record = PTR {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            ptr_name: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
+impl Serialize for PTR {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 12 for PTR)
+        // See: https://datatracker.ietf.org/doc/html/rfc1035#section-3.2.2
+        serializer.serialize_u16(QueryType::DomainPointer.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Domain name size.
+        // Saving a pointer to this field to be able to
+        // set the size after domain length computation.
+        let size_pos = serializer.position();
+        serializer.serialize_u16(0)?;
+
+        // Domain name.
+        serializer.serialize_qname(&self.ptr_name)?;
+
+        // Domain name serialization length computation and
+        // overriding length value.
+        let payload_size = serializer.position() - (size_pos + 2);
+        let current_position = serializer.position();
+        serializer.seek(size_pos)?;
+        serializer.serialize_u16(payload_size as u16)?;
+        serializer.seek(current_position)
+    }
+}
+
+impl Display for PTR {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} PTR {}", format_name(&self.domain), self.ttl.as_secs(), self.class, format_name(&self.ptr_name))
+    }
+}
+
+impl FromStr for PTR {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<PTR> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::DomainPointer)?;
+
+        let ptr_name = match (tokens.next(), tokens.next()) {
+            (Some(token), None) => parse_name(&token_str(&token)?),
+            _ => return Err(InvalidPresentation("PTR record expects a single domain name".to_string())),
+        };
+
+        Ok(PTR {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            ptr_name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::PTR;
+    use crate::ser::Serialize;
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+        let ptr = PTR {
+            domain: "1.0.0.127.in-addr.arpa".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            ptr_name: "localhost".to_string(),
+        };
+
+        let res = ptr.serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x01, 0x31, // len=1 label="1"
+            0x01, 0x30, // len=1 label="0"
+            0x01, 0x30, // len=1 label="0"
+            0x03, 0x31, 0x32, 0x37, // len=3 label="127"
+            0x07, 0x69, 0x6e, 0x2d, 0x61, 0x64, 0x64, 0x72, // len=7 label="in-addr"
+            0x04, 0x61, 0x72, 0x70, 0x61, // len=4 label="arpa"
+            0x00,
+            0x00, 0x0C, // Type PTR.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x0B, // RD length.
+            0x09, 0x6c, 0x6f, 0x63, 0x61, 0x6c, 0x68, 0x6f, 0x73, 0x74, // len=9 label="localhost"
+            0x00,
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn presentation() {
+        let ptr = PTR {
+            domain: "1.0.0.127.in-addr.arpa".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            ptr_name: "localhost".to_string(),
+        };
+
+        let text = ptr.to_string();
+        assert_eq!("1.0.0.127.in-addr.arpa. 60 IN PTR localhost.", text);
+        assert_eq!(ptr, text.parse().unwrap());
+    }
+}
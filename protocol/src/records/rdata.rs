@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use crate::de::Deserializer;
+use crate::errors::Error::InvalidData;
+use crate::packet::{Class, QueryType};
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::Serializer;
+
+/// Owner, class, TTL and data length which start every resource record.
+pub(crate) struct WireHeader {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub len: u16,
+    // Position of the end of the record data.
+    end: usize,
+}
+
+impl WireHeader {
+    /// Deserialize the record header, making sure the record type is
+    /// the expected one.
+    pub fn deserialize<D>(deserializer: &mut D, expected: QueryType) -> Result<WireHeader>
+        where
+            D: Deserializer + Seek
+    {
+        let domain = deserializer.deserialize_qname()?;
+
+        let qtype = QueryType::from_u16(deserializer.deserialize_u16()?);
+        if qtype != expected {
+            return Err(InvalidData(format!("expected a {} record but got {}", expected, qtype)));
+        }
+
+        let class = Class::from_u16(deserializer.deserialize_u16()?);
+        let ttl = Duration::from_secs(deserializer.deserialize_u32()? as u64);
+        let len = deserializer.deserialize_u16()?;
+
+        Ok(WireHeader {
+            domain,
+            class,
+            ttl,
+            len,
+            end: deserializer.position() + len as usize,
+        })
+    }
+
+    /// Make sure the record data has been entirely consumed, for the
+    /// records whose fields are read one by one.
+    pub fn check_end<D>(&self, deserializer: &D) -> Result<()>
+        where
+            D: Seek
+    {
+        if deserializer.position() != self.end {
+            return Err(InvalidData(format!("record data length {} doesn't match its content", self.len)));
+        }
+
+        Ok(())
+    }
+
+    /// Deserialize the record header along with the raw record data.
+    pub fn deserialize_with_data<D>(deserializer: &mut D, expected: QueryType) -> Result<(WireHeader, Vec<u8>)>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, expected)?;
+        let data = deserializer.deserialize_bytes(header.len as usize)?;
+        Ok((header, data))
+    }
+}
+
+/// Serialize a record whose data is already encoded, for the record
+/// types encoding their data on their own.
+pub(crate) fn serialize_record<S>(serializer: &mut S, domain: &str, qtype: QueryType, class: Class, ttl: Duration, data: &[u8]) -> Result<()>
+    where
+        S: Serializer + Seek
+{
+    if data.len() > u16::MAX as usize {
+        return Err(InvalidData(format!("{} record data of {} bytes is too long", qtype, data.len())));
+    }
+
+    // Name.
+    serializer.serialize_qname(domain)?;
+
+    // Type.
+    serializer.serialize_u16(qtype.as_u16())?;
+
+    // Class.
+    serializer.serialize_u16(class.as_u16())?;
+
+    // TTL.
+    serializer.serialize_u32(ttl.as_secs() as u32)?;
+
+    // Payload size.
+    serializer.serialize_u16(data.len() as u16)?;
+
+    serializer.serialize_bytes(data)
+}
+
+/// Cursor over the raw data of a record, used by the record types whose
+/// data doesn't contain compressed domain names.
+pub(crate) struct RDataReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RDataReader<'a> {
+    pub fn new(data: &'a [u8]) -> RDataReader<'a> {
+        RDataReader { data, pos: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16> {
+        let bytes = self.read_bytes(2)?;
+        Ok(((bytes[0] as u16) << 8) | bytes[1] as u16)
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32> {
+        let msb = (self.read_u16()? as u32) << 16;
+        let lsb = self.read_u16()? as u32;
+        Ok(msb | lsb)
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.pos + len > self.data.len() {
+            return Err(InvalidData(format!("record data of {} bytes is too short", self.data.len())));
+        }
+
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// Read a <character-string>, prefixed by its length.
+    pub fn read_character_string(&mut self) -> Result<&'a [u8]> {
+        let len = self.read_u8()? as usize;
+        self.read_bytes(len)
+    }
+
+    /// Read an uncompressed domain name.
+    pub fn read_name(&mut self) -> Result<String> {
+        let mut labels = vec![];
+
+        loop {
+            let len = self.read_u8()?;
+            match len {
+                0 => break,
+                _ if len & 0xC0 != 0 => return Err(InvalidData("unexpected compressed domain name".to_string())),
+                _ => labels.push(String::from_utf8_lossy(self.read_bytes(len as usize)?).to_lowercase()),
+            }
+        }
+
+        Ok(labels.join("."))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::records::rdata::RDataReader;
+
+    #[test]
+    fn read_fields() {
+        let mut reader = RDataReader::new(&[
+            0x00, 0x0A, // u16
+            0x03, 0x77, 0x77, 0x77, 0x00, // name="www"
+            0x02, 0x68, 0x32, // character-string="h2"
+            0xDE, 0xAD,
+        ]);
+
+        assert_eq!(10, reader.read_u16().unwrap());
+        assert_eq!("www", reader.read_name().unwrap());
+        assert_eq!(b"h2", reader.read_character_string().unwrap());
+        assert_eq!(0xDEAD, reader.read_u16().unwrap());
+        assert!(reader.is_empty());
+        assert!(reader.read_u8().is_err());
+    }
+
+    #[test]
+    fn reject_compressed_name() {
+        let mut reader = RDataReader::new(&[0xC0, 0x0C]);
+        assert!(reader.read_name().is_err());
+    }
+}
@@ -0,0 +1,478 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::growable_buffer::GrowableBuffer;
+use crate::packet::{Class, QueryType, Record};
+use crate::presentation::{format_base64, format_name, parse_base64, parse_name, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::dnssec::{canonical_record, label_count, split_rdata, Algorithm};
+use crate::records::rdata::{serialize_record, RDataReader, WireHeader};
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc4034#section-3.1
+///
+/// ```txt
+///                      1 1 1 1 1 1 1 1 1 1 2 2 2 2 2 2 2 2 2 2 3 3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |        Type Covered           |  Algorithm    |     Labels    |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                         Original TTL                          |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                      Signature Expiration                     |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                      Signature Inception                      |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |            Key Tag            |                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+         Signer's Name         /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                                                               /
+/// /                            Signature                          /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RRSIG {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub type_covered: QueryType,
+    pub algorithm: Algorithm,
+    pub labels: u8,
+    pub original_ttl: Duration,
+    // Seconds since the epoch, modulo 2^32 (RFC 4034 section 3.1.5).
+    pub expiration: u32,
+    pub inception: u32,
+    pub key_tag: u16,
+    pub signer_name: String,
+    pub signature: Vec<u8>,
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc2931#section-3
+///
+/// The SIG record shares the RRSIG format, it is only used for SIG(0)
+/// signatures covering a whole message: the type covered and labels are
+/// zero and the owner is the root, see `sig0` signing and verifying them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SIG(pub RRSIG);
+
+impl RRSIG {
+    pub fn rdata(&self) -> Vec<u8> {
+        let mut data = self.rdata_without_signature();
+        data.extend_from_slice(&self.signature);
+        data
+    }
+
+    // Data preceding the signature, which is the start of the signed data
+    // with the signer name in canonical form.
+    pub(crate) fn rdata_without_signature(&self) -> Vec<u8> {
+        let mut data = vec![];
+        data.extend_from_slice(&self.type_covered.as_u16().to_be_bytes());
+        data.push(self.algorithm.as_u8());
+        data.push(self.labels);
+        data.extend_from_slice(&(self.original_ttl.as_secs() as u32).to_be_bytes());
+        data.extend_from_slice(&self.expiration.to_be_bytes());
+        data.extend_from_slice(&self.inception.to_be_bytes());
+        data.extend_from_slice(&self.key_tag.to_be_bytes());
+
+        for label in self.signer_name.split('.').filter(|label| !label.is_empty()) {
+            data.push(label.len() as u8);
+            data.extend(label.bytes().map(|byte| byte.to_ascii_lowercase()));
+        }
+
+        data.push(0);
+        data
+    }
+
+    /// Data covered by the signature of the given RRset, made of this record
+    /// data without the signature followed by the records in canonical form
+    /// and order (RFC 4034 section 3.1.8.1).
+    ///
+    /// Records expanded from a wildcard are restored to the wildcard owner
+    /// given the labels field.
+    pub fn signed_data(&self, records: &[Record]) -> Result<Vec<u8>> {
+        let mut wires = Vec::with_capacity(records.len());
+
+        for record in records {
+            if record.qtype() != self.type_covered {
+                return Err(InvalidData(format!("RRSIG covering {} can't sign a {} record", self.type_covered, record.qtype())));
+            }
+
+            let mut record = canonical_record(record);
+            record.set_ttl(self.original_ttl);
+
+            if let Some(domain) = record.domain_mut() {
+                let labels: Vec<&str> = domain.split('.').filter(|label| !label.is_empty()).collect();
+                if labels.len() > self.labels as usize {
+                    *domain = format!("*.{}", labels[labels.len() - self.labels as usize..].join("."));
+                }
+            }
+
+            let mut serializer = GrowableBuffer::default();
+            record.serialize(&mut serializer)?;
+            wires.push(serializer.into_inner());
+        }
+
+        // Records are sorted by their data, duplicates being dropped.
+        wires.sort_by(|wire, other| split_rdata(wire).1.cmp(split_rdata(other).1));
+        wires.dedup();
+
+        let mut data = self.rdata_without_signature();
+        for wire in wires {
+            data.extend_from_slice(&wire);
+        }
+
+        Ok(data)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.labels > label_count(&self.domain) {
+            return Err(InvalidData(format!("RRSIG labels {} exceed the labels of {}", self.labels, self.domain)));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<RRSIG> {
+        let mut reader = RDataReader::new(data);
+
+        let type_covered = QueryType::from_u16(reader.read_u16()?);
+        let algorithm = Algorithm::from_u8(reader.read_u8()?);
+        let labels = reader.read_u8()?;
+        let original_ttl = Duration::from_secs(reader.read_u32()? as u64);
+        let expiration = reader.read_u32()?;
+        let inception = reader.read_u32()?;
+        let key_tag = reader.read_u16()?;
+        let signer_name = reader.read_name()?;
+
+        let mut signature = vec![];
+        while !reader.is_empty() {
+            signature.push(reader.read_u8()?);
+        }
+
+        let record = RRSIG {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            type_covered,
+            algorithm,
+            labels,
+            original_ttl,
+            expiration,
+            inception,
+            key_tag,
+            signer_name,
+            signature,
+        };
+
+        record.validate()?;
+        Ok(record)
+    }
+
+    fn serialize_as<S>(&self, qtype: QueryType, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.validate()?;
+        serialize_record(serializer, &self.domain, qtype, self.class, self.ttl, &self.rdata())
+    }
+
+    fn fmt_as(&self, qtype: QueryType, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {} {} {} {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            qtype,
+            self.type_covered,
+            self.algorithm.as_u8(),
+            self.labels,
+            self.original_ttl.as_secs(),
+            format_timestamp(self.expiration),
+            format_timestamp(self.inception),
+            self.key_tag,
+            format_name(&self.signer_name),
+            format_base64(&self.signature),
+        )
+    }
+
+    fn parse_as(s: &str, qtype: QueryType) -> Result<RRSIG> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, qtype)?;
+
+        let mut field = || match tokens.next() {
+            Some(token) => token_str(&token),
+            None => Err(InvalidPresentation(format!("{} record expects type covered, algorithm, labels, original TTL, expiration, inception, key tag, signer and signature", qtype))),
+        };
+
+        let record = RRSIG {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            type_covered: field()?.parse()?,
+            algorithm: Algorithm::from_u8(parse_number(&field()?)?),
+            labels: parse_number(&field()?)?,
+            original_ttl: Duration::from_secs(parse_number::<u32>(&field()?)? as u64),
+            expiration: parse_timestamp(&field()?)?,
+            inception: parse_timestamp(&field()?)?,
+            key_tag: parse_number(&field()?)?,
+            signer_name: parse_name(&field()?),
+            signature: parse_base64(tokens)?,
+        };
+
+        record.validate().map_err(|err| InvalidPresentation(err.to_string()))?;
+        Ok(record)
+    }
+}
+
+impl Deserialize for RRSIG {
+    fn deserialize<D>(deserializer: &mut D) -> Result<RRSIG>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::Signature)?;
+        RRSIG::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for RRSIG {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.serialize_as(QueryType::Signature, serializer)
+    }
+}
+
+impl Display for RRSIG {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.fmt_as(QueryType::Signature, f)
+    }
+}
+
+impl FromStr for RRSIG {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<RRSIG> {
+        RRSIG::parse_as(s, QueryType::Signature)
+    }
+}
+
+impl Deserialize for SIG {
+    fn deserialize<D>(deserializer: &mut D) -> Result<SIG>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::SecuritySignature)?;
+        RRSIG::from_rdata(&header.domain, header.class, header.ttl, &data).map(SIG)
+    }
+}
+
+impl Serialize for SIG {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.0.serialize_as(QueryType::SecuritySignature, serializer)
+    }
+}
+
+impl Display for SIG {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.0.fmt_as(QueryType::SecuritySignature, f)
+    }
+}
+
+impl FromStr for SIG {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SIG> {
+        RRSIG::parse_as(s, QueryType::SecuritySignature).map(SIG)
+    }
+}
+
+// Format a timestamp as YYYYMMDDHHmmSS in UTC.
+fn format_timestamp(timestamp: u32) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        year, month, day, seconds / 3600, seconds / 60 % 60, seconds % 60,
+    )
+}
+
+// Parse a timestamp either as YYYYMMDDHHmmSS or as seconds since the epoch.
+fn parse_timestamp(token: &str) -> Result<u32> {
+    if token.len() != 14 {
+        return parse_number(token);
+    }
+
+    let field = |range: std::ops::Range<usize>| parse_number::<i64>(&token[range]);
+    let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+    let (hour, minute, second) = (field(8..10)?, field(10..12)?, field(12..14)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 59 {
+        return Err(InvalidPresentation(format!("invalid timestamp: {}", token)));
+    }
+
+    let seconds = days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second;
+    if seconds < 0 {
+        return Err(InvalidPresentation(format!("timestamp before the epoch: {}", token)));
+    }
+
+    // Timestamps wrap around every 136 years, using serial number arithmetic.
+    Ok(seconds as u32)
+}
+
+// Days since the epoch of a date of the proleptic Gregorian calendar.
+// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// Date of the proleptic Gregorian calendar from days since the epoch.
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::de::Deserialize;
+    use crate::packet::{Class, QueryType, Record};
+    use crate::records::dnssec::Algorithm;
+    use crate::records::rrsig::{format_timestamp, parse_timestamp, RRSIG, SIG};
+    use crate::records::A;
+    use crate::ser::Serialize;
+
+    // Record of RFC 4034 section 3.3.
+    const PRESENTATION: &str = "host.example.com. 86400 IN RRSIG A 5 3 86400 20030322173103 20030220173103 2642 example.com. \
+        oJB1W6WNGv+ldvQ3WDG0MQkg5IEhjRip8WTrPYGv07h108dUKGMeDPKijVCHX3DDKdfb+v6oB9wfuh3DTJXUAfI/M0zmO/zz8bW0Rznl8O3tGNazPwQKkRN20XPXV6nwwfoXmJQbsLNrLfkGJ5D6fwFm8nN+6pBzeDQfsS3Ap3o=";
+
+    #[test]
+    fn timestamps() {
+        assert_eq!("20030322173103", format_timestamp(1048354263));
+        assert_eq!(1048354263, parse_timestamp("20030322173103").unwrap());
+        assert_eq!(1048354263, parse_timestamp("1048354263").unwrap());
+        assert_eq!("19700101000000", format_timestamp(0));
+        assert_eq!("21060207062815", format_timestamp(u32::MAX));
+        assert!(parse_timestamp("20031322173103").is_err());
+    }
+
+    #[test]
+    fn presentation() {
+        let record: RRSIG = PRESENTATION.parse().unwrap();
+        assert_eq!(QueryType::A, record.type_covered);
+        assert_eq!(Algorithm::RsaSha1, record.algorithm);
+        assert_eq!(3, record.labels);
+        assert_eq!(Duration::from_secs(86400), record.original_ttl);
+        assert_eq!(2642, record.key_tag);
+        assert_eq!("example.com", record.signer_name);
+        assert_eq!(PRESENTATION, record.to_string());
+
+        assert!("example.com. 86400 IN RRSIG A 5 3 86400 20030322173103 20030220173103 2642 example.com. AA==".parse::<RRSIG>().is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let record: RRSIG = PRESENTATION.parse().unwrap();
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+        let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+        assert_eq!(Record::Signature(record), Record::deserialize(&mut buffer).unwrap());
+    }
+
+    #[test]
+    fn sig() {
+        let presentation = ". 0 ANY SIG TYPE0 15 0 0 20231114222320 20231114221320 3613 example.com. AA==";
+        let record: SIG = presentation.parse().unwrap();
+        assert_eq!("", record.0.domain);
+        assert_eq!(QueryType::Unknown(0), record.0.type_covered);
+        assert_eq!(presentation, record.to_string());
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+        let mut buffer = BytePacketBuffer::from_raw_data(&serializer.bytes());
+        assert_eq!(Record::SecuritySignature(record), Record::deserialize(&mut buffer).unwrap());
+    }
+
+    #[test]
+    fn signed_data() {
+        let rrsig = RRSIG {
+            domain: "a.example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            type_covered: QueryType::A,
+            algorithm: Algorithm::Ed25519,
+            labels: 2,
+            original_ttl: Duration::from_secs(3600),
+            expiration: 2,
+            inception: 1,
+            key_tag: 0x1234,
+            signer_name: "Example.COM".to_string(),
+            signature: vec![0xFF],
+        };
+
+        let a = |ip: [u8; 4]| Record::A(A {
+            domain: "A.Example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            ip: Ipv4Addr::from(ip),
+        });
+
+        let data = rrsig.signed_data(&[a([10, 0, 0, 2]), a([10, 0, 0, 1]), a([10, 0, 0, 2])]).unwrap();
+
+        let mut expected = vec![
+            0x00, 0x01, // Type covered.
+            0x0F, // Algorithm.
+            0x02, // Labels.
+            0x00, 0x00, 0x0E, 0x10, // Original TTL.
+            0x00, 0x00, 0x00, 0x02, // Expiration.
+            0x00, 0x00, 0x00, 0x01, // Inception.
+            0x12, 0x34, // Key tag.
+            0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x03, 0x63, 0x6f, 0x6d, 0x00,
+        ];
+
+        for last in [1, 2] {
+            expected.extend_from_slice(&[
+                // Wildcard owner, given the labels.
+                0x01, 0x2a, 0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x03, 0x63, 0x6f, 0x6d, 0x00,
+                0x00, 0x01, // Type A.
+                0x00, 0x01, // Class IN.
+                0x00, 0x00, 0x0E, 0x10, // Original TTL.
+                0x00, 0x04, // RD length.
+                0x0A, 0x00, 0x00, last,
+            ]);
+        }
+
+        assert_eq!(expected, data);
+        assert!(rrsig.signed_data(&[Record::Opt(crate::records::OPT::new(512))]).is_err());
+    }
+}
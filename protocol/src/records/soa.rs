@@ -0,0 +1,240 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_name, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.13
+///
+/// ```txt
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                     MNAME                     /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// /                     RNAME                     /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                    SERIAL                     |
+/// |                                               |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                    REFRESH                    |
+/// |                                               |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                     RETRY                     |
+/// |                                               |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                    EXPIRE                     |
+/// |                                               |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                    MINIMUM                    |
+/// |                                               |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// MINIMUM is the TTL of negative answers for the zone (RFC 2308).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SOA {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub mname: String,
+    pub rname: String,
+    pub serial: u32,
+    pub refresh: Duration,
+    pub retry: Duration,
+    pub expire: Duration,
+    pub minimum: Duration,
+}
+
+impl SOA {
+    /// TTL to use when caching a negative answer carrying this record,
+    /// see https://datatracker.ietf.org/doc/html/rfc2308#section-5
+    pub fn negative_ttl(&self) -> Duration {
+        self.ttl.min(self.minimum)
+    }
+}
+
+impl Deserialize for SOA {
+    fn deserialize<D>(deserializer: &mut D) -> Result<SOA>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::StartOfAuthority)?;
+        let record = SOA {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            mname: deserializer.deserialize_qname()?,
+            rname: deserializer.deserialize_qname()?,
+            serial: deserializer.deserialize_u32()?,
+            refresh: Duration::from_secs(deserializer.deserialize_u32()? as u64),
+            retry: Duration::from_secs(deserializer.deserialize_u32()? as u64),
+            expire: Duration::from_secs(deserializer.deserialize_u32()? as u64),
+            minimum: Duration::from_secs(deserializer.deserialize_u32()? as u64),
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
+impl Serialize for SOA {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 6 for SOA)
+        serializer.serialize_u16(QueryType::StartOfAuthority.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        // Saving a pointer to this field to be able to
+        // set the size after names length computation.
+        let size_pos = serializer.position();
+        serializer.serialize_u16(0)?;
+
+        serializer.serialize_qname(&self.mname)?;
+        serializer.serialize_qname(&self.rname)?;
+        serializer.serialize_u32(self.serial)?;
+        serializer.serialize_u32(self.refresh.as_secs() as u32)?;
+        serializer.serialize_u32(self.retry.as_secs() as u32)?;
+        serializer.serialize_u32(self.expire.as_secs() as u32)?;
+        serializer.serialize_u32(self.minimum.as_secs() as u32)?;
+
+        let payload_size = serializer.position() - (size_pos + 2);
+        let current_position = serializer.position();
+        serializer.seek(size_pos)?;
+        serializer.serialize_u16(payload_size as u16)?;
+        serializer.seek(current_position)
+    }
+}
+
+impl Display for SOA {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} SOA {} {} {} {} {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            format_name(&self.mname),
+            format_name(&self.rname),
+            self.serial,
+            self.refresh.as_secs(),
+            self.retry.as_secs(),
+            self.expire.as_secs(),
+            self.minimum.as_secs(),
+        )
+    }
+}
+
+impl FromStr for SOA {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SOA> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::StartOfAuthority)?;
+
+        let fields = tokens.map(|token| token_str(&token)).collect::<Result<Vec<String>>>()?;
+        let seconds = |token: &str| parse_number::<u32>(token).map(|secs| Duration::from_secs(secs as u64));
+
+        match &fields[..] {
+            [mname, rname, serial, refresh, retry, expire, minimum] => Ok(SOA {
+                domain: header.domain,
+                class: header.class,
+                ttl: header.ttl,
+                mname: parse_name(mname),
+                rname: parse_name(rname),
+                serial: parse_number(serial)?,
+                refresh: seconds(refresh)?,
+                retry: seconds(retry)?,
+                expire: seconds(expire)?,
+                minimum: seconds(minimum)?,
+            }),
+            _ => Err(InvalidPresentation("SOA record expects mname, rname, serial, refresh, retry, expire and minimum".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::SOA;
+    use crate::ser::Serialize;
+
+    fn soa() -> SOA {
+        SOA {
+            domain: "x".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(3600),
+            mname: "ns.x".to_string(),
+            rname: "admin.x".to_string(),
+            serial: 2024010101,
+            refresh: Duration::from_secs(7200),
+            retry: Duration::from_secs(900),
+            expire: Duration::from_secs(1209600),
+            minimum: Duration::from_secs(300),
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+
+        let res = soa().serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0x00, 0x06, // Type SOA.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x0E, 0x10, // TTL.
+            0x00, 0x23, // RD length.
+            0x02, 0x6e, 0x73, // len=2 label="ns"
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0x05, 0x61, 0x64, 0x6d, 0x69, 0x6e, // len=5 label="admin"
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0x78, 0xA3, 0xF1, 0x75, // Serial.
+            0x00, 0x00, 0x1C, 0x20, // Refresh.
+            0x00, 0x00, 0x03, 0x84, // Retry.
+            0x00, 0x12, 0x75, 0x00, // Expire.
+            0x00, 0x00, 0x01, 0x2C, // Minimum.
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn negative_ttl() {
+        assert_eq!(Duration::from_secs(300), soa().negative_ttl());
+    }
+
+    #[test]
+    fn presentation() {
+        let text = soa().to_string();
+        assert_eq!("x. 3600 IN SOA ns.x. admin.x. 2024010101 7200 900 1209600 300", text);
+        assert_eq!(soa(), text.parse().unwrap());
+
+        let multiline = "x. 3600 IN SOA ns.x. admin.x. (\n 2024010101 ; serial\n 7200 900 1209600 300 )";
+        assert_eq!(soa(), multiline.parse().unwrap());
+    }
+}
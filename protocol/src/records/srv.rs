@@ -0,0 +1,295 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::InvalidPresentation;
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_name, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc2782
+///
+/// ```txt
+///                                 1  1  1  1  1  1
+///   0  1  2  3  4  5  6  7  8  9  0  1  2  3  4  5
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// .                      NAME                     .
+/// .                                               .
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                      TYPE                     |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                     CLASS                     |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                      TTL                      |
+/// |                                               |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                   RDLENGTH                    |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                   PRIORITY                    |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                    WEIGHT                     |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                     PORT                      |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// .                    TARGET                     .
+/// .                                               .
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SRV {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: String,
+}
+
+impl SRV {
+    /// Order SRV records in the sequence clients must try them, as
+    /// described by the RFC 2782 usage rules.
+    ///
+    /// Records are sorted by ascending priority and, within a priority,
+    /// picked by weighted random selection. `random` must return a
+    /// uniformly distributed number between 0 and its argument (inclusive).
+    ///
+    /// Records whose target is the root (".") are dropped, meaning the
+    /// service is decidedly not available at this domain.
+    pub fn order_targets<F>(records: &[SRV], mut random: F) -> Vec<&SRV>
+        where F: FnMut(u32) -> u32
+    {
+        let mut remaining: Vec<&SRV> = records
+            .iter()
+            .filter(|srv| !srv.target.is_empty())
+            .collect();
+        remaining.sort_by_key(|srv| srv.priority);
+
+        let mut ordered = Vec::with_capacity(remaining.len());
+        while let Some(priority) = remaining.first().map(|srv| srv.priority) {
+            let split = remaining.iter().position(|srv| srv.priority != priority).unwrap_or(remaining.len());
+            let mut group: Vec<&SRV> = remaining.drain(..split).collect();
+
+            // Records with a zero weight are placed first so they have a
+            // small chance to be selected.
+            group.sort_by_key(|srv| srv.weight != 0);
+
+            while !group.is_empty() {
+                let total: u32 = group.iter().map(|srv| srv.weight as u32).sum();
+                let selected = random(total);
+
+                let mut running_sum = 0;
+                let index = group
+                    .iter()
+                    .position(|srv| {
+                        running_sum += srv.weight as u32;
+                        running_sum >= selected
+                    })
+                    .unwrap_or(group.len() - 1);
+
+                ordered.push(group.remove(index));
+            }
+        }
+
+        ordered
+    }
+}
+
+impl Deserialize for SRV {
+    fn deserialize<D>(deserializer: &mut D) -> Result<SRV>
+        where
+            D: Deserializer + Seek
+    {
+        let header = WireHeader::deserialize(deserializer, QueryType::Service)?;
+        let record = SRV {
+            domain: header.domain.clone(),
+            class: header.class,
+            ttl: header.ttl,
+            priority: deserializer.deserialize_u16()?,
+            weight: deserializer.deserialize_u16()?,
+            port: deserializer.deserialize_u16()?,
+            target: deserializer.deserialize_qname()?,
+        };
+
+        header.check_end(deserializer)?;
+        Ok(record)
+    }
+}
+
+impl Serialize for SRV {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 33 for SRV)
+        serializer.serialize_u16(QueryType::Service.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        // Saving a pointer to this field to be able to
+        // set the size after target length computation.
+        let size_pos = serializer.position();
+        serializer.serialize_u16(0)?;
+
+        serializer.serialize_u16(self.priority)?;
+        serializer.serialize_u16(self.weight)?;
+        serializer.serialize_u16(self.port)?;
+
+        // Target, which must not be compressed.
+        serializer.serialize_qname(&self.target)?;
+
+        let payload_size = serializer.position() - (size_pos + 2);
+        let current_position = serializer.position();
+        serializer.seek(size_pos)?;
+        serializer.serialize_u16(payload_size as u16)?;
+        serializer.seek(current_position)
+    }
+}
+
+impl Display for SRV {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} SRV {} {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            self.priority,
+            self.weight,
+            self.port,
+            format_name(&self.target),
+        )
+    }
+}
+
+impl FromStr for SRV {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SRV> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::Service)?;
+
+        let fields = tokens
+            .map(|token| token_str(&token))
+            .collect::<Result<Vec<String>>>()?;
+
+        match &fields[..] {
+            [priority, weight, port, target] => Ok(SRV {
+                domain: header.domain,
+                class: header.class,
+                ttl: header.ttl,
+                priority: parse_number(priority)?,
+                weight: parse_number(weight)?,
+                port: parse_number(port)?,
+                target: parse_name(target),
+            }),
+            _ => Err(InvalidPresentation("SRV record expects priority, weight, port and target".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::SRV;
+    use crate::ser::Serialize;
+
+    fn srv(priority: u16, weight: u16, target: &str) -> SRV {
+        SRV {
+            domain: "_sip._tcp.example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            priority,
+            weight,
+            port: 5060,
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+        let record = SRV {
+            domain: "_x._tcp".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            priority: 10,
+            weight: 5,
+            port: 443,
+            target: "www.google.com".to_string(),
+        };
+
+        let res = record.serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x02, 0x5f, 0x78, // len=2 label="_x"
+            0x04, 0x5f, 0x74, 0x63, 0x70, // len=4 label="_tcp"
+            0x00,
+            0x00, 0x21, // Type SRV.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x16, // RD length.
+            0x00, 0x0A, // Priority.
+            0x00, 0x05, // Weight.
+            0x01, 0xBB, // Port.
+            0x03, 0x77, 0x77, 0x77, // len=3 label="www"
+            0x06, 0x67, 0x6f, 0x6f, 0x67, 0x6c, 0x65, // len=6 label="google"
+            0x03, 0x63, 0x6f, 0x6d, // len=3 label="com"
+            0x00,
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn presentation() {
+        let record = srv(10, 60, "sip.example.com");
+
+        let text = record.to_string();
+        assert_eq!("_sip._tcp.example.com. 60 IN SRV 10 60 5060 sip.example.com.", text);
+        assert_eq!(record, text.parse().unwrap());
+        assert!("_sip._tcp.example.com. 60 IN SRV 10 60 sip.example.com.".parse::<SRV>().is_err());
+    }
+
+    #[test]
+    fn order_by_priority_then_weight() {
+        let records = vec![
+            srv(20, 0, "backup.example.com"),
+            srv(10, 0, "zero.example.com"),
+            srv(10, 60, "big.example.com"),
+            srv(10, 40, "small.example.com"),
+        ];
+
+        // Always picking the highest number selects the last record of the
+        // running sum, starting with the zero weight record first.
+        let ordered = SRV::order_targets(&records, |total| total);
+        let targets: Vec<&str> = ordered.iter().map(|srv| srv.target.as_str()).collect();
+        assert_eq!(vec!["small.example.com", "big.example.com", "zero.example.com", "backup.example.com"], targets);
+
+        // Always picking 0 selects the first record of the running sum.
+        let ordered = SRV::order_targets(&records, |_| 0);
+        let targets: Vec<&str> = ordered.iter().map(|srv| srv.target.as_str()).collect();
+        assert_eq!(vec!["zero.example.com", "big.example.com", "small.example.com", "backup.example.com"], targets);
+    }
+
+    #[test]
+    fn order_drops_unavailable_service() {
+        let records = vec![srv(0, 0, "")];
+        assert!(SRV::order_targets(&records, |total| total).is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_hex, format_name, parse_hex, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc4255#section-3.1
+///
+/// ```txt
+///                      1 1 1 1 1 1 1 1 1 1 2 2 2 2 2 2 2 2 2 2 3 3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |   algorithm   |    fp type    |                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+                               /
+/// /                                                               /
+/// /                          fingerprint                          /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SSHFP {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub algorithm: SshAlgorithm,
+    pub fingerprint_type: FingerprintType,
+    pub fingerprint: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SshAlgorithm {
+    Rsa,
+    Dsa,
+    Ecdsa,
+    Ed25519,
+    Ed448,
+}
+
+impl SshAlgorithm {
+    pub fn from_u8(num: u8) -> Result<SshAlgorithm> {
+        match num {
+            1 => Ok(SshAlgorithm::Rsa),
+            2 => Ok(SshAlgorithm::Dsa),
+            3 => Ok(SshAlgorithm::Ecdsa),
+            4 => Ok(SshAlgorithm::Ed25519),
+            6 => Ok(SshAlgorithm::Ed448),
+            _ => Err(InvalidData(format!("unassigned SSHFP algorithm {}", num))),
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match *self {
+            SshAlgorithm::Rsa => 1,
+            SshAlgorithm::Dsa => 2,
+            SshAlgorithm::Ecdsa => 3,
+            SshAlgorithm::Ed25519 => 4,
+            SshAlgorithm::Ed448 => 6,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum FingerprintType {
+    Sha1,
+    Sha256,
+}
+
+impl FingerprintType {
+    pub fn from_u8(num: u8) -> Result<FingerprintType> {
+        match num {
+            1 => Ok(FingerprintType::Sha1),
+            2 => Ok(FingerprintType::Sha256),
+            _ => Err(InvalidData(format!("unassigned SSHFP fingerprint type {}", num))),
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match *self {
+            FingerprintType::Sha1 => 1,
+            FingerprintType::Sha256 => 2,
+        }
+    }
+
+    fn digest_len(&self) -> usize {
+        match *self {
+            FingerprintType::Sha1 => 20,
+            FingerprintType::Sha256 => 32,
+        }
+    }
+}
+
+impl SSHFP {
+    /// Check that the fingerprint length matches its type.
+    pub fn validate(&self) -> Result<()> {
+        if self.fingerprint.len() != self.fingerprint_type.digest_len() {
+            return Err(InvalidData(format!(
+                "SSHFP fingerprint of {} bytes doesn't match the {:?} fingerprint type",
+                self.fingerprint.len(),
+                self.fingerprint_type,
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<SSHFP> {
+        if data.len() < 2 {
+            return Err(InvalidData("SSHFP data is too short".to_string()));
+        }
+
+        let record = SSHFP {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            algorithm: SshAlgorithm::from_u8(data[0])?,
+            fingerprint_type: FingerprintType::from_u8(data[1])?,
+            fingerprint: data[2..].to_vec(),
+        };
+
+        record.validate()?;
+        Ok(record)
+    }
+}
+
+impl Deserialize for SSHFP {
+    fn deserialize<D>(deserializer: &mut D) -> Result<SSHFP>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::SshFingerprint)?;
+        SSHFP::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for SSHFP {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.validate()?;
+
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 44 for SSHFP)
+        serializer.serialize_u16(QueryType::SshFingerprint.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        serializer.serialize_u16((2 + self.fingerprint.len()) as u16)?;
+
+        serializer.serialize_u8(self.algorithm.as_u8())?;
+        serializer.serialize_u8(self.fingerprint_type.as_u8())?;
+        serializer.serialize_bytes(&self.fingerprint)
+    }
+}
+
+impl Display for SSHFP {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} SSHFP {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            self.algorithm.as_u8(),
+            self.fingerprint_type.as_u8(),
+            format_hex(&self.fingerprint),
+        )
+    }
+}
+
+impl FromStr for SSHFP {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SSHFP> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::SshFingerprint)?;
+
+        let mut field = || match tokens.next() {
+            Some(token) => parse_number::<u8>(&token_str(&token)?),
+            None => Err(InvalidPresentation("SSHFP record expects algorithm, fingerprint type and fingerprint".to_string())),
+        };
+
+        let record = SSHFP {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            algorithm: SshAlgorithm::from_u8(field()?)?,
+            fingerprint_type: FingerprintType::from_u8(field()?)?,
+            fingerprint: parse_hex(tokens)?,
+        };
+
+        record.validate()?;
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::sshfp::{FingerprintType, SshAlgorithm};
+    use crate::records::SSHFP;
+    use crate::ser::Serialize;
+
+    fn sshfp() -> SSHFP {
+        SSHFP {
+            domain: "host.google.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            algorithm: SshAlgorithm::Ed25519,
+            fingerprint_type: FingerprintType::Sha1,
+            fingerprint: (0..20).collect(),
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+
+        let res = sshfp().serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        let bytes = serializer.bytes();
+        assert_eq!(&[
+            0x00, 0x2C, // Type SSHFP.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x16, // RD length.
+            0x04, 0x01, // Algorithm and fingerprint type.
+        ], &bytes[17..29]);
+        assert_eq!((0..20).collect::<Vec<u8>>(), &bytes[29..]);
+    }
+
+    #[test]
+    fn validation() {
+        assert!(SSHFP::from_rdata("host", Class::Internet, Duration::from_secs(60), &[4, 2, 0, 1]).is_err());
+        assert!(SSHFP::from_rdata("host", Class::Internet, Duration::from_secs(60), &[5, 1]).is_err());
+        assert!(SSHFP::from_rdata("host", Class::Internet, Duration::from_secs(60), &[4, 3]).is_err());
+    }
+
+    #[test]
+    fn presentation() {
+        let text = sshfp().to_string();
+        assert_eq!("host.google.com. 60 IN SSHFP 4 1 000102030405060708090A0B0C0D0E0F10111213", text);
+        assert_eq!(sshfp(), text.parse().unwrap());
+    }
+}
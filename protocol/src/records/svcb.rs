@@ -0,0 +1,673 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_base64, format_name, parse_base64, parse_name, parse_number, quote, token_str, tokenize, RecordHeader};
+use crate::records::rdata::RDataReader;
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc9460#section-2.2
+///
+/// ```txt
+///                                 1  1  1  1  1  1
+///   0  1  2  3  4  5  6  7  8  9  0  1  2  3  4  5
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                  SvcPriority                  |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// .                  TargetName                   .
+/// .                                               .
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                 SvcParamKey                   |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                SvcParamLength                 |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// .                SvcParamValue                  .
+/// .                                               .
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// SvcParams are repeated until the end of the record data, in strictly
+/// increasing key order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SVCB {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub priority: u16,
+    pub target: String,
+    pub params: Vec<SvcParam>,
+}
+
+/// HTTPS records share the SVCB format, dedicated to HTTP origins.
+///
+/// See: https://datatracker.ietf.org/doc/html/rfc9460#section-9
+#[derive(Debug, Clone, PartialEq)]
+pub struct HTTPS(pub SVCB);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvcParam {
+    Unknown {
+        key: u16,
+        value: Vec<u8>,
+    },
+    // mandatory, Keys which must be understood by clients.
+    Mandatory(Vec<u16>),
+    // alpn, Supported protocol identifiers.
+    Alpn(Vec<Vec<u8>>),
+    // no-default-alpn, The default protocol is not supported.
+    NoDefaultAlpn,
+    // port, Alternative port.
+    Port(u16),
+    // ipv4hint, IPv4 addresses of the target.
+    Ipv4Hint(Vec<Ipv4Addr>),
+    // ech, Encrypted ClientHello configuration list.
+    Ech(Vec<u8>),
+    // ipv6hint, IPv6 addresses of the target.
+    Ipv6Hint(Vec<Ipv6Addr>),
+}
+
+const KEY_NAMES: &[&str] = &["mandatory", "alpn", "no-default-alpn", "port", "ipv4hint", "ech", "ipv6hint"];
+
+/// Presentation name of a SvcParamKey (e.g. `alpn` or `key667`).
+pub fn key_name(key: u16) -> String {
+    match KEY_NAMES.get(key as usize) {
+        Some(name) => name.to_string(),
+        None => format!("key{}", key),
+    }
+}
+
+/// Parse the presentation name of a SvcParamKey.
+pub fn parse_key(name: &str) -> Result<u16> {
+    let name = name.to_lowercase();
+    if let Some(key) = KEY_NAMES.iter().position(|key| *key == name) {
+        return Ok(key as u16);
+    }
+
+    match name.strip_prefix("key").map(str::parse::<u16>) {
+        // 65535 is reserved as "Invalid key".
+        Some(Ok(key)) if key != 65535 => Ok(key),
+        _ => Err(InvalidPresentation(format!("invalid SvcParamKey: {}", name))),
+    }
+}
+
+impl SvcParam {
+    pub fn key(&self) -> u16 {
+        match *self {
+            SvcParam::Mandatory(_) => 0,
+            SvcParam::Alpn(_) => 1,
+            SvcParam::NoDefaultAlpn => 2,
+            SvcParam::Port(_) => 3,
+            SvcParam::Ipv4Hint(_) => 4,
+            SvcParam::Ech(_) => 5,
+            SvcParam::Ipv6Hint(_) => 6,
+            SvcParam::Unknown { key, .. } => key,
+        }
+    }
+
+    /// Decode a SvcParamValue in wire format.
+    pub fn from_wire(key: u16, value: &[u8]) -> Result<SvcParam> {
+        let mut reader = RDataReader::new(value);
+
+        let param = match key {
+            0 => {
+                if value.is_empty() || !value.len().is_multiple_of(2) {
+                    return Err(InvalidData("mandatory expects a non empty list of keys".to_string()));
+                }
+
+                let mut keys = vec![];
+                while !reader.is_empty() {
+                    keys.push(reader.read_u16()?);
+                }
+                SvcParam::Mandatory(keys)
+            }
+            1 => {
+                let mut ids = vec![];
+                while !reader.is_empty() {
+                    let id = reader.read_character_string()?;
+                    if id.is_empty() {
+                        return Err(InvalidData("alpn identifiers cannot be empty".to_string()));
+                    }
+                    ids.push(id.to_vec());
+                }
+
+                if ids.is_empty() {
+                    return Err(InvalidData("alpn expects a non empty list of identifiers".to_string()));
+                }
+                SvcParam::Alpn(ids)
+            }
+            2 if value.is_empty() => SvcParam::NoDefaultAlpn,
+            2 => return Err(InvalidData("no-default-alpn expects an empty value".to_string())),
+            3 if value.len() == 2 => SvcParam::Port(reader.read_u16()?),
+            3 => return Err(InvalidData("port expects a 2 bytes value".to_string())),
+            4 => {
+                if value.is_empty() || !value.len().is_multiple_of(4) {
+                    return Err(InvalidData("ipv4hint expects a non empty list of IPv4 addresses".to_string()));
+                }
+
+                let mut addrs = vec![];
+                while !reader.is_empty() {
+                    addrs.push(Ipv4Addr::from(reader.read_u32()?));
+                }
+                SvcParam::Ipv4Hint(addrs)
+            }
+            5 => SvcParam::Ech(value.to_vec()),
+            6 => {
+                if value.is_empty() || !value.len().is_multiple_of(16) {
+                    return Err(InvalidData("ipv6hint expects a non empty list of IPv6 addresses".to_string()));
+                }
+
+                let mut addrs = vec![];
+                while !reader.is_empty() {
+                    let mut octets = [0u8; 16];
+                    octets.copy_from_slice(reader.read_bytes(16)?);
+                    addrs.push(Ipv6Addr::from(octets));
+                }
+                SvcParam::Ipv6Hint(addrs)
+            }
+            65535 => return Err(InvalidData("SvcParamKey 65535 is reserved".to_string())),
+            _ => SvcParam::Unknown { key, value: value.to_vec() },
+        };
+
+        Ok(param)
+    }
+
+    /// Encode the SvcParamValue in wire format.
+    pub fn to_wire(&self) -> Vec<u8> {
+        let mut value = vec![];
+
+        match self {
+            SvcParam::Mandatory(keys) => {
+                let mut keys = keys.clone();
+                keys.sort_unstable();
+                for key in keys {
+                    value.extend_from_slice(&key.to_be_bytes());
+                }
+            }
+            SvcParam::Alpn(ids) => {
+                for id in ids {
+                    value.push(id.len() as u8);
+                    value.extend_from_slice(id);
+                }
+            }
+            SvcParam::NoDefaultAlpn => {}
+            SvcParam::Port(port) => value.extend_from_slice(&port.to_be_bytes()),
+            SvcParam::Ipv4Hint(addrs) => {
+                for addr in addrs {
+                    value.extend_from_slice(&addr.octets());
+                }
+            }
+            SvcParam::Ech(config) => value.extend_from_slice(config),
+            SvcParam::Ipv6Hint(addrs) => {
+                for addr in addrs {
+                    value.extend_from_slice(&addr.octets());
+                }
+            }
+            SvcParam::Unknown { value: data, .. } => value.extend_from_slice(data),
+        }
+
+        value
+    }
+
+    /// Parse a SvcParam in presentation format (`key` or `key=value`),
+    /// the value being already unquoted.
+    pub fn from_presentation(token: &[u8]) -> Result<SvcParam> {
+        let (name, value) = match token.iter().position(|b| *b == b'=') {
+            Some(pos) => (&token[..pos], Some(&token[pos + 1..])),
+            None => (token, None),
+        };
+        let key = parse_key(&token_str(name)?)?;
+
+        let value = match (key, value) {
+            (2, None) => return Ok(SvcParam::NoDefaultAlpn),
+            (2, Some(_)) => return Err(InvalidPresentation("no-default-alpn doesn't expect a value".to_string())),
+            (_, Some(value)) => value,
+            (_, None) if key > 6 => return Ok(SvcParam::Unknown { key, value: vec![] }),
+            (_, None) => return Err(InvalidPresentation(format!("{} expects a value", key_name(key)))),
+        };
+
+        let param = match key {
+            0 => SvcParam::Mandatory(
+                split_value_list(value)
+                    .iter()
+                    .map(|key| parse_key(&token_str(key)?))
+                    .collect::<Result<Vec<u16>>>()?,
+            ),
+            1 => SvcParam::Alpn(split_value_list(value)),
+            3 => SvcParam::Port(parse_number(&token_str(value)?)?),
+            4 => SvcParam::Ipv4Hint(
+                split_value_list(value)
+                    .iter()
+                    .map(|addr| parse_addr(addr))
+                    .collect::<Result<Vec<Ipv4Addr>>>()?,
+            ),
+            5 => SvcParam::Ech(parse_base64(std::iter::once(value.to_vec()))?),
+            6 => SvcParam::Ipv6Hint(
+                split_value_list(value)
+                    .iter()
+                    .map(|addr| parse_addr(addr))
+                    .collect::<Result<Vec<Ipv6Addr>>>()?,
+            ),
+            _ => SvcParam::Unknown { key, value: value.to_vec() },
+        };
+
+        Ok(param)
+    }
+}
+
+impl Display for SvcParam {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let name = key_name(self.key());
+
+        match self {
+            SvcParam::Mandatory(keys) => {
+                let keys: Vec<String> = keys.iter().map(|key| key_name(*key)).collect();
+                write!(f, "{}={}", name, keys.join(","))
+            }
+            SvcParam::Alpn(ids) => {
+                // Commas and backslashes inside an identifier are escaped
+                // a first time for the value list, and a second time by
+                // the quoting of the whole value.
+                let ids: Vec<Vec<u8>> = ids
+                    .iter()
+                    .map(|id| {
+                        id.iter().fold(vec![], |mut escaped, byte| {
+                            if *byte == b',' || *byte == b'\\' {
+                                escaped.push(b'\\');
+                            }
+                            escaped.push(*byte);
+                            escaped
+                        })
+                    })
+                    .collect();
+                write!(f, "{}={}", name, quote(&ids.join(&b',')))
+            }
+            SvcParam::NoDefaultAlpn => f.write_str(&name),
+            SvcParam::Port(port) => write!(f, "{}={}", name, port),
+            SvcParam::Ipv4Hint(addrs) => {
+                let addrs: Vec<String> = addrs.iter().map(Ipv4Addr::to_string).collect();
+                write!(f, "{}={}", name, addrs.join(","))
+            }
+            SvcParam::Ech(config) => write!(f, "{}={}", name, format_base64(config)),
+            SvcParam::Ipv6Hint(addrs) => {
+                let addrs: Vec<String> = addrs.iter().map(Ipv6Addr::to_string).collect();
+                write!(f, "{}={}", name, addrs.join(","))
+            }
+            SvcParam::Unknown { value, .. } if value.is_empty() => f.write_str(&name),
+            SvcParam::Unknown { value, .. } => write!(f, "{}={}", name, quote(value)),
+        }
+    }
+}
+
+// Split a comma separated value list, where commas and backslashes
+// can be escaped by a backslash (RFC 9460 appendix A.1).
+fn split_value_list(value: &[u8]) -> Vec<Vec<u8>> {
+    let mut items = vec![vec![]];
+    let mut bytes = value.iter();
+
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'\\' => {
+                if let Some(escaped) = bytes.next() {
+                    items.last_mut().unwrap().push(*escaped);
+                }
+            }
+            b',' => items.push(vec![]),
+            _ => items.last_mut().unwrap().push(*byte),
+        }
+    }
+
+    items
+}
+
+fn parse_addr<T>(addr: &[u8]) -> Result<T>
+    where T: FromStr
+{
+    let addr = token_str(addr)?;
+    addr.parse().map_err(|_| InvalidPresentation(format!("invalid address: {}", addr)))
+}
+
+impl SVCB {
+    /// AliasMode records (priority 0) alias the owner to the target
+    /// name instead of providing the service bindings.
+    pub fn is_alias(&self) -> bool {
+        self.priority == 0
+    }
+
+    pub fn param(&self, key: u16) -> Option<&SvcParam> {
+        self.params.iter().find(|param| param.key() == key)
+    }
+
+    /// Check the SvcParams consistency: keys are unique and every key
+    /// listed as mandatory is present (RFC 9460 section 8).
+    pub fn validate(&self) -> Result<()> {
+        let mut keys: Vec<u16> = self.params.iter().map(SvcParam::key).collect();
+        keys.sort_unstable();
+
+        if keys.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(InvalidData("SvcParamKeys must be unique".to_string()));
+        }
+
+        if let Some(SvcParam::Mandatory(mandatory)) = self.param(0) {
+            let mut mandatory = mandatory.clone();
+            mandatory.sort_unstable();
+
+            if mandatory.windows(2).any(|pair| pair[0] == pair[1]) {
+                return Err(InvalidData("mandatory keys must be unique".to_string()));
+            }
+
+            if let Some(key) = mandatory.iter().find(|key| **key == 0 || !keys.contains(key)) {
+                return Err(InvalidData(format!("mandatory key {} is missing from the SvcParams", key_name(*key))));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<SVCB> {
+        let mut reader = RDataReader::new(data);
+        let priority = reader.read_u16()?;
+        let target = reader.read_name()?;
+
+        let mut params: Vec<SvcParam> = vec![];
+        while !reader.is_empty() {
+            let key = reader.read_u16()?;
+            if let Some(previous) = params.last() {
+                if previous.key() >= key {
+                    return Err(InvalidData("SvcParamKeys must be in strictly increasing order".to_string()));
+                }
+            }
+
+            let len = reader.read_u16()? as usize;
+            params.push(SvcParam::from_wire(key, reader.read_bytes(len)?)?);
+        }
+
+        let record = SVCB {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            priority,
+            target,
+            params,
+        };
+
+        record.validate()?;
+        Ok(record)
+    }
+
+    fn serialize_as<S>(&self, qtype: QueryType, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.validate()?;
+
+        // SvcParams are always written in increasing key order.
+        let mut params: Vec<&SvcParam> = self.params.iter().collect();
+        params.sort_by_key(|param| param.key());
+
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type.
+        serializer.serialize_u16(qtype.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        // Saving a pointer to this field to be able to
+        // set the size after the payload serialization.
+        let size_pos = serializer.position();
+        serializer.serialize_u16(0)?;
+
+        serializer.serialize_u16(self.priority)?;
+
+        // Target, which must not be compressed.
+        serializer.serialize_qname(&self.target)?;
+
+        for param in params {
+            let value = param.to_wire();
+            serializer.serialize_u16(param.key())?;
+            serializer.serialize_u16(value.len() as u16)?;
+            serializer.serialize_bytes(&value)?;
+        }
+
+        let payload_size = serializer.position() - (size_pos + 2);
+        let current_position = serializer.position();
+        serializer.seek(size_pos)?;
+        serializer.serialize_u16(payload_size as u16)?;
+        serializer.seek(current_position)
+    }
+
+    fn fmt_as(&self, qtype: QueryType, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            qtype,
+            self.priority,
+            format_name(&self.target),
+        )?;
+
+        for param in self.params.iter() {
+            write!(f, " {}", param)?;
+        }
+
+        Ok(())
+    }
+
+    fn parse_as(s: &str, qtype: QueryType) -> Result<SVCB> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, qtype)?;
+
+        let (priority, target) = match (tokens.next(), tokens.next()) {
+            (Some(priority), Some(target)) => (parse_number(&token_str(&priority)?)?, parse_name(&token_str(&target)?)),
+            _ => return Err(InvalidPresentation(format!("{} record expects a priority and a target", qtype))),
+        };
+
+        let record = SVCB {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            priority,
+            target,
+            params: tokens
+                .map(|token| SvcParam::from_presentation(&token))
+                .collect::<Result<Vec<SvcParam>>>()?,
+        };
+
+        record.validate().map_err(|err| InvalidPresentation(err.to_string()))?;
+        Ok(record)
+    }
+}
+
+impl Deserialize for SVCB {
+    fn deserialize<D>(deserializer: &mut D) -> Result<SVCB>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::ServiceBinding)?;
+        SVCB::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for SVCB {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.serialize_as(QueryType::ServiceBinding, serializer)
+    }
+}
+
+impl Display for SVCB {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.fmt_as(QueryType::ServiceBinding, f)
+    }
+}
+
+impl FromStr for SVCB {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<SVCB> {
+        SVCB::parse_as(s, QueryType::ServiceBinding)
+    }
+}
+
+impl Deserialize for HTTPS {
+    fn deserialize<D>(deserializer: &mut D) -> Result<HTTPS>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::Https)?;
+        SVCB::from_rdata(&header.domain, header.class, header.ttl, &data).map(HTTPS)
+    }
+}
+
+impl Serialize for HTTPS {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.0.serialize_as(QueryType::Https, serializer)
+    }
+}
+
+impl Display for HTTPS {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.0.fmt_as(QueryType::Https, f)
+    }
+}
+
+impl FromStr for HTTPS {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<HTTPS> {
+        SVCB::parse_as(s, QueryType::Https).map(HTTPS)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::svcb::SvcParam;
+    use crate::records::{HTTPS, SVCB};
+    use crate::ser::Serialize;
+
+    fn https(params: Vec<SvcParam>) -> HTTPS {
+        HTTPS(SVCB {
+            domain: "example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            priority: 1,
+            target: "".to_string(),
+            params,
+        })
+    }
+
+    #[test]
+    fn serialize_sorts_keys() {
+        let record = https(vec![
+            SvcParam::Port(8443),
+            SvcParam::Alpn(vec![b"h2".to_vec(), b"h3".to_vec()]),
+        ]);
+
+        let mut serializer = BytePacketBuffer::new();
+        let res = record.serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, // len=7 label="example"
+            0x03, 0x63, 0x6f, 0x6d, // len=3 label="com"
+            0x00,
+            0x00, 0x41, // Type HTTPS.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x13, // RD length.
+            0x00, 0x01, // Priority.
+            0x00, // Target ".".
+            0x00, 0x01, 0x00, 0x06, 0x02, 0x68, 0x32, 0x02, 0x68, 0x33, // alpn=h2,h3
+            0x00, 0x03, 0x00, 0x02, 0x20, 0xFB, // port=8443
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn serialize_rejects_invalid_params() {
+        let duplicated = https(vec![SvcParam::Port(443), SvcParam::Port(8443)]);
+        assert!(duplicated.serialize(&mut BytePacketBuffer::new()).is_err());
+
+        let missing_mandatory = https(vec![SvcParam::Mandatory(vec![3])]);
+        assert!(missing_mandatory.serialize(&mut BytePacketBuffer::new()).is_err());
+    }
+
+    #[test]
+    fn from_rdata() {
+        let data = &[
+            0x00, 0x01, // Priority.
+            0x00, // Target ".".
+            0x00, 0x03, 0x00, 0x02, 0x01, 0xBB, // port=443
+            0x00, 0x04, 0x00, 0x04, 0x7F, 0x00, 0x00, 0x01, // ipv4hint=127.0.0.1
+        ];
+
+        let record = SVCB::from_rdata("example.com", Class::Internet, Duration::from_secs(60), data).unwrap();
+        assert_eq!(vec![SvcParam::Port(443), SvcParam::Ipv4Hint(vec!["127.0.0.1".parse().unwrap()])], record.params);
+
+        let unordered = &[
+            0x00, 0x01, 0x00,
+            0x00, 0x04, 0x00, 0x04, 0x7F, 0x00, 0x00, 0x01,
+            0x00, 0x03, 0x00, 0x02, 0x01, 0xBB,
+        ];
+        assert!(SVCB::from_rdata("example.com", Class::Internet, Duration::from_secs(60), unordered).is_err());
+
+        let invalid_port = &[0x00, 0x01, 0x00, 0x00, 0x03, 0x00, 0x01, 0x01];
+        assert!(SVCB::from_rdata("example.com", Class::Internet, Duration::from_secs(60), invalid_port).is_err());
+    }
+
+    #[test]
+    fn presentation() {
+        let record = https(vec![
+            SvcParam::Mandatory(vec![1]),
+            SvcParam::Alpn(vec![b"h2".to_vec(), b"a,b\\".to_vec()]),
+            SvcParam::NoDefaultAlpn,
+            SvcParam::Port(8443),
+            SvcParam::Ipv4Hint(vec!["192.0.2.1".parse().unwrap(), "192.0.2.2".parse().unwrap()]),
+            SvcParam::Ech(vec![0xDE, 0xAD]),
+            SvcParam::Ipv6Hint(vec!["2001:db8::1".parse().unwrap()]),
+            SvcParam::Unknown { key: 667, value: b"hello".to_vec() },
+        ]);
+
+        let text = record.to_string();
+        assert_eq!(
+            r#"example.com. 60 IN HTTPS 1 . mandatory=alpn alpn="h2,a\\,b\\\\" no-default-alpn port=8443 ipv4hint=192.0.2.1,192.0.2.2 ech=3q0= ipv6hint=2001:db8::1 key667="hello""#,
+            text,
+        );
+        assert_eq!(record, text.parse().unwrap());
+    }
+
+    #[test]
+    fn parse_alias_mode() {
+        let record: SVCB = "_8443._foo.api.example.com. 7200 IN SVCB 0 svc4.example.net.".parse().unwrap();
+        assert!(record.is_alias());
+        assert_eq!("svc4.example.net", record.target);
+        assert!(record.params.is_empty());
+
+        assert!("example.com. 60 IN SVCB 1 . port".parse::<SVCB>().is_err());
+        assert!("example.com. 60 IN SVCB 1 . port=1 port=2".parse::<SVCB>().is_err());
+        assert!("example.com. 60 IN SVCB 1 . key65535=x".parse::<SVCB>().is_err());
+    }
+}
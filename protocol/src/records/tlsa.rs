@@ -0,0 +1,326 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_hex, format_name, parse_hex, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc6698#section-2.1
+///
+/// ```txt
+///                      1 1 1 1 1 1 1 1 1 1 2 2 2 2 2 2 2 2 2 2 3 3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |  Cert. Usage  |   Selector    | Matching Type |               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+               /
+/// /                                                               /
+/// /                 Certificate Association Data                  /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TLSA {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub usage: CertificateUsage,
+    pub selector: Selector,
+    pub matching_type: MatchingType,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum CertificateUsage {
+    // PKIX-TA, CA constraint.
+    PkixTrustAnchor,
+    // PKIX-EE, Service certificate constraint.
+    PkixEndEntity,
+    // DANE-TA, Trust anchor assertion.
+    DaneTrustAnchor,
+    // DANE-EE, Domain-issued certificate.
+    DaneEndEntity,
+    // PrivCert, Reserved for private use.
+    PrivateUse,
+}
+
+impl CertificateUsage {
+    pub fn from_u8(num: u8) -> Result<CertificateUsage> {
+        match num {
+            0 => Ok(CertificateUsage::PkixTrustAnchor),
+            1 => Ok(CertificateUsage::PkixEndEntity),
+            2 => Ok(CertificateUsage::DaneTrustAnchor),
+            3 => Ok(CertificateUsage::DaneEndEntity),
+            255 => Ok(CertificateUsage::PrivateUse),
+            _ => Err(InvalidData(format!("unassigned TLSA certificate usage {}", num))),
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match *self {
+            CertificateUsage::PkixTrustAnchor => 0,
+            CertificateUsage::PkixEndEntity => 1,
+            CertificateUsage::DaneTrustAnchor => 2,
+            CertificateUsage::DaneEndEntity => 3,
+            CertificateUsage::PrivateUse => 255,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Selector {
+    // Cert, Full certificate.
+    FullCertificate,
+    // SPKI, SubjectPublicKeyInfo.
+    SubjectPublicKeyInfo,
+    // PrivSel, Reserved for private use.
+    PrivateUse,
+}
+
+impl Selector {
+    pub fn from_u8(num: u8) -> Result<Selector> {
+        match num {
+            0 => Ok(Selector::FullCertificate),
+            1 => Ok(Selector::SubjectPublicKeyInfo),
+            255 => Ok(Selector::PrivateUse),
+            _ => Err(InvalidData(format!("unassigned TLSA selector {}", num))),
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match *self {
+            Selector::FullCertificate => 0,
+            Selector::SubjectPublicKeyInfo => 1,
+            Selector::PrivateUse => 255,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum MatchingType {
+    // Full, No hash used.
+    Full,
+    // SHA2-256, 256 bit hash by SHA2.
+    Sha256,
+    // SHA2-512, 512 bit hash by SHA2.
+    Sha512,
+    // PrivMatch, Reserved for private use.
+    PrivateUse,
+}
+
+impl MatchingType {
+    pub fn from_u8(num: u8) -> Result<MatchingType> {
+        match num {
+            0 => Ok(MatchingType::Full),
+            1 => Ok(MatchingType::Sha256),
+            2 => Ok(MatchingType::Sha512),
+            255 => Ok(MatchingType::PrivateUse),
+            _ => Err(InvalidData(format!("unassigned TLSA matching type {}", num))),
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match *self {
+            MatchingType::Full => 0,
+            MatchingType::Sha256 => 1,
+            MatchingType::Sha512 => 2,
+            MatchingType::PrivateUse => 255,
+        }
+    }
+
+    // Length of the association data, when implied by the matching type.
+    fn digest_len(&self) -> Option<usize> {
+        match *self {
+            MatchingType::Sha256 => Some(32),
+            MatchingType::Sha512 => Some(64),
+            _ => None,
+        }
+    }
+}
+
+impl TLSA {
+    /// Check that the association data length matches the hash
+    /// algorithm given by the matching type.
+    pub fn validate(&self) -> Result<()> {
+        match self.matching_type.digest_len() {
+            Some(len) if len != self.data.len() => Err(InvalidData(format!(
+                "TLSA association data of {} bytes doesn't match the {:?} matching type",
+                self.data.len(),
+                self.matching_type,
+            ))),
+            _ if self.data.is_empty() => Err(InvalidData("TLSA association data is empty".to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<TLSA> {
+        if data.len() < 3 {
+            return Err(InvalidData("TLSA data is too short".to_string()));
+        }
+
+        let record = TLSA {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            usage: CertificateUsage::from_u8(data[0])?,
+            selector: Selector::from_u8(data[1])?,
+            matching_type: MatchingType::from_u8(data[2])?,
+            data: data[3..].to_vec(),
+        };
+
+        record.validate()?;
+        Ok(record)
+    }
+}
+
+impl Deserialize for TLSA {
+    fn deserialize<D>(deserializer: &mut D) -> Result<TLSA>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::TlsAssociation)?;
+        TLSA::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for TLSA {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.validate()?;
+
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 52 for TLSA)
+        serializer.serialize_u16(QueryType::TlsAssociation.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        serializer.serialize_u16((3 + self.data.len()) as u16)?;
+
+        serializer.serialize_u8(self.usage.as_u8())?;
+        serializer.serialize_u8(self.selector.as_u8())?;
+        serializer.serialize_u8(self.matching_type.as_u8())?;
+        serializer.serialize_bytes(&self.data)
+    }
+}
+
+impl Display for TLSA {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} TLSA {} {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            self.usage.as_u8(),
+            self.selector.as_u8(),
+            self.matching_type.as_u8(),
+            format_hex(&self.data),
+        )
+    }
+}
+
+impl FromStr for TLSA {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TLSA> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::TlsAssociation)?;
+
+        let mut field = || match tokens.next() {
+            Some(token) => parse_number::<u8>(&token_str(&token)?),
+            None => Err(InvalidPresentation("TLSA record expects usage, selector, matching type and data".to_string())),
+        };
+
+        let record = TLSA {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            usage: CertificateUsage::from_u8(field()?)?,
+            selector: Selector::from_u8(field()?)?,
+            matching_type: MatchingType::from_u8(field()?)?,
+            data: parse_hex(tokens)?,
+        };
+
+        record.validate()?;
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::tlsa::{CertificateUsage, MatchingType, Selector};
+    use crate::records::TLSA;
+    use crate::ser::Serialize;
+
+    fn tlsa() -> TLSA {
+        TLSA {
+            domain: "_443._tcp.google.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            usage: CertificateUsage::DaneEndEntity,
+            selector: Selector::SubjectPublicKeyInfo,
+            matching_type: MatchingType::Sha256,
+            data: (0..32).collect(),
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+
+        let res = tlsa().serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        let bytes = serializer.bytes();
+        assert_eq!(&[
+            0x00, 0x34, // Type TLSA.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x23, // RD length.
+            0x03, 0x01, 0x01, // Usage, selector and matching type.
+        ], &bytes[22..35]);
+        assert_eq!((0..32).collect::<Vec<u8>>(), &bytes[35..]);
+    }
+
+    #[test]
+    fn validation() {
+        let mut record = tlsa();
+        record.data.pop();
+        assert!(record.serialize(&mut BytePacketBuffer::new()).is_err());
+
+        assert!(TLSA::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"\x04\x00\x00\x01").is_err());
+        assert!(TLSA::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"\x03\x02\x00\x01").is_err());
+        assert!(TLSA::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"\x03\x00\x03\x01").is_err());
+        assert!(TLSA::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"\x03\x00\x00\x01").is_ok());
+    }
+
+    #[test]
+    fn presentation() {
+        let text = tlsa().to_string();
+        assert_eq!("_443._tcp.google.com. 60 IN TLSA 3 1 1 000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F", text);
+        assert_eq!(tlsa(), text.parse().unwrap());
+
+        let split = "_443._tcp.google.com. 60 IN TLSA 3 1 1 00010203040506070809 0a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+        assert_eq!(tlsa(), split.parse().unwrap());
+        assert!("_443._tcp.google.com. 60 IN TLSA 3 1 1 0001".parse::<TLSA>().is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error::InvalidData;
+use crate::header::ResultCode;
+use crate::packet::{Class, QueryType};
+use crate::records::rdata::{serialize_record, RDataReader, WireHeader};
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// MAC algorithm of a TSIG key, identified by a domain name.
+///
+/// See: https://www.iana.org/assignments/tsig-algorithm-names/tsig-algorithm-names.xhtml
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum TsigAlgorithm {
+    // Algorithms which aren't supported, by name.
+    Unknown(String),
+    HmacSha256,
+    HmacSha384,
+    HmacSha512,
+}
+
+impl TsigAlgorithm {
+    pub fn from_name(name: &str) -> TsigAlgorithm {
+        match name.trim_end_matches('.').to_lowercase().as_str() {
+            "hmac-sha256" => TsigAlgorithm::HmacSha256,
+            "hmac-sha384" => TsigAlgorithm::HmacSha384,
+            "hmac-sha512" => TsigAlgorithm::HmacSha512,
+            _ => TsigAlgorithm::Unknown(name.to_string()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            TsigAlgorithm::HmacSha256 => "hmac-sha256",
+            TsigAlgorithm::HmacSha384 => "hmac-sha384",
+            TsigAlgorithm::HmacSha512 => "hmac-sha512",
+            TsigAlgorithm::Unknown(name) => name,
+        }
+    }
+}
+
+impl Display for TsigAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.name())
+    }
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc8945#section-4.2
+///
+/// ```txt
+///                      1 1 1 1 1 1 1 1 1 1 2 2 2 2 2 2 2 2 2 2 3 3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                         Algorithm Name                        /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                                                               |
+/// |          Time Signed          +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |                               |            Fudge              |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |          MAC Size             |                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+             MAC               /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |          Original ID          |            Error              |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |          Other Len            |                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+           Other Data          /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// The TSIG pseudo-record authenticates a message with a shared secret,
+/// it is the last record of the additional section and is never cached.
+/// The owner is the name of the key, see `tsig::TsigSession` computing
+/// and checking the MAC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TSIG {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub algorithm: TsigAlgorithm,
+    // Seconds since the epoch, on 48 bits.
+    pub time_signed: u64,
+    pub fudge: u16,
+    pub mac: Vec<u8>,
+    pub original_id: u16,
+    pub error: ResultCode,
+    // Server time for BADTIME errors, empty otherwise.
+    pub other: Vec<u8>,
+}
+
+impl TSIG {
+    /// Record for the given key, without MAC, in the ANY class with a zero
+    /// TTL as required.
+    pub fn new<S>(key_name: S, algorithm: TsigAlgorithm, time_signed: u64, fudge: u16, original_id: u16) -> TSIG
+        where S: ToString
+    {
+        TSIG {
+            domain: key_name.to_string(),
+            class: Class::Any,
+            ttl: Duration::from_secs(0),
+            algorithm,
+            time_signed,
+            fudge,
+            mac: vec![],
+            original_id,
+            error: ResultCode::NoError,
+            other: vec![],
+        }
+    }
+
+    pub fn rdata(&self) -> Vec<u8> {
+        let mut data = vec![];
+        for label in self.algorithm.name().split('.').filter(|label| !label.is_empty()) {
+            data.push(label.len() as u8);
+            data.extend_from_slice(label.as_bytes());
+        }
+
+        data.push(0);
+        data.extend_from_slice(&self.time_signed.to_be_bytes()[2..]);
+        data.extend_from_slice(&self.fudge.to_be_bytes());
+        data.extend_from_slice(&(self.mac.len() as u16).to_be_bytes());
+        data.extend_from_slice(&self.mac);
+        data.extend_from_slice(&self.original_id.to_be_bytes());
+        data.extend_from_slice(&self.error.as_u16().to_be_bytes());
+        data.extend_from_slice(&(self.other.len() as u16).to_be_bytes());
+        data.extend_from_slice(&self.other);
+        data
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.time_signed >> 48 != 0 {
+            return Err(InvalidData(format!("TSIG time signed {} doesn't fit in 48 bits", self.time_signed)));
+        }
+
+        if self.mac.len() > u16::MAX as usize || self.other.len() > u16::MAX as usize {
+            return Err(InvalidData("TSIG MAC or other data is too long".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<TSIG> {
+        let mut reader = RDataReader::new(data);
+
+        let algorithm = TsigAlgorithm::from_name(&reader.read_name()?);
+        let time_signed = ((reader.read_u16()? as u64) << 32) | reader.read_u32()? as u64;
+        let fudge = reader.read_u16()?;
+        let mac_len = reader.read_u16()?;
+        let mac = reader.read_bytes(mac_len as usize)?.to_vec();
+        let original_id = reader.read_u16()?;
+        let error = ResultCode::from_u16(reader.read_u16()?);
+        let other_len = reader.read_u16()?;
+        let other = reader.read_bytes(other_len as usize)?.to_vec();
+
+        if !reader.is_empty() {
+            return Err(InvalidData(format!("TSIG record data of {} bytes is too long", data.len())));
+        }
+
+        Ok(TSIG {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            algorithm,
+            time_signed,
+            fudge,
+            mac,
+            original_id,
+            error,
+            other,
+        })
+    }
+}
+
+impl Deserialize for TSIG {
+    fn deserialize<D>(deserializer: &mut D) -> Result<TSIG>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::TransactionSignature)?;
+        TSIG::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for TSIG {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        self.validate()?;
+        serialize_record(serializer, &self.domain, QueryType::TransactionSignature, self.class, self.ttl, &self.rdata())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::de::Deserialize;
+    use crate::header::ResultCode;
+    use crate::packet::{Class, Record};
+    use crate::records::tsig::{TsigAlgorithm, TSIG};
+    use crate::ser::Serialize;
+
+    #[test]
+    fn round_trip() {
+        let mut record = TSIG::new("key.example", TsigAlgorithm::HmacSha256, 0x0001_6554_3210, 300, 0xBEEF);
+        record.mac = vec![0xCA, 0xFE];
+        record.error = ResultCode::BadTime;
+        record.other = vec![0, 0, 0x65, 0x54, 0x32, 0x10];
+
+        let mut serializer = BytePacketBuffer::new();
+        assert!(record.serialize(&mut serializer).is_ok());
+        let bytes = serializer.bytes();
+        assert_eq!(&[
+            0x0B, 0x68, 0x6D, 0x61, 0x63, 0x2D, 0x73, 0x68, 0x61, 0x32, 0x35, 0x36, 0x00, // Algorithm.
+            0x00, 0x01, 0x65, 0x54, 0x32, 0x10, // Time signed.
+            0x01, 0x2C, // Fudge.
+            0x00, 0x02, 0xCA, 0xFE, // MAC.
+            0xBE, 0xEF, // Original ID.
+            0x00, 0x12, // Error.
+            0x00, 0x06, 0x00, 0x00, 0x65, 0x54, 0x32, 0x10, // Other data.
+        ], &bytes[23..60]);
+
+        let mut buffer = BytePacketBuffer::from_raw_data(&bytes);
+        assert_eq!(Record::TransactionSignature(record), Record::deserialize(&mut buffer).unwrap());
+    }
+
+    #[test]
+    fn algorithm() {
+        assert_eq!(TsigAlgorithm::HmacSha512, TsigAlgorithm::from_name("HMAC-SHA512."));
+        assert_eq!(TsigAlgorithm::Unknown("hmac-md5.sig-alg.reg.int".to_string()), TsigAlgorithm::from_name("hmac-md5.sig-alg.reg.int"));
+        assert_eq!("hmac-sha384", TsigAlgorithm::HmacSha384.to_string());
+
+        let record = TSIG::new("key", TsigAlgorithm::Unknown("hmac-md5.sig-alg.reg.int".to_string()), 1 << 48, 300, 0);
+        assert_eq!(Class::Any, record.class);
+        assert!(record.validate().is_err());
+    }
+}
@@ -0,0 +1,282 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, quote, tokenize, RecordHeader};
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// Maximum length of a single <character-string>.
+pub const MAX_STRING_LENGTH: usize = 255;
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.14
+///
+/// ```txt
+///                                 1  1  1  1  1  1
+///   0  1  2  3  4  5  6  7  8  9  0  1  2  3  4  5
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// .                      NAME                     .
+/// .                                               .
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                      TYPE                     |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                     CLASS                     |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                      TTL                      |
+/// |                                               |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                   RDLENGTH                    |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// .                   TXT-DATA                    .
+/// .                                               .
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// TXT-DATA is a list of one or more <character-string>, each of them
+/// being prefixed by its length on a single byte.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TXT {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub strings: Vec<Vec<u8>>,
+}
+
+impl TXT {
+    /// Build a record holding a value of any length, like SPF or DKIM
+    /// values, by splitting it across as many strings as needed.
+    pub fn from_long_value<S>(domain: S, class: Class, ttl: Duration, value: &[u8]) -> TXT
+        where S: ToString
+    {
+        let mut strings: Vec<Vec<u8>> = value
+            .chunks(MAX_STRING_LENGTH)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+
+        // A TXT record always holds at least one (maybe empty) string.
+        if strings.is_empty() {
+            strings.push(vec![]);
+        }
+
+        TXT {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            strings,
+        }
+    }
+
+    /// Concatenate every string of the record, which is how values split
+    /// across multiple strings (SPF, DKIM, ...) must be interpreted.
+    pub fn joined(&self) -> Vec<u8> {
+        self.strings.concat()
+    }
+
+    /// Same as `joined` but lossily converted to UTF-8.
+    pub fn joined_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.joined()).into_owned()
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<TXT> {
+        let mut strings = vec![];
+        let mut pos = 0;
+
+        while pos < data.len() {
+            let len = data[pos] as usize;
+            let end = pos + 1 + len;
+            if end > data.len() {
+                return Err(InvalidData(format!("character-string of {} bytes overflows the TXT data", len)));
+            }
+
+            strings.push(data[pos + 1..end].to_vec());
+            pos = end;
+        }
+
+        if strings.is_empty() {
+            return Err(InvalidData("TXT data needs at least one character-string".to_string()));
+        }
+
+        Ok(TXT {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            strings,
+        })
+    }
+}
+
+impl Deserialize for TXT {
+    fn deserialize<D>(deserializer: &mut D) -> Result<TXT>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::Text)?;
+        TXT::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for TXT {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        if let Some(string) = self.strings.iter().find(|s| s.len() > MAX_STRING_LENGTH) {
+            return Err(InvalidData(format!("character-string of {} bytes exceeds {} bytes", string.len(), MAX_STRING_LENGTH)));
+        }
+
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 16 for TXT)
+        // See: https://datatracker.ietf.org/doc/html/rfc1035#section-3.2.2
+        serializer.serialize_u16(QueryType::Text.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size. Every string is prefixed by its length.
+        let payload_size: usize = self.strings.iter().map(|s| s.len() + 1).sum();
+        serializer.serialize_u16(payload_size as u16)?;
+
+        // Strings.
+        for string in self.strings.iter() {
+            serializer.serialize_u8(string.len() as u8)?;
+            serializer.serialize_bytes(string)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for TXT {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} TXT", format_name(&self.domain), self.ttl.as_secs(), self.class)?;
+        for string in self.strings.iter() {
+            write!(f, " {}", quote(string))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for TXT {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TXT> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::Text)?;
+
+        let strings: Vec<Vec<u8>> = tokens.collect();
+        if strings.is_empty() {
+            return Err(InvalidPresentation("TXT record needs at least one character-string".to_string()));
+        }
+
+        if let Some(string) = strings.iter().find(|s| s.len() > MAX_STRING_LENGTH) {
+            return Err(InvalidPresentation(format!("character-string of {} bytes exceeds {} bytes", string.len(), MAX_STRING_LENGTH)));
+        }
+
+        Ok(TXT {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            strings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::TXT;
+    use crate::ser::Serialize;
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+        let txt = TXT {
+            domain: "google.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            strings: vec![b"v=spf1".to_vec(), b"-all".to_vec()],
+        };
+
+        let res = txt.serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x06, 0x67, 0x6f, 0x6f, 0x67, 0x6c, 0x65, // len=6 label="google"
+            0x03, 0x63, 0x6f, 0x6d, // len=3 label="com"
+            0x00,
+            0x00, 0x10, // Type TXT.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x0C, // RD length.
+            0x06, 0x76, 0x3d, 0x73, 0x70, 0x66, 0x31, // len=6 string="v=spf1"
+            0x04, 0x2d, 0x61, 0x6c, 0x6c, // len=4 string="-all"
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn serialize_too_long_string() {
+        let mut serializer = BytePacketBuffer::new();
+        let txt = TXT {
+            domain: "google.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            strings: vec![vec![b'a'; 256]],
+        };
+
+        assert!(txt.serialize(&mut serializer).is_err());
+    }
+
+    #[test]
+    fn from_rdata() {
+        let txt = TXT::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"\x06v=spf1\x00\x04-all").unwrap();
+        assert_eq!(vec![b"v=spf1".to_vec(), vec![], b"-all".to_vec()], txt.strings);
+
+        assert!(TXT::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"\x07v=spf1").is_err());
+        assert!(TXT::from_rdata("google.com", Class::Internet, Duration::from_secs(60), b"").is_err());
+    }
+
+    #[test]
+    fn long_value() {
+        let value = vec![b'k'; 600];
+        let txt = TXT::from_long_value("dkim._domainkey.google.com", Class::Internet, Duration::from_secs(60), &value);
+
+        assert_eq!(vec![255, 255, 90], txt.strings.iter().map(Vec::len).collect::<Vec<_>>());
+        assert_eq!(value, txt.joined());
+    }
+
+    #[test]
+    fn presentation() {
+        let txt = TXT {
+            domain: "google.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            strings: vec![b"v=spf1 -all".to_vec(), b"say \"hi\"\n".to_vec()],
+        };
+
+        let text = txt.to_string();
+        assert_eq!(r#"google.com. 60 IN TXT "v=spf1 -all" "say \"hi\"\010""#, text);
+        assert_eq!(txt, text.parse().unwrap());
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("google.com. 60 IN TXT".parse::<TXT>().is_err());
+        assert!("google.com. 60 IN A \"foo\"".parse::<TXT>().is_err());
+        assert!(format!("google.com. 60 IN TXT \"{}\"", "a".repeat(256)).parse::<TXT>().is_err());
+    }
+}
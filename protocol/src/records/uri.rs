@@ -0,0 +1,196 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_number, quote, token_str, tokenize, RecordHeader};
+use crate::records::rdata::RDataReader;
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// https://datatracker.ietf.org/doc/html/rfc7553#section-4.5
+///
+/// ```txt
+///                      1 1 1 1 1 1 1 1 1 1 2 2 2 2 2 2 2 2 2 2 3 3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// |          Priority             |          Weight               |
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// /                                                               /
+/// /                             Target                            /
+/// /                                                               /
+/// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// Unlike a <character-string>, the target spans over the rest of the
+/// record data and is not prefixed by its length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct URI {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub priority: u16,
+    pub weight: u16,
+    pub target: String,
+}
+
+impl URI {
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<URI> {
+        let mut reader = RDataReader::new(data);
+        let priority = reader.read_u16()?;
+        let weight = reader.read_u16()?;
+
+        let target = String::from_utf8(data[4..].to_vec())
+            .map_err(|_| InvalidData("URI target is not valid UTF-8".to_string()))?;
+        if target.is_empty() {
+            return Err(InvalidData("URI target cannot be empty".to_string()));
+        }
+
+        Ok(URI {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            priority,
+            weight,
+            target,
+        })
+    }
+}
+
+impl Deserialize for URI {
+    fn deserialize<D>(deserializer: &mut D) -> Result<URI>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::Uri)?;
+        URI::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for URI {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        if self.target.is_empty() {
+            return Err(InvalidData("URI target cannot be empty".to_string()));
+        }
+
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 256 for URI)
+        serializer.serialize_u16(QueryType::Uri.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        serializer.serialize_u16((4 + self.target.len()) as u16)?;
+
+        serializer.serialize_u16(self.priority)?;
+        serializer.serialize_u16(self.weight)?;
+        serializer.serialize_bytes(self.target.as_bytes())
+    }
+}
+
+impl Display for URI {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {} URI {} {} {}",
+            format_name(&self.domain),
+            self.ttl.as_secs(),
+            self.class,
+            self.priority,
+            self.weight,
+            quote(self.target.as_bytes()),
+        )
+    }
+}
+
+impl FromStr for URI {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<URI> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::Uri)?;
+
+        let fields: Vec<Vec<u8>> = tokens.collect();
+        match &fields[..] {
+            [priority, weight, target] if !target.is_empty() => Ok(URI {
+                domain: header.domain,
+                class: header.class,
+                ttl: header.ttl,
+                priority: parse_number(&token_str(priority)?)?,
+                weight: parse_number(&token_str(weight)?)?,
+                target: token_str(target)?,
+            }),
+            _ => Err(InvalidPresentation("URI record expects priority, weight and a non empty target".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::URI;
+    use crate::ser::Serialize;
+
+    fn uri() -> URI {
+        URI {
+            domain: "_ftp._tcp".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            priority: 10,
+            weight: 1,
+            target: "ftp://ftp1.example.com/public".to_string(),
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+
+        let res = uri().serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        let bytes = serializer.bytes();
+        assert_eq!(&[
+            0x01, 0x00, // Type URI.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x21, // RD length.
+            0x00, 0x0A, // Priority.
+            0x00, 0x01, // Weight.
+        ], &bytes[11..25]);
+        assert_eq!(b"ftp://ftp1.example.com/public", &bytes[25..]);
+    }
+
+    #[test]
+    fn from_rdata() {
+        let record = URI::from_rdata("_ftp._tcp", Class::Internet, Duration::from_secs(60), b"\x00\x0a\x00\x01ftp://ftp1.example.com/public").unwrap();
+        assert_eq!(uri(), record);
+
+        assert!(URI::from_rdata("_ftp._tcp", Class::Internet, Duration::from_secs(60), b"\x00\x0a\x00\x01").is_err());
+        assert!(URI::from_rdata("_ftp._tcp", Class::Internet, Duration::from_secs(60), b"\x00\x0a").is_err());
+    }
+
+    #[test]
+    fn presentation() {
+        let text = uri().to_string();
+        assert_eq!(r#"_ftp._tcp. 60 IN URI 10 1 "ftp://ftp1.example.com/public""#, text);
+        assert_eq!(uri(), text.parse().unwrap());
+    }
+}
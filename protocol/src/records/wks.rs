@@ -0,0 +1,243 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType};
+use crate::presentation::{format_name, parse_number, token_str, tokenize, RecordHeader};
+use crate::records::rdata::RDataReader;
+use crate::records::rdata::WireHeader;
+use crate::result::Result;
+use crate::seek::Seek;
+use crate::ser::{Serialize, Serializer};
+
+/// IP protocol number of TCP.
+pub const PROTOCOL_TCP: u8 = 6;
+
+/// IP protocol number of UDP.
+pub const PROTOCOL_UDP: u8 = 17;
+
+/// https://datatracker.ietf.org/doc/html/rfc1035#section-3.4.2
+///
+/// ```txt
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |                    ADDRESS                    |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// |       PROTOCOL        |                       |
+/// +--+--+--+--+--+--+--+--+                       |
+/// |                                               |
+/// /                   <BIT MAP>                   /
+/// /                                               /
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// The bit map has one bit per port of the protocol, starting with port
+/// 0 as the most significant bit of the first byte. The ports are kept
+/// decoded and sorted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WKS {
+    pub domain: String,
+    pub class: Class,
+    pub ttl: Duration,
+    pub address: Ipv4Addr,
+    pub protocol: u8,
+    pub ports: Vec<u16>,
+}
+
+impl WKS {
+    /// Whether the service on the given port is advertised.
+    pub fn has_port(&self, port: u16) -> bool {
+        self.ports.contains(&port)
+    }
+
+    fn bitmap(&self) -> Vec<u8> {
+        let len = self.ports.iter().max().map_or(0, |max| *max as usize / 8 + 1);
+        let mut bitmap = vec![0; len];
+        for port in &self.ports {
+            bitmap[*port as usize / 8] |= 0x80 >> (port % 8);
+        }
+
+        bitmap
+    }
+
+    pub(crate) fn from_rdata(domain: &str, class: Class, ttl: Duration, data: &[u8]) -> Result<WKS> {
+        let mut reader = RDataReader::new(data);
+        let address = Ipv4Addr::from(reader.read_u32()?);
+        let protocol = reader.read_u8()?;
+
+        let bitmap = &data[5..];
+        if bitmap.len() > 8192 {
+            return Err(InvalidData(format!("WKS bit map of {} bytes exceeds 8192 bytes", bitmap.len())));
+        }
+
+        let ports = (0..bitmap.len() * 8)
+            .filter(|bit| bitmap[bit / 8] & (0x80 >> (bit % 8)) != 0)
+            .map(|bit| bit as u16)
+            .collect();
+
+        Ok(WKS {
+            domain: domain.to_string(),
+            class,
+            ttl,
+            address,
+            protocol,
+            ports,
+        })
+    }
+}
+
+impl Deserialize for WKS {
+    fn deserialize<D>(deserializer: &mut D) -> Result<WKS>
+        where
+            D: Deserializer + Seek
+    {
+        let (header, data) = WireHeader::deserialize_with_data(deserializer, QueryType::WellKnownService)?;
+        WKS::from_rdata(&header.domain, header.class, header.ttl, &data)
+    }
+}
+
+impl Serialize for WKS {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<()>
+        where
+            S: Serializer + Seek
+    {
+        let bitmap = self.bitmap();
+
+        // Name.
+        serializer.serialize_qname(&self.domain)?;
+
+        // Type. (Always 11 for WKS)
+        serializer.serialize_u16(QueryType::WellKnownService.as_u16())?;
+
+        // Class.
+        serializer.serialize_u16(self.class.as_u16())?;
+
+        // TTL.
+        serializer.serialize_u32(self.ttl.as_secs() as u32)?;
+
+        // Payload size.
+        serializer.serialize_u16((5 + bitmap.len()) as u16)?;
+
+        // Address.
+        serializer.serialize_u32(u32::from(self.address))?;
+
+        // Protocol.
+        serializer.serialize_u8(self.protocol)?;
+
+        // Bit map.
+        serializer.serialize_bytes(&bitmap)
+    }
+}
+
+impl Display for WKS {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} {} {} WKS {} {}", format_name(&self.domain), self.ttl.as_secs(), self.class, self.address, self.protocol)?;
+        for port in &self.ports {
+            write!(f, " {}", port)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for WKS {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<WKS> {
+        let mut tokens = tokenize(s)?.into_iter();
+        let header = RecordHeader::parse(&mut tokens, QueryType::WellKnownService)?;
+
+        let address = match tokens.next() {
+            Some(token) => token_str(&token)?
+                .parse()
+                .map_err(|_| InvalidPresentation("WKS record expects an IPv4 address".to_string()))?,
+            None => return Err(InvalidPresentation("WKS record expects an IPv4 address".to_string())),
+        };
+
+        let protocol = match tokens.next().map(|token| token_str(&token)).transpose()? {
+            Some(protocol) if protocol.eq_ignore_ascii_case("tcp") => PROTOCOL_TCP,
+            Some(protocol) if protocol.eq_ignore_ascii_case("udp") => PROTOCOL_UDP,
+            Some(protocol) => parse_number(&protocol)?,
+            None => return Err(InvalidPresentation("WKS record expects a protocol".to_string())),
+        };
+
+        let mut ports = tokens
+            .map(|token| parse_number(&token_str(&token)?))
+            .collect::<Result<Vec<u16>>>()?;
+        ports.sort_unstable();
+        ports.dedup();
+
+        Ok(WKS {
+            domain: header.domain,
+            class: header.class,
+            ttl: header.ttl,
+            address,
+            protocol,
+            ports,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use crate::byte_packet_buffer::BytePacketBuffer;
+    use crate::packet::Class;
+    use crate::records::wks::PROTOCOL_TCP;
+    use crate::records::WKS;
+    use crate::ser::Serialize;
+
+    fn wks() -> WKS {
+        WKS {
+            domain: "x".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            address: Ipv4Addr::new(10, 0, 0, 1),
+            protocol: PROTOCOL_TCP,
+            ports: vec![21, 25],
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        let mut serializer = BytePacketBuffer::new();
+
+        let res = wks().serialize(&mut serializer);
+        assert!(res.is_ok());
+
+        assert_eq!(&[
+            0x01, 0x78, // len=1 label="x"
+            0x00,
+            0x00, 0x0B, // Type WKS.
+            0x00, 0x01, // Class IN.
+            0x00, 0x00, 0x00, 0x3C, // TTL.
+            0x00, 0x09, // RD length.
+            0x0A, 0x00, 0x00, 0x01, // Address.
+            0x06, // Protocol TCP.
+            0x00, 0x00, 0x04, 0x40, // Ports 21 and 25.
+        ], serializer.bytes().as_slice());
+    }
+
+    #[test]
+    fn from_rdata() {
+        let record = WKS::from_rdata("x", Class::Internet, Duration::from_secs(60), &[0x0A, 0x00, 0x00, 0x01, 0x06, 0x00, 0x00, 0x04, 0x40]).unwrap();
+        assert_eq!(wks(), record);
+        assert!(record.has_port(25));
+        assert!(!record.has_port(80));
+
+        assert!(WKS::from_rdata("x", Class::Internet, Duration::from_secs(60), &[0x0A, 0x00, 0x00, 0x01]).is_err());
+    }
+
+    #[test]
+    fn presentation() {
+        let text = wks().to_string();
+        assert_eq!("x. 60 IN WKS 10.0.0.1 6 21 25", text);
+        assert_eq!(wks(), text.parse().unwrap());
+        assert_eq!(wks(), "x. 60 IN WKS 10.0.0.1 tcp 25 21 25".parse().unwrap());
+    }
+}
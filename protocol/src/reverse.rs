@@ -0,0 +1,193 @@
+//! Conversions between IP addresses and their reverse lookup names
+//! (`in-addr.arpa` for IPv4 as of RFC 1035 section 3.5 and `ip6.arpa`
+//! for IPv6 as of RFC 3596 section 2.5).
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+use crate::errors::Error::InvalidData;
+use crate::packet::Class;
+use crate::records::PTR;
+use crate::result::Result;
+
+const IPV4_SUFFIX: &str = "in-addr.arpa";
+const IPV6_SUFFIX: &str = "ip6.arpa";
+
+/// Reverse lookup name of an address (e.g. `1.0.0.127.in-addr.arpa`).
+pub fn reverse_name(addr: IpAddr) -> String {
+    match addr {
+        IpAddr::V4(addr) => ipv4_reverse_name(addr),
+        IpAddr::V6(addr) => ipv6_reverse_name(addr),
+    }
+}
+
+/// Reverse lookup name of an IPv4 address, octets in reverse order.
+pub fn ipv4_reverse_name(addr: Ipv4Addr) -> String {
+    let [a, b, c, d] = addr.octets();
+    format!("{}.{}.{}.{}.{}", d, c, b, a, IPV4_SUFFIX)
+}
+
+/// Reverse lookup name of an IPv6 address, nibbles in reverse order.
+pub fn ipv6_reverse_name(addr: Ipv6Addr) -> String {
+    let mut name = String::with_capacity(72);
+    for byte in addr.octets().iter().rev() {
+        name.push_str(&format!("{:x}.{:x}.", byte & 0x0F, byte >> 4));
+    }
+
+    name.push_str(IPV6_SUFFIX);
+    name
+}
+
+/// Address corresponding to a complete reverse lookup name.
+///
+/// Names that don't have the right suffix or number of labels (like
+/// the names of reverse zones) don't correspond to an address.
+pub fn parse_reverse_name(name: &str) -> Option<IpAddr> {
+    let name = name.strip_suffix('.').unwrap_or(name).to_lowercase();
+
+    if let Some(labels) = name.strip_suffix(IPV4_SUFFIX).and_then(|n| n.strip_suffix('.')) {
+        let octets = labels
+            .split('.')
+            .map(|label| match label {
+                // Leading zeroes are not valid in a reverse name.
+                "0" => Some(0),
+                _ if label.starts_with('0') => None,
+                _ => label.parse::<u8>().ok(),
+            })
+            .collect::<Option<Vec<u8>>>()?;
+
+        return match octets[..] {
+            [d, c, b, a] => Some(IpAddr::V4(Ipv4Addr::new(a, b, c, d))),
+            _ => None,
+        };
+    }
+
+    if let Some(labels) = name.strip_suffix(IPV6_SUFFIX).and_then(|n| n.strip_suffix('.')) {
+        let nibbles = labels
+            .split('.')
+            .map(|label| match label.len() {
+                1 => u8::from_str_radix(label, 16).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()?;
+
+        if nibbles.len() != 32 {
+            return None;
+        }
+
+        let mut octets = [0u8; 16];
+        for (i, pair) in nibbles.chunks(2).rev().enumerate() {
+            octets[i] = (pair[1] << 4) | pair[0];
+        }
+
+        return Some(IpAddr::V6(Ipv6Addr::from(octets)));
+    }
+
+    None
+}
+
+/// Name of the reverse zone delegated for a subnet.
+///
+/// Reverse zones are delegated on label boundaries, so the prefix length
+/// must be a multiple of 8 for IPv4 and of 4 for IPv6.
+pub fn reverse_zone_name(network: IpAddr, prefix_len: u8) -> Result<String> {
+    let name = reverse_name(network);
+
+    let (label_bits, total_labels, max_prefix) = match network {
+        IpAddr::V4(_) => (8, 4, 32),
+        IpAddr::V6(_) => (4, 32, 128),
+    };
+
+    if prefix_len > max_prefix || !prefix_len.is_multiple_of(label_bits) {
+        return Err(InvalidData(format!("prefix /{} is not on a reverse zone boundary", prefix_len)));
+    }
+
+    // Skipping the host labels of the reverse name.
+    let host_labels = total_labels - (prefix_len / label_bits) as usize;
+    Ok(name.splitn(host_labels + 1, '.').last().unwrap_or_default().to_string())
+}
+
+/// Synthesize the PTR records of every address in a subnet, naming each
+/// host with the given function.
+///
+/// Records are generated lazily since IPv6 subnets are usually way too
+/// large to be materialized.
+pub fn synthesize_reverse_zone<F>(network: IpAddr, prefix_len: u8, ttl: Duration, hostname: F) -> Result<impl Iterator<Item=PTR>>
+    where F: Fn(IpAddr) -> String
+{
+    let (first, last, to_addr): (u128, u128, fn(u128) -> IpAddr) = match network {
+        IpAddr::V4(addr) if prefix_len <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+            let first = u32::from(addr) & mask;
+            (first as u128, (first | !mask) as u128, |n| IpAddr::V4(Ipv4Addr::from(n as u32)))
+        }
+        IpAddr::V6(addr) if prefix_len <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+            let first = u128::from(addr) & mask;
+            (first, first | !mask, |n| IpAddr::V6(Ipv6Addr::from(n)))
+        }
+        _ => return Err(InvalidData(format!("invalid prefix length /{}", prefix_len))),
+    };
+
+    Ok((first..=last).map(move |n| {
+        let addr = to_addr(n);
+        PTR {
+            domain: reverse_name(addr),
+            class: Class::Internet,
+            ttl,
+            ptr_name: hostname(addr),
+        }
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::time::Duration;
+
+    use crate::reverse::{parse_reverse_name, reverse_name, reverse_zone_name, synthesize_reverse_zone};
+
+    #[test]
+    fn ipv4_round_trip() {
+        let addr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 10));
+        assert_eq!("10.2.0.192.in-addr.arpa", reverse_name(addr));
+        assert_eq!(Some(addr), parse_reverse_name("10.2.0.192.in-addr.arpa."));
+    }
+
+    #[test]
+    fn ipv6_round_trip() {
+        let addr = IpAddr::V6("2001:db8::567:89ab".parse::<Ipv6Addr>().unwrap());
+        let name = "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa";
+        assert_eq!(name, reverse_name(addr));
+        assert_eq!(Some(addr), parse_reverse_name(name));
+        assert_eq!(Some(addr), parse_reverse_name(&name.to_uppercase()));
+    }
+
+    #[test]
+    fn parse_invalid_names() {
+        assert_eq!(None, parse_reverse_name("2.0.192.in-addr.arpa"));
+        assert_eq!(None, parse_reverse_name("10.02.0.192.in-addr.arpa"));
+        assert_eq!(None, parse_reverse_name("256.2.0.192.in-addr.arpa"));
+        assert_eq!(None, parse_reverse_name("8.b.d.0.1.0.0.2.ip6.arpa"));
+        assert_eq!(None, parse_reverse_name("www.google.com"));
+    }
+
+    #[test]
+    fn zone_name() {
+        assert_eq!("2.0.192.in-addr.arpa", reverse_zone_name("192.0.2.0".parse().unwrap(), 24).unwrap());
+        assert_eq!("8.b.d.0.1.0.0.2.ip6.arpa", reverse_zone_name("2001:db8::".parse().unwrap(), 32).unwrap());
+        assert!(reverse_zone_name("192.0.2.0".parse().unwrap(), 25).is_err());
+    }
+
+    #[test]
+    fn synthesize_zone() {
+        let records: Vec<_> = synthesize_reverse_zone("192.0.2.17".parse().unwrap(), 30, Duration::from_secs(60), |addr| {
+            format!("host-{}.example.com", addr.to_string().replace('.', "-"))
+        }).unwrap().collect();
+
+        assert_eq!(4, records.len());
+        assert_eq!("16.2.0.192.in-addr.arpa", records[0].domain);
+        assert_eq!("host-192-0-2-16.example.com", records[0].ptr_name);
+        assert_eq!("19.2.0.192.in-addr.arpa", records[3].domain);
+    }
+}
@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use crate::errors::Error::InvalidData;
+use crate::packet::{Class, QueryType, Record};
+use crate::result::Result;
+
+/// Records sharing the same owner, type and class (RFC 2181, section 5).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RRset {
+    pub domain: String,
+    pub qtype: QueryType,
+    pub class: Class,
+    records: Vec<Record>,
+}
+
+impl RRset {
+    pub fn new(record: Record) -> RRset {
+        RRset {
+            domain: record.domain().to_string(),
+            qtype: record.qtype(),
+            class: record.class(),
+            records: vec![record],
+        }
+    }
+
+    /// Group records into RRsets, in order of first appearance.
+    ///
+    /// OPT, TSIG and SIG(0) pseudo-records are skipped as they aren't part
+    /// of any RRset.
+    pub fn group<I>(records: I) -> Vec<RRset>
+        where I: IntoIterator<Item=Record>
+    {
+        let mut rrsets: Vec<RRset> = vec![];
+        for record in records.into_iter().filter(|record| !matches!(record, Record::Opt(_) | Record::TransactionSignature(_) | Record::SecuritySignature(_))) {
+            match rrsets.iter_mut().find(|rrset| rrset.contains_type_of(&record)) {
+                Some(rrset) => rrset.records.push(record),
+                None => rrsets.push(RRset::new(record)),
+            }
+        }
+
+        rrsets
+    }
+
+    /// Whether the record belongs to this RRset, owner names being
+    /// compared case-insensitively.
+    pub fn contains_type_of(&self, record: &Record) -> bool {
+        self.qtype == record.qtype()
+            && self.class == record.class()
+            && self.domain.eq_ignore_ascii_case(record.domain())
+    }
+
+    /// Add a record, which must belong to this RRset.
+    pub fn push(&mut self, record: Record) -> Result<()> {
+        if !self.contains_type_of(&record) {
+            return Err(InvalidData(format!(
+                "{} {} {} record doesn't belong to the {} {} {} rrset",
+                record.domain(), record.class(), record.qtype(), self.domain, self.class, self.qtype,
+            )));
+        }
+
+        self.records.push(record);
+        Ok(())
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    pub fn into_records(self) -> Vec<Record> {
+        self.records
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// TTL of the RRset, the lowest one of its records.
+    pub fn ttl(&self) -> Duration {
+        self.records.iter().map(Record::ttl).min().unwrap_or_default()
+    }
+
+    /// Whether every record has the same TTL, which is required since
+    /// RFC 2181 but not always followed.
+    pub fn has_consistent_ttl(&self) -> bool {
+        let ttl = self.ttl();
+        self.records.iter().all(|record| record.ttl() == ttl)
+    }
+
+    /// Set the TTL of every record to the lowest one, as recommended
+    /// by RFC 2181 for inconsistent RRsets.
+    pub fn normalize_ttl(&mut self) {
+        let ttl = self.ttl();
+        for record in &mut self.records {
+            record.set_ttl(ttl);
+        }
+    }
+
+    /// Remove duplicated records, which only differ by their TTL if any,
+    /// keeping the first occurrence.
+    pub fn dedup(&mut self) {
+        dedup(&mut self.records);
+    }
+}
+
+impl IntoIterator for RRset {
+    type Item = Record;
+    type IntoIter = std::vec::IntoIter<Record>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.into_iter()
+    }
+}
+
+// Remove duplicated records, keeping the first occurrence.
+pub(crate) fn dedup(records: &mut Vec<Record>) {
+    let mut unique: Vec<Record> = Vec::with_capacity(records.len());
+    for record in records.drain(..) {
+        if !unique.iter().any(|kept| is_duplicate(kept, &record)) {
+            unique.push(record);
+        }
+    }
+
+    *records = unique;
+}
+
+// Whether two records have the same owner, type, class and data.
+fn is_duplicate(record: &Record, other: &Record) -> bool {
+    if record.ttl() == other.ttl() {
+        return record == other;
+    }
+
+    let mut other = other.clone();
+    other.set_ttl(record.ttl());
+    *record == other
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use crate::packet::{Class, QueryType, Record};
+    use crate::records::{A, CName, OPT};
+    use crate::rrset::RRset;
+
+    fn a(domain: &str, ttl: u64, ip: [u8; 4]) -> Record {
+        Record::A(A {
+            domain: domain.to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(ttl),
+            ip: Ipv4Addr::from(ip),
+        })
+    }
+
+    fn cname(domain: &str, alias: &str) -> Record {
+        Record::CanonicalName(CName {
+            domain: domain.to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(60),
+            alias: alias.to_string(),
+        })
+    }
+
+    #[test]
+    fn group_records() {
+        let rrsets = RRset::group(vec![
+            cname("www.example.com", "example.com"),
+            a("example.com", 60, [127, 0, 0, 1]),
+            Record::Opt(OPT::new(512)),
+            a("Example.COM", 60, [127, 0, 0, 2]),
+            a("other.example.com", 60, [127, 0, 0, 3]),
+        ]);
+
+        assert_eq!(3, rrsets.len());
+        assert_eq!(QueryType::CanonicalName, rrsets[0].qtype);
+        assert_eq!("example.com", rrsets[1].domain);
+        assert_eq!(QueryType::A, rrsets[1].qtype);
+        assert_eq!(Class::Internet, rrsets[1].class);
+        assert_eq!(2, rrsets[1].len());
+        assert_eq!("other.example.com", rrsets[2].domain);
+    }
+
+    #[test]
+    fn push_record() {
+        let mut rrset = RRset::new(a("example.com", 60, [127, 0, 0, 1]));
+        assert!(rrset.push(a("example.com", 60, [127, 0, 0, 2])).is_ok());
+        assert!(rrset.push(cname("example.com", "other.example.com")).is_err());
+        assert_eq!(2, rrset.len());
+    }
+
+    #[test]
+    fn normalize_inconsistent_ttl() {
+        let mut rrset = RRset::group(vec![
+            a("example.com", 300, [127, 0, 0, 1]),
+            a("example.com", 60, [127, 0, 0, 2]),
+        ]).remove(0);
+
+        assert!(!rrset.has_consistent_ttl());
+        assert_eq!(Duration::from_secs(60), rrset.ttl());
+
+        rrset.normalize_ttl();
+        assert!(rrset.has_consistent_ttl());
+        assert!(rrset.records().iter().all(|record| record.ttl() == Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn dedup_records() {
+        let mut rrset = RRset::group(vec![
+            a("example.com", 300, [127, 0, 0, 1]),
+            a("example.com", 60, [127, 0, 0, 2]),
+            a("example.com", 60, [127, 0, 0, 1]),
+            a("example.com", 300, [127, 0, 0, 2]),
+        ]).remove(0);
+
+        rrset.dedup();
+        assert_eq!(vec![
+            a("example.com", 300, [127, 0, 0, 1]),
+            a("example.com", 60, [127, 0, 0, 2]),
+        ], rrset.into_records());
+    }
+}
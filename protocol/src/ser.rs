@@ -6,6 +6,14 @@ pub trait Serializer {
     fn serialize_u16(&mut self, value: u16) -> Result<()>;
     fn serialize_u32(&mut self, value: u32) -> Result<()>;
     fn serialize_qname(&mut self, qname: &str) -> Result<()>;
+
+    fn serialize_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        for byte in bytes {
+            self.serialize_u8(*byte)?;
+        }
+
+        Ok(())
+    }
 }
 
 pub trait Serialize {
@@ -0,0 +1,254 @@
+//! SIG(0) transaction signatures (RFC 2931), authenticating whole messages
+//! with the private key of a KEY record rather than a shared secret, such
+//! as dynamic updates signed by clients. The algorithms are the DNSSEC ones.
+
+use std::time::Duration;
+
+use crate::crypto;
+use crate::crypto::SigningKey;
+use crate::errors::Error::{InvalidData, InvalidPresentation, InvalidSignature};
+use crate::packet::{Class, QueryType, Record};
+use crate::records::{KEY, RRSIG, SIG};
+use crate::result::Result;
+use crate::tsig::{append, ARCOUNT};
+use crate::view::MessageView;
+
+/// Signatures are valid this many seconds before and after the time they
+/// are made, to allow for clock differences.
+pub const VALIDITY: u32 = 300;
+
+// Flags of KEY records which can't be used to verify signatures (RFC 2535
+// section 3.1.2).
+const NO_KEY: u16 = 0xC000;
+
+/// KEY record of the signer along with its private key.
+#[derive(Debug)]
+pub struct Sig0Key {
+    pub key: KEY,
+    signing_key: SigningKey,
+}
+
+impl Sig0Key {
+    pub fn new(key: KEY, signing_key: SigningKey) -> Result<Sig0Key> {
+        if key.0.algorithm != signing_key.algorithm() || key.0.public_key != signing_key.public_key() {
+            return Err(InvalidData(format!("private key doesn't match the KEY {} of {}", key.0.key_tag(), key.0.domain)));
+        }
+
+        Ok(Sig0Key { key, signing_key })
+    }
+
+    /// Load a key from the content of BIND key files, the `.key` file
+    /// holding the KEY record, possibly after comments.
+    pub fn parse(key_file: &str, private_file: &str) -> Result<Sig0Key> {
+        let key: KEY = key_file
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with(';'))
+            .ok_or_else(|| InvalidPresentation("key file has no KEY record".to_string()))?
+            .parse()?;
+
+        let signing_key = SigningKey::from_private_key(&key.0.public_key, private_file)?;
+        Sig0Key::new(key, signing_key)
+    }
+
+    /// Add a SIG(0) record at the end of a message, the signature of a
+    /// response also covering the request it answers.
+    pub fn sign(&self, message: &[u8], request: Option<&[u8]>, now: u32) -> Result<Vec<u8>> {
+        let mut sig = SIG(RRSIG {
+            domain: String::new(),
+            class: Class::Any,
+            ttl: Duration::from_secs(0),
+            type_covered: QueryType::Unknown(0),
+            algorithm: self.key.0.algorithm,
+            labels: 0,
+            original_ttl: Duration::from_secs(0),
+            expiration: now.wrapping_add(VALIDITY),
+            inception: now.wrapping_sub(VALIDITY),
+            key_tag: self.key.0.key_tag(),
+            signer_name: self.key.0.domain.clone(),
+            signature: vec![],
+        });
+
+        sig.0.signature = self.signing_key.sign(&signed_data(&sig, request, message))?;
+        append(message, &sig)
+    }
+}
+
+/// SIG(0) record of a message along with its position, when it is signed.
+pub fn find(message: &[u8]) -> Result<Option<(SIG, usize)>> {
+    match MessageView::new(message)?.signature(QueryType::SecuritySignature)? {
+        None => Ok(None),
+        Some(view) => match view.to_record()? {
+            Record::SecuritySignature(sig) => Ok(Some((sig, view.position()))),
+            _ => Err(InvalidData("SIG record is malformed".to_string())),
+        },
+    }
+}
+
+/// Verify the SIG(0) record ending a message with one of the KEY records of
+/// its signer, returning the verified signature. Responses are verified
+/// along with the request they answer.
+pub fn verify(message: &[u8], request: Option<&[u8]>, keys: &[KEY], now: u32) -> Result<SIG> {
+    let (sig, position) = find(message)?.ok_or_else(|| InvalidSignature("message has no SIG(0) record".to_string()))?;
+
+    if sig.0.type_covered != QueryType::Unknown(0) {
+        return Err(InvalidSignature(format!("SIG record covers {} records rather than the message", sig.0.type_covered)));
+    }
+
+    // Timestamps are compared using serial number arithmetic (RFC 1982).
+    if (now.wrapping_sub(sig.0.inception) as i32) < 0 {
+        return Err(InvalidSignature(format!("SIG(0) of {} isn't valid yet", sig.0.signer_name)));
+    }
+
+    if (sig.0.expiration.wrapping_sub(now) as i32) < 0 {
+        return Err(InvalidSignature(format!("SIG(0) of {} has expired", sig.0.signer_name)));
+    }
+
+    // The message is verified as it was before the SIG(0) record was added.
+    let mut unsigned = message[..position].to_vec();
+    let count = u16::from_be_bytes([unsigned[ARCOUNT], unsigned[ARCOUNT + 1]]) - 1;
+    unsigned[ARCOUNT..ARCOUNT + 2].copy_from_slice(&count.to_be_bytes());
+    let data = signed_data(&sig, request, &unsigned);
+
+    let mut last_error = None;
+    let candidates = keys.iter().filter(|key| {
+        key.0.domain.eq_ignore_ascii_case(&sig.0.signer_name)
+            && key.0.key_tag() == sig.0.key_tag
+            && key.0.algorithm == sig.0.algorithm
+            && key.0.flags & NO_KEY != NO_KEY
+    });
+
+    for key in candidates {
+        match crypto::verify(key.0.algorithm, &key.0.public_key, &data, &sig.0.signature) {
+            Ok(()) => return Ok(sig),
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| InvalidSignature(format!("no KEY of {} matches the SIG(0) record", sig.0.signer_name))))
+}
+
+// Data covered by the signature (RFC 2931 section 3.1): the SIG record data
+// without the signature, the whole request for responses, then the message
+// without the SIG record.
+fn signed_data(sig: &SIG, request: Option<&[u8]>, message: &[u8]) -> Vec<u8> {
+    let mut data = sig.0.rdata_without_signature();
+    if let Some(request) = request {
+        data.extend_from_slice(request);
+    }
+
+    data.extend_from_slice(message);
+    data
+}
+
+#[cfg(test)]
+mod test {
+    use crate::growable_buffer::GrowableBuffer;
+    use crate::header::ResultCode;
+    use crate::packet::{Class, Packet, QueryType};
+    use crate::records::KEY;
+    use crate::ser::Serialize;
+    use crate::sig0::{find, verify, Sig0Key, VALIDITY};
+    use crate::view::MessageView;
+
+    // Key of RFC 8080 section 6.1, as the KEY record of a host.
+    const KEY_FILE: &str = "; This is a zone key, for example.com.\n\
+        example.com. 3600 IN KEY 512 3 15 l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=";
+    const PRIVATE_FILE: &str = "Private-key-format: v1.2\n\
+        Algorithm: 15 (ED25519)\n\
+        PrivateKey: ODIyNjAzODQ2MjgwODAxMjI2NDUxOTAyMDQxNDIyNjI=\n";
+
+    const NOW: u32 = 1700000000;
+
+    fn key() -> Sig0Key {
+        Sig0Key::parse(KEY_FILE, PRIVATE_FILE).unwrap()
+    }
+
+    fn other_key() -> KEY {
+        "example.com. 3600 IN KEY 512 3 15 zPnZ/QwEe7S8C5SPz2OfS5RR40ATk2/rYnE9xHIEijs=".parse().unwrap()
+    }
+
+    fn wire(packet: &Packet) -> Vec<u8> {
+        let mut serializer = GrowableBuffer::default();
+        packet.serialize(&mut serializer).unwrap();
+        serializer.into_inner()
+    }
+
+    fn request() -> Vec<u8> {
+        wire(&Packet::query("example.com", QueryType::StartOfAuthority).id(0x1234).build())
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let key = key();
+        let signed = key.sign(&request(), None, NOW).unwrap();
+
+        let view = MessageView::new(&signed).unwrap();
+        assert_eq!(1, view.header().total_additional_records);
+        let (sig, position) = find(&signed).unwrap().unwrap();
+        assert_eq!(request().len(), position);
+        assert_eq!("", sig.0.domain);
+        assert_eq!(Class::Any, sig.0.class);
+        assert_eq!(QueryType::Unknown(0), sig.0.type_covered);
+        assert_eq!(key.key.0.key_tag(), sig.0.key_tag);
+        assert_eq!("example.com", sig.0.signer_name);
+        assert_eq!(NOW - VALIDITY, sig.0.inception);
+        assert_eq!(64, sig.0.signature.len());
+
+        let packet = view.to_packet().unwrap();
+        assert_eq!(Some(&sig), packet.sig0());
+        assert!(packet.additional_rrsets().is_empty());
+
+        let keys = [key.key];
+        assert_eq!(sig, verify(&signed, None, &keys, NOW).unwrap());
+        assert!(verify(&request(), None, &keys, NOW).is_err());
+
+        // Any change to the message breaks the signature.
+        let mut tampered = signed.clone();
+        tampered[1] ^= 1;
+        assert!(verify(&tampered, None, &keys, NOW).is_err());
+    }
+
+    #[test]
+    fn response() {
+        let key = key();
+        let request = key.sign(&request(), None, NOW).unwrap();
+        let response = MessageView::new(&request).unwrap().to_packet().unwrap().reply().rcode(ResultCode::Refused).build();
+        let signed = key.sign(&wire(&response), Some(&request), NOW).unwrap();
+
+        let keys = [key.key];
+        assert!(verify(&signed, Some(&request), &keys, NOW).is_ok());
+        assert!(verify(&signed, None, &keys, NOW).is_err());
+        assert!(verify(&signed, Some(&self::request()), &keys, NOW).is_err());
+    }
+
+    #[test]
+    fn keys() {
+        let key = key();
+        let signed = key.sign(&request(), None, NOW).unwrap();
+
+        assert!(verify(&signed, None, &[other_key()], NOW).is_err());
+        assert!(verify(&signed, None, &[other_key(), key.key.clone()], NOW).is_ok());
+
+        let mut renamed = key.key.clone();
+        renamed.0.domain = "example.org".to_string();
+        assert!(verify(&signed, None, &[renamed], NOW).is_err());
+
+        let mut no_key = key.key.clone();
+        no_key.0.flags |= 0xC000;
+        assert!(verify(&signed, None, &[no_key], NOW).is_err());
+
+        assert!(Sig0Key::new(other_key(), key.signing_key).is_err());
+    }
+
+    #[test]
+    fn validity() {
+        let key = key();
+        let signed = key.sign(&request(), None, NOW).unwrap();
+        let keys = [key.key];
+
+        assert!(verify(&signed, None, &keys, NOW + VALIDITY).is_ok());
+        assert!(verify(&signed, None, &keys, NOW + VALIDITY + 1).is_err());
+        assert!(verify(&signed, None, &keys, NOW - VALIDITY - 1).is_err());
+    }
+}
@@ -0,0 +1,628 @@
+//! DNSSEC signing of authoritative zones: RRSIG records made with the zone
+//! keys, NSEC or NSEC3 chains, and the DNSKEY, CDS and CDNSKEY records of
+//! the apex (RFC 4035 section 2, RFC 5155 section 7.1, RFC 7344).
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::crypto::SigningKey;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::packet::{Class, QueryType, Record};
+use crate::presentation::{format_base32hex, parse_name};
+use crate::records::dnssec::{canonical_name_cmp, label_count, DigestType, TypeBitmap};
+use crate::records::nsec3::{OPT_OUT, SHA1};
+use crate::records::{CDNSKEY, CDS, DNSKEY, NSEC, NSEC3, NSEC3PARAM, RRSIG};
+use crate::result::Result;
+use crate::rrset::RRset;
+use crate::validation::{create_ds, is_subdomain, labels_of, nsec3_hash};
+
+// Default lifetime of the signatures, and how long before their
+// expiration they are renewed.
+const VALIDITY: Duration = Duration::from_secs(30 * 24 * 3600);
+const REFRESH: Duration = Duration::from_secs(7 * 24 * 3600);
+
+// Signatures are valid from a bit in the past, for validators whose clock
+// is late.
+const INCEPTION_OFFSET: u32 = 3600;
+
+/// DNSKEY record of a zone along with its private key.
+#[derive(Debug)]
+pub struct ZoneKey {
+    pub dnskey: DNSKEY,
+    key: SigningKey,
+}
+
+impl ZoneKey {
+    pub fn new(dnskey: DNSKEY, key: SigningKey) -> Result<ZoneKey> {
+        if dnskey.algorithm != key.algorithm() || dnskey.public_key != key.public_key() {
+            return Err(InvalidData(format!("private key doesn't match the DNSKEY {} of {}", dnskey.key_tag(), dnskey.domain)));
+        }
+
+        Ok(ZoneKey { dnskey, key })
+    }
+
+    /// Load a key from the content of BIND key files: the `.key` file holds
+    /// the DNSKEY record, possibly after comments, and the `.private` file
+    /// the private key.
+    pub fn parse(key_file: &str, private_file: &str) -> Result<ZoneKey> {
+        let dnskey: DNSKEY = key_file
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with(';'))
+            .ok_or_else(|| InvalidPresentation("key file has no DNSKEY record".to_string()))?
+            .parse()?;
+
+        let key = SigningKey::from_private_key(&dnskey.public_key, private_file)?;
+        ZoneKey::new(dnskey, key)
+    }
+
+    /// Key signing keys, flagged as secure entry points, only sign the
+    /// DNSKEY, CDS and CDNSKEY records.
+    pub fn is_key_signing_key(&self) -> bool {
+        self.dnskey.is_secure_entry_point()
+    }
+
+    /// Sign an RRset on behalf of the zone `signer_name`, the signature
+    /// being valid between the inception and expiration times in seconds
+    /// since the epoch.
+    pub fn sign(&self, records: &[Record], signer_name: &str, inception: u32, expiration: u32) -> Result<RRSIG> {
+        let first = records
+            .first()
+            .ok_or_else(|| InvalidData("can't sign an empty rrset".to_string()))?;
+
+        if let Some(record) = records.iter().find(|record| !is_subdomain(record.domain(), signer_name)) {
+            return Err(InvalidData(format!("{} is outside of the {} zone", record.domain(), signer_name)));
+        }
+
+        let mut rrsig = RRSIG {
+            domain: first.domain().to_string(),
+            class: first.class(),
+            ttl: first.ttl(),
+            type_covered: first.qtype(),
+            algorithm: self.dnskey.algorithm,
+            labels: label_count(first.domain()),
+            original_ttl: first.ttl(),
+            expiration,
+            inception,
+            key_tag: self.dnskey.key_tag(),
+            signer_name: signer_name.to_string(),
+            signature: vec![],
+        };
+
+        rrsig.signature = self.key.sign(&rrsig.signed_data(records)?)?;
+        Ok(rrsig)
+    }
+}
+
+/// Records proving the denial of existence of names and types.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DenialChain {
+    Nsec,
+    // Hashed names, with opt-out leaving unsigned delegations out of the
+    // chain (RFC 5155 section 6).
+    Nsec3 {
+        iterations: u16,
+        salt: Vec<u8>,
+        opt_out: bool,
+    },
+}
+
+/// Signs the records of a zone with its keys.
+///
+/// Signatures are valid for 30 days and renewed when they expire within
+/// 7 days by default.
+#[derive(Debug)]
+pub struct ZoneSigner {
+    zone: String,
+    keys: Vec<ZoneKey>,
+    chain: DenialChain,
+    validity: Duration,
+    refresh: Duration,
+}
+
+impl ZoneSigner {
+    pub fn new<S>(zone: S, keys: Vec<ZoneKey>) -> ZoneSigner
+        where S: AsRef<str>
+    {
+        ZoneSigner {
+            zone: parse_name(zone.as_ref()),
+            keys,
+            chain: DenialChain::Nsec,
+            validity: VALIDITY,
+            refresh: REFRESH,
+        }
+    }
+
+    pub fn chain(mut self, chain: DenialChain) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    pub fn validity(mut self, validity: Duration) -> Self {
+        self.validity = validity;
+        self
+    }
+
+    pub fn refresh(mut self, refresh: Duration) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    pub fn zone(&self) -> &str {
+        &self.zone
+    }
+
+    /// Sign the records of the zone, `now` being the current time in
+    /// seconds since the epoch.
+    ///
+    /// DNSSEC records of the given records are dropped and generated again:
+    /// the DNSKEY records of the keys, the CDS and CDNSKEY records of the
+    /// key signing keys, the denial chain and the signatures. Records
+    /// below delegations are glue, they are neither signed nor part of
+    /// the chain. Each RRset is followed by its signatures.
+    pub fn sign_zone(&self, records: Vec<Record>, now: u32) -> Result<Vec<Record>> {
+        if self.keys.is_empty() {
+            return Err(InvalidData(format!("no key to sign the {} zone", self.zone)));
+        }
+
+        let mut records: Vec<Record> = records.into_iter().filter(|record| !is_generated(record.qtype())).collect();
+        if let Some(record) = records.iter().find(|record| !is_subdomain(record.domain(), &self.zone)) {
+            return Err(InvalidData(format!("{} is outside of the {} zone", record.domain(), self.zone)));
+        }
+
+        let soa = records
+            .iter()
+            .find_map(|record| match record {
+                Record::StartOfAuthority(soa) if soa.domain.eq_ignore_ascii_case(&self.zone) => Some(soa.clone()),
+                _ => None,
+            })
+            .ok_or_else(|| InvalidData(format!("the {} zone has no SOA record", self.zone)))?;
+
+        for key in &self.keys {
+            let mut dnskey = key.dnskey.clone();
+            dnskey.domain = self.zone.clone();
+            dnskey.class = soa.class;
+            dnskey.ttl = soa.ttl;
+
+            if key.is_key_signing_key() && !dnskey.is_revoked() {
+                records.push(Record::ChildDelegationSigner(CDS(create_ds(&dnskey, DigestType::Sha256)?)));
+                records.push(Record::ChildDnsKey(CDNSKEY(dnskey.clone())));
+            }
+
+            records.push(Record::DnsKey(dnskey));
+        }
+
+        let delegations: Vec<String> = records
+            .iter()
+            .filter(|record| record.qtype() == QueryType::AuthoritativeNameServer && !record.domain().eq_ignore_ascii_case(&self.zone))
+            .map(|record| record.domain().to_lowercase())
+            .collect();
+
+        let zone = Zone {
+            apex: &self.zone,
+            delegations: &delegations,
+        };
+
+        // Types of the authoritative names, and whether they hold signed
+        // RRsets.
+        let mut names: HashMap<String, (TypeBitmap, bool)> = HashMap::new();
+        for record in records.iter().filter(|record| !zone.is_glue(record)) {
+            let (types, signed) = names.entry(record.domain().to_lowercase()).or_default();
+            types.insert(record.qtype());
+            *signed |= zone.is_signed(record);
+        }
+
+        let chain = match &self.chain {
+            DenialChain::Nsec => self.nsec_chain(names, soa.class, soa.negative_ttl()),
+            DenialChain::Nsec3 { iterations, salt, opt_out } => {
+                let param = NSEC3PARAM {
+                    domain: self.zone.clone(),
+                    class: soa.class,
+                    ttl: soa.negative_ttl(),
+                    hash_algorithm: SHA1,
+                    flags: 0,
+                    iterations: *iterations,
+                    salt: salt.clone(),
+                };
+
+                self.nsec3_chain(names, &zone, param, *opt_out)
+            }
+        };
+        records.extend(chain);
+
+        let mut signed = vec![];
+        for rrset in RRset::group(records) {
+            let records = rrset.into_records();
+            let rrsigs = match zone.is_signed(&records[0]) {
+                true => self.sign_rrset(&records, now)?,
+                false => vec![],
+            };
+
+            signed.extend(records);
+            signed.extend(rrsigs.into_iter().map(Record::Signature));
+        }
+
+        Ok(signed)
+    }
+
+    /// Sign an RRset of the zone with the keys meant for its type.
+    pub fn sign_rrset(&self, records: &[Record], now: u32) -> Result<Vec<RRSIG>> {
+        let qtype = match records.first() {
+            Some(record) => record.qtype(),
+            None => return Err(InvalidData("can't sign an empty rrset".to_string())),
+        };
+
+        let inception = now.wrapping_sub(INCEPTION_OFFSET);
+        let expiration = now.wrapping_add(self.validity.as_secs() as u32);
+
+        self.signing_keys(qtype)
+            .into_iter()
+            .map(|key| key.sign(records, &self.zone, inception, expiration))
+            .collect()
+    }
+
+    /// Whether the zone must be signed again, its records having no
+    /// signature or some expiring within the refresh period.
+    pub fn needs_refresh(&self, records: &[Record], now: u32) -> bool {
+        let mut rrsigs = records
+            .iter()
+            .filter_map(|record| match record {
+                Record::Signature(rrsig) => Some(rrsig),
+                _ => None,
+            })
+            .peekable();
+
+        rrsigs.peek().is_none() || rrsigs.any(|rrsig| (rrsig.expiration.wrapping_sub(now) as i32) < self.refresh.as_secs() as i32)
+    }
+
+    // Key signing keys sign the keys of the zone, the other keys the rest
+    // of the zone. Keys of either kind sign everything when they are the
+    // only ones, and revoked keys only sign the DNSKEY records to publish
+    // their revocation (RFC 5011 section 2.1).
+    fn signing_keys(&self, qtype: QueryType) -> Vec<&ZoneKey> {
+        let for_keys = matches!(qtype, QueryType::DnsKey | QueryType::ChildDelegationSigner | QueryType::ChildDnsKey);
+        let usable: Vec<&ZoneKey> = self
+            .keys
+            .iter()
+            .filter(|key| !key.dnskey.is_revoked() || qtype == QueryType::DnsKey)
+            .collect();
+
+        let preferred: Vec<&ZoneKey> = usable.iter().copied().filter(|key| key.is_key_signing_key() == for_keys).collect();
+        match preferred.is_empty() {
+            true => usable,
+            false => preferred,
+        }
+    }
+
+    fn nsec_chain(&self, names: HashMap<String, (TypeBitmap, bool)>, class: Class, ttl: Duration) -> Vec<Record> {
+        let mut names: Vec<(String, TypeBitmap)> = names.into_iter().map(|(name, (types, _))| (name, types)).collect();
+        names.sort_by(|(name, _), (other, _)| canonical_name_cmp(name, other));
+
+        (0..names.len())
+            .map(|i| {
+                let (domain, mut types) = names[i].clone();
+                types.insert(QueryType::Signature);
+                types.insert(QueryType::NextSecure);
+
+                Record::NextSecure(NSEC {
+                    domain,
+                    class,
+                    ttl,
+                    next_domain: names[(i + 1) % names.len()].0.clone(),
+                    types,
+                })
+            })
+            .collect()
+    }
+
+    // Chain hashed with the parameters of the NSEC3PARAM record, which is
+    // added to the apex.
+    fn nsec3_chain(&self, mut names: HashMap<String, (TypeBitmap, bool)>, zone: &Zone, param: NSEC3PARAM, opt_out: bool) -> Vec<Record> {
+        if let Some((types, _)) = names.get_mut(&self.zone) {
+            types.insert(QueryType::Nsec3Parameters);
+        }
+
+        // Unsigned delegations may be left out of opt-out chains.
+        if opt_out {
+            names.retain(|name, (_, signed)| *signed || !zone.is_delegation(name));
+        }
+
+        // Empty non-terminals, the ancestors of names holding no record,
+        // are part of the chain (RFC 5155 section 7.1).
+        let zone_labels = labels_of(&self.zone).len();
+        let ancestors: Vec<String> = names
+            .keys()
+            .flat_map(|name| {
+                let labels = labels_of(name);
+                (1..labels.len().saturating_sub(zone_labels))
+                    .map(|i| labels[i..].join("."))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        for ancestor in ancestors {
+            names.entry(ancestor).or_default();
+        }
+
+        let mut hashes: Vec<(Vec<u8>, TypeBitmap)> = names
+            .into_iter()
+            .map(|(name, (mut types, signed))| {
+                if signed {
+                    types.insert(QueryType::Signature);
+                }
+
+                (nsec3_hash(&name, &param.salt, param.iterations), types)
+            })
+            .collect();
+        hashes.sort_by(|(hash, _), (other, _)| hash.cmp(other));
+
+        let mut chain: Vec<Record> = (0..hashes.len())
+            .map(|i| Record::NextSecure3(NSEC3 {
+                domain: format!("{}.{}", format_base32hex(&hashes[i].0).to_lowercase(), self.zone),
+                class: param.class,
+                ttl: param.ttl,
+                hash_algorithm: param.hash_algorithm,
+                flags: if opt_out { OPT_OUT } else { 0 },
+                iterations: param.iterations,
+                salt: param.salt.clone(),
+                next_hashed_owner: hashes[(i + 1) % hashes.len()].0.clone(),
+                types: hashes[i].1.clone(),
+            }))
+            .collect();
+
+        chain.push(Record::Nsec3Parameters(param));
+        chain
+    }
+}
+
+// Types generated when signing a zone.
+fn is_generated(qtype: QueryType) -> bool {
+    matches!(
+        qtype,
+        QueryType::Signature
+            | QueryType::NextSecure
+            | QueryType::NextSecure3
+            | QueryType::Nsec3Parameters
+            | QueryType::DnsKey
+            | QueryType::ChildDelegationSigner
+            | QueryType::ChildDnsKey
+    )
+}
+
+// Apex and delegation points of a zone, which decide what is signed.
+struct Zone<'a> {
+    apex: &'a str,
+    delegations: &'a [String],
+}
+
+impl Zone<'_> {
+    fn is_delegation(&self, name: &str) -> bool {
+        self.delegations.iter().any(|delegation| delegation.eq_ignore_ascii_case(name))
+    }
+
+    // Records below a delegation point, or other than NS, DS and NSEC
+    // records at a delegation point, belong to the child zone.
+    fn is_glue(&self, record: &Record) -> bool {
+        let name = record.domain();
+        if self.is_delegation(name) {
+            return !matches!(record.qtype(), QueryType::AuthoritativeNameServer | QueryType::DelegationSigner | QueryType::NextSecure);
+        }
+
+        self.delegations.iter().any(|delegation| is_subdomain(name, delegation))
+    }
+
+    // The NS records of delegation points are held by the child zone, so
+    // only the parent signs their DS records.
+    fn is_signed(&self, record: &Record) -> bool {
+        !self.is_glue(record)
+            && (record.qtype() != QueryType::AuthoritativeNameServer || record.domain().eq_ignore_ascii_case(self.apex))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use crate::crypto::SigningKey;
+    use crate::packet::{Class, QueryType, Record};
+    use crate::records::dnssec::Algorithm;
+    use crate::records::{AuthoritativeNameServer, MailExchange, A, DNSKEY, RRSIG, SOA};
+    use crate::rrset::RRset;
+    use crate::signing::{DenialChain, ZoneKey, ZoneSigner};
+    use crate::validation::{verify_denial, verify_rrset, Denial};
+
+    // Key of RFC 8080 section 6.1.
+    const KEY_FILE: &str = "; This is a key-signing key, keyid 3613, for example.com.\n\
+        example.com. 3600 IN DNSKEY 257 3 15 l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=";
+    const PRIVATE_FILE: &str = "Private-key-format: v1.2\n\
+        Algorithm: 15 (ED25519)\n\
+        PrivateKey: ODIyNjAzODQ2MjgwODAxMjI2NDUxOTAyMDQxNDIyNjI=\n";
+
+    const NOW: u32 = 1700000000;
+
+    fn ttl() -> Duration {
+        Duration::from_secs(3600)
+    }
+
+    fn a(domain: &str) -> Record {
+        Record::A(A {
+            domain: domain.to_string(),
+            class: Class::Internet,
+            ttl: ttl(),
+            ip: Ipv4Addr::new(192, 0, 2, 1),
+        })
+    }
+
+    fn ns(domain: &str, ns_name: &str) -> Record {
+        Record::AuthoritativeNameServer(AuthoritativeNameServer {
+            domain: domain.to_string(),
+            class: Class::Internet,
+            ttl: ttl(),
+            ns_name: ns_name.to_string(),
+        })
+    }
+
+    fn zone() -> Vec<Record> {
+        let soa: SOA = "example.com. 3600 IN SOA ns.example.com. admin.example.com. 1 7200 3600 1209600 300".parse().unwrap();
+        vec![
+            Record::StartOfAuthority(soa),
+            ns("example.com", "ns.example.com"),
+            a("ns.example.com"),
+            a("www.example.com"),
+            a("deep.empty.example.com"),
+            ns("sub.example.com", "ns.sub.example.com"),
+            a("ns.sub.example.com"),
+        ]
+    }
+
+    // Zone signing key generated on the fly, along with the key signing
+    // key of the RFC.
+    fn keys() -> Vec<ZoneKey> {
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = ring::signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key = SigningKey::from_pkcs8(Algorithm::Ed25519, pkcs8.as_ref()).unwrap();
+        let dnskey = DNSKEY::new("example.com", Class::Internet, ttl(), 256, Algorithm::Ed25519, key.public_key().to_vec());
+
+        vec![ZoneKey::parse(KEY_FILE, PRIVATE_FILE).unwrap(), ZoneKey::new(dnskey, key).unwrap()]
+    }
+
+    fn rrsigs(records: &[Record]) -> Vec<&RRSIG> {
+        records
+            .iter()
+            .filter_map(|record| match record {
+                Record::Signature(rrsig) => Some(rrsig),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Check the signatures of every RRset, returning the signed types.
+    fn verify_zone(records: &[Record]) -> Vec<(String, QueryType)> {
+        let dnskeys: Vec<DNSKEY> = records
+            .iter()
+            .filter_map(|record| match record {
+                Record::DnsKey(dnskey) => Some(dnskey.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let rrsigs = rrsigs(records);
+        let mut signed = vec![];
+        for rrset in RRset::group(records.iter().filter(|record| record.qtype() != QueryType::Signature).cloned()) {
+            let covering: Vec<&RRSIG> = rrsigs
+                .iter()
+                .copied()
+                .filter(|rrsig| rrsig.domain == rrset.domain && rrsig.type_covered == rrset.qtype)
+                .collect();
+
+            if !covering.is_empty() {
+                assert!(verify_rrset(&covering, &dnskeys, rrset.records(), NOW).is_ok(), "{} {}", rrset.domain, rrset.qtype);
+                signed.push((rrset.domain.clone(), rrset.qtype));
+            }
+        }
+
+        signed
+    }
+
+    #[test]
+    fn sign_rfc8080_rrset() {
+        let key = ZoneKey::parse(KEY_FILE, PRIVATE_FILE).unwrap();
+        assert!(key.is_key_signing_key());
+
+        let mx = Record::MailExchange(MailExchange {
+            domain: "example.com".to_string(),
+            class: Class::Internet,
+            ttl: ttl(),
+            preference: 10,
+            exchange: "mail.example.com".to_string(),
+        });
+
+        // Ed25519 signatures are deterministic.
+        let expected: RRSIG = "example.com. 3600 IN RRSIG MX 15 2 3600 1440021600 1438207200 3613 example.com. \
+            oL9krJun7xfBOIWcGHi7mag5/hdZrKWw15jPGrHpjQeRAvTdszaPD+QLs3fx8A4M3e23mRZ9VrbpMngwcrqNAg==".parse().unwrap();
+        assert_eq!(expected, key.sign(&[mx], "example.com", 1438207200, 1440021600).unwrap());
+
+        assert!(key.sign(&[], "example.com", 1438207200, 1440021600).is_err());
+        assert!(key.sign(&[a("example.org")], "example.com", 1438207200, 1440021600).is_err());
+        assert!(ZoneKey::parse(KEY_FILE, "Algorithm: 15 (ED25519)\nPrivateKey: AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=").is_err());
+        assert!(ZoneKey::parse("", PRIVATE_FILE).is_err());
+    }
+
+    #[test]
+    fn sign_nsec_zone() {
+        let signer = ZoneSigner::new("example.com.", keys());
+        let records = signer.sign_zone(zone(), NOW).unwrap();
+        let signed = verify_zone(&records);
+
+        let is_signed = |domain: &str, qtype| signed.contains(&(domain.to_string(), qtype));
+        assert!(is_signed("example.com", QueryType::StartOfAuthority));
+        assert!(is_signed("example.com", QueryType::DnsKey));
+        assert!(is_signed("example.com", QueryType::ChildDelegationSigner));
+        assert!(is_signed("example.com", QueryType::ChildDnsKey));
+        assert!(is_signed("www.example.com", QueryType::A));
+        assert!(is_signed("sub.example.com", QueryType::NextSecure));
+
+        // Delegations and glue are left unsigned.
+        assert!(!is_signed("sub.example.com", QueryType::AuthoritativeNameServer));
+        assert!(!is_signed("ns.sub.example.com", QueryType::A));
+
+        // Key signing keys only sign keys.
+        let rrsigs = rrsigs(&records);
+        assert!(rrsigs.iter().all(|rrsig| (rrsig.key_tag == 3613) == (rrsig.type_covered == QueryType::DnsKey
+            || rrsig.type_covered == QueryType::ChildDelegationSigner
+            || rrsig.type_covered == QueryType::ChildDnsKey)));
+        assert!(rrsigs.iter().all(|rrsig| rrsig.inception < NOW && rrsig.expiration > NOW));
+
+        assert_eq!(Denial::NameError, verify_denial(&records, "missing.example.com", QueryType::A).unwrap());
+        assert_eq!(Denial::NoData, verify_denial(&records, "www.example.com", QueryType::MailExchange).unwrap());
+        assert_eq!(Denial::InsecureDelegation, verify_denial(&records, "sub.example.com", QueryType::DelegationSigner).unwrap());
+        assert!(verify_denial(&records, "ns.sub.example.com", QueryType::A).is_err());
+
+        // Signing again replaces the previous DNSSEC records.
+        assert_eq!(records.len(), signer.sign_zone(records.clone(), NOW).unwrap().len());
+    }
+
+    #[test]
+    fn sign_nsec3_zone() {
+        let chain = DenialChain::Nsec3 { iterations: 0, salt: vec![], opt_out: false };
+        let signer = ZoneSigner::new("example.com", keys()).chain(chain);
+        let records = signer.sign_zone(zone(), NOW).unwrap();
+        let signed = verify_zone(&records);
+
+        assert!(signed.contains(&("example.com".to_string(), QueryType::Nsec3Parameters)));
+        assert!(!records.iter().any(|record| record.qtype() == QueryType::NextSecure));
+
+        assert_eq!(Denial::NameError, verify_denial(&records, "missing.example.com", QueryType::A).unwrap());
+        assert_eq!(Denial::NoData, verify_denial(&records, "empty.example.com", QueryType::A).unwrap());
+        assert_eq!(Denial::NoData, verify_denial(&records, "www.example.com", QueryType::MailExchange).unwrap());
+        assert_eq!(Denial::InsecureDelegation, verify_denial(&records, "sub.example.com", QueryType::DelegationSigner).unwrap());
+
+        // Opt-out chains skip the unsigned delegation.
+        let chain = DenialChain::Nsec3 { iterations: 1, salt: vec![0xAB], opt_out: true };
+        let signer = ZoneSigner::new("example.com", keys()).chain(chain);
+        let records = signer.sign_zone(zone(), NOW).unwrap();
+        verify_zone(&records);
+
+        assert_eq!(5, records.iter().filter(|record| record.qtype() == QueryType::NextSecure3).count());
+        assert_eq!(Denial::InsecureDelegation, verify_denial(&records, "sub.example.com", QueryType::DelegationSigner).unwrap());
+        assert_eq!(Denial::OptOut, verify_denial(&records, "missing.example.com", QueryType::A).unwrap());
+        assert_eq!(Denial::OptOut, verify_denial(&records, "host.sub.example.com", QueryType::A).unwrap());
+    }
+
+    #[test]
+    fn refresh() {
+        let signer = ZoneSigner::new("example.com", keys())
+            .validity(Duration::from_secs(10 * 24 * 3600))
+            .refresh(Duration::from_secs(2 * 24 * 3600));
+
+        assert!(signer.needs_refresh(&zone(), NOW));
+        assert!(signer.sign_zone(vec![a("www.example.com")], NOW).is_err());
+        assert!(signer.sign_zone(vec![a("example.org")], NOW).is_err());
+
+        let records = signer.sign_zone(zone(), NOW).unwrap();
+        assert!(!signer.needs_refresh(&records, NOW));
+        assert!(!signer.needs_refresh(&records, NOW + 7 * 24 * 3600));
+        assert!(signer.needs_refresh(&records, NOW + 9 * 24 * 3600));
+    }
+}
@@ -0,0 +1,580 @@
+//! Transaction signatures (RFC 8945), authenticating whole messages with a
+//! secret shared by both ends, such as zone transfers, NOTIFY and dynamic
+//! updates between servers.
+
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+use ring::hmac;
+
+use crate::errors::Error;
+use crate::errors::Error::{InvalidData, InvalidPresentation};
+use crate::growable_buffer::GrowableBuffer;
+use crate::header::ResultCode;
+use crate::packet::{QueryType, Record};
+use crate::records::tsig::TsigAlgorithm;
+use crate::records::TSIG;
+use crate::result::Result;
+use crate::ser::Serialize;
+use crate::validation::name_wire;
+use crate::view::MessageView;
+
+/// Allowed difference between the time a message is signed and the time it
+/// is verified, in seconds.
+pub const FUDGE: u16 = 300;
+
+// Messages of a multi-message response which may be left unsigned in a row
+// (RFC 8945 section 5.3.1).
+const MAX_UNSIGNED_MESSAGES: usize = 99;
+
+// Position of the additional records count in the header.
+pub(crate) const ARCOUNT: usize = 10;
+
+/// Secret shared with another server, identified by its name and algorithm.
+#[derive(Clone)]
+pub struct TsigKey {
+    name: String,
+    algorithm: TsigAlgorithm,
+    secret: Vec<u8>,
+}
+
+impl TsigKey {
+    pub fn new<S>(name: S, algorithm: TsigAlgorithm, secret: Vec<u8>) -> Result<TsigKey>
+        where S: ToString
+    {
+        if let TsigAlgorithm::Unknown(name) = &algorithm {
+            return Err(InvalidData(format!("unsupported TSIG algorithm {}", name)));
+        }
+
+        if secret.is_empty() {
+            return Err(InvalidData("TSIG secret is empty".to_string()));
+        }
+
+        Ok(TsigKey {
+            name: name.to_string(),
+            algorithm,
+            secret,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn algorithm(&self) -> &TsigAlgorithm {
+        &self.algorithm
+    }
+
+    /// Whether the TSIG record of a message refers to this key.
+    pub fn matches(&self, tsig: &TSIG) -> bool {
+        name_wire(&self.name) == name_wire(&tsig.domain) && self.algorithm == tsig.algorithm
+    }
+
+    /// Start an exchange signed with this key, made of a request and its
+    /// responses.
+    pub fn session(&self) -> TsigSession {
+        TsigSession {
+            key: self.clone(),
+            fudge: FUDGE,
+            mac: None,
+            messages: 0,
+            unsigned: vec![],
+            unsigned_count: 0,
+            bad_time: false,
+        }
+    }
+
+    fn hmac_key(&self) -> hmac::Key {
+        let algorithm = match self.algorithm {
+            TsigAlgorithm::HmacSha384 => hmac::HMAC_SHA384,
+            TsigAlgorithm::HmacSha512 => hmac::HMAC_SHA512,
+            _ => hmac::HMAC_SHA256,
+        };
+
+        hmac::Key::new(algorithm, &self.secret)
+    }
+}
+
+impl Debug for TsigKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("TsigKey")
+            .field("name", &self.name)
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
+
+/// Key given as `[algorithm:]name:secret`, the secret being encoded in
+/// base64 and the algorithm HMAC-SHA256 by default, like `dig -y`.
+impl FromStr for TsigKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TsigKey> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let (algorithm, name, secret) = match parts.as_slice() {
+            [name, secret] => (TsigAlgorithm::HmacSha256, *name, *secret),
+            [algorithm, name, secret] => (TsigAlgorithm::from_name(algorithm), *name, *secret),
+            _ => return Err(InvalidPresentation(format!("TSIG key {} isn't made of an algorithm, a name and a secret", s))),
+        };
+
+        let secret = data_encoding::BASE64
+            .decode(secret.as_bytes())
+            .map_err(|err| InvalidPresentation(format!("TSIG secret of {}: {}", name, err)))?;
+
+        TsigKey::new(name, algorithm, secret).map_err(|err| InvalidPresentation(err.to_string()))
+    }
+}
+
+/// Reasons for a message to fail its TSIG verification, the TSIG error
+/// answered to the signer being given by `result_code`.
+#[derive(Debug)]
+pub enum TsigError {
+    // The TSIG record is malformed or misplaced, or the message isn't signed.
+    FormError(String),
+    // The message is signed with another key.
+    BadKey(String),
+    // The MAC doesn't match the message.
+    BadSignature(String),
+    // The MAC is shorter than accepted.
+    BadTruncation(usize),
+    // The MAC matches, but the message was signed too long ago or ahead of
+    // time.
+    BadTime { time_signed: u64, now: u64 },
+    // The other end rejected our signature, with the given TSIG error.
+    Rejected(ResultCode),
+}
+
+impl TsigError {
+    pub fn result_code(&self) -> ResultCode {
+        match self {
+            TsigError::FormError(_) => ResultCode::FormError,
+            TsigError::BadKey(_) => ResultCode::BadKey,
+            TsigError::BadSignature(_) => ResultCode::BAD_SIG,
+            TsigError::BadTruncation(_) => ResultCode::BadTrunc,
+            TsigError::BadTime { .. } => ResultCode::BadTime,
+            TsigError::Rejected(rcode) => *rcode,
+        }
+    }
+}
+
+impl Display for TsigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            TsigError::FormError(reason) => write!(f, "invalid TSIG: {}", reason),
+            TsigError::BadKey(reason) => write!(f, "bad TSIG key: {}", reason),
+            TsigError::BadSignature(reason) => write!(f, "bad TSIG signature: {}", reason),
+            TsigError::BadTruncation(len) => write!(f, "TSIG MAC truncated to {} bytes", len),
+            TsigError::BadTime { time_signed, now } => write!(f, "message signed at {} is out of the time window at {}", time_signed, now),
+            TsigError::Rejected(rcode) => write!(f, "signature rejected with {}", rcode),
+        }
+    }
+}
+
+impl std::error::Error for TsigError {}
+
+impl From<Error> for TsigError {
+    fn from(err: Error) -> Self {
+        TsigError::FormError(err.to_string())
+    }
+}
+
+/// Exchange authenticated with a key: each message covers the MAC of the
+/// previous one, so that requests and responses, and the messages of a
+/// zone transfer, can't be replayed separately.
+///
+/// The first response of a request is signed along with all the TSIG
+/// fields, the following ones of a multi-message response only along with
+/// the time, and up to 99 of them may be left unsigned in a row.
+#[derive(Debug, Clone)]
+pub struct TsigSession {
+    key: TsigKey,
+    fudge: u16,
+    // MAC of the last signed message, covered by the next one.
+    mac: Option<Vec<u8>>,
+    // Messages signed or verified so far.
+    messages: usize,
+    // Messages sent or received unsigned since the last signed one.
+    unsigned: Vec<u8>,
+    unsigned_count: usize,
+    // Whether the last verified message was out of the time window, the
+    // next signed message answering BADTIME.
+    bad_time: bool,
+}
+
+impl TsigSession {
+    pub fn fudge(mut self, fudge: u16) -> Self {
+        self.fudge = fudge;
+        self
+    }
+
+    pub fn key(&self) -> &TsigKey {
+        &self.key
+    }
+
+    /// Whether messages were left unsigned since the last signed one, the
+    /// last message of a response having to be signed.
+    pub fn has_unsigned(&self) -> bool {
+        self.unsigned_count > 0
+    }
+
+    /// Send a message of a multi-message response without signing it, the
+    /// next signed message covering it.
+    pub fn leave_unsigned(&mut self, message: &[u8]) -> Result<()> {
+        if self.messages < 2 || self.unsigned_count >= MAX_UNSIGNED_MESSAGES {
+            return Err(InvalidData("the message must be signed".to_string()));
+        }
+
+        self.unsigned.extend_from_slice(message);
+        self.unsigned_count += 1;
+        Ok(())
+    }
+
+    /// Sign a message, returning it along with its TSIG record.
+    pub fn sign(&mut self, message: &[u8], now: u64) -> Result<Vec<u8>> {
+        if message.len() < 12 {
+            return Err(InvalidData(format!("message of {} bytes is too short", message.len())));
+        }
+
+        let id = u16::from_be_bytes([message[0], message[1]]);
+        let mut tsig = TSIG::new(&self.key.name, self.key.algorithm.clone(), now, self.fudge, id);
+
+        // The time of the signer is given back to the other end, for it to
+        // know by how much their clocks differ.
+        if self.bad_time {
+            tsig.error = ResultCode::BadTime;
+            tsig.other = now.to_be_bytes()[2..].to_vec();
+            self.bad_time = false;
+        }
+
+        let mut data = self.prior_mac();
+        data.extend_from_slice(&self.unsigned);
+        data.extend_from_slice(message);
+        data.extend_from_slice(&variables(&tsig, self.messages >= 2));
+        tsig.mac = hmac::sign(&self.key.hmac_key(), &data).as_ref().to_vec();
+
+        let signed = append(message, &tsig)?;
+        self.mac = Some(tsig.mac);
+        self.messages += 1;
+        self.unsigned.clear();
+        self.unsigned_count = 0;
+        Ok(signed)
+    }
+
+    /// Verify the TSIG record of a message, which must be the last record.
+    ///
+    /// A message whose time is off is answered with a BADTIME error by the
+    /// next signed message.
+    pub fn verify(&mut self, message: &[u8], now: u64) -> std::result::Result<(), TsigError> {
+        let (tsig, position) = match find(message)? {
+            Some(found) => found,
+            None if self.messages >= 2 && self.unsigned_count < MAX_UNSIGNED_MESSAGES => {
+                self.unsigned.extend_from_slice(message);
+                self.unsigned_count += 1;
+                return Ok(());
+            }
+            None => return Err(TsigError::FormError("the message isn't signed".to_string())),
+        };
+
+        if !self.key.matches(&tsig) {
+            return Err(TsigError::BadKey(format!("the message is signed with the {} {} key", tsig.domain, tsig.algorithm)));
+        }
+
+        // Errors come along with an empty MAC, except BADTIME whose
+        // response is signed.
+        if !matches!(tsig.error, ResultCode::NoError | ResultCode::BadTime) {
+            return Err(TsigError::Rejected(tsig.error));
+        }
+
+        // Truncated MACs are allowed by RFC 8945 section 5.2.2.1 but left
+        // out by local policy, as nothing needs them nowadays.
+        let len = self.key.hmac_key().algorithm().digest_algorithm().output_len();
+        if tsig.mac.len() > len {
+            return Err(TsigError::FormError(format!("MAC of {} bytes is too long", tsig.mac.len())));
+        }
+
+        if tsig.mac.len() < len {
+            return Err(TsigError::BadTruncation(tsig.mac.len()));
+        }
+
+        // The MAC covers the message as it was before the TSIG record was
+        // added, with its original ID.
+        let mut unsigned = message[..position].to_vec();
+        unsigned[..2].copy_from_slice(&tsig.original_id.to_be_bytes());
+        let count = u16::from_be_bytes([unsigned[ARCOUNT], unsigned[ARCOUNT + 1]]) - 1;
+        unsigned[ARCOUNT..ARCOUNT + 2].copy_from_slice(&count.to_be_bytes());
+
+        let mut data = self.prior_mac();
+        data.extend_from_slice(&self.unsigned);
+        data.extend_from_slice(&unsigned);
+        data.extend_from_slice(&variables(&tsig, self.messages >= 2));
+        hmac::verify(&self.key.hmac_key(), &data, &tsig.mac).map_err(|_| TsigError::BadSignature("the MAC doesn't match".to_string()))?;
+
+        self.mac = Some(tsig.mac);
+        self.messages += 1;
+        self.unsigned.clear();
+        self.unsigned_count = 0;
+
+        if tsig.error == ResultCode::BadTime {
+            return Err(TsigError::Rejected(ResultCode::BadTime));
+        }
+
+        if now.abs_diff(tsig.time_signed) > tsig.fudge as u64 {
+            self.bad_time = true;
+            return Err(TsigError::BadTime { time_signed: tsig.time_signed, now });
+        }
+
+        Ok(())
+    }
+
+    // MAC of the previous message, prefixed by its length.
+    fn prior_mac(&self) -> Vec<u8> {
+        let mut data = vec![];
+        if let Some(mac) = &self.mac {
+            data.extend_from_slice(&(mac.len() as u16).to_be_bytes());
+            data.extend_from_slice(mac);
+        }
+
+        data
+    }
+}
+
+/// TSIG record of a message along with its position, when it is signed.
+pub fn find(message: &[u8]) -> Result<Option<(TSIG, usize)>> {
+    match MessageView::new(message)?.signature(QueryType::TransactionSignature)? {
+        None => Ok(None),
+        Some(view) => match view.to_record()? {
+            Record::TransactionSignature(tsig) => Ok(Some((tsig, view.position()))),
+            _ => Err(InvalidData("TSIG record is malformed".to_string())),
+        },
+    }
+}
+
+/// Answer a request which couldn't be verified, with the error in a TSIG
+/// record without MAC (RFC 8945 section 5.3.2).
+pub fn reject(response: &[u8], request: &TSIG, error: &TsigError, now: u64) -> Result<Vec<u8>> {
+    if response.len() < 12 {
+        return Err(InvalidData(format!("message of {} bytes is too short", response.len())));
+    }
+
+    let id = u16::from_be_bytes([response[0], response[1]]);
+    let mut tsig = TSIG::new(&request.domain, request.algorithm.clone(), now, request.fudge, id);
+    tsig.error = error.result_code();
+    append(response, &tsig)
+}
+
+// Add a signature record at the end of a message.
+pub(crate) fn append<R>(message: &[u8], record: &R) -> Result<Vec<u8>>
+    where R: Serialize
+{
+    let mut serializer = GrowableBuffer::default();
+    record.serialize(&mut serializer)?;
+
+    let mut data = message.to_vec();
+    let count = u16::from_be_bytes([data[ARCOUNT], data[ARCOUNT + 1]])
+        .checked_add(1)
+        .ok_or_else(|| InvalidData("too many additional records to sign the message".to_string()))?;
+    data[ARCOUNT..ARCOUNT + 2].copy_from_slice(&count.to_be_bytes());
+    data.extend_from_slice(&serializer.into_inner());
+    Ok(data)
+}
+
+// Fields of the TSIG record covered by the MAC (RFC 8945 section 4.3.3),
+// only the timers for the subsequent messages of a response.
+fn variables(tsig: &TSIG, timers_only: bool) -> Vec<u8> {
+    let mut data = vec![];
+    if !timers_only {
+        data.extend_from_slice(&name_wire(&tsig.domain));
+        data.extend_from_slice(&tsig.class.as_u16().to_be_bytes());
+        data.extend_from_slice(&(tsig.ttl.as_secs() as u32).to_be_bytes());
+        data.extend_from_slice(&name_wire(tsig.algorithm.name()));
+    }
+
+    data.extend_from_slice(&tsig.time_signed.to_be_bytes()[2..]);
+    data.extend_from_slice(&tsig.fudge.to_be_bytes());
+
+    if !timers_only {
+        data.extend_from_slice(&tsig.error.as_u16().to_be_bytes());
+        data.extend_from_slice(&(tsig.other.len() as u16).to_be_bytes());
+        data.extend_from_slice(&tsig.other);
+    }
+
+    data
+}
+
+#[cfg(test)]
+mod test {
+    use crate::growable_buffer::GrowableBuffer;
+    use crate::header::ResultCode;
+    use crate::packet::{Packet, QueryType, Record};
+    use crate::records::tsig::TsigAlgorithm;
+    use crate::ser::Serialize;
+    use crate::tsig::{find, reject, TsigError, TsigKey};
+    use crate::view::MessageView;
+
+    const NOW: u64 = 1700000000;
+
+    fn key() -> TsigKey {
+        "hmac-sha256:transfer.example.:c2VjcmV0LXNoYXJlZC1ieS1ib3RoLWVuZHM=".parse().unwrap()
+    }
+
+    fn wire(packet: &Packet) -> Vec<u8> {
+        let mut serializer = GrowableBuffer::default();
+        packet.serialize(&mut serializer).unwrap();
+        serializer.into_inner()
+    }
+
+    fn request() -> Packet {
+        Packet::query("example.com", QueryType::StartOfAuthority).id(0x1234).build()
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let key = key();
+        let mut client = key.session();
+        let signed = client.sign(&wire(&request()), NOW).unwrap();
+
+        let view = MessageView::new(&signed).unwrap();
+        assert_eq!(1, view.header().total_additional_records);
+        let (tsig, _) = find(&signed).unwrap().unwrap();
+        assert_eq!(0x1234, tsig.original_id);
+        assert_eq!(32, tsig.mac.len());
+        assert!(key.matches(&tsig));
+
+        let packet = view.to_packet().unwrap();
+        assert_eq!(Some(&tsig), packet.tsig());
+        assert!(packet.additional_rrsets().is_empty());
+
+        let mut server = key.session();
+        assert!(server.verify(&signed, NOW + 10).is_ok());
+
+        let response = wire(&request().reply().build());
+        let signed_response = server.sign(&response, NOW + 10).unwrap();
+        assert!(client.verify(&signed_response, NOW + 20).is_ok());
+
+        // Signatures only hold within their exchange.
+        assert!(matches!(key.session().verify(&signed_response, NOW), Err(TsigError::BadSignature(_))));
+
+        let mut tampered = signed.clone();
+        tampered[3] ^= 0x01;
+        assert!(matches!(key.session().verify(&tampered, NOW), Err(TsigError::BadSignature(_))));
+
+        assert!(matches!(key.session().verify(&wire(&request()), NOW), Err(TsigError::FormError(_))));
+    }
+
+    #[test]
+    fn mac() {
+        // MAC computed separately over the message and the TSIG variables.
+        let signed = key().session().sign(&wire(&request()), NOW).unwrap();
+        let (tsig, _) = find(&signed).unwrap().unwrap();
+        assert_eq!("5d743665fb645ff203cf4fb7b73d05c46c74610456db38fa703c990ad5e51ee1", data_encoding::HEXLOWER.encode(&tsig.mac));
+    }
+
+    #[test]
+    fn original_id() {
+        // Forwarders may change the ID of a message, the original one being
+        // covered by the MAC.
+        let key = key();
+        let mut signed = key.session().sign(&wire(&request()), NOW).unwrap();
+        signed[..2].copy_from_slice(&[0xAB, 0xCD]);
+        assert!(key.session().verify(&signed, NOW).is_ok());
+    }
+
+    #[test]
+    fn multi_message() {
+        let session = key().session();
+        let (mut client, mut server) = (session.clone(), session);
+
+        let signed = client.sign(&wire(&request()), NOW).unwrap();
+        assert!(server.verify(&signed, NOW).is_ok());
+
+        let messages: Vec<Vec<u8>> = (0..5u16).map(|i| wire(&Packet::query(format!("{}.example.com", i), QueryType::A).id(0x1234).build())).collect();
+
+        // The first and last messages are signed, along with one in between.
+        let mut stream = vec![];
+        for (i, message) in messages.iter().enumerate() {
+            if i == 0 || i == 2 || i == messages.len() - 1 {
+                stream.push(server.sign(message, NOW).unwrap());
+            } else {
+                server.leave_unsigned(message).unwrap();
+                stream.push(message.clone());
+            }
+        }
+
+        for (i, message) in stream.iter().enumerate() {
+            assert!(client.verify(message, NOW).is_ok(), "message {}", i);
+            assert_eq!(i == 1 || i == 3, client.has_unsigned());
+        }
+
+        // Only the timers follow the first signed response, which can't be
+        // verified out of order.
+        let mut client = key().session();
+        client.sign(&wire(&request()), NOW).unwrap();
+        assert!(client.verify(&stream[2], NOW).is_err());
+
+        // The first response must be signed.
+        let mut client = key().session();
+        client.sign(&wire(&request()), NOW).unwrap();
+        assert!(matches!(client.verify(&messages[0], NOW), Err(TsigError::FormError(_))));
+        assert!(key().session().leave_unsigned(&messages[0]).is_err());
+    }
+
+    #[test]
+    fn bad_time() {
+        let key = key();
+        let mut client = key.session();
+        let signed = client.sign(&wire(&request()), NOW).unwrap();
+
+        let mut server = key.session();
+        assert!(matches!(server.verify(&signed, NOW + 301), Err(TsigError::BadTime { .. })));
+
+        let response = wire(&request().reply().rcode(ResultCode::NotAuth).build());
+        let response = server.sign(&response, NOW + 301).unwrap();
+        let (tsig, _) = find(&response).unwrap().unwrap();
+        assert_eq!(ResultCode::BadTime, tsig.error);
+        assert_eq!(&(NOW + 301).to_be_bytes()[2..], tsig.other.as_slice());
+
+        assert!(matches!(client.verify(&response, NOW), Err(TsigError::Rejected(ResultCode::BadTime))));
+    }
+
+    #[test]
+    fn bad_key() {
+        let other = TsigKey::new("transfer.example", TsigAlgorithm::HmacSha512, b"secret".to_vec()).unwrap();
+        let mut client = other.session();
+        let signed = client.sign(&wire(&request()), NOW).unwrap();
+
+        let err = key().session().verify(&signed, NOW).unwrap_err();
+        assert!(matches!(err, TsigError::BadKey(_)));
+        assert_eq!(ResultCode::BadKey, err.result_code());
+
+        let (tsig, _) = find(&signed).unwrap().unwrap();
+        let response = reject(&wire(&request().reply().rcode(ResultCode::NotAuth).build()), &tsig, &err, NOW).unwrap();
+        let (tsig, _) = find(&response).unwrap().unwrap();
+        assert!(tsig.mac.is_empty());
+        assert!(matches!(client.verify(&response, NOW), Err(TsigError::Rejected(ResultCode::BadKey))));
+    }
+
+    #[test]
+    fn truncated_mac() {
+        let key = key();
+        let signed = key.session().sign(&wire(&request()), NOW).unwrap();
+        let (mut tsig, _) = find(&signed).unwrap().unwrap();
+        tsig.mac.truncate(16);
+
+        let mut packet = request();
+        packet.additionals.push(Record::TransactionSignature(tsig));
+        assert!(matches!(key.session().verify(&wire(&packet), NOW), Err(TsigError::BadTruncation(16))));
+    }
+
+    #[test]
+    fn parse_key() {
+        let key: TsigKey = "update.example:c2VjcmV0".parse().unwrap();
+        assert_eq!("update.example", key.name());
+        assert_eq!(&TsigAlgorithm::HmacSha256, key.algorithm());
+        assert!(!format!("{:?}", key).contains("c2VjcmV0"));
+
+        assert_eq!(&TsigAlgorithm::HmacSha384, "hmac-sha384:update.example:c2VjcmV0".parse::<TsigKey>().unwrap().algorithm());
+        assert!("hmac-md5:update.example:c2VjcmV0".parse::<TsigKey>().is_err());
+        assert!("update.example:not base64".parse::<TsigKey>().is_err());
+        assert!("update.example".parse::<TsigKey>().is_err());
+    }
+}
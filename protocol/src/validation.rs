@@ -0,0 +1,714 @@
+//! DNSSEC validation of signed RRsets and of authenticated denials of
+//! existence (RFC 4035 section 5, RFC 5155 section 8).
+
+use std::cmp::Ordering;
+
+use ring::digest;
+
+use crate::crypto;
+use crate::errors::Error::{InvalidData, InvalidSignature};
+use crate::packet::{QueryType, Record};
+use crate::presentation::parse_base32hex;
+use crate::records::dnssec::{canonical_name_cmp, DigestType, TypeBitmap};
+use crate::records::{DNSKEY, DS, NSEC, NSEC3, RRSIG};
+use crate::result::Result;
+
+/// What a denial of existence proves about the queried name and type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Denial {
+    // The name doesn't exist, nor a wildcard that could match it.
+    NameError,
+    // The name exists but has no record of the queried type.
+    NoData,
+    // The name is a delegation to an unsigned zone, proven by the absence
+    // of DS records or by an opt-out NSEC3 record.
+    InsecureDelegation,
+    // The name doesn't exist among the signed names, but the opt-out NSEC3
+    // record covering it may hide an unsigned delegation, so the denial
+    // is insecure (RFC 5155 section 9.2).
+    OptOut,
+    // The NSEC3 records use more hash iterations than validators compute,
+    // they are insecure without being checked (RFC 9276 section 3.2).
+    ExcessiveIterations,
+}
+
+/// Maximum number of additional hash iterations of the NSEC3 records which
+/// are checked, more expensive records being insecure (RFC 9276 section 3.2).
+pub const MAX_NSEC3_ITERATIONS: u16 = 150;
+
+/// Digest of a DNSKEY record, as held by the DS records referring to it
+/// (RFC 4034 section 5.1.4).
+pub fn ds_digest(dnskey: &DNSKEY, digest_type: DigestType) -> Result<Vec<u8>> {
+    let mut data = name_wire(&dnskey.domain);
+    data.extend_from_slice(&dnskey.rdata());
+    crypto::digest(digest_type, &data)
+}
+
+/// DS record referring to a DNSKEY record, to be published in the parent
+/// zone.
+pub fn create_ds(dnskey: &DNSKEY, digest_type: DigestType) -> Result<DS> {
+    Ok(DS {
+        domain: dnskey.domain.clone(),
+        class: dnskey.class,
+        ttl: dnskey.ttl,
+        key_tag: dnskey.key_tag(),
+        algorithm: dnskey.algorithm,
+        digest_type,
+        digest: ds_digest(dnskey, digest_type)?,
+    })
+}
+
+/// Whether the DS record refers to the DNSKEY record.
+pub fn matches_ds(dnskey: &DNSKEY, ds: &DS) -> bool {
+    ds.key_tag == dnskey.key_tag()
+        && ds.algorithm == dnskey.algorithm
+        && ds.domain.eq_ignore_ascii_case(&dnskey.domain)
+        && ds_digest(dnskey, ds.digest_type).is_ok_and(|digest| digest == ds.digest)
+}
+
+/// Verify the signature of an RRset with the given key, `now` being the
+/// current time in seconds since the epoch.
+pub fn verify_rrsig(rrsig: &RRSIG, dnskey: &DNSKEY, records: &[Record], now: u32) -> Result<()> {
+    if !dnskey.is_zone_key() || dnskey.is_revoked() {
+        return Err(InvalidSignature(format!("DNSKEY {} of {} can't sign zone data", dnskey.key_tag(), dnskey.domain)));
+    }
+
+    verify_signature(rrsig, dnskey, records, now)
+}
+
+/// Verify that a revoked key signed the DNSKEY RRset publishing its
+/// revocation, which is the only RRset it may sign (RFC 5011 section 2.1).
+pub fn verify_revocation(rrsigs: &[&RRSIG], dnskey: &DNSKEY, records: &[Record], now: u32) -> Result<()> {
+    if !dnskey.is_revoked() {
+        return Err(InvalidSignature(format!("DNSKEY {} of {} isn't revoked", dnskey.key_tag(), dnskey.domain)));
+    }
+
+    let mut last_error = None;
+    for rrsig in rrsigs.iter().filter(|rrsig| rrsig.type_covered == QueryType::DnsKey && rrsig.key_tag == dnskey.key_tag()) {
+        match verify_signature(rrsig, dnskey, records, now) {
+            Ok(()) => return Ok(()),
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| InvalidSignature(format!("revoked DNSKEY {} of {} didn't sign its revocation", dnskey.key_tag(), dnskey.domain))))
+}
+
+// Checks of a signature shared by current and revoked keys.
+fn verify_signature(rrsig: &RRSIG, dnskey: &DNSKEY, records: &[Record], now: u32) -> Result<()> {
+    if !rrsig.signer_name.eq_ignore_ascii_case(&dnskey.domain) {
+        return Err(InvalidSignature(format!("RRSIG signer {} doesn't own the DNSKEY of {}", rrsig.signer_name, dnskey.domain)));
+    }
+
+    if rrsig.key_tag != dnskey.key_tag() || rrsig.algorithm != dnskey.algorithm {
+        return Err(InvalidSignature(format!("RRSIG wasn't made with the DNSKEY {} of {}", dnskey.key_tag(), dnskey.domain)));
+    }
+
+    if let Some(record) = records.iter().find(|record| !is_subdomain(record.domain(), &rrsig.signer_name)) {
+        return Err(InvalidSignature(format!("{} is outside of the {} zone", record.domain(), rrsig.signer_name)));
+    }
+
+    // Timestamps are compared using serial number arithmetic (RFC 1982).
+    if (now.wrapping_sub(rrsig.inception) as i32) < 0 {
+        return Err(InvalidSignature(format!("RRSIG of {} isn't valid yet", rrsig.domain)));
+    }
+
+    if (rrsig.expiration.wrapping_sub(now) as i32) < 0 {
+        return Err(InvalidSignature(format!("RRSIG of {} has expired", rrsig.domain)));
+    }
+
+    let data = rrsig.signed_data(records)?;
+    crypto::verify(dnskey.algorithm, &dnskey.public_key, &data, &rrsig.signature)
+}
+
+/// Verify an RRset with any of its signatures made by one of the keys,
+/// returning the verified signature.
+pub fn verify_rrset<'a>(rrsigs: &[&'a RRSIG], dnskeys: &[DNSKEY], records: &[Record], now: u32) -> Result<&'a RRSIG> {
+    let mut last_error = None;
+    for rrsig in rrsigs {
+        for dnskey in dnskeys.iter().filter(|dnskey| dnskey.key_tag() == rrsig.key_tag) {
+            match verify_rrsig(rrsig, dnskey, records, now) {
+                Ok(()) => return Ok(rrsig),
+                Err(err) => last_error = Some(err),
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| InvalidSignature("no DNSKEY matches the RRSIG records".to_string())))
+}
+
+/// Hash of a name, used as NSEC3 owner name label (RFC 5155 section 5).
+pub fn nsec3_hash(name: &str, salt: &[u8], iterations: u16) -> Vec<u8> {
+    let mut hash = name_wire(name);
+    for _ in 0..=iterations {
+        let mut context = digest::Context::new(&digest::SHA1_FOR_LEGACY_USE_ONLY);
+        context.update(&hash);
+        context.update(salt);
+        hash = context.finish().as_ref().to_vec();
+    }
+
+    hash
+}
+
+/// Verify what the NSEC or NSEC3 records, whose signatures have already
+/// been verified, prove about the queried name and type.
+pub fn verify_denial(records: &[Record], qname: &str, qtype: QueryType) -> Result<Denial> {
+    let nsecs: Vec<&NSEC> = records
+        .iter()
+        .filter_map(|record| match record {
+            Record::NextSecure(nsec) => Some(nsec),
+            _ => None,
+        })
+        .collect();
+
+    if !nsecs.is_empty() {
+        return nsec_denial(&nsecs, qname, qtype);
+    }
+
+    let nsec3s: Vec<&NSEC3> = records
+        .iter()
+        .filter_map(|record| match record {
+            Record::NextSecure3(nsec3) => Some(nsec3),
+            _ => None,
+        })
+        .collect();
+
+    if nsec3s.iter().any(|nsec3| nsec3.iterations > MAX_NSEC3_ITERATIONS) {
+        return Ok(Denial::ExcessiveIterations);
+    }
+
+    if !nsec3s.is_empty() {
+        return nsec3_denial(&nsec3s, qname, qtype);
+    }
+
+    Err(InvalidData(format!("no NSEC or NSEC3 record proves the denial of {} {}", qname, qtype)))
+}
+
+/// Whether some of the NSEC3 records use more hash iterations than checked.
+pub fn has_excessive_iterations(records: &[Record]) -> bool {
+    records
+        .iter()
+        .any(|record| matches!(record, Record::NextSecure3(nsec3) if nsec3.iterations > MAX_NSEC3_ITERATIONS))
+}
+
+/// Verify that a wildcard was legitimately expanded to answer `qname`,
+/// `labels` being the labels field of the answer RRSIG: the records must
+/// prove that no closer name exists (RFC 4035 section 5.3.4).
+pub fn verify_wildcard_expansion(records: &[Record], qname: &str, labels: u8) -> Result<()> {
+    let names = labels_of(qname);
+    if names.len() <= labels as usize {
+        return Ok(());
+    }
+
+    // Name one label longer than the closest encloser, the wildcard owner.
+    let next_closer = names[names.len() - labels as usize - 1..].join(".");
+    let proven = records.iter().any(|record| match record {
+        Record::NextSecure(nsec) => nsec_covers(nsec, qname),
+        Record::NextSecure3(nsec3) => nsec3_covers(nsec3, &next_closer),
+        _ => false,
+    });
+
+    if !proven {
+        return Err(InvalidData(format!("no record proves the wildcard expansion of {}", qname)));
+    }
+
+    Ok(())
+}
+
+/// Closest encloser of a name proven not to exist by the NSEC or NSEC3
+/// records, the wildcard below it being the one which could match the name.
+pub fn closest_encloser(records: &[Record], qname: &str) -> Result<String> {
+    let nsecs: Vec<&NSEC> = records
+        .iter()
+        .filter_map(|record| match record {
+            Record::NextSecure(nsec) => Some(nsec),
+            _ => None,
+        })
+        .collect();
+
+    if !nsecs.is_empty() {
+        return nsec_closest_encloser(&nsecs, qname);
+    }
+
+    let nsec3s: Vec<&NSEC3> = records
+        .iter()
+        .filter_map(|record| match record {
+            Record::NextSecure3(nsec3) => Some(nsec3),
+            _ => None,
+        })
+        .collect();
+
+    nsec3_closest_encloser(&nsec3s, qname).map(|(encloser, _)| encloser)
+}
+
+/// Whether the NSEC or NSEC3 record matches or covers the name, taking part
+/// in the proofs about it.
+pub fn is_denial_of(record: &Record, name: &str) -> bool {
+    match record {
+        Record::NextSecure(nsec) => nsec.domain.eq_ignore_ascii_case(name) || nsec_covers(nsec, name),
+        Record::NextSecure3(nsec3) => nsec3_matches(nsec3, name) || nsec3_covers(nsec3, name),
+        _ => false,
+    }
+}
+
+fn nsec_denial(nsecs: &[&NSEC], qname: &str, qtype: QueryType) -> Result<Denial> {
+    if let Some(nsec) = nsecs.iter().find(|nsec| nsec.domain.eq_ignore_ascii_case(qname)) {
+        return type_denial(&nsec.types, qname, qtype);
+    }
+
+    // Empty non-terminals have no NSEC record, the one covering them
+    // leading to a name below them.
+    let encloser = nsec_closest_encloser(nsecs, qname)?;
+    if encloser.eq_ignore_ascii_case(qname) {
+        return Ok(Denial::NoData);
+    }
+
+    let wildcard = wildcard_of(&encloser);
+
+    if let Some(nsec) = nsecs.iter().find(|nsec| nsec.domain.eq_ignore_ascii_case(&wildcard)) {
+        return type_denial(&nsec.types, &wildcard, qtype);
+    }
+
+    if nsecs.iter().any(|nsec| nsec_covers(nsec, &wildcard)) {
+        return Ok(Denial::NameError);
+    }
+
+    Err(InvalidData(format!("no NSEC record denies the {} wildcard", wildcard)))
+}
+
+// The closest encloser is the longest ancestor of a missing name existing
+// in the zone, which is shared with one of the names of the NSEC record
+// covering the name.
+fn nsec_closest_encloser(nsecs: &[&NSEC], qname: &str) -> Result<String> {
+    let covering = nsecs
+        .iter()
+        .find(|nsec| nsec_covers(nsec, qname))
+        .ok_or_else(|| InvalidData(format!("no NSEC record covers {}", qname)))?;
+
+    if is_subdomain(qname, &covering.domain) && is_delegation(&covering.types) {
+        return Err(InvalidData(format!("{} is below the {} delegation", qname, covering.domain)));
+    }
+
+    Ok([&covering.domain, &covering.next_domain]
+        .iter()
+        .map(|name| common_ancestor(qname, name))
+        .max_by_key(|ancestor| labels_of(ancestor).len())
+        .unwrap_or_default())
+}
+
+fn nsec3_denial(nsec3s: &[&NSEC3], qname: &str, qtype: QueryType) -> Result<Denial> {
+    let matching = |name: &str| nsec3s.iter().find(|nsec3| nsec3_matches(nsec3, name));
+    let covering = |name: &str| nsec3s.iter().find(|nsec3| nsec3_covers(nsec3, name));
+
+    if let Some(nsec3) = matching(qname) {
+        return type_denial(&nsec3.types, qname, qtype);
+    }
+
+    let (encloser, covering_next) = nsec3_closest_encloser(nsec3s, qname)?;
+
+    // Opt-out records may cover unsigned delegations (RFC 5155 section 8.6).
+    if qtype == QueryType::DelegationSigner && covering_next.is_opt_out() {
+        return Ok(Denial::InsecureDelegation);
+    }
+
+    let wildcard = wildcard_of(&encloser);
+    if let Some(nsec3) = matching(&wildcard) {
+        return type_denial(&nsec3.types, &wildcard, qtype);
+    }
+
+    if covering(&wildcard).is_some() && covering_next.is_opt_out() {
+        return Ok(Denial::OptOut);
+    }
+
+    if covering(&wildcard).is_some() {
+        return Ok(Denial::NameError);
+    }
+
+    Err(InvalidData(format!("no NSEC3 record denies the {} wildcard", wildcard)))
+}
+
+// Closest encloser proof (RFC 5155 section 8.3): an ancestor of the name
+// exists while the next closer name is covered, returning the ancestor and
+// the record covering the next closer name.
+fn nsec3_closest_encloser<'a>(nsec3s: &[&'a NSEC3], qname: &str) -> Result<(String, &'a NSEC3)> {
+    let names = labels_of(qname);
+    let encloser = (1..=names.len())
+        .map(|i| (names[i..].join("."), names[i - 1..].join(".")))
+        .find_map(|(encloser, next_closer)| {
+            nsec3s
+                .iter()
+                .find(|nsec3| nsec3_matches(nsec3, &encloser))
+                .map(|nsec3| (encloser, next_closer, nsec3))
+        });
+
+    let (encloser, next_closer, matching) = match encloser {
+        Some(encloser) => encloser,
+        None => return Err(InvalidData(format!("no NSEC3 record matches an ancestor of {}", qname))),
+    };
+
+    if is_delegation(&matching.types) {
+        return Err(InvalidData(format!("{} is below the {} delegation", qname, encloser)));
+    }
+
+    let covering_next = nsec3s
+        .iter()
+        .find(|nsec3| nsec3_covers(nsec3, &next_closer))
+        .ok_or_else(|| InvalidData(format!("no NSEC3 record covers {}", next_closer)))?;
+
+    Ok((encloser, covering_next))
+}
+
+// Delegation points have NS records but no SOA, the parent zone being
+// only authoritative for their DS records, so its NSEC and NSEC3 records
+// prove nothing about the names below them (RFC 6840 section 4.1).
+fn is_delegation(types: &TypeBitmap) -> bool {
+    types.contains(QueryType::AuthoritativeNameServer) && !types.contains(QueryType::StartOfAuthority)
+}
+
+// What the types of an existing name prove about the queried type.
+fn type_denial(types: &TypeBitmap, name: &str, qtype: QueryType) -> Result<Denial> {
+    if types.contains(qtype) || types.contains(QueryType::CanonicalName) {
+        return Err(InvalidData(format!("{} has {} or CNAME records", name, qtype)));
+    }
+
+    if is_delegation(types) {
+        if qtype == QueryType::DelegationSigner {
+            return Ok(Denial::InsecureDelegation);
+        }
+
+        return Err(InvalidData(format!("{} is a delegation, its {} records aren't in this zone", name, qtype)));
+    }
+
+    Ok(Denial::NoData)
+}
+
+// Whether the name falls between the NSEC owner and next names, the last
+// NSEC record of a zone wrapping around to the apex.
+// Names between two names of a zone are in the zone as well, the last
+// NSEC record of the chain leading back to the apex only covering names
+// below it.
+fn nsec_covers(nsec: &NSEC, name: &str) -> bool {
+    let after_owner = canonical_name_cmp(&nsec.domain, name) == Ordering::Less;
+    let before_next = canonical_name_cmp(name, &nsec.next_domain) == Ordering::Less;
+
+    match canonical_name_cmp(&nsec.domain, &nsec.next_domain) {
+        Ordering::Less => after_owner && before_next,
+        _ => (after_owner || before_next) && is_subdomain(name, &nsec.next_domain),
+    }
+}
+
+// Owner hash and zone of an NSEC3 record, which is never hashed against
+// when it uses too many iterations.
+fn nsec3_owner(nsec3: &NSEC3) -> Option<(Vec<u8>, &str)> {
+    if nsec3.iterations > MAX_NSEC3_ITERATIONS {
+        return None;
+    }
+
+    let (label, zone) = nsec3.domain.split_once('.').unwrap_or((&nsec3.domain, ""));
+    parse_base32hex(label).ok().map(|hash| (hash, zone))
+}
+
+fn nsec3_matches(nsec3: &NSEC3, name: &str) -> bool {
+    match nsec3_owner(nsec3) {
+        Some((owner, zone)) if is_subdomain(name, zone) => owner == nsec3_hash(name, &nsec3.salt, nsec3.iterations),
+        _ => false,
+    }
+}
+
+fn nsec3_covers(nsec3: &NSEC3, name: &str) -> bool {
+    let owner = match nsec3_owner(nsec3) {
+        Some((owner, zone)) if is_subdomain(name, zone) => owner,
+        _ => return false,
+    };
+
+    let hash = nsec3_hash(name, &nsec3.salt, nsec3.iterations);
+    let next = &nsec3.next_hashed_owner;
+
+    if owner < *next {
+        owner < hash && hash < *next
+    } else {
+        owner < hash || hash < *next
+    }
+}
+
+// Uncompressed wire format of a name in canonical form.
+pub(crate) fn name_wire(name: &str) -> Vec<u8> {
+    let mut data = vec![];
+    for label in labels_of(name) {
+        data.push(label.len() as u8);
+        data.extend(label.bytes().map(|byte| byte.to_ascii_lowercase()));
+    }
+
+    data.push(0);
+    data
+}
+
+pub(crate) fn labels_of(name: &str) -> Vec<&str> {
+    name.split('.').filter(|label| !label.is_empty()).collect()
+}
+
+fn wildcard_of(name: &str) -> String {
+    if name.is_empty() {
+        return "*".to_string();
+    }
+
+    format!("*.{}", name)
+}
+
+// Longest ancestor shared by the names, compared case-insensitively.
+fn common_ancestor(name: &str, other: &str) -> String {
+    let (labels, others) = (labels_of(name), labels_of(other));
+    let shared = labels
+        .iter()
+        .rev()
+        .zip(others.iter().rev())
+        .take_while(|(label, other)| label.eq_ignore_ascii_case(other))
+        .count();
+
+    labels[labels.len() - shared..].join(".")
+}
+
+// Whether the name is the domain or one of its subdomains, the root
+// being the empty name.
+pub(crate) fn is_subdomain(name: &str, domain: &str) -> bool {
+    let (labels, domains) = (labels_of(name), labels_of(domain));
+    labels.len() >= domains.len()
+        && labels
+            .iter()
+            .rev()
+            .zip(domains.iter().rev())
+            .all(|(label, domain)| label.eq_ignore_ascii_case(domain))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::packet::{Class, QueryType, Record};
+    use crate::presentation::format_base32hex;
+    use crate::records::dnssec::{canonical_name_cmp, DigestType, TypeBitmap};
+    use crate::records::nsec3::{OPT_OUT, SHA1};
+    use crate::records::{MailExchange, DNSKEY, DS, NSEC, NSEC3, RRSIG};
+    use crate::crypto::SigningKey;
+    use crate::signing::ZoneKey;
+    use crate::validation::{closest_encloser, create_ds, has_excessive_iterations, is_denial_of, matches_ds, nsec3_hash, verify_denial, verify_revocation, verify_rrset, verify_rrsig, verify_wildcard_expansion, Denial, MAX_NSEC3_ITERATIONS};
+
+    // Key and MX record of RFC 8080 section 6.1.
+    const DNSKEY: &str = "example.com. 3600 IN DNSKEY 257 3 15 l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=";
+    const RRSIG: &str = "example.com. 3600 IN RRSIG MX 15 2 3600 1440021600 1438207200 3613 example.com. \
+        oL9krJun7xfBOIWcGHi7mag5/hdZrKWw15jPGrHpjQeRAvTdszaPD+QLs3fx8A4M3e23mRZ9VrbpMngwcrqNAg==";
+
+    fn mx(preference: u16) -> Record {
+        Record::MailExchange(MailExchange {
+            domain: "example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(3600),
+            preference,
+            exchange: "mail.example.com".to_string(),
+        })
+    }
+
+    #[test]
+    fn verify_ed25519_rrsig() {
+        let dnskey: DNSKEY = DNSKEY.parse().unwrap();
+        let rrsig: RRSIG = RRSIG.parse().unwrap();
+        let records = vec![mx(10)];
+
+        assert_eq!(3613, dnskey.key_tag());
+        assert!(verify_rrsig(&rrsig, &dnskey, &records, 1439000000).is_ok());
+        assert!(verify_rrset(&[&rrsig], std::slice::from_ref(&dnskey), &records, 1439000000).is_ok());
+
+        // Outside of the validity period.
+        assert!(verify_rrsig(&rrsig, &dnskey, &records, 1438207199).is_err());
+        assert!(verify_rrsig(&rrsig, &dnskey, &records, 1440021601).is_err());
+
+        // Altered data.
+        let other = vec![mx(20)];
+        assert!(verify_rrsig(&rrsig, &dnskey, &other, 1439000000).is_err());
+
+        // Revoked key.
+        let mut revoked = dnskey;
+        revoked.flags |= crate::records::dnskey::REVOKE;
+        assert!(verify_rrsig(&rrsig, &revoked, &records, 1439000000).is_err());
+    }
+
+    #[test]
+    fn revoked_key() {
+        let mut dnskey: DNSKEY = DNSKEY.parse().unwrap();
+        dnskey.flags |= crate::records::dnskey::REVOKE;
+
+        let private_key = "Algorithm: 15 (ED25519)\nPrivateKey: ODIyNjAzODQ2MjgwODAxMjI2NDUxOTAyMDQxNDIyNjI=";
+        let key = ZoneKey::new(dnskey.clone(), SigningKey::from_private_key(&dnskey.public_key, private_key).unwrap()).unwrap();
+        let records = vec![Record::DnsKey(dnskey.clone())];
+        let rrsig = key.sign(&records, "example.com", 1438207200, 1440021600).unwrap();
+
+        // Revoked keys only sign their own revocation.
+        assert!(verify_revocation(&[&rrsig], &dnskey, &records, 1439000000).is_ok());
+        assert!(verify_rrsig(&rrsig, &dnskey, &records, 1439000000).is_err());
+        assert!(verify_revocation(&[&rrsig], &dnskey, &[mx(10)], 1439000000).is_err());
+        assert!(verify_revocation(&[], &dnskey, &records, 1439000000).is_err());
+
+        let current: DNSKEY = DNSKEY.parse().unwrap();
+        assert!(verify_revocation(&[&rrsig], &current, &records, 1439000000).is_err());
+    }
+
+    #[test]
+    fn ds_matching() {
+        // Records of RFC 4034 section 5.4.
+        let dnskey: DNSKEY = "dskey.example.com. 86400 IN DNSKEY 256 3 5 \
+            AQOeiiR0GOMYkDshWoSKz9XzfwJr1AYtsmx3TGkJaNXVbfi/2pHm822aJ5iI9BMzNXxeYCmZDRD99WYwYqUSdjMmmAphXdvxegXd/M5+X7OrzKBaMbCVdFLUUh6DhweJBjEVv5f2wwjM9XzcnOf+EPbtG9DMBmADjFDc2w/rljwvFw==".parse().unwrap();
+        let ds: DS = "dskey.example.com. 86400 IN DS 60485 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118".parse().unwrap();
+
+        assert!(matches_ds(&dnskey, &ds));
+        assert_eq!(ds, create_ds(&dnskey, DigestType::Sha1).unwrap());
+        assert!(matches_ds(&dnskey, &create_ds(&dnskey, DigestType::Sha256).unwrap()));
+
+        let other: DNSKEY = DNSKEY.parse().unwrap();
+        assert!(!matches_ds(&other, &ds));
+    }
+
+    #[test]
+    fn hash_names() {
+        // Hashes of RFC 5155 appendix A.
+        let salt = [0xAA, 0xBB, 0xCC, 0xDD];
+        assert_eq!("0P9MHAVEQVM6T7VBL5LOP2U3T2RP3TOM", format_base32hex(&nsec3_hash("example", &salt, 12)));
+        assert_eq!("35MTHGPGCU1QG68FAB165KLNSNK3DPVL", format_base32hex(&nsec3_hash("a.example", &salt, 12)));
+        assert_eq!(nsec3_hash("A.Example.", &salt, 12), nsec3_hash("a.example", &salt, 12));
+    }
+
+    fn types(types: &[QueryType]) -> TypeBitmap {
+        types.iter().copied().collect()
+    }
+
+    // NSEC chain of a zone made of the given names and types.
+    fn nsec_chain(names: &[(&str, &[QueryType])]) -> Vec<Record> {
+        let mut names = names.to_vec();
+        names.sort_by(|(name, _), (other, _)| canonical_name_cmp(name, other));
+
+        (0..names.len())
+            .map(|i| Record::NextSecure(NSEC {
+                domain: names[i].0.to_string(),
+                class: Class::Internet,
+                ttl: Duration::from_secs(3600),
+                next_domain: names[(i + 1) % names.len()].0.to_string(),
+                types: types(names[i].1),
+            }))
+            .collect()
+    }
+
+    // NSEC3 chain of a zone made of the given names and types.
+    fn nsec3_chain(zone: &str, names: &[(&str, &[QueryType])], flags: u8) -> Vec<Record> {
+        let mut hashes: Vec<(Vec<u8>, &[QueryType])> = names
+            .iter()
+            .map(|(name, types)| (nsec3_hash(name, &[0xAB], 2), *types))
+            .collect();
+        hashes.sort_by(|(hash, _), (other, _)| hash.cmp(other));
+
+        (0..hashes.len())
+            .map(|i| Record::NextSecure3(NSEC3 {
+                domain: format!("{}.{}", format_base32hex(&hashes[i].0), zone),
+                class: Class::Internet,
+                ttl: Duration::from_secs(3600),
+                hash_algorithm: SHA1,
+                flags,
+                iterations: 2,
+                salt: vec![0xAB],
+                next_hashed_owner: hashes[(i + 1) % hashes.len()].0.clone(),
+                types: types(hashes[i].1),
+            }))
+            .collect()
+    }
+
+    const APEX: &[QueryType] = &[QueryType::StartOfAuthority, QueryType::AuthoritativeNameServer, QueryType::NextSecure];
+    const HOST: &[QueryType] = &[QueryType::A, QueryType::NextSecure];
+    const DELEGATION: &[QueryType] = &[QueryType::AuthoritativeNameServer, QueryType::NextSecure];
+
+    #[test]
+    fn nsec_denials() {
+        let chain = nsec_chain(&[("example", APEX), ("a.example", HOST), ("sub.example", DELEGATION), ("*.w.example", HOST), ("x.w.example", HOST)]);
+
+        assert_eq!(Denial::NoData, verify_denial(&chain, "a.example", QueryType::MailExchange).unwrap());
+        assert_eq!(Denial::NameError, verify_denial(&chain, "b.example", QueryType::A).unwrap());
+        assert_eq!(Denial::NameError, verify_denial(&chain, "zz.example", QueryType::A).unwrap());
+        assert_eq!(Denial::InsecureDelegation, verify_denial(&chain, "sub.example", QueryType::DelegationSigner).unwrap());
+        assert_eq!(Denial::NoData, verify_denial(&chain, "y.w.example", QueryType::MailExchange).unwrap());
+        assert_eq!(Denial::NoData, verify_denial(&chain, "w.example", QueryType::A).unwrap());
+
+        // Existing records, or names matching a wildcard, can't be denied.
+        assert!(verify_denial(&chain, "a.example", QueryType::A).is_err());
+        assert!(verify_denial(&chain, "y.w.example", QueryType::A).is_err());
+        assert!(verify_denial(&chain, "sub.example", QueryType::A).is_err());
+        assert!(verify_denial(&[], "b.example", QueryType::A).is_err());
+
+        // The parent zone proves nothing below its delegations.
+        assert!(verify_denial(&chain, "a.sub.example", QueryType::A).is_err());
+        assert!(closest_encloser(&chain, "a.sub.example").is_err());
+
+        assert_eq!("w.example", closest_encloser(&chain, "y.w.example").unwrap());
+        assert_eq!("example", closest_encloser(&chain, "b.example").unwrap());
+        assert!(chain.iter().any(|record| is_denial_of(record, "b.example")));
+        assert!(!is_denial_of(&mx(10), "b.example"));
+
+        assert!(verify_wildcard_expansion(&chain, "y.w.example", 2).is_ok());
+        assert!(verify_wildcard_expansion(&chain, "x.w.example", 2).is_err());
+        assert!(verify_wildcard_expansion(&chain, "x.w.example", 3).is_ok());
+    }
+
+    #[test]
+    fn nsec_zone() {
+        let chain = nsec_chain(&[("example.org", APEX), ("zz.example.org", HOST)]);
+
+        // The last record of the chain wraps around to the apex, it doesn't
+        // cover the names of other zones.
+        assert_eq!(Denial::NameError, verify_denial(&chain, "zzz.example.org", QueryType::A).unwrap());
+        assert!(verify_denial(&chain, "www.bank.com", QueryType::A).is_err());
+        assert!(verify_denial(&chain, "a.org", QueryType::A).is_err());
+        assert!(!chain.iter().any(|record| is_denial_of(record, "www.bank.com")));
+    }
+
+    #[test]
+    fn nsec3_denials() {
+        let names: &[(&str, &[QueryType])] = &[("example", APEX), ("a.example", HOST), ("sub.example", DELEGATION), ("w.example", &[]), ("*.w.example", HOST)];
+        let chain = nsec3_chain("example", names, 0);
+
+        assert_eq!(Denial::NoData, verify_denial(&chain, "a.example", QueryType::MailExchange).unwrap());
+        assert_eq!(Denial::NameError, verify_denial(&chain, "b.example", QueryType::A).unwrap());
+        assert_eq!(Denial::NameError, verify_denial(&chain, "c.b.example", QueryType::A).unwrap());
+        assert_eq!(Denial::InsecureDelegation, verify_denial(&chain, "sub.example", QueryType::DelegationSigner).unwrap());
+        assert_eq!(Denial::NoData, verify_denial(&chain, "y.w.example", QueryType::MailExchange).unwrap());
+        assert!(verify_denial(&chain, "a.example", QueryType::A).is_err());
+        assert!(verify_denial(&chain, "y.w.example", QueryType::A).is_err());
+        assert!(verify_denial(&chain, "a.sub.example", QueryType::A).is_err());
+
+        assert!(verify_wildcard_expansion(&chain, "y.w.example", 2).is_ok());
+        assert!(verify_wildcard_expansion(&chain, "a.example", 1).is_err());
+
+        assert_eq!("example", closest_encloser(&chain, "c.b.example").unwrap());
+        assert_eq!(1, chain.iter().filter(|record| is_denial_of(record, "c.b.example")).count());
+        assert_eq!(1, chain.iter().filter(|record| is_denial_of(record, "example")).count());
+
+        // Unsigned delegations may be skipped by opt-out chains.
+        let chain = nsec3_chain("example", &names[..2], OPT_OUT);
+        assert_eq!(Denial::InsecureDelegation, verify_denial(&chain, "unsigned.example", QueryType::DelegationSigner).unwrap());
+    }
+
+    #[test]
+    fn nsec3_iterations() {
+        let mut chain = nsec3_chain("example", &[("example", APEX), ("a.example", HOST)], 0);
+        assert!(!has_excessive_iterations(&chain));
+
+        for record in &mut chain {
+            if let Record::NextSecure3(nsec3) = record {
+                nsec3.iterations = MAX_NSEC3_ITERATIONS + 1;
+            }
+        }
+
+        // Records are insecure without computing any hash.
+        assert!(has_excessive_iterations(&chain));
+        assert_eq!(Denial::ExcessiveIterations, verify_denial(&chain, "b.example", QueryType::A).unwrap());
+        assert_eq!(Denial::ExcessiveIterations, verify_denial(&chain, "a.example", QueryType::MailExchange).unwrap());
+        assert!(!chain.iter().any(|record| is_denial_of(record, "b.example")));
+        assert!(closest_encloser(&chain, "b.example").is_err());
+        assert!(verify_wildcard_expansion(&chain, "b.example", 1).is_err());
+    }
+}
@@ -0,0 +1,556 @@
+//! Borrowed, zero-copy view over a message.
+//!
+//! Unlike `Packet::deserialize`, nothing is decoded until asked for:
+//! questions and records are iterated lazily and names are only
+//! decoded when formatted or compared, so that a message can be
+//! inspected and forwarded without allocating.
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::byte_packet_buffer::MAX_POINTER_JUMPS;
+use crate::de::{Deserialize, Deserializer};
+use crate::errors::Error::{InvalidData, OutOfRange};
+use crate::header::Header;
+use crate::packet::{Class, Packet, QueryType, Question, Record};
+use crate::result::Result;
+use crate::seek::Seek;
+
+const HEADER_SIZE: usize = 12;
+
+/// View over a message, only the header is decoded on creation.
+#[derive(Debug, Clone)]
+pub struct MessageView<'a> {
+    data: &'a [u8],
+    header: Header,
+}
+
+impl<'a> MessageView<'a> {
+    pub fn new(data: &'a [u8]) -> Result<MessageView<'a>> {
+        let header = Header::deserialize(&mut Cursor { data, pos: 0 })?;
+
+        Ok(MessageView { data, header })
+    }
+
+    /// Header of the message, its result code only holds the lower 4 bits
+    /// as the upper ones are carried by the OPT record.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn questions(&self) -> Questions<'a> {
+        Questions {
+            data: self.data,
+            pos: HEADER_SIZE,
+            remaining: self.header.total_questions,
+        }
+    }
+
+    pub fn answers(&self) -> Records<'a> {
+        self.records(0, self.header.total_answer_records)
+    }
+
+    pub fn authorities(&self) -> Records<'a> {
+        self.records(self.header.total_answer_records as u32, self.header.total_authority_records)
+    }
+
+    pub fn additionals(&self) -> Records<'a> {
+        let skipped = self.header.total_answer_records as u32 + self.header.total_authority_records as u32;
+        self.records(skipped, self.header.total_additional_records)
+    }
+
+    /// Record of the given type signing the message, such as TSIG or SIG(0),
+    /// which must be the last additional record.
+    pub fn signature(&self, qtype: QueryType) -> Result<Option<RecordView<'a>>> {
+        let count = self.header.total_additional_records as usize;
+
+        let mut found = None;
+        for (i, record) in self.additionals().enumerate() {
+            let record = record?;
+            if record.qtype != qtype {
+                continue;
+            }
+
+            if i + 1 != count {
+                return Err(InvalidData(format!("{} record isn't the last record of the message", qtype)));
+            }
+
+            found = Some(record);
+        }
+
+        Ok(found)
+    }
+
+    /// Decode the whole message.
+    pub fn to_packet(&self) -> Result<Packet> {
+        Packet::deserialize(&mut Cursor { data: self.data, pos: 0 })
+    }
+
+    fn records(&self, skipped: u32, count: u16) -> Records<'a> {
+        Records {
+            data: self.data,
+            pos: HEADER_SIZE,
+            skipped_questions: self.header.total_questions,
+            skipped_records: skipped,
+            remaining: count,
+        }
+    }
+}
+
+/// Name in a message, possibly compressed.
+#[derive(Debug, Clone, Copy)]
+pub struct NameView<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> NameView<'a> {
+    /// Validate the name at the given position of the message, returning it
+    /// along with the position right after it.
+    fn parse(data: &'a [u8], pos: usize) -> Result<(NameView<'a>, usize)> {
+        let mut working_pos = pos;
+        let mut end = None;
+        let mut jumps = 0;
+
+        loop {
+            let len = *data.get(working_pos).ok_or(OutOfRange { expected: working_pos + 1, max: data.len() })?;
+            working_pos += 1;
+
+            match len {
+                0 => break,
+                _ if len & 0xC0 == 0xC0 => {
+                    let lsb = *data.get(working_pos).ok_or(OutOfRange { expected: working_pos + 1, max: data.len() })?;
+                    if end.is_none() {
+                        end = Some(working_pos + 1);
+                    }
+
+                    jumps += 1;
+                    if jumps > MAX_POINTER_JUMPS {
+                        return Err(InvalidData("too many compression pointers in domain name".to_string()));
+                    }
+
+                    working_pos = (((len & 0x3F) as usize) << 8) | lsb as usize;
+                }
+                _ if len & 0xC0 != 0 => {
+                    return Err(InvalidData(format!("unsupported label type {:#04x}", len & 0xC0)));
+                }
+                _ => {
+                    working_pos += len as usize;
+                    if working_pos > data.len() {
+                        return Err(OutOfRange { expected: working_pos, max: data.len() });
+                    }
+                }
+            }
+        }
+
+        Ok((NameView { data, pos }, end.unwrap_or(working_pos)))
+    }
+
+    /// Labels of the name, from the leftmost one, without the root.
+    pub fn labels(&self) -> Labels<'a> {
+        Labels { data: self.data, pos: self.pos }
+    }
+
+    /// Whether the name is the given one, ignoring case.
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.strip_suffix('.').unwrap_or(name);
+        let mut labels = self.labels();
+
+        if !name.is_empty() {
+            for expected in name.split('.') {
+                match labels.next() {
+                    Some(label) if label.eq_ignore_ascii_case(expected.as_bytes()) => {}
+                    _ => return false,
+                }
+            }
+        }
+
+        labels.next().is_none()
+    }
+
+    /// Whether the name is the given one or one of its subdomains, ignoring case.
+    pub fn is_subdomain_of(&self, domain: &str) -> bool {
+        let domain = domain.strip_suffix('.').unwrap_or(domain);
+        if domain.is_empty() {
+            return true;
+        }
+
+        let mut labels = self.labels();
+        let count = labels.clone().count();
+        let expected = domain.split('.').count();
+        if count < expected {
+            return false;
+        }
+
+        for _ in 0..count - expected {
+            labels.next();
+        }
+
+        labels.zip(domain.split('.')).all(|(label, expected)| label.eq_ignore_ascii_case(expected.as_bytes()))
+    }
+}
+
+/// Formats the name the way `Deserializer::deserialize_qname` decodes it.
+impl Display for NameView<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for (i, label) in self.labels().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+
+            f.write_str(&String::from_utf8_lossy(label).to_lowercase())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl PartialEq for NameView<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let mut labels = self.labels();
+        let mut other = other.labels();
+
+        loop {
+            match (labels.next(), other.next()) {
+                (None, None) => return true,
+                (Some(label), Some(other)) if label.eq_ignore_ascii_case(other) => {}
+                _ => return false,
+            }
+        }
+    }
+}
+
+/// Iterator over the labels of a validated name.
+#[derive(Debug, Clone)]
+pub struct Labels<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for Labels<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        loop {
+            let len = *self.data.get(self.pos)?;
+            if len & 0xC0 == 0xC0 {
+                self.pos = (((len & 0x3F) as usize) << 8) | *self.data.get(self.pos + 1)? as usize;
+                continue;
+            }
+
+            if len == 0 {
+                return None;
+            }
+
+            let label = self.data.get(self.pos + 1..self.pos + 1 + len as usize)?;
+            self.pos += 1 + len as usize;
+            return Some(label);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuestionView<'a> {
+    pub name: NameView<'a>,
+    pub qtype: QueryType,
+    pub class: Class,
+}
+
+impl QuestionView<'_> {
+    pub fn to_question(&self) -> Question {
+        Question {
+            name: self.name.to_string(),
+            qtype: self.qtype,
+            class: self.class,
+        }
+    }
+}
+
+/// Iterator over the questions of a message, stopping at the first error.
+#[derive(Debug, Clone)]
+pub struct Questions<'a> {
+    data: &'a [u8],
+    pos: usize,
+    remaining: u16,
+}
+
+impl<'a> Iterator for Questions<'a> {
+    type Item = Result<QuestionView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+        let question = (|| {
+            let (name, pos) = NameView::parse(self.data, self.pos)?;
+            let mut cursor = Cursor { data: self.data, pos };
+            let qtype = QueryType::from_u16(cursor.deserialize_u16()?);
+            let class = Class::from_u16(cursor.deserialize_u16()?);
+            self.pos = cursor.pos;
+
+            Ok(QuestionView { name, qtype, class })
+        })();
+
+        if question.is_err() {
+            self.remaining = 0;
+        }
+
+        Some(question)
+    }
+}
+
+/// Record of a message, with its data left undecoded.
+#[derive(Debug, Clone)]
+pub struct RecordView<'a> {
+    pub name: NameView<'a>,
+    pub qtype: QueryType,
+    // Raw class, which holds the UDP payload size of OPT records.
+    pub class: u16,
+    // Raw TTL, which holds the extended result code and flags of OPT records.
+    pub ttl: u32,
+    pub data: &'a [u8],
+    message: &'a [u8],
+    pos: usize,
+}
+
+impl RecordView<'_> {
+    /// Decode the record.
+    pub fn to_record(&self) -> Result<Record> {
+        Record::deserialize(&mut Cursor { data: self.message, pos: self.pos })
+    }
+
+    /// Position of the record in the message.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+/// Iterator over the records of a section, stopping at the first error.
+#[derive(Debug, Clone)]
+pub struct Records<'a> {
+    data: &'a [u8],
+    pos: usize,
+    // Questions and records of the previous sections, skipped on the first call.
+    skipped_questions: u16,
+    skipped_records: u32,
+    remaining: u16,
+}
+
+impl<'a> Records<'a> {
+    fn skip(&mut self) -> Result<()> {
+        while self.skipped_questions > 0 {
+            let (_, pos) = NameView::parse(self.data, self.pos)?;
+            self.pos = pos + 4;
+            self.skipped_questions -= 1;
+        }
+
+        while self.skipped_records > 0 {
+            self.record()?;
+            self.skipped_records -= 1;
+        }
+
+        Ok(())
+    }
+
+    fn record(&mut self) -> Result<RecordView<'a>> {
+        let start = self.pos;
+        let (name, pos) = NameView::parse(self.data, self.pos)?;
+        let mut cursor = Cursor { data: self.data, pos };
+        let qtype = QueryType::from_u16(cursor.deserialize_u16()?);
+        let class = cursor.deserialize_u16()?;
+        let ttl = cursor.deserialize_u32()?;
+        let len = cursor.deserialize_u16()? as usize;
+
+        let data = self.data.get(cursor.pos..cursor.pos + len)
+            .ok_or(OutOfRange { expected: cursor.pos + len, max: self.data.len() })?;
+        self.pos = cursor.pos + len;
+
+        Ok(RecordView { name, qtype, class, ttl, data, message: self.data, pos: start })
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Result<RecordView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+        let record = self.skip().and_then(|_| self.record());
+        if record.is_err() {
+            self.remaining = 0;
+        }
+
+        Some(record)
+    }
+}
+
+// Deserializer over the whole message, so that decoding isn't bound to the
+// size of a `BytePacketBuffer`.
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Deserializer for Cursor<'_> {
+    fn deserialize_u8(&mut self) -> Result<u8> {
+        let byte = *self.data.get(self.pos).ok_or(OutOfRange { expected: self.pos + 1, max: self.data.len() })?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn deserialize_u16(&mut self) -> Result<u16> {
+        Ok(((self.deserialize_u8()? as u16) << 8) | self.deserialize_u8()? as u16)
+    }
+
+    fn deserialize_u32(&mut self) -> Result<u32> {
+        Ok(((self.deserialize_u16()? as u32) << 16) | self.deserialize_u16()? as u32)
+    }
+
+    fn deserialize_qname(&mut self) -> Result<String> {
+        let (name, end) = NameView::parse(self.data, self.pos)?;
+        self.pos = end;
+        Ok(name.to_string())
+    }
+
+    fn deserialize_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        let bytes = self.data.get(self.pos..self.pos + len)
+            .ok_or(OutOfRange { expected: self.pos + len, max: self.data.len() })?;
+        self.pos += len;
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Seek for Cursor<'_> {
+    fn seek(&mut self, pos: usize) -> Result<()> {
+        if pos > self.data.len() {
+            return Err(OutOfRange { expected: pos, max: self.data.len() });
+        }
+
+        self.pos = pos;
+        Ok(())
+    }
+
+    fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+
+    use crate::packet::{Class, QueryType, Record};
+    use crate::view::MessageView;
+
+    const RESPONSE: &[u8] = &[
+        0x86, 0x2a, 0x81, 0x80, // Header.
+        0x00, 0x01, // 1 question.
+        0x00, 0x02, // 2 answers.
+        0x00, 0x00, // 0 authority.
+        0x00, 0x01, // 1 additional.
+        0x03, 0x57, 0x57, 0x57, // len=3 label="WWW"
+        0x07, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, // len=7 label="example"
+        0x03, 0x63, 0x6f, 0x6d, // len=3 label="com"
+        0x00,
+        0x00, 0x01, // Type A.
+        0x00, 0x01, // Class IN.
+        0xC0, 0x0C, // Pointer to the question name.
+        0x00, 0x01, // Type A.
+        0x00, 0x01, // Class IN.
+        0x00, 0x00, 0x00, 0x3C, // TTL.
+        0x00, 0x04, // RD length.
+        0x7F, 0x00, 0x00, 0x01, // Address.
+        0xC0, 0x0C, // Pointer to the question name.
+        0x00, 0x01, // Type A.
+        0x00, 0x01, // Class IN.
+        0x00, 0x00, 0x00, 0x3C, // TTL.
+        0x00, 0x04, // RD length.
+        0x7F, 0x00, 0x00, 0x02, // Address.
+        0x00, // Root.
+        0x00, 0x29, // Type OPT.
+        0x04, 0xD0, // UDP payload size.
+        0x00, 0x00, 0x00, 0x00, // Extended RCODE and flags.
+        0x00, 0x00, // RD length.
+    ];
+
+    #[test]
+    fn view_message() {
+        let view = MessageView::new(RESPONSE).unwrap();
+        assert_eq!(0x862a, view.header().id);
+
+        let questions = view.questions().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(1, questions.len());
+        assert!(questions[0].name.matches("www.example.com."));
+        assert!(!questions[0].name.matches("example.com"));
+        assert!(questions[0].name.is_subdomain_of("Example.com"));
+        assert!(!questions[0].name.is_subdomain_of("ample.com"));
+        assert_eq!("www.example.com", questions[0].name.to_string());
+        assert_eq!(QueryType::A, questions[0].qtype);
+        assert_eq!(Class::Internet, questions[0].class);
+
+        let answers = view.answers().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(2, answers.len());
+        assert_eq!(questions[0].name, answers[1].name);
+        assert_eq!(&[0x7F, 0x00, 0x00, 0x02], answers[1].data);
+        match answers[1].to_record().unwrap() {
+            Record::A(a) => assert_eq!(Ipv4Addr::new(127, 0, 0, 2), a.ip),
+            record => panic!("unexpected record {:?}", record),
+        }
+
+        assert_eq!(0, view.authorities().count());
+
+        let additionals = view.additionals().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(1, additionals.len());
+        assert_eq!(QueryType::Opt, additionals[0].qtype);
+        assert_eq!(1232, additionals[0].class);
+        assert!(additionals[0].name.matches("."));
+    }
+
+    #[test]
+    fn view_to_packet() {
+        let packet = MessageView::new(RESPONSE).unwrap().to_packet().unwrap();
+        assert_eq!("www.example.com", packet.questions[0].name);
+        assert_eq!(2, packet.answers.len());
+        assert_eq!(1232, packet.edns().unwrap().udp_payload_size);
+    }
+
+    #[test]
+    fn view_truncated_message() {
+        let view = MessageView::new(&RESPONSE[..50]).unwrap();
+        assert!(view.questions().all(|question| question.is_ok()));
+
+        let mut answers = view.answers();
+        assert!(answers.next().unwrap().is_ok());
+        assert!(answers.next().unwrap().is_err());
+        assert!(answers.next().is_none());
+
+        assert!(view.additionals().next().unwrap().is_err());
+        assert!(view.to_packet().is_err());
+    }
+
+    #[test]
+    fn view_pointer_loop() {
+        let view = MessageView::new(&[
+            0x00, 0x00, 0x00, 0x00, // Header.
+            0x00, 0x01, // 1 question.
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xC0, 0x0C, // Pointer to itself.
+            0x00, 0x01, 0x00, 0x01,
+        ]).unwrap();
+
+        assert!(view.questions().next().unwrap().is_err());
+    }
+
+    #[test]
+    fn view_short_header() {
+        assert!(MessageView::new(&[0x00; 11]).is_err());
+    }
+}
@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use protocol::header::ResultCode;
+use protocol::packet::{Packet, QueryType, Record};
+use protocol::records::dnssec::label_count;
+use protocol::records::RRSIG;
+use protocol::rrset::RRset;
+use protocol::validation::{self, Denial};
+
+use crate::validator::is_subdomain;
+
+// Maximum number of RRsets kept, beyond which new ones are dropped until
+// some expire.
+const MAX_ENTRIES: usize = 10_000;
+
+/// Validated NSEC and NSEC3 records, along with the SOA records and the
+/// wildcard RRsets of their zones, answering queries for the names and
+/// types they deny without asking upstream (RFC 8198).
+///
+/// Queries for random subdomains of a signed zone are then answered from
+/// the few records of its denial chain.
+pub struct DenialCache {
+    // Entries of the zones, by apex.
+    zones: Mutex<HashMap<String, Vec<Entry>>>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    rrset: RRset,
+    rrsigs: Vec<RRSIG>,
+    expiration: Instant,
+}
+
+impl DenialCache {
+    pub fn new() -> DenialCache {
+        DenialCache {
+            zones: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remember the SOA, NSEC and NSEC3 RRsets of an authority section
+    /// whose signatures have been verified.
+    ///
+    /// Opt-out NSEC3 records are left out as they may cover unsigned
+    /// delegations, whose names exist.
+    pub fn insert_proofs(&self, authorities: &[Record]) {
+        let rrsigs = signatures(authorities);
+        let negative_ttl = authorities.iter().find_map(|record| match record {
+            Record::StartOfAuthority(soa) => Some(soa.negative_ttl()),
+            _ => None,
+        });
+
+        let rrsets = RRset::group(authorities.iter().filter(|record| !matches!(record, Record::Signature(_))).cloned())
+            .into_iter()
+            .filter(|rrset| matches!(rrset.qtype, QueryType::StartOfAuthority | QueryType::NextSecure | QueryType::NextSecure3))
+            .filter(|rrset| !rrset.records().iter().any(|record| matches!(record, Record::NextSecure3(nsec3) if nsec3.is_opt_out())));
+
+        for rrset in rrsets {
+            // Denials are cached no longer than negative answers (RFC 9077).
+            let ttl = match (rrset.qtype, negative_ttl) {
+                (QueryType::StartOfAuthority, _) | (_, None) => rrset.ttl(),
+                (_, Some(negative_ttl)) => rrset.ttl().min(negative_ttl),
+            };
+
+            let covering = covering(&rrsigs, &rrset);
+            self.insert(rrset, covering, ttl);
+        }
+    }
+
+    /// Remember a verified RRset expanded from a wildcard, under the name
+    /// of the wildcard given the labels of its signature.
+    pub fn insert_wildcard(&self, rrset: &RRset, rrsig: &RRSIG) {
+        let labels: Vec<&str> = rrset.domain.split('.').filter(|label| !label.is_empty()).collect();
+        if labels.len() <= rrsig.labels as usize {
+            return;
+        }
+
+        let wildcard = format!("*.{}", labels[labels.len() - rrsig.labels as usize..].join("."));
+        let records = rename(rrset.records(), &wildcard);
+        let mut rrsig = rrsig.clone();
+        rrsig.domain = wildcard;
+
+        if let Some(rrset) = RRset::group(records).pop() {
+            let ttl = rrset.ttl();
+            self.insert(rrset, vec![rrsig], ttl);
+        }
+    }
+
+    /// Answer a query from the cached records of the closest zone of the
+    /// name, when they prove that the name or type doesn't exist or that
+    /// a cached wildcard matches the name.
+    ///
+    /// DS records of a zone are held by its parent, the NSEC records of the
+    /// apex of the zone itself never denying them (RFC 4035 section 3.1.4.1).
+    pub fn synthesize(&self, qname: &str, qtype: QueryType) -> Option<Packet> {
+        let now = Instant::now();
+        let zones = self.zones.lock().unwrap();
+
+        let (apex, entries) = zones
+            .iter()
+            .filter(|(apex, _)| is_subdomain(qname, apex))
+            .filter(|(apex, _)| qtype != QueryType::DelegationSigner || !qname.trim_end_matches('.').eq_ignore_ascii_case(apex))
+            .max_by_key(|(apex, _)| label_count(apex))?;
+        let entries: Vec<&Entry> = entries.iter().filter(|entry| entry.expiration > now).collect();
+
+        let soa = entries
+            .iter()
+            .find(|entry| entry.rrset.qtype == QueryType::StartOfAuthority && entry.rrset.domain.eq_ignore_ascii_case(apex))?;
+
+        // Records which match or cover the name, its ancestors within the
+        // zone or the wildcards below them.
+        let labels: Vec<&str> = qname.split('.').filter(|label| !label.is_empty()).collect();
+        let names: Vec<String> = (0..labels.len())
+            .map(|i| labels[i..].join("."))
+            .filter(|name| is_subdomain(name, apex))
+            .flat_map(|name| vec![format!("*.{}", name), name])
+            .collect();
+
+        let proofs: Vec<&Entry> = entries
+            .iter()
+            .copied()
+            .filter(|entry| matches!(entry.rrset.qtype, QueryType::NextSecure | QueryType::NextSecure3))
+            .filter(|entry| {
+                entry.rrset
+                    .records()
+                    .iter()
+                    .any(|record| names.iter().any(|name| validation::is_denial_of(record, name)))
+            })
+            .collect();
+        let records: Vec<Record> = proofs.iter().flat_map(|entry| entry.rrset.records().iter().cloned()).collect();
+
+        // The name doesn't exist but a wildcard of the same type matches it.
+        if let Ok(encloser) = validation::closest_encloser(&records, qname) {
+            let wildcard = format!("*.{}", encloser);
+            let expanded = entries
+                .iter()
+                .find(|entry| entry.rrset.qtype == qtype && entry.rrset.domain.eq_ignore_ascii_case(&wildcard));
+
+            if let Some(expanded) = expanded {
+                let answers = rename(&expanded.records(now), qname);
+                return Some(response(qname, qtype, ResultCode::NoError, answers, proofs.iter().flat_map(|entry| entry.records(now))));
+            }
+        }
+
+        let rcode = match validation::verify_denial(&records, qname, qtype).ok()? {
+            Denial::NameError => ResultCode::NxDomain,
+            Denial::NoData | Denial::InsecureDelegation => ResultCode::NoError,
+            Denial::OptOut | Denial::ExcessiveIterations => return None,
+        };
+
+        let authorities = soa.records(now).into_iter().chain(proofs.iter().flat_map(|entry| entry.records(now)));
+        Some(response(qname, qtype, rcode, vec![], authorities))
+    }
+
+    // Cache an RRset under the zone of its signer, replacing the previous
+    // version of the RRset. Unsigned RRsets and those outside of the zone
+    // of their signer are dropped.
+    fn insert(&self, rrset: RRset, rrsigs: Vec<RRSIG>, ttl: Duration) {
+        let apex = match rrsigs.first() {
+            Some(rrsig) => rrsig.signer_name.trim_end_matches('.').to_lowercase(),
+            None => return,
+        };
+
+        if !is_subdomain(&rrset.domain, &apex) {
+            return;
+        }
+
+        let rrsigs = rrsigs
+            .into_iter()
+            .filter(|rrsig| rrsig.signer_name.trim_end_matches('.').eq_ignore_ascii_case(&apex))
+            .collect();
+
+        let now = Instant::now();
+        let mut zones = self.zones.lock().unwrap();
+        for entries in zones.values_mut() {
+            entries.retain(|entry| entry.expiration > now);
+        }
+        zones.retain(|_, entries| !entries.is_empty());
+
+        let size: usize = zones.values().map(Vec::len).sum();
+        let entries = zones.entry(apex).or_default();
+        entries.retain(|entry| !(entry.rrset.qtype == rrset.qtype && entry.rrset.domain.eq_ignore_ascii_case(&rrset.domain)));
+
+        if size < MAX_ENTRIES {
+            entries.push(Entry {
+                rrset,
+                rrsigs,
+                expiration: now + ttl,
+            });
+        }
+    }
+}
+
+impl Entry {
+    // Records of the RRset followed by its signatures, with the time left
+    // before they expire as TTL.
+    fn records(&self, now: Instant) -> Vec<Record> {
+        let ttl = self.expiration.saturating_duration_since(now);
+        let mut records: Vec<Record> = self.rrset
+            .records()
+            .iter()
+            .cloned()
+            .chain(self.rrsigs.iter().cloned().map(Record::Signature))
+            .collect();
+
+        for record in &mut records {
+            record.set_ttl(Duration::from_secs(ttl.as_secs()).min(record.ttl()));
+        }
+
+        records
+    }
+}
+
+fn response<I>(qname: &str, qtype: QueryType, rcode: ResultCode, answers: Vec<Record>, authorities: I) -> Packet
+    where I: IntoIterator<Item=Record>
+{
+    let mut response = Packet::query(qname, qtype).build().reply().rcode(rcode).answers(answers).authorities(authorities).build();
+    response.header.authenticated_data = true;
+    response
+}
+
+// Copies of the records, and of the signatures among them, owned by the
+// given name.
+fn rename(records: &[Record], name: &str) -> Vec<Record> {
+    records
+        .iter()
+        .cloned()
+        .map(|mut record| {
+            if let Some(domain) = record.domain_mut() {
+                *domain = name.to_string();
+            }
+
+            record
+        })
+        .collect()
+}
+
+fn signatures(records: &[Record]) -> Vec<&RRSIG> {
+    records
+        .iter()
+        .filter_map(|record| match record {
+            Record::Signature(rrsig) => Some(rrsig),
+            _ => None,
+        })
+        .collect()
+}
+
+fn covering(rrsigs: &[&RRSIG], rrset: &RRset) -> Vec<RRSIG> {
+    rrsigs
+        .iter()
+        .filter(|rrsig| rrsig.type_covered == rrset.qtype && rrsig.domain.eq_ignore_ascii_case(&rrset.domain))
+        .map(|rrsig| (*rrsig).clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use protocol::header::ResultCode;
+    use protocol::packet::{Class, QueryType, Record};
+    use protocol::records::{AuthoritativeNameServer, A, SOA};
+    use protocol::rrset::RRset;
+    use protocol::signing::{DenialChain, ZoneKey, ZoneSigner};
+
+    use crate::denial_cache::DenialCache;
+
+    // Key of RFC 8080 section 6.1, signing the whole zone.
+    const KEY_FILE: &str = "example.com. 3600 IN DNSKEY 257 3 15 l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=";
+    const PRIVATE_FILE: &str = "Private-key-format: v1.2\n\
+        Algorithm: 15 (ED25519)\n\
+        PrivateKey: ODIyNjAzODQ2MjgwODAxMjI2NDUxOTAyMDQxNDIyNjI=\n";
+
+    fn a(domain: &str) -> Record {
+        Record::A(A {
+            domain: domain.to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(3600),
+            ip: Ipv4Addr::new(192, 0, 2, 1),
+        })
+    }
+
+    fn signed_zone(chain: DenialChain) -> Vec<Record> {
+        let soa: SOA = "example.com. 3600 IN SOA ns.example.com. admin.example.com. 1 7200 3600 1209600 300".parse().unwrap();
+        let records = vec![
+            Record::StartOfAuthority(soa),
+            Record::AuthoritativeNameServer(AuthoritativeNameServer {
+                domain: "sub.example.com".to_string(),
+                class: Class::Internet,
+                ttl: Duration::from_secs(3600),
+                ns_name: "ns.sub.example.com".to_string(),
+            }),
+            a("ns.example.com"),
+            a("www.example.com"),
+            a("*.wild.example.com"),
+        ];
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as u32;
+        let key = ZoneKey::parse(KEY_FILE, PRIVATE_FILE).unwrap();
+        ZoneSigner::new("example.com", vec![key]).chain(chain).sign_zone(records, now).unwrap()
+    }
+
+    // Authority section holding every denial record of the zone.
+    fn authorities(records: &[Record]) -> Vec<Record> {
+        records
+            .iter()
+            .filter(|record| match record {
+                Record::Signature(rrsig) => {
+                    matches!(rrsig.type_covered, QueryType::StartOfAuthority | QueryType::NextSecure | QueryType::NextSecure3)
+                }
+                record => matches!(record.qtype(), QueryType::StartOfAuthority | QueryType::NextSecure | QueryType::NextSecure3),
+            })
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn nsec_denials() {
+        let cache = DenialCache::new();
+        assert!(cache.synthesize("missing.example.com", QueryType::A).is_none());
+
+        cache.insert_proofs(&authorities(&signed_zone(DenialChain::Nsec)));
+
+        let response = cache.synthesize("missing.example.com", QueryType::A).unwrap();
+        assert_eq!(response.header.result_code, ResultCode::NxDomain);
+        assert!(response.header.authenticated_data);
+        assert!(response.answers.is_empty());
+        assert!(response.authorities.iter().any(|record| record.qtype() == QueryType::StartOfAuthority));
+        assert!(response.authorities.iter().any(|record| record.qtype() == QueryType::NextSecure));
+        assert!(response.authorities.iter().any(|record| record.qtype() == QueryType::Signature));
+        assert!(response.authorities.iter().all(|record| record.ttl() <= Duration::from_secs(3600)));
+
+        let response = cache.synthesize("www.example.com", QueryType::MailExchange).unwrap();
+        assert_eq!(response.header.result_code, ResultCode::NoError);
+        assert!(response.answers.is_empty());
+
+        // Existing records, names below delegations and other zones are
+        // left to upstream.
+        assert!(cache.synthesize("www.example.com", QueryType::A).is_none());
+        assert!(cache.synthesize("host.sub.example.com", QueryType::A).is_none());
+        assert!(cache.synthesize("missing.example.org", QueryType::A).is_none());
+    }
+
+    #[test]
+    fn delegation_signers() {
+        let cache = DenialCache::new();
+        cache.insert_proofs(&authorities(&signed_zone(DenialChain::Nsec)));
+
+        // The apex NSEC record of a zone doesn't deny its DS records, which
+        // are left to its parent.
+        assert!(cache.synthesize("example.com", QueryType::DelegationSigner).is_none());
+        assert!(cache.synthesize("example.com.", QueryType::DelegationSigner).is_none());
+        assert_eq!(ResultCode::NoError, cache.synthesize("example.com", QueryType::MailExchange).unwrap().header.result_code);
+
+        // Delegations of the zone are denied DS records by the zone itself.
+        let response = cache.synthesize("sub.example.com", QueryType::DelegationSigner).unwrap();
+        assert_eq!(response.header.result_code, ResultCode::NoError);
+        assert!(response.answers.is_empty());
+    }
+
+    #[test]
+    fn nsec3_denials() {
+        let cache = DenialCache::new();
+        cache.insert_proofs(&authorities(&signed_zone(DenialChain::Nsec3 { iterations: 0, salt: vec![], opt_out: false })));
+
+        let response = cache.synthesize("missing.example.com", QueryType::A).unwrap();
+        assert_eq!(response.header.result_code, ResultCode::NxDomain);
+        assert!(response.authorities.iter().any(|record| record.qtype() == QueryType::NextSecure3));
+
+        let response = cache.synthesize("www.example.com", QueryType::MailExchange).unwrap();
+        assert_eq!(response.header.result_code, ResultCode::NoError);
+        assert!(cache.synthesize("www.example.com", QueryType::A).is_none());
+
+        // Opt-out records may cover unsigned delegations.
+        let cache = DenialCache::new();
+        cache.insert_proofs(&authorities(&signed_zone(DenialChain::Nsec3 { iterations: 0, salt: vec![], opt_out: true })));
+        assert!(cache.synthesize("missing.example.com", QueryType::A).is_none());
+    }
+
+    #[test]
+    fn wildcard() {
+        let records = signed_zone(DenialChain::Nsec);
+        let cache = DenialCache::new();
+        cache.insert_proofs(&authorities(&records));
+
+        // Without the wildcard RRset, names matching it can't be answered.
+        assert!(cache.synthesize("host.wild.example.com", QueryType::A).is_none());
+
+        let rrsig = records
+            .iter()
+            .find_map(|record| match record {
+                Record::Signature(rrsig) if rrsig.domain == "*.wild.example.com" && rrsig.type_covered == QueryType::A => Some(rrsig),
+                _ => None,
+            })
+            .unwrap();
+        let rrset = RRset::group(vec![a("other.wild.example.com")]).pop().unwrap();
+        cache.insert_wildcard(&rrset, rrsig);
+
+        let response = cache.synthesize("host.wild.example.com", QueryType::A).unwrap();
+        assert_eq!(response.header.result_code, ResultCode::NoError);
+        assert_eq!(response.answers.len(), 2);
+        assert!(response.answers.iter().all(|record| record.domain() == "host.wild.example.com"));
+        assert!(response.answers.iter().any(|record| matches!(record, Record::Signature(rrsig) if rrsig.labels == 3)));
+
+        let response = cache.synthesize("host.wild.example.com", QueryType::MailExchange).unwrap();
+        assert_eq!(response.header.result_code, ResultCode::NoError);
+        assert!(response.answers.is_empty());
+    }
+
+    #[test]
+    fn unverified_denials() {
+        let records = authorities(&signed_zone(DenialChain::Nsec));
+
+        // Records without signatures are never cached.
+        let cache = DenialCache::new();
+        let unsigned: Vec<Record> = records.iter().filter(|record| record.qtype() != QueryType::Signature).cloned().collect();
+        cache.insert_proofs(&unsigned);
+        assert!(cache.synthesize("missing.example.com", QueryType::A).is_none());
+
+        // Nor are records whose signatures claim an unrelated signer zone.
+        let cache = DenialCache::new();
+        let spoofed: Vec<Record> = records
+            .iter()
+            .cloned()
+            .map(|mut record| {
+                if let Record::Signature(rrsig) = &mut record {
+                    rrsig.signer_name = "example.org".to_string();
+                }
+
+                record
+            })
+            .collect();
+        cache.insert_proofs(&spoofed);
+        assert!(cache.synthesize("missing.example.com", QueryType::A).is_none());
+        assert!(cache.synthesize("missing.example.org", QueryType::A).is_none());
+    }
+}
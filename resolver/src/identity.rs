@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use protocol::builder::PacketBuilder;
+use protocol::header::ResultCode;
+use protocol::packet::{Class, QueryType, Question, Record};
+use protocol::records::TXT;
+
+/// Values returned to the CHAOS class TXT queries that operators use
+/// to identify which server answered (RFC 4892).
+///
+/// Every value is optional, a disabled value makes the server refuse
+/// the corresponding query instead of answering it.
+#[derive(Debug, Default)]
+pub struct ServerIdentity {
+    // Answer for `version.bind` and `version.server`.
+    pub version: Option<String>,
+
+    // Answer for `hostname.bind` and `id.server`.
+    pub id: Option<String>,
+}
+
+impl ServerIdentity {
+    /// Answer a CHAOS class question of the request being replied.
+    ///
+    /// Queries for unknown names, disabled values or types other than
+    /// TXT (or ANY) are refused: CHAOS questions are never forwarded.
+    pub fn answer(&self, question: &Question, reply: PacketBuilder) -> PacketBuilder {
+        let reply = reply.authoritative(true);
+
+        // Names are compared case-insensitively, fully qualified or not.
+        let value = match question.name.trim_end_matches('.').to_ascii_lowercase().as_str() {
+            "version.bind" | "version.server" => self.version.as_ref(),
+            "hostname.bind" | "id.server" => self.id.as_ref(),
+            _ => None,
+        };
+
+        match (value, question.qtype) {
+            (Some(value), QueryType::Text) | (Some(value), QueryType::Unknown(255)) => {
+                reply.answer(Record::Text(TXT::from_long_value(
+                    &question.name,
+                    Class::Chaos,
+                    Duration::from_secs(0),
+                    value.as_bytes(),
+                )))
+            }
+            _ => reply.rcode(ResultCode::Refused),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use protocol::header::ResultCode;
+    use protocol::packet::{Class, Packet, QueryType, Record};
+
+    use crate::identity::ServerIdentity;
+
+    fn answer(identity: &ServerIdentity, name: &str, qtype: QueryType) -> Packet {
+        let request = Packet::query(name, qtype).class(Class::Chaos).build();
+        identity.answer(&request.questions[0], request.reply()).build()
+    }
+
+    #[test]
+    fn answer_version() {
+        let identity = ServerIdentity {
+            version: Some("dns-rs".to_string()),
+            id: None,
+        };
+
+        let response = answer(&identity, "version.bind", QueryType::Text);
+        assert_eq!(ResultCode::NoError, response.header.result_code);
+        assert!(response.header.authoritative_answer);
+
+        match &response.answers[..] {
+            [Record::Text(txt)] => {
+                assert_eq!("version.bind", txt.domain);
+                assert_eq!(Class::Chaos, txt.class);
+                assert_eq!(vec![b"dns-rs".to_vec()], txt.strings);
+            }
+            answers => panic!("unexpected answers: {:?}", answers),
+        }
+    }
+
+    #[test]
+    fn refuse_disabled_value() {
+        let identity = ServerIdentity {
+            version: Some("dns-rs".to_string()),
+            id: None,
+        };
+
+        let response = answer(&identity, "id.server", QueryType::Text);
+        assert_eq!(ResultCode::Refused, response.header.result_code);
+        assert!(response.answers.is_empty());
+    }
+
+    #[test]
+    fn refuse_unknown_name() {
+        let identity = ServerIdentity {
+            version: Some("dns-rs".to_string()),
+            id: Some("ns1".to_string()),
+        };
+
+        let response = answer(&identity, "authors.bind", QueryType::Text);
+        assert_eq!(ResultCode::Refused, response.header.result_code);
+    }
+
+    #[test]
+    fn answer_any_case() {
+        let identity = ServerIdentity {
+            version: Some("dns-rs".to_string()),
+            id: Some("ns1".to_string()),
+        };
+
+        let response = answer(&identity, "VERSION.Bind", QueryType::Text);
+        assert_eq!(ResultCode::NoError, response.header.result_code);
+        assert_eq!("VERSION.Bind", response.answers[0].domain());
+
+        let response = answer(&identity, "id.server.", QueryType::Text);
+        assert_eq!(ResultCode::NoError, response.header.result_code);
+    }
+}
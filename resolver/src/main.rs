@@ -1,23 +1,83 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use anyhow::Result;
 use structopt::StructOpt;
 use tokio::net::UdpSocket;
 
+use protocol::signing::DenialChain;
+use protocol::tsig::TsigKey;
+
+use crate::identity::ServerIdentity;
 use crate::resolver::Resolver;
 use crate::server::Listener;
+use crate::trust_anchors::TrustAnchors;
+use crate::validator::now;
+use crate::zones::Zones;
 
+mod denial_cache;
+mod identity;
 mod resolver;
 mod server;
+mod trust_anchors;
+mod validator;
+mod zones;
+
+// Interval at which the signatures of the served zones are checked, zones
+// being signed again a week before their signatures expire.
+const ZONE_REFRESH: Duration = Duration::from_secs(3600);
 
-#[derive(Debug, StructOpt, Copy, Clone)]
+#[derive(Debug, StructOpt, Clone)]
 #[structopt(name = "DNS Server", about = "An example of StructOpt usage.")]
 struct ServerOptions {
     #[structopt(short, long)]
     bind_addr: SocketAddr,
     #[structopt(long)]
     no_recursive: bool,
+    /// Value answered to `hostname.bind` and `id.server` CHAOS queries.
+    /// Those queries are refused when not set.
+    #[structopt(long)]
+    server_id: Option<String>,
+    /// Value answered to `version.bind` and `version.server` CHAOS queries.
+    #[structopt(long, default_value = concat!("dns-rs ", env!("CARGO_PKG_VERSION")))]
+    server_version: String,
+    /// Refuse `version.bind` and `version.server` CHAOS queries.
+    #[structopt(long)]
+    hide_version: bool,
+    /// Don't validate responses with DNSSEC.
+    #[structopt(long)]
+    no_dnssec: bool,
+    /// File of DS or DNSKEY trust anchors, replacing the built-in root
+    /// trust anchors. Key rollovers are tracked and saved to it.
+    #[structopt(long, parse(from_os_str))]
+    trust_anchors: Option<PathBuf>,
+    /// Always ask upstream, rather than answering from validated NSEC and
+    /// NSEC3 records the queries they deny.
+    #[structopt(long)]
+    no_aggressive_nsec: bool,
+    /// Key authenticating the queries of other servers, given as
+    /// `[algorithm:]name:secret` with a base64 secret, can be repeated.
+    /// Signed queries are answered with signed responses, zone transfers,
+    /// NOTIFY and UPDATE not being supported.
+    #[structopt(long = "tsig-key")]
+    tsig_keys: Vec<TsigKey>,
+    /// Zone file served authoritatively, with one record per line, can be
+    /// repeated. Zones are signed with the keys of their apex.
+    #[structopt(long = "zone", parse(from_os_str))]
+    zones: Vec<PathBuf>,
+    /// Key signing the zones, given as the path of BIND key files without
+    /// their `.key` and `.private` extensions, can be repeated.
+    #[structopt(long = "zone-key", parse(from_os_str))]
+    zone_keys: Vec<PathBuf>,
+    /// Deny the existence of names with NSEC3 rather than NSEC records.
+    #[structopt(long)]
+    nsec3: bool,
+    /// Leave unsigned delegations out of the NSEC3 chains.
+    #[structopt(long, requires = "nsec3")]
+    nsec3_opt_out: bool,
 }
 
 #[tokio::main]
@@ -29,12 +89,54 @@ async fn main() -> Result<()> {
 
     // Create an UDP socket and bind it to the given bind address.
     let socket = UdpSocket::bind(opt.bind_addr).await?;
-    let resolver = Resolver::builder().recursive(!opt.no_recursive).build();
+    let mut resolver = Resolver::builder()
+        .recursive(!opt.no_recursive)
+        .dnssec(!opt.no_dnssec)
+        .aggressive_nsec(!opt.no_aggressive_nsec);
+    if let Some(path) = &opt.trust_anchors {
+        resolver = resolver.trust_anchors(TrustAnchors::load(path)?);
+    }
+    let resolver = Arc::new(resolver.build());
+
+    // Keys of the anchored zones are refreshed in the background, so that
+    // their rollovers are followed (RFC 5011 section 2.3). Lookups block,
+    // the refresh runs on a thread of its own.
+    let refreshed = resolver.clone();
+    thread::spawn(move || {
+        while let Some(delay) = refreshed.refresh_trust_anchors() {
+            thread::sleep(delay);
+        }
+    });
+
+    // NSEC3 chains use no extra iterations nor salt (RFC 9276 section 3.1).
+    let chain = match opt.nsec3 {
+        true => DenialChain::Nsec3 { iterations: 0, salt: vec![], opt_out: opt.nsec3_opt_out },
+        false => DenialChain::Nsec,
+    };
+    let zones = Arc::new(Zones::load(&opt.zones, &opt.zone_keys, chain, now())?);
+
+    // Zones are signed again in the background before their signatures
+    // expire.
+    let signed = zones.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ZONE_REFRESH);
+        loop {
+            interval.tick().await;
+            signed.refresh(now());
+        }
+    });
 
+    let identity = ServerIdentity {
+        version: if opt.hide_version { None } else { Some(opt.server_version) },
+        id: opt.server_id,
+    };
 
     let listener = Listener {
         socket: Arc::new(socket),
-        resolver: Arc::new(resolver),
+        resolver,
+        zones,
+        identity: Arc::new(identity),
+        tsig_keys: Arc::new(opt.tsig_keys),
     };
 
     listener.run().await
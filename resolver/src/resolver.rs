@@ -1,11 +1,20 @@
-use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
-
-use anyhow::Result;
-
-use protocol::byte_packet_buffer::BytePacketBuffer;
-use protocol::header::{Header, OpCode, ResultCode};
-use protocol::packet::{Packet, QueryType, Question, Record};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, UdpSocket};
+use std::time::Duration;
+
+use tracing::warn;
+
+use protocol::growable_buffer::GrowableBuffer;
+use protocol::header::ResultCode;
+use protocol::packet::{Packet, QueryType, Record};
+use protocol::records::opt::{EdnsOption, ExtendedErrorCode};
+use protocol::records::OPT;
 use protocol::ser::Serialize;
+use protocol::view::MessageView;
+
+use crate::trust_anchors::TrustAnchors;
+use crate::validator::{Security, Validator};
 
 // https://www.internic.net/domain/named.root
 const ROOT_SERVERS: &[(&str, [u8; 4])] = &[
@@ -24,9 +33,79 @@ const ROOT_SERVERS: &[(&str, [u8; 4])] = &[
     ("m.root-servers.net", [202, 12, 27, 33]),
 ];
 
+// Time to wait for an upstream server response.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+// UDP payload size advertised to upstream servers, avoiding IP
+// fragmentation (https://www.dnsflagday.net/2020/).
+const UDP_PAYLOAD_SIZE: u16 = 1232;
+
+type Result<T> = std::result::Result<T, ResolveError>;
+
+/// Reasons for a resolution to fail, reported to clients with an
+/// extended DNS error (RFC 8914).
+#[derive(Debug)]
+pub enum ResolveError {
+    // No authoritative server could be found or reached.
+    NoReachableAuthority(String),
+    // Exchanging with an upstream server failed or timed out.
+    Network(io::Error),
+    // An upstream response couldn't be parsed.
+    InvalidData(protocol::errors::Error),
+    // The DNSSEC validation of an upstream response failed.
+    Bogus(String),
+}
+
+impl ResolveError {
+    pub fn result_code(&self) -> ResultCode {
+        ResultCode::ServerFailure
+    }
+
+    pub fn extended_error(&self) -> EdnsOption {
+        let info_code = match self {
+            ResolveError::NoReachableAuthority(_) => ExtendedErrorCode::NoReachableAuthority,
+            ResolveError::Network(_) => ExtendedErrorCode::NetworkError,
+            ResolveError::InvalidData(_) => ExtendedErrorCode::InvalidData,
+            ResolveError::Bogus(_) => ExtendedErrorCode::DnssecBogus,
+        };
+
+        EdnsOption::ExtendedDnsError {
+            info_code,
+            extra_text: self.to_string(),
+        }
+    }
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ResolveError::NoReachableAuthority(reason) => write!(f, "{}", reason),
+            ResolveError::Network(err) => write!(f, "network error: {}", err),
+            ResolveError::InvalidData(err) => write!(f, "upstream response: {}", err),
+            ResolveError::Bogus(reason) => write!(f, "dnssec validation failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+impl From<io::Error> for ResolveError {
+    fn from(err: io::Error) -> Self {
+        ResolveError::Network(err)
+    }
+}
+
+impl From<protocol::errors::Error> for ResolveError {
+    fn from(err: protocol::errors::Error) -> Self {
+        ResolveError::InvalidData(err)
+    }
+}
+
 pub struct Resolver {
     pub(crate) recursive: bool,
     root_servers: Vec<(String, IpAddr)>,
+    // Validator of upstream responses, when DNSSEC is enabled.
+    validator: Option<Validator>,
 }
 
 impl Resolver {
@@ -34,6 +113,7 @@ impl Resolver {
         Resolver {
             recursive: false,
             root_servers: vec![],
+            validator: None,
         }
     }
 
@@ -41,6 +121,12 @@ impl Resolver {
         ResolverBuilder::new()
     }
 
+    /// Refresh the keys of the zones with trust anchors, returning the time
+    /// until the next refresh, or nothing without DNSSEC validation.
+    pub fn refresh_trust_anchors(&self) -> Option<Duration> {
+        self.validator.as_ref().map(|validator| validator.refresh_trust_anchors(self))
+    }
+
     pub fn resolve<S>(&self, qname: S, qtype: QueryType, recursion_desired: bool) -> Result<Packet>
         where S: AsRef<str>
     {
@@ -49,6 +135,43 @@ impl Resolver {
         self.recursive_lookup(qname, qtype, *addr, recursion_desired)
     }
 
+    /// Resolve a question and validate the response with DNSSEC, unless
+    /// the client disabled checking: secure responses are flagged as
+    /// authenticated while bogus ones are an error.
+    pub fn resolve_validated<S>(&self, qname: S, qtype: QueryType, recursion_desired: bool, checking_disabled: bool) -> Result<Packet>
+        where S: AsRef<str>
+    {
+        // Referrals returned without recursion can't be validated.
+        let validator = match &self.validator {
+            Some(validator) if !checking_disabled && self.recursive && recursion_desired => Some(validator),
+            _ => None,
+        };
+
+        // Names and types denied by validated records are answered without
+        // asking upstream.
+        if let Some(validator) = validator {
+            if let Some(response) = validator.synthesize(qname.as_ref(), qtype) {
+                return Ok(response);
+            }
+        }
+
+        let mut response = self.resolve(&qname, qtype, recursion_desired)?;
+        response.header.authenticated_data = false;
+
+        let validator = match validator {
+            Some(validator) => validator,
+            None => return Ok(response),
+        };
+
+        match validator.validate(self, qname.as_ref(), qtype, &response) {
+            Security::Secure => response.header.authenticated_data = true,
+            Security::Insecure => {}
+            Security::Bogus(reason) => return Err(ResolveError::Bogus(reason)),
+        }
+
+        Ok(response)
+    }
+
     fn get_root_server(&self) -> &(String, IpAddr) {
         let index = rand::random::<usize>() % self.root_servers.len();
         &self.root_servers[index]
@@ -62,7 +185,7 @@ impl Resolver {
         loop {
             println!("Looking up of {:?} for {} with {}", qtype, qname.as_ref(), server_ip);
 
-            let response = self.lookup(&qname, qtype, server_ip)?;
+            let mut response = self.lookup(&qname, qtype, server_ip)?;
 
             // If we received some answers and the result code is ok then we found
             // a match for the query.
@@ -70,14 +193,21 @@ impl Resolver {
             //       be complete. To make it fully usable, it needs to recursively resolve
             //       the CNAME alias to match the query type.
             if !response.answers.is_empty() && response.header.result_code == ResultCode::NoError {
+                Resolver::normalize_answers(&mut response);
                 return Ok(response);
             }
 
             // NXDomain means that the authoritative server doesn't know
-            // the queried domain. In this case we are just returning an
-            // error to the user.
-            if response.header.result_code == ResultCode::NxDomain {
-                return Err(anyhow::anyhow!("The requested domain {} does not exist", qname.as_ref()));
+            // the queried domain. In this case the response is forwarded
+            // as is to the user, like any other error.
+            if response.header.result_code != ResultCode::NoError {
+                return Ok(response);
+            }
+
+            // Empty answers along with the SOA record of the zone mean that
+            // the name exists without records of the queried type.
+            if response.authorities.iter().any(|record| matches!(record, Record::StartOfAuthority(_))) {
+                return Ok(response);
             }
 
             // When the --no-recursive option is enabled, we are not
@@ -94,7 +224,7 @@ impl Resolver {
             let ns = authoritative_name_servers.next();
 
             if ns.is_none() {
-                return Err(anyhow::anyhow!("Recursion not available because no authoritative name servers"));
+                return Err(ResolveError::NoReachableAuthority(format!("no authoritative name server found for {}", qname.as_ref())));
             }
 
             let ns = ns.unwrap();
@@ -122,13 +252,27 @@ impl Resolver {
 
                     match ip {
                         Some(ip) => IpAddr::V4(*ip),
-                        None => return Err(anyhow::anyhow!("No recursion available because name server ip not found"))
+                        None => return Err(ResolveError::NoReachableAuthority(format!("no address found for name server {}", ns.ns_name)))
                     }
                 }
             };
         }
     }
 
+    // Drop duplicated answers and fix the TTLs of inconsistent RRsets,
+    // so that answers can be cached as RRsets.
+    fn normalize_answers(response: &mut Packet) {
+        response.dedup();
+
+        let mut rrsets = response.answer_rrsets();
+        for rrset in rrsets.iter_mut().filter(|rrset| !rrset.has_consistent_ttl()) {
+            warn!(domain = %rrset.domain, qtype = %rrset.qtype, "inconsistent ttls in rrset");
+            rrset.normalize_ttl();
+        }
+
+        response.answers = rrsets.into_iter().flatten().collect();
+    }
+
     fn authoritative_name_servers(records: &[protocol::packet::Record]) -> impl Iterator<Item=&protocol::records::AuthoritativeNameServer> {
         records
             .iter()
@@ -154,17 +298,54 @@ impl Resolver {
         where S: AsRef<str>
     {
         let server_endpoint = SocketAddr::from((server_ip, 53));
-        let socket = UdpSocket::bind("0.0.0.0:43053")?;
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.set_read_timeout(Some(LOOKUP_TIMEOUT))?;
+
+        // Advertising EDNS support to receive extended DNS errors,
+        // within the size of the response buffer, and asking for DNSSEC
+        // records when validating responses.
+        let query = Packet::query(qname.as_ref(), qtype)
+            .id(self.get_random_id())
+            .recursion_desired(true)
+            .authenticated_data(true)
+            .edns(UDP_PAYLOAD_SIZE)
+            .dnssec_ok(self.validator.is_some())
+            .build();
+        let mut buf = GrowableBuffer::default();
+        query.serialize(&mut buf)?;
+        let query = buf.into_inner();
+        socket.send_to(&query, server_endpoint)?;
+
+        let mut data = [0u8; UDP_PAYLOAD_SIZE as usize];
+        let len = socket.recv(&mut data)?;
+        let mut response = MessageView::new(&data[..len])?.to_packet()?;
+
+        // Truncated responses are retried over TCP (RFC 7766).
+        if response.header.truncated {
+            response = self.lookup_tcp(&query, server_endpoint)?;
+        }
 
-        let query = Query::new(self.get_random_id(), qname.as_ref(), qtype, true);
-        let mut buf = BytePacketBuffer::new();
-        query.write_to_buffer(&mut buf);
-        socket.send_to(&buf.bytes(), server_endpoint)?;
+        for (info_code, extra_text) in response.edns().into_iter().flat_map(OPT::extended_errors) {
+            warn!(server = %server_ip, %info_code, extra_text, "upstream extended dns error");
+        }
 
-        let mut data = [0u8; 512];
-        socket.recv(&mut data)?;
-        let mut buffer = BytePacketBuffer::from_raw_data(&data);
-        Ok(Packet::from_buffer(&mut buffer))
+        Ok(response)
+    }
+
+    fn lookup_tcp(&self, query: &[u8], server_endpoint: SocketAddr) -> Result<Packet> {
+        let mut stream = TcpStream::connect_timeout(&server_endpoint, LOOKUP_TIMEOUT)?;
+        stream.set_read_timeout(Some(LOOKUP_TIMEOUT))?;
+
+        // Messages are prefixed with their length over TCP.
+        stream.write_all(&(query.len() as u16).to_be_bytes())?;
+        stream.write_all(query)?;
+
+        let mut len = [0u8; 2];
+        stream.read_exact(&mut len)?;
+        let mut data = vec![0u8; u16::from_be_bytes(len) as usize];
+        stream.read_exact(&mut data)?;
+
+        Ok(MessageView::new(&data)?.to_packet()?)
     }
 
     fn get_random_id(&self) -> u16 {
@@ -175,6 +356,9 @@ impl Resolver {
 pub struct ResolverBuilder {
     recursive: bool,
     root_servers: Vec<(String, IpAddr)>,
+    dnssec: bool,
+    trust_anchors: Option<TrustAnchors>,
+    aggressive_nsec: bool,
 }
 
 impl ResolverBuilder {
@@ -185,6 +369,9 @@ impl ResolverBuilder {
                 .iter()
                 .map(|(domain, addr)| (domain.to_string(), IpAddr::V4(Ipv4Addr::from(*addr))))
                 .collect(),
+            dnssec: true,
+            trust_anchors: None,
+            aggressive_nsec: true,
         }
     }
 
@@ -193,54 +380,61 @@ impl ResolverBuilder {
         self
     }
 
+    /// Validate responses with DNSSEC, from the root trust anchors.
+    pub fn dnssec(mut self, dnssec: bool) -> Self {
+        self.dnssec = dnssec;
+        self
+    }
+
+    /// Validate from the given trust anchors rather than the built-in root
+    /// trust anchors.
+    pub fn trust_anchors(mut self, trust_anchors: TrustAnchors) -> Self {
+        self.trust_anchors = Some(trust_anchors);
+        self
+    }
+
+    /// Answer from validated NSEC and NSEC3 records the queries they
+    /// deny, without asking upstream (RFC 8198).
+    pub fn aggressive_nsec(mut self, aggressive_nsec: bool) -> Self {
+        self.aggressive_nsec = aggressive_nsec;
+        self
+    }
+
     pub fn build(self) -> Resolver {
         let mut resolver = Resolver::new();
         resolver.recursive = self.recursive;
         resolver.root_servers = self.root_servers;
+        let aggressive_nsec = self.aggressive_nsec;
+        resolver.validator = match (self.dnssec, self.trust_anchors) {
+            (false, _) => None,
+            (true, Some(trust_anchors)) => Some(Validator::new(trust_anchors)),
+            (true, None) => Some(Validator::with_root_anchors()),
+        }
+        .map(|validator| validator.aggressive_nsec(aggressive_nsec));
         resolver
     }
 }
 
-struct Query {
-    packet: Packet,
-}
+#[cfg(test)]
+mod test {
+    use std::io;
 
-impl Query {
-    fn new<S>(id: u16, qname: S, qtype: QueryType, recursion_desired: bool) -> Query
-        where S: ToString
-    {
-        Query {
-            packet: Packet {
-                header: Header {
-                    id,
-                    is_response: false,
-                    opcode: OpCode::Query,
-                    authoritative_answer: false,
-                    truncated: false,
-                    recursion_desired,
-                    recursion_available: false,
-                    z: false,
-                    authenticated_data: true,
-                    checking_disabled: false,
-                    result_code: ResultCode::NoError,
-                    total_questions: 1,
-                    total_answer_records: 0,
-                    total_authority_records: 0,
-                    total_additional_records: 0,
-                },
-                questions: vec![Question {
-                    name: qname.to_string(),
-                    qtype,
-                    _class: 1,
-                }],
-                answers: vec![],
-                authorities: vec![],
-                additionals: vec![],
-            }
-        }
-    }
+    use protocol::header::ResultCode;
+    use protocol::records::opt::{EdnsOption, ExtendedErrorCode};
+
+    use crate::resolver::ResolveError;
 
-    fn write_to_buffer(self, buf: &mut BytePacketBuffer) {
-        self.packet.serialize(buf).unwrap();
+    #[test]
+    fn extended_errors() {
+        let err = ResolveError::Bogus("missing signature for example.com A".to_string());
+        assert_eq!(ResultCode::ServerFailure, err.result_code());
+        assert_eq!(EdnsOption::ExtendedDnsError {
+            info_code: ExtendedErrorCode::DnssecBogus,
+            extra_text: "dnssec validation failed: missing signature for example.com A".to_string(),
+        }, err.extended_error());
+
+        let err = ResolveError::from(io::Error::new(io::ErrorKind::TimedOut, "timed out"));
+        assert_eq!(ResultCode::ServerFailure, err.result_code());
+        assert!(matches!(err.extended_error(), EdnsOption::ExtendedDnsError { info_code: ExtendedErrorCode::NetworkError, .. }));
     }
-}
\ No newline at end of file
+}
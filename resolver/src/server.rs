@@ -1,14 +1,28 @@
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 use tokio::net::UdpSocket;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use protocol::byte_packet_buffer::BytePacketBuffer;
-use protocol::packet::{Packet, Question};
+use protocol::growable_buffer::GrowableBuffer;
+use protocol::header::{Header, OpCode, ResultCode};
+use protocol::packet::{Class, Packet, QueryType, Record};
+use protocol::records::{OPT, TSIG};
 use protocol::ser::Serialize;
+use protocol::tsig::{self, TsigError, TsigKey, TsigSession};
+use protocol::view::MessageView;
 
-use crate::resolver::Resolver;
+use crate::identity::ServerIdentity;
+use crate::resolver::{ResolveError, Resolver};
+use crate::zones::Zones;
+
+// Size of the buffer receiving requests, the largest UDP payload size
+// advertised by EDNS clients in practice.
+const MAX_REQUEST_SIZE: usize = 4096;
+
+// TSIG record of a request which couldn't be verified, and why.
+type Rejection = (TSIG, TsigError);
 
 pub struct Listener {
     // Reference to a bind UDP socket.
@@ -20,6 +34,15 @@ pub struct Listener {
     pub(crate) socket: Arc<UdpSocket>,
 
     pub(crate) resolver: Arc<Resolver>,
+
+    // Zones answered authoritatively rather than resolved.
+    pub(crate) zones: Arc<Zones>,
+
+    // Values used to answer CHAOS class identification queries.
+    pub(crate) identity: Arc<ServerIdentity>,
+
+    // Keys shared with other servers, authenticating their requests.
+    pub(crate) tsig_keys: Arc<Vec<TsigKey>>,
 }
 
 impl Listener {
@@ -32,13 +55,12 @@ impl Listener {
         info!("accepting dns packets");
 
         loop {
-            // Prepare a buffer which can accept only 512 bytes.
+            // Prepare a buffer which can accept the largest requests.
             //
-            // In DNS protocol, 512 bytes is the maximum length,
-            // if the length is bigger that this number, the rfc
-            // suggests to use TCP along with the truncated DNS
-            // header attributes.
-            let mut buffer = [0u8; 512];
+            // Without EDNS, 512 bytes is the maximum length, but
+            // clients supporting it may send larger requests, such
+            // as signed ones, up to their advertised payload size.
+            let mut buffer = [0u8; MAX_REQUEST_SIZE];
 
             // Clone the socket to have a safe reference to the handler.
             //
@@ -52,7 +74,10 @@ impl Listener {
             let handler = Handler {
                 socket,
                 resolver: self.resolver.clone(),
-                request_data: (&buffer[..len]).to_vec(),
+                zones: self.zones.clone(),
+                identity: self.identity.clone(),
+                tsig_keys: self.tsig_keys.clone(),
+                request_data: buffer[..len].to_vec(),
             };
 
             tokio::spawn(async move {
@@ -67,6 +92,9 @@ impl Listener {
 struct Handler {
     socket: Arc<UdpSocket>,
     resolver: Arc<Resolver>,
+    zones: Arc<Zones>,
+    identity: Arc<ServerIdentity>,
+    tsig_keys: Arc<Vec<TsigKey>>,
     request_data: Vec<u8>,
 }
 
@@ -74,36 +102,173 @@ impl Handler {
     async fn run(&self) -> Result<()> {
         // Parse the input raw data into a valid DNS packet.
         //
-        // TODO: Handle error when available.
-        let mut buffer = BytePacketBuffer::from_raw_data(self.request_data.as_slice());
-        let mut request = Packet::from_buffer(&mut buffer);
+        // Malformed requests are answered with a format error, unless
+        // even their header can't be read (RFC 1035 section 4.1.1).
+        let view = MessageView::new(&self.request_data)?;
+        let request = match view.to_packet() {
+            Ok(request) => request,
+            Err(err) => return self.reject_malformed(view.header().clone(), err).await,
+        };
+        let dnssec_ok = request.edns().is_some_and(OPT::dnssec_ok);
+        let reply = request.reply()
+            .recursion_available(self.resolver.recursive)
+            .checking_disabled(request.header.checking_disabled);
 
-        // Create an empty response to prepare the request answer.
-        //
-        // The response is empty if there is not question in the request
-        // or if the resolver doesn't manage to get an answer.
-        let mut response = Packet::new();
+        // Signed requests are only answered once verified, the response
+        // being signed with the same key (RFC 8945 section 5.2).
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let (session, rejection) = self.verify_request(now)?;
+        if let Some((_, err)) = &rejection {
+            warn!(cause = %err, "request verification failed");
+        }
 
-        // Taking the first question and resolve it.
+        // Only standard queries are supported, other operations like
+        // NOTIFY or UPDATE are refused as not implemented.
         //
-        // It overwrite the response in case of success.
+        // Otherwise, taking the first question and resolve it.
+        //
+        // The response is empty if there is not question in the request.
         // TODO: Maybe considering looping over all the questions in the future.
-        if let Some(question) = request.questions.pop() {
-            let Question { name, qtype, .. } = question;
-            response = self.resolver.resolve(name, qtype, request.header.recursion_desired)?;
+        //
+        // CHAOS class questions are answered locally with the server
+        // identity, they don't have any meaning for upstream servers.
+        let reply = match request.questions.first() {
+            _ if rejection.is_some() => reply.rcode(match rejection {
+                Some((_, TsigError::FormError(_))) => ResultCode::FormError,
+                _ => ResultCode::NotAuth,
+            }),
+            _ if request.header.opcode != OpCode::Query => reply.rcode(ResultCode::NotImplemented),
+            None => reply,
+            Some(question) if question.class == Class::Chaos => self.identity.answer(question, reply),
+            Some(question) => match self.resolve(question.name.as_str(), question.qtype, &request) {
+                // The OPT record is hop-by-hop, the one of an upstream response
+                // is replaced by ours when the client supports EDNS so that the
+                // extended result codes and errors can be forwarded.
+                //
+                // Validated data is only flagged as such to clients aware of
+                // DNSSEC (RFC 6840 section 5.8).
+                Ok(upstream) => reply
+                    .authoritative(upstream.header.authoritative_answer)
+                    .authenticated_data(upstream.header.authenticated_data && (request.header.authenticated_data || dnssec_ok))
+                    .rcode(upstream.header.result_code)
+                    .answers(dnssec_records(upstream.answers, question.qtype, dnssec_ok))
+                    .authorities(dnssec_records(upstream.authorities, question.qtype, dnssec_ok))
+                    .additionals(dnssec_records(upstream.additionals, question.qtype, dnssec_ok).filter(|record| !matches!(record, Record::Opt(_) | Record::TransactionSignature(_) | Record::SecuritySignature(_)))),
+
+                // Resolution failures are explained with an extended DNS error.
+                Err(err) => {
+                    warn!(cause = %err, "resolution failed");
+
+                    let reply = reply.rcode(err.result_code());
+                    if request.edns().is_some() {
+                        reply.edns_option(err.extended_error())
+                    } else {
+                        reply
+                    }
+                }
+            },
+        };
+
+        let mut response = if request.edns().is_some() { reply.edns(512).dnssec_ok(dnssec_ok) } else { reply }.build();
+        if response.edns().is_none() && response.header.result_code.is_extended() {
+            response.header.result_code = ResultCode::ServerFailure;
         }
 
-        // Re-overwriting the response header if it successfully found an answer.
-        response.header.id = request.header.id;
-        response.header.recursion_desired = request.header.recursion_desired;
-        response.header.recursion_available = self.resolver.recursive;
-        response.header.is_response = true;
+        // Responses larger than what the requester accepts are truncated,
+        // for it to retry over TCP.
+        let max_size = request.edns().map_or(512, |opt| opt.udp_payload_size.max(512)) as usize;
+        let mut data = sign(serialize(&response)?, session.clone(), rejection.as_ref(), now)?;
+        if data.len() > max_size {
+            response.header.truncated = true;
+            response.answers.clear();
+            response.authorities.clear();
+            response.additionals.retain(|record| matches!(record, Record::Opt(_)));
+            data = sign(serialize(&response)?, session, rejection.as_ref(), now)?;
+        }
 
         // Send back the response to the requester.
-        let mut buffer = BytePacketBuffer::new();
-        response.serialize(&mut buffer)?;
-        self.socket.send(&buffer.bytes()).await?;
+        self.socket.send(&data).await?;
+
+        Ok(())
+    }
+
+    // Answer a request which couldn't be parsed with a format error, giving
+    // back its ID but none of its questions. Responses are never answered.
+    async fn reject_malformed(&self, header: Header, err: protocol::errors::Error) -> Result<()> {
+        warn!(cause = %err, "malformed request");
+        if header.is_response {
+            return Ok(());
+        }
+
+        let mut request = Packet::new();
+        request.header = header;
+        let response = request.reply().rcode(ResultCode::FormError).build();
+        self.socket.send(&serialize(&response)?).await?;
 
         Ok(())
     }
+
+    // Questions about the served zones are answered from their signed
+    // records, the others being resolved.
+    fn resolve(&self, qname: &str, qtype: QueryType, request: &Packet) -> std::result::Result<Packet, ResolveError> {
+        match self.zones.answer(qname, qtype) {
+            Some(response) => Ok(response),
+            None => self.resolver.resolve_validated(qname, qtype, request.header.recursion_desired, request.header.checking_disabled),
+        }
+    }
+
+    // Verify the TSIG record of the request if any, returning the session
+    // signing the response, and why the request is rejected if it is.
+    //
+    // Requests signed at the wrong time are still answered with a signed
+    // response, giving the time of the server.
+    fn verify_request(&self, now: u64) -> protocol::result::Result<(Option<TsigSession>, Option<Rejection>)> {
+        let tsig = match tsig::find(&self.request_data)? {
+            Some((tsig, _)) => tsig,
+            None => return Ok((None, None)),
+        };
+
+        let key = match self.tsig_keys.iter().find(|key| key.matches(&tsig)) {
+            Some(key) => key,
+            None => {
+                let err = TsigError::BadKey(format!("unknown {} {} key", tsig.domain, tsig.algorithm));
+                return Ok((None, Some((tsig, err))));
+            }
+        };
+
+        let mut session = key.session();
+        match session.verify(&self.request_data, now) {
+            Ok(()) => Ok((Some(session), None)),
+            Err(err @ TsigError::BadTime { .. }) => Ok((Some(session), Some((tsig, err)))),
+            Err(err) => Ok((None, Some((tsig, err)))),
+        }
+    }
+}
+
+// Add the TSIG record of a response to a signed request, signing it with the
+// key of the request unless it couldn't be verified. Malformed TSIG records
+// are answered without any.
+fn sign(data: Vec<u8>, session: Option<TsigSession>, rejection: Option<&Rejection>, now: u64) -> protocol::result::Result<Vec<u8>> {
+    match (session, rejection) {
+        (Some(mut session), _) => session.sign(&data, now),
+        (None, Some((_, TsigError::FormError(_)))) => Ok(data),
+        (None, Some((request, err))) => tsig::reject(&data, request, err, now),
+        (None, None) => Ok(data),
+    }
+}
+
+fn serialize(packet: &Packet) -> protocol::result::Result<Vec<u8>> {
+    let mut buffer = GrowableBuffer::default();
+    packet.serialize(&mut buffer)?;
+    Ok(buffer.into_inner())
+}
+
+// DNSSEC records are only sent to the clients which asked for them with
+// the DO bit, or which queried them explicitly (RFC 4035 section 3.2.1).
+fn dnssec_records(records: Vec<Record>, qtype: QueryType, dnssec_ok: bool) -> impl Iterator<Item=Record> {
+    records.into_iter().filter(move |record| {
+        let record_type = record.qtype();
+        let is_dnssec = matches!(record_type, QueryType::Signature | QueryType::NextSecure | QueryType::NextSecure3);
+        dnssec_ok || !is_dnssec || record_type == qtype
+    })
 }
\ No newline at end of file
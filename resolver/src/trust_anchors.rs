@@ -0,0 +1,527 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use tracing::{info, warn};
+
+use protocol::packet::Record;
+use protocol::records::dnssec::DigestType;
+use protocol::records::{DNSKEY, DS, RRSIG};
+use protocol::validation;
+
+// DS records of the root zone key signing keys.
+//
+// https://data.iana.org/root-anchors/root-anchors.xml
+const ROOT_TRUST_ANCHORS: &[&str] = &[
+    ". 0 IN DS 20326 8 2 E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237C7F8EC8D",
+    ". 0 IN DS 38696 8 2 683D2D0ACB8C9B712A1948B27F741219298D0A450D612C483AF444A4C0FB2B16",
+];
+
+// Time a new key must be continuously published before being trusted, and
+// a revoked key kept before being forgotten (RFC 5011 section 2.4.1). The
+// add hold-down lasts at least the original TTL of the DNSKEY RRset.
+const ADD_HOLD_DOWN: u32 = 30 * 24 * 3600;
+const REMOVE_HOLD_DOWN: u32 = 30 * 24 * 3600;
+
+/// Key of a trust anchor, DS anchors being replaced by the DNSKEY records
+/// they refer to once seen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnchorKey {
+    Ds(DS),
+    DnsKey(DNSKEY),
+}
+
+/// Rollover state of a trust anchor (RFC 5011 section 4).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnchorState {
+    // New key waiting for the add hold-down time to elapse.
+    AddPending,
+    // Trusted key.
+    Valid,
+    // Trusted key which is no longer published.
+    Missing,
+    // Key revoked by its owner, waiting for the remove hold-down time to
+    // elapse before being forgotten.
+    Revoked,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Anchor {
+    key: AnchorKey,
+    state: AnchorState,
+    // Time of the last state change, in seconds since the epoch.
+    changed: u32,
+}
+
+/// Trust anchors of the validator, kept up to date with the keys the
+/// zones publish following RFC 5011.
+///
+/// When loaded from a file, the anchors and their state are saved back to
+/// it on every change so rollovers survive restarts.
+#[derive(Debug)]
+pub struct TrustAnchors {
+    anchors: Vec<Anchor>,
+    path: Option<PathBuf>,
+}
+
+impl TrustAnchors {
+    /// Anchors of the root zone key signing keys, built in.
+    pub fn root() -> TrustAnchors {
+        let anchors = ROOT_TRUST_ANCHORS
+            .iter()
+            .map(|anchor| Anchor::parse(anchor).expect("valid root trust anchor"))
+            .collect();
+
+        TrustAnchors { anchors, path: None }
+    }
+
+    /// Load anchors from a file of DS or DNSKEY records in presentation
+    /// format, one per line, possibly followed by their state as saved.
+    pub fn load<P>(path: P) -> Result<TrustAnchors>
+        where P: AsRef<Path>
+    {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).with_context(|| format!("can't read trust anchors from {}", path.display()))?;
+
+        let anchors = content
+            .lines()
+            .map(str::trim)
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with(';'))
+            .map(|(i, line)| Anchor::parse(line).with_context(|| format!("invalid trust anchor at {}:{}", path.display(), i + 1)))
+            .collect::<Result<Vec<Anchor>>>()?;
+
+        if anchors.is_empty() {
+            bail!("no trust anchor in {}", path.display());
+        }
+
+        Ok(TrustAnchors {
+            anchors,
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Whether the zone has trust anchors, trusted or not.
+    pub fn is_anchored(&self, zone: &str) -> bool {
+        self.anchors.iter().any(|anchor| anchor.zone().eq_ignore_ascii_case(zone))
+    }
+
+    /// Zones with trust anchors.
+    pub fn zones(&self) -> Vec<String> {
+        let mut zones: Vec<String> = self.anchors.iter().map(|anchor| anchor.zone().to_ascii_lowercase()).collect();
+        zones.sort();
+        zones.dedup();
+        zones
+    }
+
+    /// DS records of the trusted anchors of the zone.
+    pub fn trusted(&self, zone: &str) -> Vec<DS> {
+        self.anchors
+            .iter()
+            .filter(|anchor| anchor.zone().eq_ignore_ascii_case(zone))
+            .filter(|anchor| matches!(anchor.state, AnchorState::Valid | AnchorState::Missing))
+            .filter_map(|anchor| match &anchor.key {
+                AnchorKey::Ds(ds) => Some(ds.clone()),
+                AnchorKey::DnsKey(dnskey) => validation::create_ds(dnskey, DigestType::Sha256).ok(),
+            })
+            .collect()
+    }
+
+    /// Track the key signing keys of the DNSKEY RRset of an anchored zone,
+    /// which must have been validated with the trusted anchors, `now` being
+    /// the current time in seconds since the epoch.
+    ///
+    /// New keys are trusted once published for the add hold-down time, or
+    /// the original TTL of the RRset when longer, and keys revoked with a
+    /// signature of their own are distrusted at once.
+    pub fn update(&mut self, zone: &str, records: &[Record], rrsigs: &[&RRSIG], now: u32) {
+        let keys: Vec<&DNSKEY> = records
+            .iter()
+            .filter_map(|record| match record {
+                Record::DnsKey(dnskey) if dnskey.is_zone_key() && dnskey.is_secure_entry_point() => Some(dnskey),
+                _ => None,
+            })
+            .collect();
+
+        let original_ttl = rrsigs
+            .iter()
+            .map(|rrsig| rrsig.original_ttl)
+            .chain(records.iter().map(Record::ttl))
+            .max()
+            .map_or(0, |ttl| ttl.as_secs().min(u32::MAX as u64) as u32);
+        let add_hold_down = ADD_HOLD_DOWN.max(original_ttl);
+
+        let mut changed = false;
+
+        // DS anchors are converted to the keys they refer to.
+        for anchor in self.anchors.iter_mut().filter(|anchor| anchor.zone().eq_ignore_ascii_case(zone)) {
+            if let AnchorKey::Ds(ds) = &anchor.key {
+                if let Some(dnskey) = keys.iter().find(|dnskey| validation::matches_ds(dnskey, ds)) {
+                    anchor.key = AnchorKey::DnsKey((*dnskey).clone());
+                    changed = true;
+                }
+            }
+        }
+
+        for dnskey in &keys {
+            let position = self.anchors.iter().position(|anchor| anchor.is_same_key(dnskey));
+
+            if dnskey.is_revoked() {
+                let anchor = match position {
+                    Some(position) => &mut self.anchors[position],
+                    None => continue,
+                };
+
+                if anchor.state != AnchorState::Revoked && validation::verify_revocation(rrsigs, dnskey, records, now).is_ok() {
+                    info!(zone = %zone, key_tag = dnskey.key_tag(), "trust anchor revoked");
+                    anchor.key = AnchorKey::DnsKey((*dnskey).clone());
+                    anchor.transition(AnchorState::Revoked, now);
+                    changed = true;
+                }
+
+                continue;
+            }
+
+            match position {
+                None => {
+                    info!(zone = %zone, key_tag = dnskey.key_tag(), "new trust anchor pending");
+                    self.anchors.push(Anchor {
+                        key: AnchorKey::DnsKey((*dnskey).clone()),
+                        state: AnchorState::AddPending,
+                        changed: now,
+                    });
+                    changed = true;
+                }
+                Some(position) => {
+                    let anchor = &mut self.anchors[position];
+                    let held_down = now.wrapping_sub(anchor.changed) >= add_hold_down;
+
+                    if anchor.state == AnchorState::Missing || (anchor.state == AnchorState::AddPending && held_down) {
+                        info!(zone = %zone, key_tag = dnskey.key_tag(), "trust anchor valid");
+                        anchor.transition(AnchorState::Valid, now);
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        // Anchors which are no longer published are kept as long as they
+        // may come back, pending keys having to start over.
+        let before = self.anchors.len();
+        self.anchors.retain(|anchor| {
+            let published = keys.iter().any(|dnskey| anchor.is_same_key(dnskey));
+            !anchor.zone().eq_ignore_ascii_case(zone)
+                || published
+                || match anchor.state {
+                    AnchorState::AddPending => false,
+                    AnchorState::Revoked => now.wrapping_sub(anchor.changed) < REMOVE_HOLD_DOWN,
+                    _ => true,
+                }
+        });
+        changed |= self.anchors.len() != before;
+
+        for anchor in self.anchors.iter_mut().filter(|anchor| anchor.zone().eq_ignore_ascii_case(zone)) {
+            let published = keys.iter().any(|dnskey| anchor.is_same_key(dnskey));
+            if anchor.state == AnchorState::Valid && !published && matches!(anchor.key, AnchorKey::DnsKey(_)) {
+                anchor.transition(AnchorState::Missing, now);
+                changed = true;
+            }
+        }
+
+        if changed {
+            if let Err(err) = self.save() {
+                warn!(err = %err, "failed to save the trust anchors");
+            }
+        }
+    }
+
+    // Write the anchors to their file, through a temporary file so that a
+    // crash never leaves a partial file.
+    fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let content: String = self.anchors.iter().map(|anchor| format!("{}\n", anchor)).collect();
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, content)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
+
+impl Anchor {
+    // Parse a DS or DNSKEY record, followed by a `; state=<state>
+    // changed=<time>` comment for anchors saved with their state.
+    fn parse(line: &str) -> Result<Anchor> {
+        let (record, comment) = line.split_once(';').unwrap_or((line, ""));
+
+        let key = match record.parse::<DS>() {
+            Ok(ds) => AnchorKey::Ds(ds),
+            Err(_) => AnchorKey::DnsKey(record.parse::<DNSKEY>().map_err(|err| anyhow!("expected a DS or DNSKEY record: {}", err))?),
+        };
+
+        let mut anchor = Anchor {
+            key,
+            state: AnchorState::Valid,
+            changed: 0,
+        };
+
+        for (name, value) in comment.split_whitespace().filter_map(|field| field.split_once('=')) {
+            match name {
+                "state" => anchor.state = value.parse()?,
+                "changed" => anchor.changed = value.parse().with_context(|| format!("invalid change time {}", value))?,
+                _ => {}
+            }
+        }
+
+        Ok(anchor)
+    }
+
+    fn zone(&self) -> &str {
+        match &self.key {
+            AnchorKey::Ds(ds) => &ds.domain,
+            AnchorKey::DnsKey(dnskey) => &dnskey.domain,
+        }
+    }
+
+    // Whether the anchor is the key, regardless of its revoke flag.
+    fn is_same_key(&self, dnskey: &DNSKEY) -> bool {
+        match &self.key {
+            AnchorKey::DnsKey(key) => {
+                key.algorithm == dnskey.algorithm && key.public_key == dnskey.public_key && key.domain.eq_ignore_ascii_case(&dnskey.domain)
+            }
+            AnchorKey::Ds(_) => false,
+        }
+    }
+
+    fn transition(&mut self, state: AnchorState, now: u32) {
+        self.state = state;
+        self.changed = now;
+    }
+}
+
+impl Display for Anchor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.key {
+            AnchorKey::Ds(ds) => write!(f, "{}", ds)?,
+            AnchorKey::DnsKey(dnskey) => write!(f, "{}", dnskey)?,
+        }
+
+        write!(f, " ; state={} changed={}", self.state, self.changed)
+    }
+}
+
+impl Display for AnchorState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let state = match self {
+            AnchorState::AddPending => "add-pending",
+            AnchorState::Valid => "valid",
+            AnchorState::Missing => "missing",
+            AnchorState::Revoked => "revoked",
+        };
+
+        write!(f, "{}", state)
+    }
+}
+
+impl std::str::FromStr for AnchorState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<AnchorState> {
+        match s {
+            "add-pending" => Ok(AnchorState::AddPending),
+            "valid" => Ok(AnchorState::Valid),
+            "missing" => Ok(AnchorState::Missing),
+            "revoked" => Ok(AnchorState::Revoked),
+            _ => Err(anyhow!("unknown trust anchor state {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::time::Duration;
+
+    use protocol::crypto::SigningKey;
+    use protocol::packet::Record;
+    use protocol::records::dnskey::REVOKE;
+    use protocol::records::dnssec::DigestType;
+    use protocol::records::{DNSKEY, RRSIG};
+    use protocol::signing::ZoneKey;
+    use protocol::validation::create_ds;
+
+    use crate::trust_anchors::{AnchorKey, AnchorState, TrustAnchors, ADD_HOLD_DOWN, REMOVE_HOLD_DOWN};
+
+    // Ed25519 keys of RFC 8080 section 6.
+    const KEYS: &[(&str, &str)] = &[
+        ("l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=", "ODIyNjAzODQ2MjgwODAxMjI2NDUxOTAyMDQxNDIyNjI="),
+        ("zPnZ/QwEe7S8C5SPz2OfS5RR40ATk2/rYnE9xHIEijs=", "DSSF3o0s0f+ElWzj9E/Osxw8hLpk55chkmx0LYN5WiY="),
+    ];
+
+    const NOW: u32 = 1700000000;
+
+    fn key(index: usize, flags: u16) -> ZoneKey {
+        let (public_key, private_key) = KEYS[index];
+        let dnskey: DNSKEY = format!("example.com. 3600 IN DNSKEY {} 3 15 {}", flags, public_key).parse().unwrap();
+        let private_key = format!("Algorithm: 15 (ED25519)\nPrivateKey: {}", private_key);
+        ZoneKey::new(dnskey.clone(), SigningKey::from_private_key(&dnskey.public_key, &private_key).unwrap()).unwrap()
+    }
+
+    // DNSKEY RRset of the keys, signed by all of them.
+    fn rrset(keys: &[ZoneKey]) -> (Vec<Record>, Vec<RRSIG>) {
+        let records: Vec<Record> = keys.iter().map(|key| Record::DnsKey(key.dnskey.clone())).collect();
+        let rrsigs = keys
+            .iter()
+            .map(|key| key.sign(&records, "example.com", NOW - 3600, NOW + 30 * 24 * 3600 * 3).unwrap())
+            .collect();
+
+        (records, rrsigs)
+    }
+
+    fn update(anchors: &mut TrustAnchors, keys: &[ZoneKey], now: u32) {
+        let (records, rrsigs) = rrset(keys);
+        let rrsigs: Vec<&RRSIG> = rrsigs.iter().collect();
+        anchors.update("example.com", &records, &rrsigs, now);
+    }
+
+    fn anchors(ds: &[&ZoneKey]) -> TrustAnchors {
+        let content: String = ds
+            .iter()
+            .map(|key| format!("{}\n", create_ds(&key.dnskey, DigestType::Sha256).unwrap()))
+            .collect();
+
+        let path = std::env::temp_dir().join(format!("trust-anchors-{}-{}", std::process::id(), rand::random::<u32>()));
+        fs::write(&path, content).unwrap();
+        TrustAnchors::load(&path).unwrap()
+    }
+
+    fn states(anchors: &TrustAnchors) -> Vec<(u16, AnchorState)> {
+        anchors
+            .anchors
+            .iter()
+            .map(|anchor| match &anchor.key {
+                AnchorKey::Ds(ds) => (ds.key_tag, anchor.state),
+                AnchorKey::DnsKey(dnskey) => (dnskey.key_tag(), anchor.state),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn root_anchors() {
+        let anchors = TrustAnchors::root();
+        assert!(anchors.is_anchored(""));
+        assert!(!anchors.is_anchored("com"));
+        assert_eq!(vec![20326, 38696], anchors.trusted("").iter().map(|ds| ds.key_tag).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn load_and_save() {
+        let (current, next) = (key(0, 257), key(1, 257));
+        let mut anchors = anchors(&[&current]);
+        assert!(anchors.is_anchored("example.com"));
+        assert_eq!(1, anchors.trusted("example.com").len());
+
+        // The DS anchor is replaced by its key and the new key is pending.
+        update(&mut anchors, &[key(0, 257), key(1, 257)], NOW);
+        assert_eq!(vec![(current.dnskey.key_tag(), AnchorState::Valid), (next.dnskey.key_tag(), AnchorState::AddPending)], states(&anchors));
+
+        // The state is saved along with the anchors.
+        let path = anchors.path.clone().unwrap();
+        let saved = TrustAnchors::load(&path).unwrap();
+        assert_eq!(anchors.anchors, saved.anchors);
+        assert!(fs::read_to_string(&path).unwrap().contains("state=add-pending changed=1700000000"));
+        fs::remove_file(path).unwrap();
+
+        assert!(TrustAnchors::load("/nonexistent/trust-anchors").is_err());
+    }
+
+    #[test]
+    fn rollover() {
+        let (current, next) = (key(0, 257), key(1, 257));
+        let mut anchors = anchors(&[&current]);
+
+        // New keys are only trusted after the add hold-down time.
+        update(&mut anchors, &[key(0, 257), key(1, 257)], NOW);
+        update(&mut anchors, &[key(0, 257), key(1, 257)], NOW + ADD_HOLD_DOWN - 1);
+        assert_eq!(1, anchors.trusted("example.com").len());
+        update(&mut anchors, &[key(0, 257), key(1, 257)], NOW + ADD_HOLD_DOWN);
+        assert_eq!(2, anchors.trusted("example.com").len());
+
+        // The old key is revoked by its owner, and is no longer trusted.
+        let now = NOW + ADD_HOLD_DOWN + 3600;
+        update(&mut anchors, &[key(0, 257 | REVOKE), key(1, 257)], now);
+        assert_eq!(vec![(key(0, 257 | REVOKE).dnskey.key_tag(), AnchorState::Revoked), (next.dnskey.key_tag(), AnchorState::Valid)], states(&anchors));
+        assert_eq!(1, anchors.trusted("example.com").len());
+
+        // It is forgotten once gone for the remove hold-down time.
+        update(&mut anchors, &[key(1, 257)], now + 3600);
+        assert_eq!(2, anchors.anchors.len());
+        update(&mut anchors, &[key(1, 257)], now + REMOVE_HOLD_DOWN);
+        assert_eq!(vec![(next.dnskey.key_tag(), AnchorState::Valid)], states(&anchors));
+
+        fs::remove_file(anchors.path.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn long_ttl_hold_down() {
+        let current = key(0, 257);
+        let mut anchors = anchors(&[&current]);
+
+        // The add hold-down lasts as long as the RRset may be cached.
+        let ttl = ADD_HOLD_DOWN + 24 * 3600;
+        let keys = [key(0, 257), key(1, 257)];
+        let records: Vec<Record> = keys
+            .iter()
+            .map(|key| {
+                let mut dnskey = key.dnskey.clone();
+                dnskey.ttl = Duration::from_secs(ttl.into());
+                Record::DnsKey(dnskey)
+            })
+            .collect();
+        let rrsigs: Vec<RRSIG> = keys.iter().map(|key| key.sign(&records, "example.com", NOW - 3600, NOW + ttl * 3).unwrap()).collect();
+        let rrsigs: Vec<&RRSIG> = rrsigs.iter().collect();
+        assert_eq!(Duration::from_secs(ttl.into()), rrsigs[0].original_ttl);
+
+        anchors.update("example.com", &records, &rrsigs, NOW);
+        anchors.update("example.com", &records, &rrsigs, NOW + ADD_HOLD_DOWN);
+        assert_eq!(1, anchors.trusted("example.com").len());
+        anchors.update("example.com", &records, &rrsigs, NOW + ttl);
+        assert_eq!(2, anchors.trusted("example.com").len());
+
+        assert_eq!(vec!["example.com"], anchors.zones());
+        fs::remove_file(anchors.path.unwrap()).unwrap();
+    }
+
+    #[test]
+    fn missing_and_pending_keys() {
+        let current = key(0, 257);
+        let mut anchors = anchors(&[&current]);
+
+        // Pending keys have to start over when they disappear.
+        update(&mut anchors, &[key(0, 257), key(1, 257)], NOW);
+        update(&mut anchors, &[key(0, 257)], NOW + 3600);
+        assert_eq!(1, anchors.anchors.len());
+
+        // Valid keys stay trusted while missing.
+        update(&mut anchors, &[key(1, 257)], NOW + 7200);
+        assert_eq!(AnchorState::Missing, anchors.anchors[0].state);
+        assert_eq!(1, anchors.trusted("example.com").len());
+        update(&mut anchors, &[key(0, 257)], NOW + 10800);
+        assert_eq!(AnchorState::Valid, anchors.anchors[0].state);
+
+        // Revocations must be signed by the revoked key itself.
+        let records = vec![Record::DnsKey(key(0, 257 | REVOKE).dnskey)];
+        anchors.update("example.com", &records, &[], NOW + 14400);
+        assert_eq!(AnchorState::Valid, anchors.anchors[0].state);
+
+        // Zone keys without the secure entry point flag aren't tracked.
+        update(&mut anchors, &[key(0, 257), key(1, 256)], NOW + 18000);
+        assert_eq!(1, anchors.anchors.len());
+
+        fs::remove_file(anchors.path.unwrap()).unwrap();
+    }
+}
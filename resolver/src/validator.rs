@@ -0,0 +1,662 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tracing::{debug, warn};
+
+use protocol::crypto;
+use protocol::header::ResultCode;
+use protocol::packet::{Packet, QueryType, Record};
+use protocol::records::dnssec::label_count;
+use protocol::records::{DNSKEY, DS, RRSIG};
+use protocol::rrset::RRset;
+use protocol::validation::{self, Denial};
+
+use crate::denial_cache::DenialCache;
+use crate::resolver::Resolver;
+use crate::trust_anchors::TrustAnchors;
+
+// Time during which an unsigned zone is remembered as such.
+const INSECURE_ZONE_TTL: Duration = Duration::from_secs(3600);
+
+// Bounds of the time between two refreshes of the keys of anchored zones,
+// half their TTL otherwise (RFC 5011 section 2.3). Failed refreshes are
+// retried after the lower bound.
+const MIN_ANCHOR_REFRESH: Duration = Duration::from_secs(3600);
+const MAX_ANCHOR_REFRESH: Duration = Duration::from_secs(15 * 24 * 3600);
+
+// Maximum number of zones walked to validate a single response, which
+// bounds the lookups made on behalf of a client.
+const MAX_DEPTH: usize = 32;
+
+// Outcome of a validation step, the error being the reason why the data
+// is bogus.
+type Verdict<T> = std::result::Result<T, String>;
+
+/// Security status of a response (RFC 4035 section 4.3).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Security {
+    // Every record is signed along a chain of trust from a trust anchor.
+    Secure,
+    // The records belong to an unsigned zone, delegated as such.
+    Insecure,
+    // The records should be signed but their validation failed.
+    Bogus(String),
+}
+
+#[derive(Debug, Clone)]
+enum ZoneKeys {
+    // Keys of the zone, whose DNSKEY RRset has been validated.
+    Secure(Vec<DNSKEY>),
+    // The zone isn't signed, or only with unsupported algorithms.
+    Insecure,
+}
+
+/// Validates responses by building the chain of trust from the trust
+/// anchors down to the zone of the records (RFC 4035 section 5).
+pub struct Validator {
+    trust_anchors: Mutex<TrustAnchors>,
+    // Validated keys of the zones, until their TTL expires.
+    keys: Mutex<HashMap<String, (ZoneKeys, Instant)>>,
+    // Validated denials answering queries on their own, when enabled.
+    denials: Option<DenialCache>,
+}
+
+impl Validator {
+    pub fn new(trust_anchors: TrustAnchors) -> Validator {
+        Validator {
+            trust_anchors: Mutex::new(trust_anchors),
+            keys: Mutex::new(HashMap::new()),
+            denials: Some(DenialCache::new()),
+        }
+    }
+
+    /// Answer queries from the validated NSEC and NSEC3 records covering
+    /// them, enabled by default (RFC 8198).
+    pub fn aggressive_nsec(mut self, enabled: bool) -> Self {
+        self.denials = if enabled { Some(DenialCache::new()) } else { None };
+        self
+    }
+
+    /// Validator anchored on the root zone key signing keys.
+    pub fn with_root_anchors() -> Validator {
+        Validator::new(TrustAnchors::root())
+    }
+
+    /// Validate the response to a question, looking up the DNSKEY and DS
+    /// records of the zones involved with the resolver.
+    pub fn validate(&self, resolver: &Resolver, qname: &str, qtype: QueryType, response: &Packet) -> Security {
+        match self.validate_response(resolver, qname, qtype, response) {
+            Ok(security) => security,
+            Err(reason) => Security::Bogus(reason),
+        }
+    }
+
+    /// Secure answer to a question synthesized from validated denials,
+    /// without asking upstream.
+    pub fn synthesize(&self, qname: &str, qtype: QueryType) -> Option<Packet> {
+        let response = self.denials.as_ref()?.synthesize(qname, qtype)?;
+        debug!(qname = %qname, qtype = %qtype, rcode = %response.header.result_code, "synthesized answer from validated denials");
+        Some(response)
+    }
+
+    /// Fetch the DNSKEY RRsets of the anchored zones again to follow their
+    /// rollovers even when unused (RFC 5011 section 2.3), returning the
+    /// time until the next refresh.
+    pub fn refresh_trust_anchors(&self, resolver: &Resolver) -> Duration {
+        let zones = self.trust_anchors.lock().unwrap().zones();
+        let mut next = MAX_ANCHOR_REFRESH;
+
+        for zone in zones {
+            let trusted = self.trust_anchors.lock().unwrap().trusted(&zone);
+            let keys = if trusted.is_empty() {
+                Err(format!("no trust anchor of {} is trusted", zone))
+            } else {
+                self.fetch_keys(resolver, &zone, &trusted)
+            };
+
+            match keys {
+                Ok((keys, ttl)) => {
+                    debug!(zone = %zone, keys = ?keys, "refreshed trust anchors");
+                    self.keys.lock().unwrap().insert(zone, (keys, Instant::now() + ttl));
+                    next = next.min(ttl / 2);
+                }
+                Err(reason) => {
+                    warn!(zone = %zone, reason = %reason, "can't refresh trust anchors");
+                    next = MIN_ANCHOR_REFRESH;
+                }
+            }
+        }
+
+        next.max(MIN_ANCHOR_REFRESH)
+    }
+
+    fn validate_response(&self, resolver: &Resolver, qname: &str, qtype: QueryType, response: &Packet) -> Verdict<Security> {
+        let now = now();
+        let signatures = signatures(response);
+
+        for rrset in &unsigned_rrsets(&response.answers) {
+            let rrsig = match self.verify_rrset(resolver, rrset, &signatures, now)? {
+                Some(rrsig) => rrsig,
+                None => return Ok(Security::Insecure),
+            };
+
+            // Records expanded from a wildcard come with the proof that
+            // the queried name doesn't exist.
+            if rrsig.labels < label_count(&rrset.domain) {
+                let proofs = zone_proofs(&response.authorities, &signatures, &rrset.domain);
+                if !self.verify_proofs(resolver, response, &signatures, now)? || validation::has_excessive_iterations(&proofs) {
+                    return Ok(Security::Insecure);
+                }
+
+                validation::verify_wildcard_expansion(&proofs, &rrset.domain, rrsig.labels)
+                    .map_err(|err| err.to_string())?;
+
+                if let Some(denials) = &self.denials {
+                    denials.insert_proofs(&response.authorities);
+                    denials.insert_wildcard(rrset, &rrsig);
+                }
+            }
+        }
+
+        // The queried name may be an alias, the answer or its denial being
+        // about the target of the last CNAME record.
+        let chain = alias_chain(&response.answers, qname, qtype)?;
+        let name = chain.last().cloned().unwrap_or_default();
+
+        let answered = response.answers
+            .iter()
+            .any(|record| record.domain().eq_ignore_ascii_case(&name) && record.qtype() == qtype);
+        if answered {
+            return Ok(Security::Secure);
+        }
+
+        // Aliases whose targets weren't resolved upstream come without any
+        // denial of existence, the targets being unproven.
+        let has_denial = response.authorities
+            .iter()
+            .any(|record| matches!(record, Record::NextSecure(_) | Record::NextSecure3(_)));
+        if chain.len() > 1 && response.header.result_code == ResultCode::NoError && !has_denial {
+            return Ok(Security::Insecure);
+        }
+
+        if !self.verify_proofs(resolver, response, &signatures, now)? {
+            return Ok(Security::Insecure);
+        }
+
+        let proofs = zone_proofs(&response.authorities, &signatures, &name);
+        let denial = validation::verify_denial(&proofs, &name, qtype).map_err(|err| err.to_string())?;
+        match (response.header.result_code, denial) {
+            (ResultCode::NxDomain, Denial::NameError) | (ResultCode::NoError, Denial::NoData) | (ResultCode::NoError, Denial::InsecureDelegation) => {
+                if let Some(denials) = &self.denials {
+                    denials.insert_proofs(&response.authorities);
+                }
+
+                Ok(Security::Secure)
+            }
+            (ResultCode::NxDomain, Denial::OptOut) | (_, Denial::ExcessiveIterations) => Ok(Security::Insecure),
+            (rcode, denial) => Err(format!("{:?} proof of {} doesn't match the {} result code", denial, name, rcode)),
+        }
+    }
+
+    // Verify the SOA, NSEC and NSEC3 RRsets of the authority section,
+    // returning whether they are secure.
+    fn verify_proofs(&self, resolver: &Resolver, response: &Packet, signatures: &[&RRSIG], now: u32) -> Verdict<bool> {
+        let proofs: Vec<RRset> = unsigned_rrsets(&response.authorities)
+            .into_iter()
+            .filter(|rrset| matches!(rrset.qtype, QueryType::StartOfAuthority | QueryType::NextSecure | QueryType::NextSecure3))
+            .collect();
+
+        if proofs.is_empty() {
+            return Err("missing denial of existence".to_string());
+        }
+
+        for rrset in &proofs {
+            if self.verify_rrset(resolver, rrset, signatures, now)?.is_none() {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    // Verify an RRset with the keys of its signer, returning the verified
+    // signature or nothing when the RRset is part of an unsigned zone.
+    fn verify_rrset(&self, resolver: &Resolver, rrset: &RRset, signatures: &[&RRSIG], now: u32) -> Verdict<Option<RRSIG>> {
+        let covering: Vec<&RRSIG> = signatures
+            .iter()
+            .copied()
+            .filter(|rrsig| rrsig.type_covered == rrset.qtype && rrsig.domain.eq_ignore_ascii_case(&rrset.domain))
+            .collect();
+
+        let signer = match signer(rrset, &covering)? {
+            Some(signer) => signer,
+            None => {
+                return match self.name_security(resolver, &rrset.domain, 0)? {
+                    Security::Insecure => Ok(None),
+                    _ => Err(format!("missing signature for {} {}", rrset.domain, rrset.qtype)),
+                };
+            }
+        };
+
+        match self.zone_keys(resolver, &signer, 0)? {
+            // The signer is claimed by the records, their own zone must be
+            // unsigned as well for them to be insecure.
+            ZoneKeys::Insecure => match self.name_security(resolver, &rrset.domain, 0)? {
+                Security::Insecure => Ok(None),
+                _ => Err(format!("{} {} signed by the unsigned zone {}", rrset.domain, rrset.qtype, signer)),
+            },
+            ZoneKeys::Secure(keys) => {
+                let covering: Vec<&RRSIG> = covering
+                    .into_iter()
+                    .filter(|rrsig| rrsig.signer_name.eq_ignore_ascii_case(&signer))
+                    .collect();
+
+                validation::verify_rrset(&covering, &keys, rrset.records(), now)
+                    .map(|rrsig| Some(rrsig.clone()))
+                    .map_err(|err| format!("{} {}: {}", rrset.domain, rrset.qtype, err))
+            }
+        }
+    }
+
+    // Security of the zone a name belongs to, found with its SOA record.
+    fn name_security(&self, resolver: &Resolver, name: &str, depth: usize) -> Verdict<Security> {
+        let response = resolver.resolve(name, QueryType::StartOfAuthority, true).map_err(|err| err.to_string())?;
+        let apex = response.answers
+            .iter()
+            .chain(&response.authorities)
+            .find_map(|record| match record {
+                Record::StartOfAuthority(soa) if is_subdomain(name, &soa.domain) => Some(soa.domain.clone()),
+                _ => None,
+            })
+            .ok_or_else(|| format!("no zone found for {}", name))?;
+
+        match self.zone_keys(resolver, &apex, depth + 1)? {
+            ZoneKeys::Secure(_) => Ok(Security::Secure),
+            ZoneKeys::Insecure => Ok(Security::Insecure),
+        }
+    }
+
+    // Validated keys of a zone, from the cache or by walking the chain of
+    // trust down from the root.
+    fn zone_keys(&self, resolver: &Resolver, zone: &str, depth: usize) -> Verdict<ZoneKeys> {
+        let zone = zone.trim_end_matches('.').to_lowercase();
+        if let Some((keys, expiration)) = self.keys.lock().unwrap().get(&zone) {
+            if *expiration > Instant::now() {
+                return Ok(keys.clone());
+            }
+        }
+
+        if depth > MAX_DEPTH {
+            return Err(format!("too many zones to validate {}", zone));
+        }
+
+        // Zones with trust anchors start a chain of trust, the others below
+        // no anchor being unsigned as far as the validator knows.
+        let anchors = self.trust_anchors.lock().unwrap();
+        let trusted = anchors.is_anchored(&zone).then(|| anchors.trusted(&zone));
+        drop(anchors);
+
+        let keys = if let Some(trusted) = trusted {
+            if trusted.is_empty() {
+                return Err(format!("no trust anchor of {} is trusted", zone));
+            }
+
+            self.fetch_keys(resolver, &zone, &trusted)?
+        } else if zone.is_empty() {
+            (ZoneKeys::Insecure, INSECURE_ZONE_TTL)
+        } else {
+            match self.delegation(resolver, &zone, depth)? {
+                Some(ds) => self.fetch_keys(resolver, &zone, &ds)?,
+                None => (ZoneKeys::Insecure, INSECURE_ZONE_TTL),
+            }
+        };
+
+        debug!(zone = %zone, keys = ?keys.0, "validated zone keys");
+        self.keys.lock().unwrap().insert(zone, (keys.0.clone(), Instant::now() + keys.1));
+        Ok(keys.0)
+    }
+
+    // DS records of a zone validated with the keys of its parent, nothing
+    // meaning a proven unsigned delegation.
+    fn delegation(&self, resolver: &Resolver, zone: &str, depth: usize) -> Verdict<Option<Vec<DS>>> {
+        let response = resolver.resolve(zone, QueryType::DelegationSigner, true).map_err(|err| err.to_string())?;
+        let signatures = signatures(&response);
+
+        // An unsigned response is only acceptable from an unsigned parent.
+        let signer = match signatures.first() {
+            Some(rrsig) => rrsig.signer_name.clone(),
+            None => {
+                return match self.name_security(resolver, parent(zone), depth + 1)? {
+                    Security::Insecure => Ok(None),
+                    _ => Err(format!("missing signature for the DS records of {}", zone)),
+                };
+            }
+        };
+
+        // DS records and their denials are signed by the parent zone only,
+        // which also prevents loops in the chain of trust.
+        if signer.eq_ignore_ascii_case(zone) || !is_subdomain(zone, &signer) {
+            return Err(format!("DS records of {} signed by {}", zone, signer));
+        }
+
+        let keys = match self.zone_keys(resolver, &signer, depth + 1)? {
+            ZoneKeys::Secure(keys) => keys,
+            ZoneKeys::Insecure => return Ok(None),
+        };
+
+        let now = now();
+        let rrsets: Vec<RRset> = unsigned_rrsets(&response.answers)
+            .into_iter()
+            .chain(unsigned_rrsets(&response.authorities))
+            .filter(|rrset| matches!(rrset.qtype, QueryType::DelegationSigner | QueryType::StartOfAuthority | QueryType::NextSecure | QueryType::NextSecure3))
+            .collect();
+
+        for rrset in &rrsets {
+            let covering: Vec<&RRSIG> = signatures
+                .iter()
+                .copied()
+                .filter(|rrsig| rrsig.type_covered == rrset.qtype && rrsig.domain.eq_ignore_ascii_case(&rrset.domain))
+                .collect();
+
+            validation::verify_rrset(&covering, &keys, rrset.records(), now)
+                .map_err(|err| format!("{} {}: {}", rrset.domain, rrset.qtype, err))?;
+        }
+
+        let ds = rrsets
+            .iter()
+            .find(|rrset| rrset.qtype == QueryType::DelegationSigner && rrset.domain.eq_ignore_ascii_case(zone));
+        if let Some(ds) = ds {
+            let ds = ds.records()
+                .iter()
+                .filter_map(|record| match record {
+                    Record::DelegationSigner(ds) => Some(ds.clone()),
+                    _ => None,
+                })
+                .collect();
+
+            return Ok(Some(ds));
+        }
+
+        match validation::verify_denial(&response.authorities, zone, QueryType::DelegationSigner) {
+            Ok(Denial::InsecureDelegation) | Ok(Denial::ExcessiveIterations) => Ok(None),
+            Ok(denial) => Err(format!("{:?} proof of the DS records of {}", denial, zone)),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    // DNSKEY records of a zone, whose RRset is signed by a key matching
+    // one of the DS records, along with the TTL of the RRset.
+    fn fetch_keys(&self, resolver: &Resolver, zone: &str, ds: &[DS]) -> Verdict<(ZoneKeys, Duration)> {
+        // Zones only signed with unsupported algorithms are treated as
+        // unsigned (RFC 4035 section 5.2).
+        let ds: Vec<&DS> = ds
+            .iter()
+            .filter(|ds| crypto::is_supported(ds.algorithm) && crypto::is_supported_digest(ds.digest_type))
+            .collect();
+        if ds.is_empty() {
+            return Ok((ZoneKeys::Insecure, INSECURE_ZONE_TTL));
+        }
+
+        let response = resolver.resolve(zone, QueryType::DnsKey, true).map_err(|err| err.to_string())?;
+        let rrset = unsigned_rrsets(&response.answers)
+            .into_iter()
+            .find(|rrset| rrset.qtype == QueryType::DnsKey && rrset.domain.eq_ignore_ascii_case(zone))
+            .ok_or_else(|| format!("missing DNSKEY records for {}", zone))?;
+
+        let dnskeys: Vec<DNSKEY> = rrset.records()
+            .iter()
+            .filter_map(|record| match record {
+                Record::DnsKey(dnskey) => Some(dnskey.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let entry_keys: Vec<DNSKEY> = dnskeys
+            .iter()
+            .filter(|dnskey| ds.iter().any(|ds| validation::matches_ds(dnskey, ds)))
+            .cloned()
+            .collect();
+        if entry_keys.is_empty() {
+            return Err(format!("no DNSKEY of {} matches its DS records", zone));
+        }
+
+        let signatures = signatures(&response);
+        let covering: Vec<&RRSIG> = signatures
+            .into_iter()
+            .filter(|rrsig| rrsig.type_covered == QueryType::DnsKey && rrsig.domain.eq_ignore_ascii_case(zone))
+            .collect();
+
+        let now = now();
+        validation::verify_rrset(&covering, &entry_keys, rrset.records(), now)
+            .map_err(|err| format!("{} DNSKEY: {}", zone, err))?;
+
+        // Keys of anchored zones are tracked for rollovers (RFC 5011).
+        let mut anchors = self.trust_anchors.lock().unwrap();
+        if anchors.is_anchored(zone) {
+            anchors.update(zone, rrset.records(), &covering, now);
+        }
+
+        Ok((ZoneKeys::Secure(dnskeys), rrset.ttl()))
+    }
+}
+
+// Signatures of the answer and authority sections.
+fn signatures(response: &Packet) -> Vec<&RRSIG> {
+    response.answers
+        .iter()
+        .chain(&response.authorities)
+        .filter_map(|record| match record {
+            Record::Signature(rrsig) => Some(rrsig),
+            _ => None,
+        })
+        .collect()
+}
+
+// Names of the CNAME chain starting at the queried name, every answer
+// being owned by one of them.
+fn alias_chain(answers: &[Record], qname: &str, qtype: QueryType) -> Verdict<Vec<String>> {
+    let mut chain = vec![qname.to_string()];
+    for _ in 0..answers.len() {
+        let name = &chain[chain.len() - 1];
+        match answers.iter().find(|record| record.domain().eq_ignore_ascii_case(name) && record.qtype() == QueryType::CanonicalName) {
+            Some(Record::CanonicalName(cname)) if qtype != QueryType::CanonicalName => {
+                if chain.iter().any(|name| name.eq_ignore_ascii_case(&cname.alias)) {
+                    return Err(format!("CNAME loop at {}", cname.alias));
+                }
+
+                chain.push(cname.alias.clone());
+            }
+            _ => break,
+        }
+    }
+
+    match answers.iter().find(|record| !chain.iter().any(|name| record.domain().eq_ignore_ascii_case(name))) {
+        Some(record) => Err(format!("{} {} doesn't answer {}", record.domain(), record.qtype(), qname)),
+        None => Ok(chain),
+    }
+}
+
+// Zone signing an RRset according to its signatures, which can only be the
+// zone of the records or one of its ancestors.
+fn signer(rrset: &RRset, covering: &[&RRSIG]) -> Verdict<Option<String>> {
+    if let Some(rrsig) = covering.iter().find(|rrsig| !is_subdomain(&rrset.domain, &rrsig.signer_name)) {
+        return Err(format!("{} {} signed by {} outside of its zone", rrset.domain, rrset.qtype, rrsig.signer_name));
+    }
+
+    Ok(covering.first().map(|rrsig| rrsig.signer_name.clone()))
+}
+
+// Authority records which may take part in the proofs about a name, NSEC
+// and NSEC3 records being left out unless they are signed by a zone the
+// name belongs to, so that another zone can't deny it.
+fn zone_proofs(authorities: &[Record], signatures: &[&RRSIG], name: &str) -> Vec<Record> {
+    authorities
+        .iter()
+        .filter(|record| match record {
+            Record::NextSecure(_) | Record::NextSecure3(_) => {
+                let mut covering = signatures
+                    .iter()
+                    .filter(|rrsig| rrsig.type_covered == record.qtype() && rrsig.domain.eq_ignore_ascii_case(record.domain()))
+                    .peekable();
+
+                covering.peek().is_some() && covering.all(|rrsig| is_subdomain(name, &rrsig.signer_name))
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+// RRsets of the records, without their signatures.
+fn unsigned_rrsets(records: &[Record]) -> Vec<RRset> {
+    RRset::group(records.iter().filter(|record| !matches!(record, Record::Signature(_))).cloned())
+}
+
+fn parent(name: &str) -> &str {
+    name.split_once('.').map_or("", |(_, parent)| parent)
+}
+
+// Whether the name is the domain or one of its subdomains.
+pub(crate) fn is_subdomain(name: &str, domain: &str) -> bool {
+    let (name, domain) = (name.trim_end_matches('.').to_lowercase(), domain.trim_end_matches('.').to_lowercase());
+    domain.is_empty() || name == domain || name.ends_with(&format!(".{}", domain))
+}
+
+// Current time in seconds since the epoch, as used by RRSIG records.
+pub(crate) fn now() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use protocol::packet::{Class, QueryType, Record};
+    use protocol::records::dnssec::{Algorithm, TypeBitmap};
+    use protocol::records::{CName, A, NSEC, RRSIG};
+    use protocol::rrset::RRset;
+
+    use crate::validator::{alias_chain, is_subdomain, parent, signer, zone_proofs, Validator};
+
+    fn a(domain: &str) -> Record {
+        Record::A(A {
+            domain: domain.to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(3600),
+            ip: Ipv4Addr::new(192, 0, 2, 1),
+        })
+    }
+
+    fn cname(domain: &str, alias: &str) -> Record {
+        Record::CanonicalName(CName {
+            domain: domain.to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(3600),
+            alias: alias.to_string(),
+        })
+    }
+
+    #[test]
+    fn root_anchors() {
+        let validator = Validator::with_root_anchors();
+        let trusted = validator.trust_anchors.lock().unwrap().trusted("");
+        assert_eq!(2, trusted.len());
+        assert!(trusted.iter().all(|ds| ds.domain.is_empty()));
+        assert_eq!(20326, trusted[0].key_tag);
+    }
+
+    #[test]
+    fn zone_names() {
+        assert_eq!("example.com", parent("www.example.com"));
+        assert_eq!("", parent("com"));
+        assert!(is_subdomain("www.Example.com.", "example.com"));
+        assert!(is_subdomain("com", ""));
+        assert!(!is_subdomain("badexample.com", "example.com"));
+    }
+
+    #[test]
+    fn spoofed_signer() {
+        let rrset = RRset::group(vec![a("www.example.com")]).pop().unwrap();
+
+        let rrsig = |signer_name: &str| RRSIG {
+            domain: "www.example.com".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(3600),
+            type_covered: QueryType::A,
+            algorithm: Algorithm::Ed25519,
+            labels: 3,
+            original_ttl: Duration::from_secs(3600),
+            expiration: 0,
+            inception: 0,
+            key_tag: 0,
+            signer_name: signer_name.to_string(),
+            signature: vec![],
+        };
+
+        assert_eq!(Ok(None), signer(&rrset, &[]));
+        assert_eq!(Ok(Some("example.com".to_string())), signer(&rrset, &[&rrsig("example.com")]));
+        assert_eq!(Ok(Some("com".to_string())), signer(&rrset, &[&rrsig("com")]));
+
+        // Signatures claiming to come from an unrelated, possibly unsigned,
+        // zone are bogus rather than insecure.
+        assert!(signer(&rrset, &[&rrsig("example.org")]).is_err());
+        assert!(signer(&rrset, &[&rrsig("example.com"), &rrsig("unsigned.example.net")]).is_err());
+        assert!(signer(&rrset, &[&rrsig("sub.www.example.com")]).is_err());
+    }
+
+    #[test]
+    fn foreign_denials() {
+        let nsec = Record::NextSecure(NSEC {
+            domain: "zz.example.org".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(3600),
+            next_domain: "example.org".to_string(),
+            types: TypeBitmap::new(),
+        });
+        let rrsig = RRSIG {
+            domain: "zz.example.org".to_string(),
+            class: Class::Internet,
+            ttl: Duration::from_secs(3600),
+            type_covered: QueryType::NextSecure,
+            algorithm: Algorithm::Ed25519,
+            labels: 3,
+            original_ttl: Duration::from_secs(3600),
+            expiration: 0,
+            inception: 0,
+            key_tag: 0,
+            signer_name: "example.org".to_string(),
+            signature: vec![],
+        };
+        let authorities = vec![nsec, Record::Signature(rrsig.clone())];
+
+        assert_eq!(2, zone_proofs(&authorities, &[&rrsig], "www.example.org").len());
+        assert_eq!(2, zone_proofs(&authorities, &[&rrsig], "example.org").len());
+
+        // NSEC records of another zone prove nothing about the name, even
+        // when their chain wraps around past it.
+        assert_eq!(1, zone_proofs(&authorities, &[&rrsig], "www.bank.com").len());
+        assert!(zone_proofs(&authorities[..1], &[], "www.example.org").is_empty());
+    }
+
+    #[test]
+    fn alias_chains() {
+        let answers = vec![cname("www.example.com", "web.example.net"), cname("Web.Example.net", "cdn.example.org"), a("cdn.example.org")];
+        let chain = alias_chain(&answers, "www.example.com", QueryType::A).unwrap();
+        assert_eq!(vec!["www.example.com", "web.example.net", "cdn.example.org"], chain);
+        assert_eq!(vec!["www.example.com"], alias_chain(&answers[..1], "www.example.com", QueryType::CanonicalName).unwrap());
+
+        // Unresolved targets end the chain, which is then longer than the
+        // queried name alone.
+        assert_eq!(2, alias_chain(&answers[..1], "www.example.com", QueryType::A).unwrap().len());
+
+        // Records unrelated to the question, such as replayed RRsets of
+        // other names, are rejected.
+        assert!(alias_chain(&[a("other.example.com")], "www.example.com", QueryType::A).is_err());
+        assert!(alias_chain(&[a("www.example.com"), a("other.example.com")], "www.example.com", QueryType::A).is_err());
+        assert!(alias_chain(&[cname("www.example.com", "www.example.com")], "www.example.com", QueryType::A).is_err());
+    }
+}
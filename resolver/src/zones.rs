@@ -0,0 +1,592 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use anyhow::{bail, Context, Result};
+use tracing::{info, warn};
+
+use protocol::header::ResultCode;
+use protocol::packet::{Packet, QueryType, Record};
+use protocol::records::dnssec::label_count;
+use protocol::signing::{DenialChain, ZoneKey, ZoneSigner};
+use protocol::validation;
+
+use crate::validator::is_subdomain;
+
+// Maximum number of CNAME records followed within a zone to answer a
+// question.
+const MAX_ALIASES: usize = 8;
+
+/// Zones served authoritatively, signed with their keys when loaded and
+/// signed again before their signatures expire (RFC 4035 section 2).
+pub struct Zones {
+    zones: RwLock<Vec<Zone>>,
+}
+
+struct Zone {
+    signer: ZoneSigner,
+    // Records of the zone file, without the records generated when signing.
+    records: Vec<Record>,
+    // Records served, along with their signatures and the denial chain.
+    signed: Vec<Record>,
+}
+
+impl Zones {
+    pub fn new() -> Zones {
+        Zones {
+            zones: RwLock::new(vec![]),
+        }
+    }
+
+    /// Load zone files and sign each zone with the keys of its apex, read
+    /// from BIND key files: `<path>.key` holds the DNSKEY record and
+    /// `<path>.private` the private key. `now` is the current time in
+    /// seconds since the epoch.
+    ///
+    /// Zone files hold one record per line in presentation format, with
+    /// fully qualified names. The owner of the SOA record is the apex.
+    pub fn load<Z, K>(zone_files: &[Z], key_files: &[K], chain: DenialChain, now: u32) -> Result<Zones>
+        where
+            Z: AsRef<Path>,
+            K: AsRef<Path>,
+    {
+        let mut keys = key_files.iter().map(load_key).collect::<Result<Vec<ZoneKey>>>()?;
+
+        let zones = Zones::new();
+        for path in zone_files {
+            let path = path.as_ref();
+            let records = load_records(path)?;
+            let soas: Vec<&Record> = records.iter().filter(|record| record.qtype() == QueryType::StartOfAuthority).collect();
+            let apex = match soas[..] {
+                [soa] => soa.domain().to_string(),
+                _ => bail!("{} must hold a single SOA record", path.display()),
+            };
+
+            let (zone_keys, others): (Vec<ZoneKey>, Vec<ZoneKey>) = keys
+                .into_iter()
+                .partition(|key| key.dnskey.domain.eq_ignore_ascii_case(&apex));
+            keys = others;
+            if zone_keys.is_empty() {
+                bail!("no key to sign the {} zone of {}", apex, path.display());
+            }
+
+            let signer = ZoneSigner::new(&apex, zone_keys).chain(chain.clone());
+            zones.insert(signer, records, now).with_context(|| format!("can't sign {}", path.display()))?;
+        }
+
+        if let Some(key) = keys.first() {
+            bail!("the key {} of {} matches no zone", key.dnskey.key_tag(), key.dnskey.domain);
+        }
+
+        Ok(zones)
+    }
+
+    /// Sign the records of a zone and serve them, replacing the zone if it
+    /// is already served.
+    pub fn insert(&self, signer: ZoneSigner, records: Vec<Record>, now: u32) -> Result<()> {
+        let signed = signer.sign_zone(records.clone(), now)?;
+        info!(zone = %signer.zone(), records = signed.len(), "zone signed");
+
+        let mut zones = self.zones.write().unwrap();
+        zones.retain(|zone| !zone.apex().eq_ignore_ascii_case(signer.zone()));
+        zones.push(Zone { signer, records, signed });
+        Ok(())
+    }
+
+    /// Sign again the zones whose signatures expire within their refresh
+    /// period, the previous signatures being served meanwhile.
+    pub fn refresh(&self, now: u32) {
+        let signed: Vec<(usize, Vec<Record>)> = self.zones
+            .read()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .filter(|(_, zone)| zone.signer.needs_refresh(&zone.signed, now))
+            .filter_map(|(i, zone)| match zone.signer.sign_zone(zone.records.clone(), now) {
+                Ok(signed) => Some((i, signed)),
+                Err(err) => {
+                    warn!(zone = %zone.apex(), cause = %err, "zone signing failed");
+                    None
+                }
+            })
+            .collect();
+
+        let mut zones = self.zones.write().unwrap();
+        for (i, signed) in signed {
+            info!(zone = %zones[i].apex(), records = signed.len(), "zone signed again");
+            zones[i].signed = signed;
+        }
+    }
+
+    /// Authoritative answer to a question about the closest served zone of
+    /// the name, nothing when no zone holds it.
+    pub fn answer(&self, qname: &str, qtype: QueryType) -> Option<Packet> {
+        let qname = qname.trim_end_matches('.').to_lowercase();
+        let zones = self.zones.read().unwrap();
+        let zone = zones
+            .iter()
+            .filter(|zone| is_subdomain(&qname, zone.apex()))
+            .max_by_key(|zone| label_count(zone.apex()))?;
+
+        Some(zone.answer(&qname, qtype))
+    }
+}
+
+impl Zone {
+    fn apex(&self) -> &str {
+        self.signer.zone()
+    }
+
+    // Answer with the records of the name, or of the wildcard matching it,
+    // following the CNAME records within the zone. Names below delegation
+    // points are referred to the child zone.
+    fn answer(&self, qname: &str, qtype: QueryType) -> Packet {
+        let mut answers = vec![];
+        let mut authorities = vec![];
+        let mut additionals = vec![];
+        let mut authoritative = true;
+        let mut name = qname.to_string();
+
+        let rcode = loop {
+            if let Some(cut) = self.delegation(&name, qtype) {
+                let ns = self.rrset(&cut, QueryType::AuthoritativeNameServer);
+                let ds = self.rrset(&cut, QueryType::DelegationSigner);
+
+                // Unsigned delegations are proven with the denial of their
+                // DS records (RFC 4035 section 3.1.4).
+                additionals = self.glue(&ns);
+                authorities.extend(ns);
+                authorities.extend(if ds.is_empty() { self.proofs(&[cut.clone(), parent(&cut).to_string()]) } else { ds });
+                authoritative = !answers.is_empty();
+                break ResultCode::NoError;
+            }
+
+            // Names which don't exist may be matched by the wildcard of
+            // their closest encloser, whose expansion is proven by the
+            // denial of the name (RFC 4035 section 3.1.3.3).
+            let encloser = self.closest_encloser(&name);
+            let source = if encloser == name {
+                name.clone()
+            } else {
+                let wildcard = format!("*.{}", encloser);
+                if !self.exists(&wildcard) {
+                    authorities.extend(self.rrset(self.apex(), QueryType::StartOfAuthority));
+                    authorities.extend(self.proofs(&denied_names(&name, &encloser)));
+                    break ResultCode::NxDomain;
+                }
+
+                authorities.extend(self.proofs(&denied_names(&name, &encloser)));
+                wildcard
+            };
+
+            let records = self.rrset(&source, qtype);
+            if !records.is_empty() {
+                answers.extend(rename(records, &name));
+                break ResultCode::NoError;
+            }
+
+            let cname = self.rrset(&source, QueryType::CanonicalName);
+            let alias = cname.iter().find_map(|record| match record {
+                Record::CanonicalName(cname) => Some(cname.alias.clone()),
+                _ => None,
+            });
+
+            match alias {
+                Some(alias) if qtype != QueryType::CanonicalName => {
+                    answers.extend(rename(cname, &name));
+
+                    let aliases = answers.iter().filter(|record| record.qtype() == QueryType::CanonicalName).count();
+                    let followed = answers.iter().any(|record| record.domain().eq_ignore_ascii_case(&alias));
+                    if aliases > MAX_ALIASES || followed || !is_subdomain(&alias, self.apex()) {
+                        break ResultCode::NoError;
+                    }
+
+                    name = alias.to_lowercase();
+                }
+                _ => {
+                    authorities.extend(self.rrset(self.apex(), QueryType::StartOfAuthority));
+                    authorities.extend(self.proofs(&[source]));
+                    break ResultCode::NoError;
+                }
+            }
+        };
+
+        let mut response = Packet::query(qname, qtype)
+            .build()
+            .reply()
+            .authoritative(authoritative)
+            .rcode(rcode)
+            .answers(answers)
+            .authorities(authorities)
+            .additionals(additionals)
+            .build();
+        response.dedup();
+        response
+    }
+
+    // Topmost delegation point at or above the name, DS records being
+    // answered by the parent side of the delegation.
+    fn delegation(&self, name: &str, qtype: QueryType) -> Option<String> {
+        ancestors(name, self.apex())
+            .into_iter()
+            .rev()
+            .filter(|cut| cut != self.apex())
+            .filter(|cut| cut != name || qtype != QueryType::DelegationSigner)
+            .find(|cut| self.served().any(|record| record.qtype() == QueryType::AuthoritativeNameServer && record.domain().eq_ignore_ascii_case(cut)))
+    }
+
+    // Whether the name holds records or is an empty non-terminal, the
+    // owners of the NSEC3 records being hashes rather than names.
+    fn exists(&self, name: &str) -> bool {
+        self.served().any(|record| is_subdomain(record.domain(), name))
+    }
+
+    fn closest_encloser(&self, name: &str) -> String {
+        ancestors(name, self.apex())
+            .into_iter()
+            .find(|ancestor| self.exists(ancestor))
+            .unwrap_or_else(|| self.apex().to_string())
+    }
+
+    // Records of the name and type followed by their signatures, every
+    // type matching ANY questions.
+    fn rrset(&self, name: &str, qtype: QueryType) -> Vec<Record> {
+        let matches = |record_type: QueryType| qtype == QueryType::Unknown(255) || record_type == qtype;
+        let records = self.signed.iter().filter(|record| record.domain().eq_ignore_ascii_case(name));
+
+        let (signatures, records): (Vec<&Record>, Vec<&Record>) = records.partition(|record| record.qtype() == QueryType::Signature);
+        records
+            .into_iter()
+            .filter(|record| matches(record.qtype()))
+            .chain(signatures.into_iter().filter(|record| match record {
+                Record::Signature(rrsig) => qtype == QueryType::Signature || matches(rrsig.type_covered),
+                _ => false,
+            }))
+            .cloned()
+            .collect()
+    }
+
+    // NSEC or NSEC3 records matching or covering the names, along with
+    // their signatures.
+    fn proofs(&self, names: &[String]) -> Vec<Record> {
+        let proofs: Vec<&Record> = self.signed
+            .iter()
+            .filter(|record| names.iter().any(|name| validation::is_denial_of(record, name)))
+            .collect();
+
+        self.signed
+            .iter()
+            .filter(|record| {
+                proofs.contains(record)
+                    || matches!(record, Record::Signature(rrsig) if proofs.iter().any(|proof| proof.qtype() == rrsig.type_covered && proof.domain().eq_ignore_ascii_case(&rrsig.domain)))
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Addresses of the name servers of a delegation held by the zone.
+    fn glue(&self, ns: &[Record]) -> Vec<Record> {
+        ns.iter()
+            .filter_map(|record| match record {
+                Record::AuthoritativeNameServer(ns) => Some(ns.ns_name.as_str()),
+                _ => None,
+            })
+            .filter(|name| is_subdomain(name, self.apex()))
+            .flat_map(|name| self.signed.iter().filter(move |record| record.qtype() == QueryType::A && record.domain().eq_ignore_ascii_case(name)))
+            .cloned()
+            .collect()
+    }
+
+    // Records of the zone other than the NSEC3 chain and its signatures.
+    fn served(&self) -> impl Iterator<Item=&Record> {
+        self.signed.iter().filter(|record| match record {
+            Record::NextSecure3(_) => false,
+            Record::Signature(rrsig) => rrsig.type_covered != QueryType::NextSecure3,
+            _ => true,
+        })
+    }
+}
+
+/// Load the records of a zone file, one per line in presentation format.
+pub fn load_records<P>(path: P) -> Result<Vec<Record>>
+    where P: AsRef<Path>
+{
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).with_context(|| format!("can't read zone from {}", path.display()))?;
+
+    content
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with(';'))
+        .map(|(i, line)| line.parse().with_context(|| format!("invalid record at {}:{}", path.display(), i + 1)))
+        .collect()
+}
+
+/// Load a zone key from BIND key files, `<path>.key` and `<path>.private`.
+pub fn load_key<P>(path: P) -> Result<ZoneKey>
+    where P: AsRef<Path>
+{
+    // Key names hold dots, such as `Kexample.com.+015+03613`.
+    let path = path.as_ref();
+    let with_suffix = |suffix: &str| {
+        let mut file = path.as_os_str().to_owned();
+        file.push(suffix);
+        PathBuf::from(file)
+    };
+    let (key_path, private_path) = (with_suffix(".key"), with_suffix(".private"));
+
+    let key_file = fs::read_to_string(&key_path).with_context(|| format!("can't read key from {}", key_path.display()))?;
+    let private_file = fs::read_to_string(&private_path).with_context(|| format!("can't read private key from {}", private_path.display()))?;
+    ZoneKey::parse(&key_file, &private_file).with_context(|| format!("invalid key {}", path.display()))
+}
+
+// The name and its ancestors down to the apex, closest first.
+fn ancestors(name: &str, apex: &str) -> Vec<String> {
+    let labels: Vec<&str> = name.split('.').filter(|label| !label.is_empty()).collect();
+    (0..=labels.len())
+        .map(|i| labels[i..].join("."))
+        .filter(|ancestor| is_subdomain(ancestor, apex))
+        .collect()
+}
+
+fn parent(name: &str) -> &str {
+    name.split_once('.').map_or("", |(_, parent)| parent)
+}
+
+// Names whose denial proves that a name doesn't exist below its closest
+// encloser: the name and the next closer name for NSEC and NSEC3 records,
+// the encloser itself and its wildcard.
+fn denied_names(name: &str, encloser: &str) -> Vec<String> {
+    let next_closer = ancestors(name, encloser)
+        .into_iter()
+        .rev()
+        .nth(1)
+        .unwrap_or_else(|| name.to_string());
+
+    vec![name.to_string(), next_closer, encloser.to_string(), format!("*.{}", encloser)]
+}
+
+// Copies of the records, and of their signatures, owned by the name.
+fn rename(records: Vec<Record>, name: &str) -> Vec<Record> {
+    records
+        .into_iter()
+        .map(|mut record| {
+            if let Some(domain) = record.domain_mut() {
+                *domain = name.to_string();
+            }
+
+            record
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use protocol::header::ResultCode;
+    use protocol::packet::{Packet, QueryType, Record};
+    use protocol::records::{DNSKEY, RRSIG};
+    use protocol::signing::DenialChain;
+    use protocol::validation::{verify_denial, verify_rrset, Denial};
+
+    use crate::zones::Zones;
+
+    // Key of RFC 8080 section 6.1, signing the whole zone.
+    const KEY_FILE: &str = "; This is a key-signing key, keyid 3613, for example.com.\n\
+        example.com. 3600 IN DNSKEY 257 3 15 l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=";
+    const PRIVATE_FILE: &str = "Private-key-format: v1.2\n\
+        Algorithm: 15 (ED25519)\n\
+        PrivateKey: ODIyNjAzODQ2MjgwODAxMjI2NDUxOTAyMDQxNDIyNjI=\n";
+
+    const ZONE_FILE: &str = "; Zone of the tests.\n\
+        example.com. 3600 IN SOA ns.example.com. admin.example.com. 1 7200 3600 1209600 300\n\
+        example.com. 3600 IN NS ns.example.com.\n\
+        example.com. 3600 IN MX 10 mail.example.com.\n\
+        ns.example.com. 3600 IN A 192.0.2.1\n\
+        www.example.com. 3600 IN A 192.0.2.2\n\
+        alias.example.com. 3600 IN CNAME www.example.com.\n\
+        *.wild.example.com. 3600 IN A 192.0.2.3\n\
+        host.deep.example.com. 3600 IN A 192.0.2.4\n\
+        sub.example.com. 3600 IN NS ns.sub.example.com.\n\
+        ns.sub.example.com. 3600 IN A 192.0.2.5\n";
+
+    const NOW: u32 = 1700000000;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}-{}-{}", name, std::process::id(), rand::random::<u32>()))
+    }
+
+    fn zones(chain: DenialChain) -> Zones {
+        let (zone, key) = (temp_path("zone"), temp_path("Kexample.com.+015+03613"));
+        let key_file = PathBuf::from(format!("{}.key", key.display()));
+        let private_file = PathBuf::from(format!("{}.private", key.display()));
+        fs::write(&zone, ZONE_FILE).unwrap();
+        fs::write(&key_file, KEY_FILE).unwrap();
+        fs::write(&private_file, PRIVATE_FILE).unwrap();
+
+        let zones = Zones::load(&[&zone], &[&key], chain, NOW);
+        for path in [zone, key_file, private_file] {
+            fs::remove_file(path).unwrap();
+        }
+
+        zones.unwrap()
+    }
+
+    fn dnskeys(zones: &Zones) -> Vec<DNSKEY> {
+        zones
+            .answer("example.com", QueryType::DnsKey)
+            .unwrap()
+            .answers
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::DnsKey(dnskey) => Some(dnskey),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Verify the signatures of an RRset of a response section.
+    fn verify(zones: &Zones, records: &[Record], name: &str, qtype: QueryType) -> RRSIG {
+        let rrset: Vec<Record> = records.iter().filter(|record| record.qtype() == qtype && record.domain() == name).cloned().collect();
+        let rrsigs: Vec<&RRSIG> = records
+            .iter()
+            .filter_map(|record| match record {
+                Record::Signature(rrsig) if rrsig.type_covered == qtype && rrsig.domain == name => Some(rrsig),
+                _ => None,
+            })
+            .collect();
+
+        assert!(!rrset.is_empty());
+        verify_rrset(&rrsigs, &dnskeys(zones), &rrset, NOW).unwrap().clone()
+    }
+
+    fn denial(response: &Packet, qname: &str, qtype: QueryType) -> Denial {
+        verify_denial(&response.authorities, qname, qtype).unwrap()
+    }
+
+    #[test]
+    fn answers() {
+        let zones = zones(DenialChain::Nsec);
+        assert!(zones.answer("example.org", QueryType::A).is_none());
+
+        let response = zones.answer("WWW.Example.com.", QueryType::A).unwrap();
+        assert!(response.header.authoritative_answer);
+        assert_eq!(ResultCode::NoError, response.header.result_code);
+        assert_eq!(2, response.answers.len());
+        verify(&zones, &response.answers, "www.example.com", QueryType::A);
+
+        // DNSKEY, CDS and CDNSKEY records are generated at the apex.
+        assert_eq!(1, dnskeys(&zones).len());
+        for qtype in [QueryType::ChildDelegationSigner, QueryType::ChildDnsKey] {
+            let response = zones.answer("example.com", qtype).unwrap();
+            verify(&zones, &response.answers, "example.com", qtype);
+        }
+
+        // Aliases are followed within the zone.
+        let response = zones.answer("alias.example.com", QueryType::A).unwrap();
+        verify(&zones, &response.answers, "alias.example.com", QueryType::CanonicalName);
+        verify(&zones, &response.answers, "www.example.com", QueryType::A);
+    }
+
+    #[test]
+    fn denials() {
+        for chain in [DenialChain::Nsec, DenialChain::Nsec3 { iterations: 0, salt: vec![], opt_out: false }] {
+            let zones = zones(chain);
+
+            let response = zones.answer("missing.example.com", QueryType::A).unwrap();
+            assert_eq!(ResultCode::NxDomain, response.header.result_code);
+            assert!(response.answers.is_empty());
+            verify(&zones, &response.authorities, "example.com", QueryType::StartOfAuthority);
+            assert_eq!(Denial::NameError, denial(&response, "missing.example.com", QueryType::A));
+
+            let response = zones.answer("www.example.com", QueryType::Text).unwrap();
+            assert_eq!(ResultCode::NoError, response.header.result_code);
+            assert!(response.answers.is_empty());
+            assert_eq!(Denial::NoData, denial(&response, "www.example.com", QueryType::Text));
+
+            // Empty non-terminals exist without any record.
+            let response = zones.answer("deep.example.com", QueryType::A).unwrap();
+            assert_eq!(ResultCode::NoError, response.header.result_code);
+            assert_eq!(Denial::NoData, denial(&response, "deep.example.com", QueryType::A));
+        }
+    }
+
+    #[test]
+    fn wildcards() {
+        for chain in [DenialChain::Nsec, DenialChain::Nsec3 { iterations: 0, salt: vec![], opt_out: false }] {
+            let zones = zones(chain);
+
+            let response = zones.answer("host.wild.example.com", QueryType::A).unwrap();
+            assert_eq!(ResultCode::NoError, response.header.result_code);
+            let rrsig = verify(&zones, &response.answers, "host.wild.example.com", QueryType::A);
+            assert_eq!(3, rrsig.labels);
+            assert!(!response.authorities.is_empty());
+
+            let response = zones.answer("host.wild.example.com", QueryType::Text).unwrap();
+            assert!(response.answers.is_empty());
+            assert_eq!(Denial::NoData, denial(&response, "host.wild.example.com", QueryType::Text));
+        }
+    }
+
+    #[test]
+    fn referrals() {
+        let zones = zones(DenialChain::Nsec);
+
+        let response = zones.answer("www.sub.example.com", QueryType::A).unwrap();
+        assert!(!response.header.authoritative_answer);
+        assert_eq!(ResultCode::NoError, response.header.result_code);
+        assert!(response.answers.is_empty());
+        assert!(response.authorities.iter().any(|record| record.qtype() == QueryType::AuthoritativeNameServer));
+        assert_eq!(Denial::InsecureDelegation, denial(&response, "sub.example.com", QueryType::DelegationSigner));
+        assert_eq!(1, response.additionals.len());
+        assert_eq!("ns.sub.example.com", response.additionals[0].domain());
+
+        // DS records are answered by the parent.
+        let response = zones.answer("sub.example.com", QueryType::DelegationSigner).unwrap();
+        assert!(response.header.authoritative_answer);
+        assert_eq!(Denial::InsecureDelegation, denial(&response, "sub.example.com", QueryType::DelegationSigner));
+    }
+
+    #[test]
+    fn refresh() {
+        let zones = zones(DenialChain::Nsec);
+        let inception = |zones: &Zones| {
+            zones.answer("www.example.com", QueryType::A).unwrap().answers.iter().find_map(|record| match record {
+                Record::Signature(rrsig) => Some(rrsig.inception),
+                _ => None,
+            })
+        };
+        let first = inception(&zones).unwrap();
+        verify(&zones, &zones.answer("www.example.com", QueryType::A).unwrap().answers, "www.example.com", QueryType::A);
+
+        // Signatures are only made again when about to expire.
+        zones.refresh(NOW + 3600);
+        assert_eq!(Some(first), inception(&zones));
+
+        let later = NOW + 25 * 24 * 3600;
+        zones.refresh(later);
+        assert!(inception(&zones).unwrap() > first);
+        assert!(zones.answer("example.com", QueryType::DnsKey).unwrap().answers.iter().all(|record| match record {
+            Record::Signature(rrsig) => rrsig.expiration > later + 7 * 24 * 3600,
+            _ => true,
+        }));
+    }
+
+    #[test]
+    fn load_errors() {
+        let zone = temp_path("zone");
+        fs::write(&zone, ZONE_FILE).unwrap();
+
+        // Zones can't be served without their keys.
+        let keys: &[PathBuf] = &[];
+        assert!(Zones::load(&[&zone], keys, DenialChain::Nsec, NOW).is_err());
+
+        fs::write(&zone, "example.com. 3600 IN A").unwrap();
+        assert!(Zones::load(&[&zone], keys, DenialChain::Nsec, NOW).is_err());
+
+        fs::remove_file(zone).unwrap();
+    }
+}